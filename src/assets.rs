@@ -1,19 +1,27 @@
 use crate::{
     mesh::{MeshBuilder, MeshBuilderError},
     rocketsim::Team,
-    settings::cache_handler::{get_default_mesh_cache, get_material_cache, get_mesh_cache, get_texture_cache},
+    settings::{
+        cache_handler::{get_default_mesh_cache, get_material_cache, get_mesh_cache, get_texture_cache},
+        material_editor::{material_override, override_key},
+    },
 };
 use bevy::{
     asset::{io::Reader, AssetLoader},
     color::palettes::css,
     prelude::*,
-    render::renderer::RenderDevice,
+    render::{
+        mesh::{Indices, PrimitiveTopology},
+        render_asset::RenderAssetUsages,
+        renderer::RenderDevice,
+    },
     utils::ConditionalSendFuture,
 };
 use byteorder::{LittleEndian, ReadBytesExt};
 use std::{
     collections::HashMap,
     ffi::OsStr,
+    fs,
     io::{self, Read},
     path::Path,
     sync::Mutex,
@@ -132,6 +140,10 @@ pub fn get_mesh_info(name: &str, meshes: &mut Assets<Mesh>) -> Option<Vec<Handle
         return None;
     }
 
+    if let Some(meshes) = get_custom_mesh(name, meshes) {
+        return Some(meshes);
+    }
+
     let mut local_path = name
         .replace(".Modular", "")
         .replace(".Meshes", ".StaticMesh3")
@@ -149,7 +161,7 @@ pub fn get_mesh_info(name: &str, meshes: &mut Assets<Mesh>) -> Option<Vec<Handle
         }
     }
 
-    let file_name = local_path.split('/').last().unwrap();
+    let file_name = local_path.split('/').next_back().unwrap();
     let cache_path = format!("./cache/mesh/{file_name}.bin");
 
     let extension = if name.contains(".SkeletalMesh3") { "psk" } else { "pskx" };
@@ -158,6 +170,233 @@ pub fn get_mesh_info(name: &str, meshes: &mut Assets<Mesh>) -> Option<Vec<Handle
     get_mesh_cache(cache_path, asset_path, name, meshes)
 }
 
+/// Folder with user-provided models that replace the uncooked ones, for those who can't extract them
+const CUSTOM_ASSETS_DIR: &str = "./assets/custom";
+
+/// Each line is `mesh=file`, where `mesh` is the name of an uncooked mesh,
+/// e.g. `Stadium_P.Meshes.Field_STD`, and `file` is a `.gltf`, `.glb` or `.obj` file in `CUSTOM_ASSETS_DIR`
+const CUSTOM_MANIFEST_NAME: &str = "manifest.txt";
+
+static CUSTOM_MANIFEST: Mutex<Option<HashMap<String, String>>> = Mutex::new(None);
+static CUSTOM_MESHES: Mutex<Option<HashMap<String, Vec<Handle<Mesh>>>>> = Mutex::new(None);
+
+fn read_custom_manifest() -> HashMap<String, String> {
+    let path = Path::new(CUSTOM_ASSETS_DIR).join(CUSTOM_MANIFEST_NAME);
+    let manifest = match fs::read_to_string(&path) {
+        Ok(manifest) => manifest,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return HashMap::new(),
+        Err(e) => {
+            error!("Failed to read {} due to: {e}", path.display());
+            return HashMap::new();
+        }
+    };
+
+    manifest
+        .lines()
+        .filter_map(|line| line.split_once('='))
+        .map(|(mesh, file)| (mesh.trim().to_string(), file.trim().to_string()))
+        .filter(|(mesh, file)| !mesh.is_empty() && !file.is_empty())
+        .collect()
+}
+
+#[non_exhaustive]
+#[derive(Debug, Error)]
+pub enum CustomMeshError {
+    #[error("Couldn't read model: {0}")]
+    Io(#[from] io::Error),
+    #[error("Couldn't load glTF: {0}")]
+    Gltf(#[from] gltf::Error),
+    #[error("Couldn't load OBJ: {0}")]
+    Obj(String),
+    #[error("Unsupported model format {0}, expected gltf, glb or obj")]
+    UnsupportedFormat(String),
+}
+
+/// Loads a user-provided replacement for an uncooked mesh, if the manifest has one
+///
+/// Models should already be in the same coordinate system and scale as the field, with Y up
+fn get_custom_mesh(name: &str, meshes: &mut Assets<Mesh>) -> Option<Vec<Handle<Mesh>>> {
+    let file = CUSTOM_MANIFEST
+        .lock()
+        .unwrap()
+        .get_or_insert_with(read_custom_manifest)
+        .get(name)?
+        .clone();
+
+    let mut custom_meshes_lock = CUSTOM_MESHES.lock().unwrap();
+    let custom_meshes = custom_meshes_lock.get_or_insert_with(HashMap::new);
+
+    if let Some(handles) = custom_meshes.get(name) {
+        return Some(handles.clone());
+    }
+
+    let path = Path::new(CUSTOM_ASSETS_DIR).join(&file);
+    let extension = path.extension().and_then(OsStr::to_str).unwrap_or_default().to_lowercase();
+    let loaded = match extension.as_str() {
+        "gltf" | "glb" => load_gltf_meshes(&path),
+        "obj" => load_obj_meshes(&path),
+        _ => Err(CustomMeshError::UnsupportedFormat(extension)),
+    };
+
+    match loaded {
+        Ok(loaded) => {
+            let handles = loaded.into_iter().map(|mesh| meshes.add(mesh)).collect::<Vec<_>>();
+            custom_meshes.insert(name.to_string(), handles.clone());
+            Some(handles)
+        }
+        Err(e) => {
+            error!("Failed to load {} for {name} due to: {e}", path.display());
+            None
+        }
+    }
+}
+
+fn finish_custom_mesh(
+    positions: Vec<[f32; 3]>,
+    normals: Option<Vec<[f32; 3]>>,
+    uvs: Option<Vec<[f32; 2]>>,
+    indices: Vec<u32>,
+) -> Mesh {
+    let mut mesh = Mesh::new(PrimitiveTopology::TriangleList, RenderAssetUsages::default())
+        .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+        .with_inserted_indices(Indices::U32(indices));
+
+    match normals {
+        Some(normals) => mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals),
+        None => {
+            mesh.duplicate_vertices();
+            mesh.compute_flat_normals();
+        }
+    }
+
+    if let Some(uvs) = uvs {
+        mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, uvs);
+        if let Err(e) = mesh.generate_tangents() {
+            warn!("Failed to generate tangents for a custom mesh due to: {e}");
+        }
+    }
+
+    mesh
+}
+
+/// One mesh per primitive, in the order they appear in the file, like the material slots of a pskx mesh
+fn load_gltf_meshes(path: &Path) -> Result<Vec<Mesh>, CustomMeshError> {
+    let (document, buffers, _) = gltf::import(path)?;
+
+    Ok(document
+        .meshes()
+        .flat_map(|mesh| mesh.primitives().collect::<Vec<_>>())
+        .filter_map(|primitive| {
+            let reader = primitive.reader(|buffer| buffers.get(buffer.index()).map(|data| &data.0[..]));
+
+            let positions = reader.read_positions()?.collect::<Vec<_>>();
+            let indices = reader.read_indices().map_or_else(
+                || (0..positions.len() as u32).collect(),
+                |indices| indices.into_u32().collect(),
+            );
+            let normals = reader.read_normals().map(Iterator::collect);
+            let uvs = reader.read_tex_coords(0).map(|uvs| uvs.into_f32().collect());
+
+            Some(finish_custom_mesh(positions, normals, uvs, indices))
+        })
+        .collect())
+}
+
+/// One mesh per `usemtl` group, in the order they first appear in the file
+fn load_obj_meshes(path: &Path) -> Result<Vec<Mesh>, CustomMeshError> {
+    fn parse_floats<const N: usize>(parts: std::str::SplitWhitespace) -> Option<[f32; N]> {
+        let mut values = [0.; N];
+        let mut parts = parts.map(str::parse::<f32>);
+        for value in &mut values {
+            *value = parts.next()?.ok()?;
+        }
+
+        Some(values)
+    }
+
+    let file = fs::read_to_string(path)?;
+
+    let mut positions = Vec::new();
+    let mut normals = Vec::new();
+    let mut uvs = Vec::new();
+
+    // the corners of each face, as indices into the lists above, per material group
+    let mut groups: Vec<(String, Vec<[Option<usize>; 3]>)> = Vec::new();
+    let mut current_group = 0;
+
+    for (line_num, line) in file.lines().enumerate() {
+        let invalid = || CustomMeshError::Obj(format!("invalid line {}: {line}", line_num + 1));
+
+        let mut parts = line.split_whitespace();
+        match parts.next() {
+            Some("v") => positions.push(parse_floats::<3>(parts).ok_or_else(invalid)?),
+            Some("vn") => normals.push(parse_floats::<3>(parts).ok_or_else(invalid)?),
+            // OBJ's v goes up, while Bevy's goes down
+            Some("vt") => uvs.push(parse_floats::<2>(parts).map(|[u, v]| [u, 1. - v]).ok_or_else(invalid)?),
+            Some("usemtl") => {
+                let material = parts.next().unwrap_or_default();
+                current_group = groups.iter().position(|(name, _)| name == material).unwrap_or_else(|| {
+                    groups.push((material.to_string(), Vec::new()));
+                    groups.len() - 1
+                });
+            }
+            Some("f") => {
+                let corners = parts
+                    .map(|corner| {
+                        // indices are 1-based, and negative ones count back from the end
+                        let resolve = |index: Option<&str>, len: usize| -> Option<Option<usize>> {
+                            match index.filter(|index| !index.is_empty()) {
+                                None => Some(None),
+                                Some(index) => {
+                                    let index = index.parse::<isize>().ok()?;
+                                    let index = if index < 0 { len as isize + index } else { index - 1 };
+                                    usize::try_from(index).ok().filter(|&index| index < len).map(Some)
+                                }
+                            }
+                        };
+
+                        let mut indices = corner.split('/');
+                        Some([
+                            resolve(indices.next(), positions.len())?,
+                            resolve(indices.next(), uvs.len())?,
+                            resolve(indices.next(), normals.len())?,
+                        ])
+                    })
+                    .collect::<Option<Vec<_>>>()
+                    .filter(|corners| corners.len() >= 3 && corners.iter().all(|corner| corner[0].is_some()))
+                    .ok_or_else(invalid)?;
+
+                if groups.is_empty() {
+                    groups.push((String::new(), Vec::new()));
+                }
+
+                // triangulate as a fan
+                let faces = &mut groups[current_group].1;
+                for i in 1..corners.len() - 1 {
+                    faces.extend([corners[0], corners[i], corners[i + 1]]);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Ok(groups
+        .into_iter()
+        .filter(|(_, corners)| !corners.is_empty())
+        .map(|(_, corners)| {
+            let has_uvs = corners.iter().all(|corner| corner[1].is_some());
+            let has_normals = corners.iter().all(|corner| corner[2].is_some());
+
+            let mesh_positions = corners.iter().map(|corner| positions[corner[0].unwrap()]).collect();
+            let mesh_uvs = has_uvs.then(|| corners.iter().map(|corner| uvs[corner[1].unwrap()]).collect());
+            let mesh_normals = has_normals.then(|| corners.iter().map(|corner| normals[corner[2].unwrap()]).collect());
+            let indices = (0..corners.len() as u32).collect();
+
+            finish_custom_mesh(mesh_positions, mesh_normals, mesh_uvs, indices)
+        })
+        .collect())
+}
+
 const DOUBLE_SIDED_MATS: [&str; 28] = [
     "Trees.Materials.LombardyPoplar_B_NoWind_MIC",
     "Trees.Materials.LombardyPoplar_B_Mat",
@@ -269,7 +508,7 @@ fn retreive_material(
         }
     }
 
-    let file_name = pre_path.split('/').last().unwrap();
+    let file_name = pre_path.split('/').next_back().unwrap();
     let cache_path = format!("./cache/material/{file_name}.bin");
     let mesh_material = get_material_cache(cache_path, pre_path, name)?;
 
@@ -424,19 +663,39 @@ pub fn get_material(
     material_names
         .entry(key)
         .or_insert_with(|| {
-            materials.add(
+            let mut material =
                 retreive_material(name, asset_server, base_color, side, images, render_device).unwrap_or(StandardMaterial {
                     base_color,
                     metallic: 0.1,
                     cull_mode: None,
                     double_sided: true,
                     ..default()
-                }),
-            )
+                });
+
+            if let Some(material_override) = material_override(name, side) {
+                material_override.apply(&mut material);
+            }
+
+            materials.add(material)
         })
         .clone()
 }
 
+/// Every material loaded by `get_material` so far, sorted by the name its overrides are saved under
+pub fn loaded_materials() -> Vec<(String, Handle<StandardMaterial>)> {
+    let material_names_lock = MATERIALS.lock().unwrap();
+    let Some(material_names) = material_names_lock.as_ref() else {
+        return Vec::new();
+    };
+
+    let mut loaded = material_names
+        .iter()
+        .map(|(&(name, side), handle)| (override_key(name, side), handle.clone()))
+        .collect::<Vec<_>>();
+    loaded.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
+    loaded
+}
+
 pub fn read_vertices(chunk_data: &[u8], data_count: usize, vertices: &mut Vec<f32>) {
     vertices.reserve(data_count * 3);
 
@@ -0,0 +1,162 @@
+use crate::{
+    settings::options::ShowBallSpin,
+    udp::{Ball, GameStates, ToBevyVec},
+};
+use bevy::{
+    color::palettes::css,
+    pbr::{NotShadowCaster, NotShadowReceiver},
+    prelude::*,
+    render::{
+        render_asset::RenderAssetUsages,
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
+    },
+};
+use std::f32::consts::{PI, TAU};
+
+pub struct BallSpinPlugin;
+
+impl Plugin for BallSpinPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                spawn_seam,
+                update_seam_visibility.run_if(resource_changed::<ShowBallSpin>),
+                draw_spin_axis.run_if(|show_ball_spin: Res<ShowBallSpin>| show_ball_spin.0),
+            ),
+        );
+    }
+}
+
+const BALL_RADIUS: f32 = 91.25;
+/// Just above the ball's surface, so the seam isn't hidden inside of it
+const SEAM_RADIUS: f32 = BALL_RADIUS * 1.01;
+/// How wide each seam is, as an angle from the center of the ball
+const SEAM_HALF_WIDTH: f32 = 0.04;
+const SEAM_TEXTURE_SIZE: UVec2 = UVec2::new(512, 256);
+const EQUATOR_COLOR: Srgba = css::RED;
+const MERIDIAN_COLOR: Srgba = css::WHITE;
+
+/// The ball's max angular velocity in RocketSim, in radians per second
+const MAX_ANG_VEL: f32 = 6.;
+/// How long the arrow is when the ball is spinning as fast as it can
+const MAX_AXIS_LENGTH: f32 = 300.;
+/// Spinning slower than this doesn't get an arrow, it would be too short to see anyways
+const MIN_ANG_VEL: f32 = 0.05;
+const SLOW_SPIN_COLOR: Srgba = css::AQUA;
+const FAST_SPIN_COLOR: Srgba = css::MAGENTA;
+
+/// A sphere slightly bigger than the ball with two seams painted on it, which turns with the ball
+#[derive(Component)]
+struct BallSeam;
+
+/// Two great circles at right angles, so at least one of them visibly turns no matter which way the ball spins
+///
+/// The texture is wrapped around a UV sphere, with longitude along the width and latitude along the height
+fn seam_texture() -> Image {
+    let half_width = SEAM_HALF_WIDTH.sin();
+    let edge = half_width * 0.25;
+
+    let mut data = Vec::with_capacity((SEAM_TEXTURE_SIZE.x * SEAM_TEXTURE_SIZE.y * 4) as usize);
+    for y in 0..SEAM_TEXTURE_SIZE.y {
+        let lat = (0.5 - (y as f32 + 0.5) / SEAM_TEXTURE_SIZE.y as f32) * PI;
+
+        for x in 0..SEAM_TEXTURE_SIZE.x {
+            let lon = (x as f32 + 0.5) / SEAM_TEXTURE_SIZE.x as f32 * TAU;
+
+            // distance from the plane of each circle, which stays the same width all the way around the sphere
+            let equator = lat.sin().abs();
+            let meridian = (lat.cos() * lon.sin()).abs();
+
+            let (distance, color) = if equator < meridian {
+                (equator, EQUATOR_COLOR)
+            } else {
+                (meridian, MERIDIAN_COLOR)
+            };
+
+            // fade the edges out a little so they don't flicker
+            let alpha = ((half_width - distance) / edge).clamp(0., 1.);
+            let [r, g, b, _] = color.to_u8_array();
+            data.extend_from_slice(&[r, g, b, (alpha * 255.) as u8]);
+        }
+    }
+
+    Image::new(
+        Extent3d {
+            width: SEAM_TEXTURE_SIZE.x,
+            height: SEAM_TEXTURE_SIZE.y,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::RENDER_WORLD,
+    )
+}
+
+fn spawn_seam(
+    mut commands: Commands,
+    balls: Query<Entity, Added<Ball>>,
+    show_ball_spin: Res<ShowBallSpin>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    let Ok(ball) = balls.get_single() else {
+        return;
+    };
+
+    let seam = commands
+        .spawn((
+            BallSeam,
+            Mesh3d(meshes.add(Sphere::new(SEAM_RADIUS).mesh().uv(48, 24))),
+            MeshMaterial3d(materials.add(StandardMaterial {
+                base_color_texture: Some(images.add(seam_texture())),
+                alpha_mode: AlphaMode::Blend,
+                unlit: true,
+                ..default()
+            })),
+            if show_ball_spin.0 {
+                Visibility::Inherited
+            } else {
+                Visibility::Hidden
+            },
+            NotShadowCaster,
+            NotShadowReceiver,
+        ))
+        .id();
+
+    // the ball's point light has to stay its first child
+    commands.entity(ball).add_child(seam);
+}
+
+fn update_seam_visibility(show_ball_spin: Res<ShowBallSpin>, mut seams: Query<&mut Visibility, With<BallSeam>>) {
+    for mut visibility in &mut seams {
+        *visibility = if show_ball_spin.0 {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    }
+}
+
+/// An arrow along the ball's spin axis, pointing the way a right hand's thumb would if its fingers curled with the spin
+fn draw_spin_axis(states: Res<GameStates>, mut gizmos: Gizmos) {
+    let ball = &states.current.ball;
+
+    // swapping y and z to go to Bevy's coordinates mirrors the world, which flips which way an axis of rotation points
+    let ang_vel = -ball.ang_vel.to_bevy();
+    let speed = ang_vel.length();
+    if speed < MIN_ANG_VEL {
+        return;
+    }
+
+    let axis = ang_vel / speed;
+    let fraction = (speed / MAX_ANG_VEL).min(1.);
+
+    let center = ball.pos.to_bevy();
+    let start = center + axis * SEAM_RADIUS;
+    let end = start + axis * MAX_AXIS_LENGTH * fraction;
+
+    gizmos.arrow(start, end, SLOW_SPIN_COLOR.mix(&FAST_SPIN_COLOR, fraction));
+}
@@ -0,0 +1,130 @@
+use crate::{
+    settings::options::Blueprint,
+    udp::{get_color_from_team, Car, CarBody, GameStates},
+};
+use bevy::{prelude::*, utils::HashMap};
+
+pub struct BlueprintPlugin;
+
+impl Plugin for BlueprintPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(OriginalMaterials::default()).add_systems(
+            Update,
+            (
+                apply_blueprint.run_if(|blueprint: Res<Blueprint>| blueprint.enabled),
+                restore_materials.run_if(|blueprint: Res<Blueprint>, originals: Res<OriginalMaterials>| {
+                    !blueprint.enabled && originals.clear_color.is_some()
+                }),
+            ),
+        );
+    }
+}
+
+/// How much of the team color is mixed into the blueprint color for car bodies
+const CAR_TEAM_TINT: f32 = 0.35;
+
+#[derive(Resource, Default)]
+struct OriginalMaterials {
+    materials: HashMap<AssetId<StandardMaterial>, StandardMaterial>,
+    clear_color: Option<Color>,
+    applied: Option<(Color, Color)>,
+}
+
+fn set_blueprint_material(material: &mut StandardMaterial, color: Color) {
+    // alpha is driven by other systems (demos, boost pads) so it's left untouched
+    material.base_color = color.with_alpha(material.base_color.alpha());
+    material.base_color_texture = None;
+    material.normal_map_texture = None;
+    material.emissive = LinearRgba::BLACK;
+    material.unlit = true;
+}
+
+fn apply_blueprint(
+    blueprint: Res<Blueprint>,
+    states: Res<GameStates>,
+    cars: Query<&Car>,
+    car_bodies: Query<(&MeshMaterial3d<StandardMaterial>, &Parent), With<CarBody>>,
+    mut originals: ResMut<OriginalMaterials>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut material_events: EventReader<AssetEvent<StandardMaterial>>,
+    mut clear_color: ResMut<ClearColor>,
+) {
+    let color = Color::srgb_from_array(blueprint.color);
+    let background = Color::srgb_from_array(blueprint.background);
+
+    let originals = originals.as_mut();
+
+    // cars get their own copies of materials, so forget the ones that went away with respawned cars
+    for event in material_events.read() {
+        if let AssetEvent::Removed { id } | AssetEvent::Unused { id } = event {
+            originals.materials.remove(id);
+        }
+    }
+
+    if originals.clear_color.is_none() {
+        originals.clear_color = Some(clear_color.0);
+    }
+
+    let changed = originals.applied != Some((color, background));
+    if changed {
+        clear_color.0 = background;
+        originals.applied = Some((color, background));
+    }
+
+    // only touch materials that are new (or all of them if the palette changed) to avoid re-uploading every frame
+    let ids = materials
+        .ids()
+        .filter(|id| changed || !originals.materials.contains_key(id))
+        .collect::<Vec<_>>();
+
+    for id in ids {
+        let material = materials.get_mut(id).unwrap();
+        originals.materials.entry(id).or_insert_with(|| material.clone());
+        set_blueprint_material(material, color);
+    }
+
+    for (material_handle, parent) in &car_bodies {
+        let Some(team) = cars
+            .get(parent.get())
+            .ok()
+            .and_then(|car| states.current.cars.iter().find(|info| info.id == car.id()))
+            .map(|info| info.team)
+        else {
+            continue;
+        };
+
+        let tinted = color.mix(&get_color_from_team(team), CAR_TEAM_TINT);
+        let Some(material) = materials.get(material_handle) else {
+            continue;
+        };
+
+        if material.base_color.with_alpha(1.) != tinted.with_alpha(1.) {
+            set_blueprint_material(materials.get_mut(material_handle).unwrap(), tinted);
+        }
+    }
+}
+
+fn restore_materials(
+    mut originals: ResMut<OriginalMaterials>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut clear_color: ResMut<ClearColor>,
+) {
+    for (id, original) in originals.materials.drain() {
+        let Some(material) = materials.get_mut(id) else {
+            continue;
+        };
+
+        let alpha = material.base_color.alpha();
+        let alpha_mode = material.alpha_mode;
+
+        *material = original;
+        material.base_color.set_alpha(alpha);
+        material.alpha_mode = alpha_mode;
+    }
+
+    if let Some(color) = originals.clear_color.take() {
+        clear_color.0 = color;
+    }
+
+    originals.applied = None;
+}
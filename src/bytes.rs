@@ -1,4 +1,6 @@
 use crate::{
+    message_feed::BotMessage,
+    nametags::CarMetadata,
     renderer::{CustomColor as Color, Render, RenderMessage},
     rocketsim::{
         BallHitInfo, BallState, BoostPad, BoostPadState, CarConfig, CarContact, CarControls, CarInfo, CarState, GameMode,
@@ -16,6 +18,11 @@ pub trait FromBytesExact: FromBytes {
     const NUM_BYTES: usize;
 }
 
+/// Decoding for packets that come from outside, which gives `None` instead of panicking on bytes that don't make sense
+pub trait TryFromBytes: Sized {
+    fn try_from_bytes(bytes: &[u8]) -> Option<Self>;
+}
+
 struct ByteReader<'a> {
     idx: usize,
     bytes: &'a [u8],
@@ -34,6 +41,21 @@ impl<'a> ByteReader<'a> {
         item
     }
 
+    /// Like `read`, but gives `None` if there aren't enough bytes left
+    pub fn try_read<I: FromBytesExact>(&mut self) -> Option<I> {
+        let item = I::from_bytes(self.bytes.get(self.idx..self.idx + I::NUM_BYTES)?);
+        self.idx += I::NUM_BYTES;
+        Some(item)
+    }
+
+    /// Reads a UTF-8 string that's prefixed by its length as a u16
+    pub fn try_read_string(&mut self) -> Option<String> {
+        let len = self.try_read::<u16>()? as usize;
+        let string = String::from_utf8_lossy(self.bytes.get(self.idx..self.idx + len)?).into_owned();
+        self.idx += len;
+        Some(string)
+    }
+
     #[inline]
     #[track_caller]
     pub fn debug_assert_num_bytes(&self, num_bytes: usize) {
@@ -136,14 +158,21 @@ impl FromBytesExact for Team {
     const NUM_BYTES: usize = 1;
 }
 
+impl Team {
+    #[inline]
+    const fn from_byte(byte: u8) -> Option<Self> {
+        match byte {
+            0 => Some(Self::Blue),
+            1 => Some(Self::Orange),
+            _ => None,
+        }
+    }
+}
+
 impl FromBytes for Team {
     #[inline]
     fn from_bytes(bytes: &[u8]) -> Self {
-        match bytes[0] {
-            0 => Self::Blue,
-            1 => Self::Orange,
-            _ => unreachable!(),
-        }
+        Self::from_byte(bytes[0]).expect("invalid team")
     }
 }
 
@@ -151,17 +180,24 @@ impl FromBytesExact for GameMode {
     const NUM_BYTES: usize = 1;
 }
 
+impl GameMode {
+    #[inline]
+    const fn from_byte(byte: u8) -> Option<Self> {
+        match byte {
+            0 => Some(Self::Soccar),
+            1 => Some(Self::Hoops),
+            2 => Some(Self::HeatSeeker),
+            3 => Some(Self::Snowday),
+            4 => Some(Self::TheVoid),
+            _ => None,
+        }
+    }
+}
+
 impl FromBytes for GameMode {
     #[inline]
     fn from_bytes(bytes: &[u8]) -> Self {
-        match bytes[0] {
-            0 => Self::Soccar,
-            1 => Self::Hoops,
-            2 => Self::HeatSeeker,
-            3 => Self::Snowday,
-            4 => Self::TheVoid,
-            _ => unreachable!(),
-        }
+        Self::from_byte(bytes[0]).expect("invalid game mode")
     }
 }
 
@@ -444,27 +480,66 @@ impl Render {
             Self::Line2D { .. } => 1 + Vec2::NUM_BYTES * 2 + Color::NUM_BYTES,
             Self::Line { .. } => 1 + Vec3::NUM_BYTES * 2 + Color::NUM_BYTES,
             Self::LineStrip { positions, .. } => 1 + u16::NUM_BYTES + positions.len() * Vec3::NUM_BYTES + Color::NUM_BYTES,
+            Self::Sphere { .. } => 1 + Vec3::NUM_BYTES + f32::NUM_BYTES + Color::NUM_BYTES,
+            Self::Box { .. } => 1 + Vec3::NUM_BYTES * 2 + Color::NUM_BYTES,
+            Self::Text { text, .. } => 1 + Vec3::NUM_BYTES + u16::NUM_BYTES + text.len() + Color::NUM_BYTES,
+            Self::FilledRect2D { .. } => 1 + Vec2::NUM_BYTES * 2 + Color::NUM_BYTES,
+            Self::Polygon { positions, .. } => 1 + u16::NUM_BYTES + positions.len() * Vec2::NUM_BYTES + Color::NUM_BYTES,
+            Self::Circle { .. } => 1 + Vec2::NUM_BYTES + f32::NUM_BYTES + Color::NUM_BYTES,
         }
     }
 
-    fn from_reader(reader: &mut ByteReader) -> Self {
-        match reader.read::<u8>() {
+    fn try_from_reader(reader: &mut ByteReader) -> Option<Self> {
+        Some(match reader.try_read::<u8>()? {
             0 => Self::Line2D {
-                start: reader.read(),
-                end: reader.read(),
-                color: reader.read(),
+                start: reader.try_read()?,
+                end: reader.try_read()?,
+                color: reader.try_read()?,
             },
             1 => Self::Line {
-                start: reader.read(),
-                end: reader.read(),
-                color: reader.read(),
+                start: reader.try_read()?,
+                end: reader.try_read()?,
+                color: reader.try_read()?,
             },
             2 => Self::LineStrip {
-                positions: (0..reader.read::<u16>()).map(|_| reader.read()).collect(),
-                color: reader.read(),
+                positions: (0..reader.try_read::<u16>()?)
+                    .map(|_| reader.try_read())
+                    .collect::<Option<_>>()?,
+                color: reader.try_read()?,
             },
-            _ => unreachable!(),
-        }
+            3 => Self::Sphere {
+                pos: reader.try_read()?,
+                radius: reader.try_read()?,
+                color: reader.try_read()?,
+            },
+            4 => Self::Box {
+                pos: reader.try_read()?,
+                size: reader.try_read()?,
+                color: reader.try_read()?,
+            },
+            5 => Self::Text {
+                pos: reader.try_read()?,
+                text: reader.try_read_string()?,
+                color: reader.try_read()?,
+            },
+            6 => Self::FilledRect2D {
+                pos: reader.try_read()?,
+                size: reader.try_read()?,
+                color: reader.try_read()?,
+            },
+            7 => Self::Polygon {
+                positions: (0..reader.try_read::<u16>()?)
+                    .map(|_| reader.try_read())
+                    .collect::<Option<_>>()?,
+                color: reader.try_read()?,
+            },
+            8 => Self::Circle {
+                pos: reader.try_read()?,
+                radius: reader.try_read()?,
+                color: reader.try_read()?,
+            },
+            _ => return None,
+        })
     }
 }
 
@@ -496,6 +571,47 @@ impl ToBytes for Render {
 
                 bytes.extend_from_slice(&color.to_bytes());
             }
+            Render::Sphere { pos, radius, color } => {
+                bytes.push(3);
+                bytes.extend_from_slice(&pos.to_bytes());
+                bytes.extend_from_slice(&radius.to_bytes());
+                bytes.extend_from_slice(&color.to_bytes());
+            }
+            Render::Box { pos, size, color } => {
+                bytes.push(4);
+                bytes.extend_from_slice(&pos.to_bytes());
+                bytes.extend_from_slice(&size.to_bytes());
+                bytes.extend_from_slice(&color.to_bytes());
+            }
+            Render::Text { pos, text, color } => {
+                bytes.push(5);
+                bytes.extend_from_slice(&pos.to_bytes());
+                bytes.extend_from_slice(&(text.len() as u16).to_bytes());
+                bytes.extend_from_slice(text.as_bytes());
+                bytes.extend_from_slice(&color.to_bytes());
+            }
+            Render::FilledRect2D { pos, size, color } => {
+                bytes.push(6);
+                bytes.extend_from_slice(&pos.to_bytes());
+                bytes.extend_from_slice(&size.to_bytes());
+                bytes.extend_from_slice(&color.to_bytes());
+            }
+            Render::Polygon { positions, color } => {
+                bytes.push(7);
+                bytes.extend_from_slice(&(positions.len() as u16).to_bytes());
+
+                for pos in positions {
+                    bytes.extend_from_slice(&pos.to_bytes());
+                }
+
+                bytes.extend_from_slice(&color.to_bytes());
+            }
+            Render::Circle { pos, radius, color } => {
+                bytes.push(8);
+                bytes.extend_from_slice(&pos.to_bytes());
+                bytes.extend_from_slice(&radius.to_bytes());
+                bytes.extend_from_slice(&color.to_bytes());
+            }
         }
 
         debug_assert_eq!(bytes.len(), num_bytes);
@@ -504,19 +620,22 @@ impl ToBytes for Render {
     }
 }
 
-impl FromBytes for RenderMessage {
-    fn from_bytes(bytes: &[u8]) -> Self {
+impl TryFromBytes for RenderMessage {
+    fn try_from_bytes(bytes: &[u8]) -> Option<Self> {
         let mut reader = ByteReader::new(bytes);
-        reader.read::<u32>();
+        reader.try_read::<u32>()?;
 
-        match reader.read::<u8>() {
+        Some(match reader.try_read::<u8>()? {
             0 => Self::AddRender(
-                reader.read(),
-                (0..reader.read::<u16>()).map(|_| Render::from_reader(&mut reader)).collect(),
+                reader.try_read()?,
+                (0..reader.try_read::<u16>()?)
+                    .map(|_| Render::try_from_reader(&mut reader))
+                    .collect::<Option<_>>()?,
             ),
-            1 => Self::RemoveRender(reader.read()),
-            _ => unreachable!(),
-        }
+            1 => Self::RemoveRender(reader.try_read()?),
+            2 => Self::NameGroup(reader.try_read()?, reader.try_read_string()?),
+            _ => return None,
+        })
     }
 }
 
@@ -533,6 +652,7 @@ impl RenderMessage {
                     + renders.iter().map(Render::count_bytes).sum::<usize>()
             }
             Self::RemoveRender(_) => Self::MIN_NUM_BYTES + i32::NUM_BYTES,
+            Self::NameGroup(_, name) => Self::MIN_NUM_BYTES + 1 + i32::NUM_BYTES + u16::NUM_BYTES + name.len(),
         }
     }
 
@@ -558,6 +678,12 @@ impl ToBytes for RenderMessage {
                 bytes.push(1);
                 bytes.extend_from_slice(&id.to_bytes());
             }
+            Self::NameGroup(id, name) => {
+                bytes.push(2);
+                bytes.extend_from_slice(&id.to_bytes());
+                bytes.extend_from_slice(&(name.len() as u16).to_bytes());
+                bytes.extend_from_slice(name.as_bytes());
+            }
         }
 
         debug_assert_eq!(bytes.len(), num_bytes);
@@ -566,10 +692,25 @@ impl ToBytes for RenderMessage {
     }
 }
 
-impl FromBytes for GameState {
-    #[inline]
-    fn from_bytes(bytes: &[u8]) -> Self {
-        Self {
+impl TryFromBytes for GameState {
+    /// Everything but the game mode and the teams has a fixed size, so once the length matches the counts
+    /// in the header, those bytes are the only ones that can be invalid
+    fn try_from_bytes(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() < Self::MIN_NUM_BYTES || Self::get_num_bytes(bytes) != bytes.len() {
+            return None;
+        }
+
+        GameMode::from_byte(bytes[u64::NUM_BYTES + f32::NUM_BYTES])?;
+
+        let cars_start = Self::MIN_NUM_BYTES + BallState::NUM_BYTES + Self::read_num_pads(bytes) * BoostPad::NUM_BYTES;
+        if bytes[cars_start..]
+            .chunks_exact(CarInfo::NUM_BYTES)
+            .any(|car| Team::from_byte(car[u32::NUM_BYTES]).is_none())
+        {
+            return None;
+        }
+
+        Some(Self {
             tick_count: Self::read_tick_count(bytes),
             tick_rate: Self::read_tick_rate(bytes),
             game_mode: Self::read_game_mode(bytes),
@@ -583,7 +724,47 @@ impl FromBytes for GameState {
                 .chunks_exact(CarInfo::NUM_BYTES)
                 .map(CarInfo::from_bytes)
                 .collect(),
-        }
+        })
+    }
+}
+
+impl CarMetadata {
+    pub const MIN_NUM_BYTES: usize = u32::NUM_BYTES;
+
+    pub fn get_num_bytes(bytes: &[u8]) -> usize {
+        u32::from_bytes(&bytes[..u32::NUM_BYTES]) as usize
+    }
+}
+
+impl TryFromBytes for CarMetadata {
+    fn try_from_bytes(bytes: &[u8]) -> Option<Self> {
+        let mut reader = ByteReader::new(bytes);
+        reader.try_read::<u32>()?;
+
+        (0..reader.try_read::<u32>()?)
+            .map(|_| Some((reader.try_read()?, reader.try_read_string()?)))
+            .collect::<Option<_>>()
+            .map(Self)
+    }
+}
+
+impl BotMessage {
+    pub const MIN_NUM_BYTES: usize = u32::NUM_BYTES;
+
+    pub fn get_num_bytes(bytes: &[u8]) -> usize {
+        u32::from_bytes(&bytes[..u32::NUM_BYTES]) as usize
+    }
+}
+
+impl TryFromBytes for BotMessage {
+    fn try_from_bytes(bytes: &[u8]) -> Option<Self> {
+        let mut reader = ByteReader::new(bytes);
+        reader.try_read::<u32>()?;
+
+        Some(Self {
+            car_id: reader.try_read()?,
+            text: reader.try_read_string()?,
+        })
     }
 }
 
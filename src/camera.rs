@@ -1,24 +1,35 @@
-use crate::spectator::{Spectator, SpectatorPlugin, SpectatorSettings};
+#[cfg(feature = "sound")]
+use crate::sound::EAR_GAP;
+use crate::{
+    camera_path::CameraPathPlugin,
+    nametags::CarNames,
+    rocketsim::{CarInfo, Team},
+    settings::{
+        keybinds::{Action, Keybinds},
+        options::{LightingPreset, Options},
+    },
+    spectator::{Spectator, SpectatorPlugin, SpectatorSettings},
+    udp::{Ball, Car, GameStates},
+};
 use bevy::{
     color::palettes::css,
-    core_pipeline::tonemapping::Tonemapping,
-    pbr::{CascadeShadowConfigBuilder, DirectionalLightShadowMap, ShadowFilteringMethod},
+    core_pipeline::{experimental::taa::TemporalAntiAliasPlugin, tonemapping::Tonemapping},
+    input::mouse::{MouseMotion, MouseScrollUnit, MouseWheel},
+    pbr::{CascadeShadowConfigBuilder, DirectionalLightShadowMap},
     prelude::*,
+    window::{CursorGrabMode, PrimaryWindow},
 };
 use serde::{Deserialize, Serialize};
-use std::f32::consts::PI;
+use std::{
+    f32::consts::PI,
+    mem::{self, Discriminant},
+};
 
 use bevy_atmosphere::prelude::*;
 use bevy_framepace::{FramepacePlugin, FramepaceSettings};
 use bevy_vector_shapes::prelude::*;
 use std::time::Duration;
 
-#[cfg(feature = "ssao")]
-use bevy::{
-    core_pipeline::experimental::taa::{TemporalAntiAliasPlugin, TemporalAntiAliasing},
-    pbr::ScreenSpaceAmbientOcclusion,
-};
-
 #[derive(Component)]
 pub struct Sun;
 
@@ -36,16 +47,232 @@ pub enum PrimaryCamera {
     #[default]
     Spectator,
     Director(u32),
+    /// Follows the car with this id
     TrackCar(u32),
+    /// Orbits around the ball (0) or the car with this id
+    Orbit(u32),
+}
+
+impl PrimaryCamera {
+    /// The id of the car that the camera is following, if it's following one
+    pub const fn car_id(self) -> Option<u32> {
+        match self {
+            Self::Spectator => None,
+            Self::Director(id) | Self::TrackCar(id) | Self::Orbit(id) => {
+                if id == 0 {
+                    None
+                } else {
+                    Some(id)
+                }
+            }
+        }
+    }
+}
+
+/// How many of the car keys go to each team, with blue getting the first ones
+const TEAM_SLOTS: usize = Action::TRACK_CARS.len() / 2;
+
+/// Which car each of the car keys follows
+///
+/// Cars get a slot on their team's side when they show up and keep it until they leave,
+/// so the keys don't switch to a different car when someone else joins or leaves mid-match
+#[derive(Resource, Default)]
+pub struct CarSlots {
+    /// The id of the car in each slot, or 0 if it's free
+    slots: Vec<u32>,
+}
+
+impl CarSlots {
+    /// The id of the car in the slot, which starts at 0 for the first car key
+    pub fn car_id(&self, slot: usize) -> Option<u32> {
+        self.slots.get(slot).copied().filter(|&id| id != 0)
+    }
+
+    /// The ids of the cars in every slot that isn't free, in order
+    pub fn car_ids(&self) -> impl Iterator<Item = u32> + '_ {
+        self.slots.iter().copied().filter(|&id| id != 0)
+    }
+
+    /// The slot of the car with this id, if it has one
+    pub fn slot(&self, car_id: u32) -> Option<usize> {
+        self.slots.iter().position(|&id| id == car_id)
+    }
+
+    fn update(&mut self, cars: &[CarInfo]) {
+        for id in &mut self.slots {
+            if !cars.iter().any(|car_info| car_info.id == *id) {
+                *id = 0;
+            }
+        }
+
+        let mut new_cars = cars
+            .iter()
+            .filter(|car_info| self.slot(car_info.id).is_none())
+            .collect::<Vec<_>>();
+        new_cars.sort_unstable_by_key(|car_info| car_info.id);
+
+        for car_info in new_cars {
+            let team_slots = match car_info.team {
+                Team::Blue => 0..TEAM_SLOTS,
+                Team::Orange => TEAM_SLOTS..TEAM_SLOTS * 2,
+            };
+
+            // fall back to any free slot once the team's are full
+            let free_slot = team_slots
+                .chain(0..self.slots.len())
+                .find(|&slot| self.car_id(slot).is_none())
+                .unwrap_or(self.slots.len());
+
+            if free_slot >= self.slots.len() {
+                self.slots.resize(free_slot + 1, 0);
+            }
+
+            self.slots[free_slot] = car_info.id;
+        }
+    }
+}
+
+fn update_car_slots(states: Res<GameStates>, mut car_slots: ResMut<CarSlots>) {
+    car_slots.update(&states.current.cars);
+}
+
+/// Switches the camera over to the car with the same name as the one it was following if that car leaves,
+/// like when a bot reconnects or a new match starts with new car ids
+fn follow_car_by_name(
+    states: Res<GameStates>,
+    car_names: Res<CarNames>,
+    mut options: ResMut<Options>,
+    mut last_name: Local<Option<String>>,
+) {
+    let camera_state = options.camera_state;
+    let (PrimaryCamera::TrackCar(id) | PrimaryCamera::Orbit(id)) = camera_state else {
+        *last_name = None;
+        return;
+    };
+
+    if id == 0 {
+        *last_name = None;
+        return;
+    }
+
+    if states.current.cars.iter().any(|car_info| car_info.id == id) {
+        if let Some(name) = car_names.0.get(&id) {
+            if last_name.as_ref() != Some(name) {
+                *last_name = Some(name.clone());
+            }
+        }
+
+        return;
+    }
+
+    let Some(name) = last_name.as_ref() else {
+        return;
+    };
+
+    let Some(new_id) = states
+        .current
+        .cars
+        .iter()
+        .map(|car_info| car_info.id)
+        .find(|id| car_names.0.get(id) == Some(name))
+    else {
+        return;
+    };
+
+    options.camera_state = match camera_state {
+        PrimaryCamera::Orbit(_) => PrimaryCamera::Orbit(new_id),
+        _ => PrimaryCamera::TrackCar(new_id),
+    };
+}
+
+/// Glides the camera over to its new spot whenever it starts following something else, instead of jumping there
+#[derive(Resource)]
+pub struct CameraTransition {
+    /// How long a transition takes in seconds, 0 to jump straight there
+    pub duration: f32,
+    /// Whether the Director's cuts between cars glide over too
+    pub smooth_cuts: bool,
+    /// The camera mode and the id of the car or ball it was following the last time it was moved
+    last_target: Option<(Discriminant<PrimaryCamera>, u32)>,
+    /// Where the camera was when the current transition started, and how long ago that was
+    from: Option<(Transform, f32)>,
+}
+
+impl Default for CameraTransition {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            duration: 0.5,
+            smooth_cuts: true,
+            last_target: None,
+            from: None,
+        }
+    }
+}
+
+impl CameraTransition {
+    /// Moves the current transition along by `delta` seconds, and starts a new one if the camera is now following something else
+    ///
+    /// `camera` is where the camera is right now, before it's moved to where it should be this frame
+    pub fn update(&mut self, primary_camera: PrimaryCamera, target_id: u32, camera: &Transform, delta: f32) {
+        if let Some((_, elapsed)) = &mut self.from {
+            *elapsed += delta;
+            if *elapsed >= self.duration {
+                self.from = None;
+            }
+        }
+
+        let target = (mem::discriminant(&primary_camera), target_id);
+        let Some(last_target) = self.last_target.replace(target) else {
+            return;
+        };
+
+        if last_target == target {
+            return;
+        }
+
+        let is_cut = matches!(primary_camera, PrimaryCamera::Director(_)) && last_target.0 == target.0;
+        self.from = (self.duration > 0. && (self.smooth_cuts || !is_cut)).then_some((*camera, 0.));
+    }
+
+    /// Blends the camera from where it was when the transition started to where it should be now
+    pub fn blend(&self, camera: &mut Transform) {
+        let Some((from, elapsed)) = &self.from else {
+            return;
+        };
+
+        let t = elapsed / self.duration;
+        // ease in and out
+        let t = t * t * (3. - 2. * t);
+
+        camera.translation = from.translation.lerp(camera.translation, t);
+        camera.rotation = from.rotation.slerp(camera.rotation, t);
+    }
+}
+
+/// The free camera starts from wherever the camera already is, so there's nothing to glide over to,
+/// but the next mode should glide over from wherever it ends up
+fn free_camera_transition(camera: Query<&PrimaryCamera>, mut transition: ResMut<CameraTransition>) {
+    if camera
+        .get_single()
+        .is_ok_and(|primary_camera| *primary_camera == PrimaryCamera::Spectator)
+    {
+        transition.last_target = Some((mem::discriminant(&PrimaryCamera::Spectator), 0));
+        transition.from = None;
+    }
 }
 
 pub const BOOST_INDICATOR_POS: Vec2 = Vec2::new(150., 150.);
 pub const BOOST_INDICATOR_FONT_SIZE: f32 = 60.0;
 pub const TIME_DISPLAY_POS: Vec2 = Vec2::new(0., 60.);
+/// Vertical field of view of the primary camera when it isn't using the Rocket League camera settings
+pub const DEFAULT_FOV: f32 = PI / 3.;
+/// The emissive color is scaled down by the camera's exposure, so glows need to be this bright to be seen
+pub const GLOW_STRENGTH: f32 = 2000.;
 
 fn setup(mut commands: Commands) {
     commands.insert_resource(AmbientLight {
-        brightness: 500.,
+        brightness: DEFAULT_AMBIENT_BRIGHTNESS,
         ..default()
     });
 
@@ -62,34 +289,29 @@ fn setup(mut commands: Commands) {
         Sun,
     ));
 
-    #[allow(unused_variables, unused_mut)]
-    let mut camera_spawn = commands.spawn((
+    commands.spawn((
         PrimaryCamera::default(),
         Camera3d::default(),
-        PerspectiveProjection {
+        Projection::Perspective(PerspectiveProjection {
             near: 5.,
             far: 500_000.,
-            fov: PI / 3.,
+            fov: DEFAULT_FOV,
             ..default()
-        },
+        }),
         Transform::from_translation(Vec3::new(-3000., 1000., 0.)).looking_to(Vec3::X, Vec3::Y),
-        Camera { order: 0, ..default() },
-        Tonemapping::ReinhardLuminance,
-        if cfg!(feature = "ssao") {
-            ShadowFilteringMethod::Temporal
-        } else {
-            ShadowFilteringMethod::Gaussian
+        // bloom needs the extra range to tell glowing things apart from things that are just bright
+        Camera {
+            order: 0,
+            hdr: true,
+            ..default()
         },
+        Tonemapping::ReinhardLuminance,
         // AtmosphereCamera::default(),
-        if cfg!(feature = "ssao") { Msaa::Off } else { Msaa::default() },
+        #[cfg(feature = "sound")]
+        SpatialListener::new(EAR_GAP),
         Spectator,
     ));
 
-    #[cfg(feature = "ssao")]
-    camera_spawn
-        .insert(ScreenSpaceAmbientOcclusion::default())
-        .insert(TemporalAntiAliasing::default());
-
     commands.spawn((
         Camera2d,
         Camera {
@@ -169,11 +391,127 @@ fn daylight_cycle(
         if let Some((mut light_trans, mut directional)) = query.single_mut().into() {
             light_trans.translation = sun_position * 100_000.;
             light_trans.look_at(Vec3::ZERO, Vec3::Y);
-            directional.illuminance = t.sin().max(0.0).powi(2) * 10000.;
+            directional.illuminance = t.sin().max(0.0).powi(2) * MAX_SUN_ILLUMINANCE;
         }
     }
 }
 
+const DEFAULT_AMBIENT_BRIGHTNESS: f32 = 500.;
+const MAX_SUN_ILLUMINANCE: f32 = 10000.;
+
+/// Floodlights placed around the stadium for the night preset
+#[derive(Component)]
+struct StadiumLight;
+
+/// Where the stadium lights are, in Bevy's coordinates; they all point at the center of the field
+const STADIUM_LIGHT_POSITIONS: [Vec3; 4] = [
+    Vec3::new(-5000., 3000., -6000.),
+    Vec3::new(5000., 3000., -6000.),
+    Vec3::new(-5000., 3000., 6000.),
+    Vec3::new(5000., 3000., 6000.),
+];
+const STADIUM_LIGHT_INTENSITY: f32 = 2e12;
+const STADIUM_LIGHT_COLOR: Color = Color::srgb(0.95, 0.95, 1.);
+
+struct PresetLighting {
+    /// Direction towards the sun
+    sun_position: Vec3,
+    sun_illuminance: f32,
+    sun_color: Color,
+    ambient_brightness: f32,
+    ambient_color: Color,
+}
+
+impl PresetLighting {
+    fn new(preset: LightingPreset) -> Option<Self> {
+        match preset {
+            LightingPreset::DayCycle => None,
+            LightingPreset::Noon => Some(Self {
+                sun_position: Vec3::new(-0.3, 1., 0.2),
+                sun_illuminance: MAX_SUN_ILLUMINANCE,
+                sun_color: Color::WHITE,
+                ambient_brightness: DEFAULT_AMBIENT_BRIGHTNESS,
+                ambient_color: Color::WHITE,
+            }),
+            LightingPreset::Sunset => Some(Self {
+                sun_position: Vec3::new(-1., 0.12, 0.3),
+                sun_illuminance: MAX_SUN_ILLUMINANCE * 0.4,
+                sun_color: Color::srgb(1., 0.65, 0.4),
+                ambient_brightness: DEFAULT_AMBIENT_BRIGHTNESS * 0.6,
+                ambient_color: Color::srgb(1., 0.8, 0.7),
+            }),
+            LightingPreset::Night => Some(Self {
+                // below the horizon, so the sky goes dark
+                sun_position: Vec3::new(-1., -0.4, 0.),
+                sun_illuminance: 0.,
+                sun_color: Color::WHITE,
+                ambient_brightness: DEFAULT_AMBIENT_BRIGHTNESS * 0.3,
+                ambient_color: Color::srgb(0.6, 0.7, 1.),
+            }),
+            LightingPreset::Studio => Some(Self {
+                sun_position: Vec3::Y,
+                sun_illuminance: MAX_SUN_ILLUMINANCE * 0.2,
+                sun_color: Color::WHITE,
+                ambient_brightness: DEFAULT_AMBIENT_BRIGHTNESS * 6.,
+                ambient_color: Color::WHITE,
+            }),
+        }
+    }
+}
+
+fn apply_lighting_preset(
+    preset: Res<LightingPreset>,
+    mut commands: Commands,
+    mut atmosphere: AtmosphereMut<Nishita>,
+    mut ambient_light: ResMut<AmbientLight>,
+    mut sun: Query<(&mut Transform, &mut DirectionalLight), With<Sun>>,
+    stadium_lights: Query<Entity, With<StadiumLight>>,
+) {
+    let is_night = *preset == LightingPreset::Night;
+    if !is_night {
+        for entity in &stadium_lights {
+            commands.entity(entity).despawn();
+        }
+    } else if stadium_lights.is_empty() {
+        for pos in STADIUM_LIGHT_POSITIONS {
+            commands.spawn((
+                SpotLight {
+                    color: STADIUM_LIGHT_COLOR,
+                    intensity: STADIUM_LIGHT_INTENSITY,
+                    range: 20000.,
+                    outer_angle: PI / 5.,
+                    inner_angle: PI / 8.,
+                    ..default()
+                },
+                Transform::from_translation(pos).looking_at(Vec3::ZERO, Vec3::Y),
+                StadiumLight,
+            ));
+        }
+    }
+
+    let Some(lighting) = PresetLighting::new(*preset) else {
+        // the day cycle takes over the sun again
+        ambient_light.color = Color::WHITE;
+        ambient_light.brightness = DEFAULT_AMBIENT_BRIGHTNESS;
+        if let Ok((_, mut directional)) = sun.get_single_mut() {
+            directional.color = Color::WHITE;
+        }
+        return;
+    };
+
+    let sun_position = lighting.sun_position.normalize();
+    atmosphere.sun_position = sun_position;
+    ambient_light.color = lighting.ambient_color;
+    ambient_light.brightness = lighting.ambient_brightness;
+
+    if let Ok((mut light_trans, mut directional)) = sun.get_single_mut() {
+        light_trans.translation = sun_position * 100_000.;
+        light_trans.look_at(Vec3::ZERO, Vec3::Y);
+        directional.illuminance = lighting.sun_illuminance;
+        directional.color = lighting.sun_color;
+    }
+}
+
 #[cfg(debug_assertions)]
 #[derive(Component)]
 pub struct EntityName {
@@ -207,6 +545,105 @@ impl From<String> for EntityName {
 #[derive(Component, Clone, Copy, Default)]
 pub struct HighlightedEntity;
 
+#[derive(Resource)]
+struct OrbitState {
+    yaw: f32,
+    pitch: f32,
+    distance: f32,
+}
+
+impl Default for OrbitState {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            yaw: 0.,
+            pitch: 0.3,
+            distance: 1500.,
+        }
+    }
+}
+
+const MIN_ORBIT_DISTANCE: f32 = 200.;
+const MAX_ORBIT_DISTANCE: f32 = 10000.;
+/// How much one line of scrolling zooms in or out
+const ORBIT_ZOOM_STEP: f32 = 0.1;
+const MIN_ORBIT_CAMERA_HEIGHT: f32 = 30.;
+
+fn orbit_camera(
+    settings: Res<SpectatorSettings>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    ball: Query<&Transform, With<Ball>>,
+    cars: Query<(&Transform, &Car)>,
+    mut camera: Query<(&PrimaryCamera, &mut Transform), (Without<Ball>, Without<Car>)>,
+    mut motion: EventReader<MouseMotion>,
+    mut scroll: EventReader<MouseWheel>,
+    key: Res<ButtonInput<KeyCode>>,
+    keybinds: Res<Keybinds>,
+    mut orbit: ResMut<OrbitState>,
+    mut transition: ResMut<CameraTransition>,
+    time: Res<Time>,
+) {
+    let (&primary_camera, mut camera_transform) = camera.single_mut();
+
+    let PrimaryCamera::Orbit(target) = primary_camera else {
+        motion.clear();
+        scroll.clear();
+        return;
+    };
+
+    let target_pos = if target == 0 {
+        ball.get_single().ok().map(|transform| transform.translation)
+    } else {
+        cars.iter()
+            .find(|(_, car)| car.id() == target)
+            .map(|(transform, _)| transform.translation)
+    };
+
+    let Some(target_pos) = target_pos else {
+        motion.clear();
+        scroll.clear();
+        return;
+    };
+
+    // only take mouse input while the menu is closed
+    if windows
+        .get_single()
+        .is_ok_and(|window| window.cursor_options.grab_mode != CursorGrabMode::None)
+    {
+        let mouse_delta = motion.read().fold(Vec2::ZERO, |acc, d| acc + d.delta) * settings.sensitivity;
+        orbit.yaw -= mouse_delta.x;
+        orbit.pitch = (orbit.pitch + mouse_delta.y).clamp(-89f32.to_radians(), 89f32.to_radians());
+
+        // scrolling while holding the speed scrub modifier changes the game speed instead
+        let lines = if keybinds.pressed(&key, Action::SpeedScrubModifier) {
+            scroll.clear();
+            0.
+        } else {
+            scroll
+                .read()
+                .map(|event| match event.unit {
+                    MouseScrollUnit::Line => event.y,
+                    MouseScrollUnit::Pixel => event.y / 100.,
+                })
+                .sum::<f32>()
+        };
+
+        orbit.distance = (orbit.distance * (1. - lines * ORBIT_ZOOM_STEP)).clamp(MIN_ORBIT_DISTANCE, MAX_ORBIT_DISTANCE);
+    } else {
+        motion.clear();
+        scroll.clear();
+    }
+
+    let target_id = primary_camera.car_id().unwrap_or_default();
+    transition.update(primary_camera, target_id, &camera_transform, time.delta_secs());
+
+    let offset = Quat::from_euler(EulerRot::YXZ, orbit.yaw, -orbit.pitch, 0.) * Vec3::Z * orbit.distance;
+    camera_transform.translation = target_pos + offset;
+    camera_transform.translation.y = camera_transform.translation.y.max(MIN_ORBIT_CAMERA_HEIGHT);
+    camera_transform.look_at(target_pos, Vec3::Y);
+    transition.blend(&mut camera_transform);
+}
+
 pub struct CameraPlugin;
 
 impl Plugin for CameraPlugin {
@@ -221,18 +658,34 @@ impl Plugin for CameraPlugin {
             })
             .insert_resource(AtmosphereModel::default())
             .add_plugins((FramepacePlugin, AtmospherePlugin, Shape2dPlugin::default()))
-            .add_systems(Update, daylight_cycle);
+            .add_systems(
+                Update,
+                (
+                    apply_lighting_preset.run_if(resource_changed::<LightingPreset>),
+                    daylight_cycle.run_if(resource_equals(LightingPreset::DayCycle)),
+                )
+                    .chain(),
+            );
         }
 
         app.insert_resource(SpectatorSettings::default())
             .insert_resource(DaylightOffset::default())
             .insert_resource(DirectionalLightShadowMap::default())
-            .add_plugins((
-                SpectatorPlugin,
-                MeshPickingPlugin,
-                #[cfg(feature = "ssao")]
-                TemporalAntiAliasPlugin,
-            ))
-            .add_systems(Startup, setup);
+            .insert_resource(OrbitState::default())
+            .insert_resource(CameraTransition::default())
+            .insert_resource(CarSlots::default())
+            .add_plugins((SpectatorPlugin, CameraPathPlugin, MeshPickingPlugin, TemporalAntiAliasPlugin))
+            .add_systems(Startup, setup)
+            .add_systems(
+                Update,
+                (
+                    (
+                        update_car_slots.run_if(resource_changed::<GameStates>),
+                        follow_car_by_name.run_if(resource_changed::<GameStates>),
+                    ),
+                    orbit_camera,
+                    free_camera_transition,
+                ),
+            );
     }
 }
@@ -0,0 +1,259 @@
+use crate::{
+    camera::PrimaryCamera,
+    settings::{
+        gui::Toast,
+        keybinds::{Action, Keybinds},
+        options::{MenuFocused, Options},
+    },
+    spectator::Spectator,
+};
+use bevy::{math::VectorSpace, prelude::*};
+use serde::{Deserialize, Serialize};
+use std::{fs, io};
+
+pub struct CameraPathPlugin;
+
+impl Plugin for CameraPathPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(CameraPath::default()).add_systems(
+            Update,
+            (
+                listen.run_if(resource_equals(MenuFocused(false))),
+                record_camera_path.run_if(|path: Res<CameraPath>| path.is_recording()),
+                play_camera_path.run_if(|path: Res<CameraPath>| path.is_playing()),
+            )
+                .chain(),
+        );
+    }
+}
+
+pub const CAMERA_PATH_FILE: &str = "camera_path.json";
+
+/// How often the free camera's position is saved while recording a path, in seconds
+const KEYFRAME_INTERVAL: f32 = 0.1;
+
+/// Where the free camera was at some point during the recording
+#[derive(Clone, Copy, Serialize, Deserialize)]
+struct CameraKeyframe {
+    /// Seconds since the recording started
+    time: f32,
+    translation: Vec3,
+    rotation: Quat,
+}
+
+impl CameraKeyframe {
+    fn new(time: f32, transform: &Transform) -> Self {
+        Self {
+            time,
+            translation: transform.translation,
+            rotation: transform.rotation,
+        }
+    }
+}
+
+enum PathState {
+    Idle,
+    Recording { elapsed: f32, since_keyframe: f32 },
+    Playing { elapsed: f32 },
+}
+
+/// A free camera path that can be recorded, saved, and played back smoothly for cinematics
+#[derive(Resource)]
+pub struct CameraPath {
+    keyframes: Vec<CameraKeyframe>,
+    state: PathState,
+}
+
+impl Default for CameraPath {
+    fn default() -> Self {
+        Self {
+            keyframes: Vec::new(),
+            state: PathState::Idle,
+        }
+    }
+}
+
+impl CameraPath {
+    #[inline]
+    pub const fn is_recording(&self) -> bool {
+        matches!(self.state, PathState::Recording { .. })
+    }
+
+    #[inline]
+    pub const fn is_playing(&self) -> bool {
+        matches!(self.state, PathState::Playing { .. })
+    }
+
+    /// Whether there are enough keyframes to move the camera between
+    #[inline]
+    pub fn is_playable(&self) -> bool {
+        self.keyframes.len() >= 2
+    }
+
+    /// How long the path takes to play, in seconds
+    pub fn duration(&self) -> f32 {
+        self.keyframes.last().map_or(0., |keyframe| keyframe.time)
+    }
+
+    /// Throws away the current path and starts recording a new one
+    pub fn record(&mut self) {
+        self.keyframes.clear();
+        self.state = PathState::Recording {
+            elapsed: 0.,
+            // save the first keyframe right away
+            since_keyframe: KEYFRAME_INTERVAL,
+        };
+    }
+
+    /// Plays the path from the start, returning false if there isn't one to play
+    pub fn play(&mut self) -> bool {
+        if !self.is_playable() {
+            return false;
+        }
+
+        self.state = PathState::Playing { elapsed: 0. };
+        true
+    }
+
+    /// Stops recording or playing the path
+    pub fn stop(&mut self) {
+        self.state = PathState::Idle;
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        fs::write(CAMERA_PATH_FILE, serde_json::to_string(&self.keyframes)?)
+    }
+
+    pub fn load(&mut self) -> io::Result<()> {
+        self.keyframes = serde_json::from_str(&fs::read_to_string(CAMERA_PATH_FILE)?)?;
+        self.state = PathState::Idle;
+        Ok(())
+    }
+
+    /// Where the camera should be `time` seconds into the path, smoothed through the keyframes with a Catmull-Rom spline
+    fn sample(&self, time: f32) -> Option<Transform> {
+        let last = self.keyframes.len().checked_sub(1)?;
+
+        // the keyframe that starts the segment the time falls in
+        let i = self
+            .keyframes
+            .partition_point(|keyframe| keyframe.time <= time)
+            .saturating_sub(1)
+            .min(last.saturating_sub(1));
+
+        let [p0, p1, p2, p3] = [i.saturating_sub(1), i, (i + 1).min(last), (i + 2).min(last)].map(|i| self.keyframes[i]);
+
+        let segment = p2.time - p1.time;
+        let t = if segment > 0. {
+            ((time - p1.time) / segment).clamp(0., 1.)
+        } else {
+            0.
+        };
+
+        let translation = catmull_rom([p0, p1, p2, p3].map(|keyframe| keyframe.translation), t);
+
+        // keep the rotations on the same side as the start of the segment so it doesn't spin the long way around
+        let rotations = [p0, p1, p2, p3].map(|keyframe| {
+            let rotation = Vec4::from(keyframe.rotation);
+            if rotation.dot(Vec4::from(p1.rotation)) < 0. {
+                -rotation
+            } else {
+                rotation
+            }
+        });
+        let rotation = Quat::from_vec4(catmull_rom(rotations, t)).normalize();
+
+        Some(Transform::from_translation(translation).with_rotation(rotation))
+    }
+}
+
+/// A uniform Catmull-Rom spline between `p[1]` and `p[2]`, which passes through every keyframe
+fn catmull_rom<P: VectorSpace>([p0, p1, p2, p3]: [P; 4], t: f32) -> P {
+    let t2 = t * t;
+    let t3 = t2 * t;
+
+    (p1 * 2. + (p2 - p0) * t + (p0 * 2. - p1 * 5. + p2 * 4. - p3) * t2 + (p1 * 3. - p0 - p2 * 3. + p3) * t3) * 0.5
+}
+
+fn listen(
+    key: Res<ButtonInput<KeyCode>>,
+    keybinds: Res<Keybinds>,
+    mut options: ResMut<Options>,
+    mut camera_path: ResMut<CameraPath>,
+    mut toast: ResMut<Toast>,
+) {
+    if keybinds.just_pressed(&key, Action::RecordCameraPath) {
+        if camera_path.is_recording() {
+            camera_path.stop();
+            toast.show(format!("Recorded a {:.1}s camera path", camera_path.duration()));
+        } else if options.camera_state != PrimaryCamera::Spectator {
+            toast.show(String::from("Switch to the free camera to record a camera path"));
+        } else {
+            camera_path.record();
+            toast.show(String::from("Recording camera path"));
+        }
+    }
+
+    if keybinds.just_pressed(&key, Action::PlayCameraPath) {
+        if camera_path.is_playing() {
+            camera_path.stop();
+        } else if camera_path.play() {
+            options.camera_state = PrimaryCamera::Spectator;
+        } else {
+            toast.show(String::from("Record or load a camera path to play it"));
+        }
+    }
+}
+
+fn record_camera_path(
+    time: Res<Time>,
+    primary_camera: Query<&PrimaryCamera>,
+    cameras: Query<&Transform, With<Spectator>>,
+    mut camera_path: ResMut<CameraPath>,
+) {
+    let (Ok(&PrimaryCamera::Spectator), Ok(transform)) = (primary_camera.get_single(), cameras.get_single()) else {
+        camera_path.stop();
+        return;
+    };
+
+    let camera_path = &mut *camera_path;
+    let PathState::Recording { elapsed, since_keyframe } = &mut camera_path.state else {
+        return;
+    };
+
+    if *since_keyframe >= KEYFRAME_INTERVAL {
+        *since_keyframe %= KEYFRAME_INTERVAL;
+        camera_path.keyframes.push(CameraKeyframe::new(*elapsed, transform));
+    }
+
+    *elapsed += time.delta_secs();
+    *since_keyframe += time.delta_secs();
+}
+
+fn play_camera_path(
+    time: Res<Time>,
+    primary_camera: Query<&PrimaryCamera>,
+    mut cameras: Query<&mut Transform, With<Spectator>>,
+    mut camera_path: ResMut<CameraPath>,
+) {
+    // switching to another camera stops the playback
+    let (Ok(&PrimaryCamera::Spectator), Ok(mut transform)) = (primary_camera.get_single(), cameras.get_single_mut()) else {
+        camera_path.stop();
+        return;
+    };
+
+    let PathState::Playing { elapsed } = &mut camera_path.state else {
+        return;
+    };
+
+    *elapsed += time.delta_secs();
+    let elapsed = *elapsed;
+
+    if let Some(sample) = camera_path.sample(elapsed) {
+        *transform = sample;
+    }
+
+    if elapsed >= camera_path.duration() {
+        camera_path.stop();
+    }
+}
@@ -0,0 +1,407 @@
+use crate::{
+    camera::PrimaryCamera,
+    settings::{
+        gui::Toast,
+        keybinds::{Action, Keybinds},
+        options::{GameSpeed, MenuFocused, Options},
+    },
+};
+use bevy::{
+    core_pipeline::{bloom::Bloom, tonemapping::Tonemapping},
+    prelude::*,
+    render::{
+        camera::RenderTarget,
+        render_asset::RenderAssetUsages,
+        render_resource::{Extent3d, TextureDimension, TextureFormat, TextureUsages},
+        renderer::RenderDevice,
+        view::screenshot::{save_to_disk, Screenshot, ScreenshotCaptured},
+    },
+    window::PrimaryWindow,
+};
+use std::{
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+    process::{Child, ChildStdin, Command, Stdio},
+    sync::{Arc, Mutex},
+    thread,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+pub struct CapturePlugin;
+
+impl Plugin for CapturePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Recording::default()).add_systems(
+            Update,
+            (
+                (listen, start_supersampled_screenshot).run_if(resource_equals(MenuFocused(false))),
+                capture_frames.run_if(|recording: Res<Recording>| recording.is_recording()),
+                capture_supersampled_screenshot,
+            )
+                .chain(),
+        );
+    }
+}
+
+const SCREENSHOT_FOLDER: &str = "screenshots";
+
+/// Seconds since the unix epoch, used to give captures unique names
+pub fn unix_timestamp() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default()
+}
+
+/// Saves a screenshot of the primary window to `screenshots/{name}.png` at the end of the frame
+pub fn save_screenshot(commands: &mut Commands, name: &str) {
+    if let Err(e) = fs::create_dir_all(SCREENSHOT_FOLDER) {
+        error!("Failed to create {SCREENSHOT_FOLDER} folder due to: {e}");
+        return;
+    }
+
+    let path = Path::new(SCREENSHOT_FOLDER).join(format!("{name}.png"));
+    info!("Saving screenshot to {}", path.display());
+
+    commands.spawn(Screenshot::primary_window()).observe(save_to_disk(path));
+}
+
+/// A screenshot path in `screenshots/` that isn't taken yet, even when there are a few screenshots in the same second
+fn unique_screenshot_path(name: &str) -> PathBuf {
+    let folder = Path::new(SCREENSHOT_FOLDER);
+    let mut path = folder.join(format!("{name}.png"));
+
+    let mut copy = 1;
+    while path.exists() {
+        path = folder.join(format!("{name}_{copy}.png"));
+        copy += 1;
+    }
+
+    path
+}
+
+/// How many frames the supersampled camera renders before it's captured, so bloom and auto exposure have caught up
+const SUPERSAMPLE_WARMUP_FRAMES: u8 = 2;
+
+/// Renders what the primary camera sees to an image that's bigger than the window, and is despawned once it's been saved
+#[derive(Component)]
+struct SupersampledCamera {
+    frames_left: u8,
+    /// Taken once the screenshot is requested
+    path: Option<PathBuf>,
+}
+
+/// Starts a screenshot of the 3D view at `screenshot_scale` times the window's resolution, without the UI
+fn start_supersampled_screenshot(
+    key: Res<ButtonInput<KeyCode>>,
+    keybinds: Res<Keybinds>,
+    options: Res<Options>,
+    render_device: Res<RenderDevice>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    primary_camera: Query<(&Transform, &Projection, &Tonemapping, &Msaa, Option<&Bloom>), With<PrimaryCamera>>,
+    pending: Query<(), With<SupersampledCamera>>,
+    mut images: ResMut<Assets<Image>>,
+    mut toast: ResMut<Toast>,
+    mut commands: Commands,
+) {
+    if !keybinds.just_pressed(&key, Action::Screenshot) || !pending.is_empty() {
+        return;
+    }
+
+    let (Ok(window), Ok((transform, projection, tonemapping, msaa, bloom))) =
+        (windows.get_single(), primary_camera.get_single())
+    else {
+        return;
+    };
+
+    if let Err(e) = fs::create_dir_all(SCREENSHOT_FOLDER) {
+        error!("Failed to create {SCREENSHOT_FOLDER} folder due to: {e}");
+        return;
+    }
+
+    // the biggest texture the GPU can render to limits how far the window can be scaled up
+    let window_size = window.physical_size().max(UVec2::ONE);
+    let max_scale = render_device.limits().max_texture_dimension_2d / window_size.max_element();
+    let scale = options.screenshot_scale.min(max_scale).max(1);
+    let size = window_size * scale;
+
+    let mut image = Image::new_fill(
+        Extent3d {
+            width: size.x,
+            height: size.y,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        &[0, 0, 0, 255],
+        TextureFormat::Bgra8UnormSrgb,
+        RenderAssetUsages::RENDER_WORLD,
+    );
+    image.texture_descriptor.usage =
+        TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_DST | TextureUsages::RENDER_ATTACHMENT;
+
+    let path = unique_screenshot_path(&format!("screenshot_{}", unix_timestamp()));
+    toast.show(format!("Taking a {}x{} screenshot", size.x, size.y));
+
+    let mut camera = commands.spawn((
+        SupersampledCamera {
+            frames_left: SUPERSAMPLE_WARMUP_FRAMES,
+            path: Some(path),
+        },
+        Camera3d::default(),
+        projection.clone(),
+        *transform,
+        Camera {
+            target: RenderTarget::Image(images.add(image)),
+            hdr: true,
+            ..default()
+        },
+        *tonemapping,
+        *msaa,
+    ));
+
+    if let Some(bloom) = bloom {
+        camera.insert(bloom.clone());
+    }
+}
+
+fn capture_supersampled_screenshot(mut cameras: Query<(Entity, &Camera, &mut SupersampledCamera)>, mut commands: Commands) {
+    for (entity, camera, mut supersampled) in &mut cameras {
+        if supersampled.frames_left > 0 {
+            supersampled.frames_left -= 1;
+            continue;
+        }
+
+        let (RenderTarget::Image(image), Some(path)) = (&camera.target, supersampled.path.take()) else {
+            continue;
+        };
+
+        commands.spawn(Screenshot::image(image.clone())).observe(
+            move |trigger: Trigger<ScreenshotCaptured>, mut toast: ResMut<Toast>, mut commands: Commands| {
+                commands.entity(entity).despawn();
+
+                let image = trigger.event().0.clone();
+                let path = path.clone();
+                toast.show(format!("Saving screenshot to {}", path.display()));
+
+                // a big PNG takes a while to encode, so it's saved without holding up the next frame
+                thread::spawn(move || {
+                    let saved = image.try_into_dynamic().map_err(|e| e.to_string()).and_then(|image| {
+                        image
+                            .to_rgb8()
+                            .save_with_format(&path, image::ImageFormat::Png)
+                            .map_err(|e| e.to_string())
+                    });
+
+                    match saved {
+                        Ok(()) => info!("Saved screenshot to {}", path.display()),
+                        Err(e) => error!("Failed to save {} due to: {e}", path.display()),
+                    }
+                });
+            },
+        );
+    }
+}
+
+/// Where the frames of a recording go
+enum RecordingOutput {
+    /// Each frame is saved as a numbered PNG in this folder
+    Images(PathBuf),
+    /// Frames are piped to ffmpeg, which is started once the size of the frames is known
+    Ffmpeg {
+        path: PathBuf,
+        fps: u32,
+        encoder: Option<(Child, ChildStdin, UVec2)>,
+    },
+}
+
+impl RecordingOutput {
+    fn write_frame(&mut self, frame: u32, image: &Image) {
+        let rgb = match image.clone().try_into_dynamic() {
+            Ok(image) => image.to_rgb8(),
+            Err(e) => {
+                error!("Failed to convert recorded frame due to: {e}");
+                return;
+            }
+        };
+
+        match self {
+            Self::Images(folder) => {
+                let path = folder.join(format!("frame_{frame:06}.png"));
+                if let Err(e) = rgb.save_with_format(&path, image::ImageFormat::Png) {
+                    error!("Failed to save {} due to: {e}", path.display());
+                }
+            }
+            Self::Ffmpeg { path, fps, encoder } => {
+                let size = UVec2::new(rgb.width(), rgb.height());
+
+                if encoder.is_none() {
+                    match start_ffmpeg(path, *fps, size) {
+                        Ok(started) => *encoder = Some(started),
+                        Err(e) => {
+                            error!("Failed to start ffmpeg due to: {e}");
+                            return;
+                        }
+                    }
+                }
+
+                let Some((_, stdin, encoder_size)) = encoder else {
+                    return;
+                };
+
+                // ffmpeg can't change the size of the video partway through
+                if *encoder_size != size {
+                    warn!("Skipping recorded frame because the window was resized");
+                    return;
+                }
+
+                if let Err(e) = stdin.write_all(rgb.as_raw()) {
+                    error!("Failed to send recorded frame to ffmpeg due to: {e}");
+                }
+            }
+        }
+    }
+}
+
+impl Drop for RecordingOutput {
+    fn drop(&mut self) {
+        let Self::Ffmpeg { path, encoder, .. } = self else {
+            return;
+        };
+
+        let Some((mut child, stdin, _)) = encoder.take() else {
+            return;
+        };
+
+        // closing stdin tells ffmpeg there are no more frames, and it can take a while to finish encoding after that
+        drop(stdin);
+
+        let path = path.clone();
+        thread::spawn(move || match child.wait() {
+            Ok(status) if status.success() => info!("Saved recording to {}", path.display()),
+            Ok(status) => error!("ffmpeg exited with {status} while saving {}", path.display()),
+            Err(e) => error!("Failed to wait for ffmpeg due to: {e}"),
+        });
+    }
+}
+
+fn has_ffmpeg() -> bool {
+    Command::new("ffmpeg")
+        .arg("-version")
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .is_ok_and(|status| status.success())
+}
+
+fn start_ffmpeg(path: &Path, fps: u32, size: UVec2) -> std::io::Result<(Child, ChildStdin, UVec2)> {
+    let mut child = Command::new("ffmpeg")
+        .args(["-y", "-loglevel", "error", "-f", "rawvideo", "-pix_fmt", "rgb24", "-s"])
+        .arg(format!("{}x{}", size.x, size.y))
+        .arg("-framerate")
+        .arg(fps.to_string())
+        .args(["-i", "-", "-c:v", "libx264", "-pix_fmt", "yuv420p"])
+        // libx264 needs even dimensions
+        .args(["-vf", "pad=ceil(iw/2)*2:ceil(ih/2)*2"])
+        .arg(path)
+        .stdin(Stdio::piped())
+        .spawn()?;
+
+    let stdin = child.stdin.take().unwrap();
+    Ok((child, stdin, size))
+}
+
+struct ActiveRecording {
+    /// Shared with the screenshots that haven't been captured yet, which write to it when they're ready
+    output: Arc<Mutex<RecordingOutput>>,
+    frame_interval: f32,
+    frames: u32,
+    since_frame: f32,
+}
+
+/// Captures the primary window at a fixed rate of game time, to either an MP4 or a PNG sequence
+#[derive(Resource, Default)]
+pub struct Recording(Option<ActiveRecording>);
+
+impl Recording {
+    #[inline]
+    pub const fn is_recording(&self) -> bool {
+        self.0.is_some()
+    }
+
+    /// Starts a new recording in `folder`, or stops the current one
+    pub fn toggle(&mut self, folder: &str, fps: u32) {
+        if let Some(recording) = self.0.take() {
+            info!("Stopped recording after {} frames", recording.frames);
+            return;
+        }
+
+        let name = format!("recording_{}", unix_timestamp());
+        let output = if has_ffmpeg() {
+            if let Err(e) = fs::create_dir_all(folder) {
+                error!("Failed to create {folder} folder due to: {e}");
+                return;
+            }
+
+            let path = Path::new(folder).join(format!("{name}.mp4"));
+            info!("Recording to {}", path.display());
+            RecordingOutput::Ffmpeg {
+                path,
+                fps,
+                encoder: None,
+            }
+        } else {
+            let path = Path::new(folder).join(name);
+            if let Err(e) = fs::create_dir_all(&path) {
+                error!("Failed to create {} folder due to: {e}", path.display());
+                return;
+            }
+
+            info!("ffmpeg wasn't found, recording frames to {}", path.display());
+            RecordingOutput::Images(path)
+        };
+
+        let frame_interval = 1. / fps.max(1) as f32;
+        self.0 = Some(ActiveRecording {
+            output: Arc::new(Mutex::new(output)),
+            frame_interval,
+            frames: 0,
+            // capture the first frame right away
+            since_frame: frame_interval,
+        });
+    }
+}
+
+fn listen(key: Res<ButtonInput<KeyCode>>, keybinds: Res<Keybinds>, options: Res<Options>, mut recording: ResMut<Recording>) {
+    if keybinds.just_pressed(&key, Action::ToggleRecording) {
+        recording.toggle(&options.capture_path, options.capture_fps);
+    }
+}
+
+/// Takes at most one frame per rendered frame,
+/// so the recording plays back too fast if the visualizer renders slower than the capture rate
+fn capture_frames(time: Res<Time>, game_speed: Res<GameSpeed>, mut recording: ResMut<Recording>, mut commands: Commands) {
+    let Some(recording) = &mut recording.0 else {
+        return;
+    };
+
+    if !game_speed.paused {
+        recording.since_frame += time.delta_secs() * game_speed.speed;
+    }
+
+    if recording.since_frame < recording.frame_interval {
+        return;
+    }
+
+    recording.since_frame %= recording.frame_interval;
+
+    let frame = recording.frames;
+    recording.frames += 1;
+
+    let output = recording.output.clone();
+    commands
+        .spawn(Screenshot::primary_window())
+        .observe(move |trigger: Trigger<ScreenshotCaptured>| {
+            output.lock().unwrap().write_frame(frame, &trigger.event().0);
+        });
+}
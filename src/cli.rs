@@ -0,0 +1,250 @@
+use crate::Transport;
+use std::{
+    env,
+    net::IpAddr,
+    ops::RangeInclusive,
+    path::{Path, PathBuf},
+    process,
+};
+
+const USAGE: &str = "\
+Usage: rlviser [OPTIONS] [PRIMARY_PORT] [SECONDARY_PORT]
+
+Arguments:
+  [PRIMARY_PORT]    Same as --primary-port
+  [SECONDARY_PORT]  Same as --secondary-port
+
+Options:
+      --primary-port <PORT>          The port of the simulation to connect to [default: 34254, or 23234 with rlbot]
+      --secondary-port <PORT>        The port to bind the UDP socket to [default: 45243]
+      --host <IP>                    The IPv4 or IPv6 address of the simulation [default: remote_host in the settings]
+      --transport <udp|tcp|rlbot>    How to connect to the simulation [default: udp]
+      --port-retry <FIRST>-<LAST>    Ports to try binding to if the secondary port is already in use
+      --settings <PATH>              The settings file to use [default: settings.txt]
+      --profile <NAME>               Apply a settings profile on startup, like performance or quality
+      --replay <FILE>                Play back a state script once connected, or a Rocket League .replay file
+      --headless                     Track the game state without opening a window
+      --new-instance                 Start a separate visualizer, even if one is already running
+      --take-over                    Make the running visualizer connect to these ports, even if it's connected elsewhere
+      --precook                      Build cache.zip from the game's assets, only in debug builds
+  -h, --help                         Print this message
+";
+
+/// The command line arguments
+///
+/// The ports can also be given as the first two positional arguments, which is how rlviser-py launches the visualizer
+#[derive(Default)]
+pub struct Args {
+    pub primary_port: Option<u16>,
+    pub secondary_port: Option<u16>,
+    pub host: Option<IpAddr>,
+    pub transport: Transport,
+    pub port_retry: Option<RangeInclusive<u16>>,
+    pub settings: Option<PathBuf>,
+    pub profile: Option<String>,
+    pub replay: Option<String>,
+    /// A `.replay` file given with `--replay`, which is played instead of connecting to a simulation
+    #[cfg(feature = "replay_import")]
+    pub replay_file: Option<PathBuf>,
+    pub headless: bool,
+    pub new_instance: bool,
+    pub take_over: bool,
+    pub precook: bool,
+}
+
+impl Args {
+    /// Parses the arguments the visualizer was started with, printing the usage and exiting if they're invalid or `--help` was given
+    pub fn from_env() -> Self {
+        match Self::parse(env::args().skip(1)) {
+            Ok(Some(args)) => args,
+            Ok(None) => {
+                print!("{USAGE}");
+                process::exit(0);
+            }
+            Err(e) => {
+                eprintln!("{e}\n\n{USAGE}");
+                process::exit(2);
+            }
+        }
+    }
+
+    /// `None` if the usage should be printed instead
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Option<Self>, String> {
+        let mut parsed = Self::default();
+        let mut positional = 0;
+
+        while let Some(arg) = args.next() {
+            // `--key=value` works the same as `--key value`
+            let (key, inline_value) = match arg.split_once('=') {
+                Some((key, value)) if arg.starts_with("--") => (key, Some(value.to_string())),
+                _ => (arg.as_str(), None),
+            };
+
+            let mut value = || {
+                inline_value
+                    .clone()
+                    .or_else(|| args.next())
+                    .ok_or_else(|| format!("Missing a value after {key}"))
+            };
+
+            match key {
+                "-h" | "--help" => return Ok(None),
+                "--primary-port" => parsed.primary_port = Some(parse_port(key, &value()?)?),
+                "--secondary-port" => parsed.secondary_port = Some(parse_port(key, &value()?)?),
+                "--host" => {
+                    let host = value()?;
+                    parsed.host = Some(
+                        host.parse()
+                            .map_err(|_| format!("Invalid host {host}, expected an IPv4 or IPv6 address"))?,
+                    );
+                }
+                "--transport" => parsed.transport = value()?.parse()?,
+                "--port-retry" => parsed.port_retry = Some(parse_port_range(&value()?)?),
+                "--settings" => parsed.settings = Some(PathBuf::from(value()?)),
+                "--profile" => parsed.profile = Some(value()?),
+                "--replay" => {
+                    let replay = value()?;
+
+                    if Path::new(&replay)
+                        .extension()
+                        .is_some_and(|extension| extension.eq_ignore_ascii_case("replay"))
+                    {
+                        #[cfg(feature = "replay_import")]
+                        {
+                            parsed.replay_file = Some(PathBuf::from(replay));
+                        }
+                        #[cfg(not(feature = "replay_import"))]
+                        return Err(String::from(
+                            "RLViser was built without the replay_import feature, so it can't play .replay files",
+                        ));
+                    } else {
+                        parsed.replay = Some(replay);
+                    }
+                }
+                "--headless" => parsed.headless = true,
+                "--new-instance" => parsed.new_instance = true,
+                "--take-over" => parsed.take_over = true,
+                "--precook" => parsed.precook = true,
+                _ if key.starts_with('-') => return Err(format!("Unknown argument {key}")),
+                _ => {
+                    match positional {
+                        0 => parsed.primary_port = Some(parse_port("the primary port", key)?),
+                        1 => parsed.secondary_port = Some(parse_port("the secondary port", key)?),
+                        _ => return Err(format!("Unexpected argument {key}, only two ports can be given")),
+                    }
+
+                    positional += 1;
+                }
+            }
+        }
+
+        // a replay is watched in its own window, instead of being handed off to one that's watching a simulation
+        #[cfg(feature = "replay_import")]
+        if parsed.replay_file.is_some() {
+            parsed.transport = Transport::ReplayFile;
+            parsed.new_instance = true;
+        }
+
+        Ok(Some(parsed))
+    }
+}
+
+fn parse_port(name: &str, port: &str) -> Result<u16, String> {
+    port.parse()
+        .map_err(|_| format!("Invalid port {port} for {name}, expected a number from 0 to 65535"))
+}
+
+fn parse_port_range(range: &str) -> Result<RangeInclusive<u16>, String> {
+    range
+        .split_once('-')
+        .and_then(|(first, last)| Some(first.trim().parse().ok()?..=last.trim().parse().ok()?))
+        .filter(|range| !range.is_empty())
+        .ok_or_else(|| format!("Invalid port range {range}, expected something like 45244-45254"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Option<Args>, String> {
+        Args::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    fn parse_ok(args: &[&str]) -> Args {
+        match parse(args) {
+            Ok(Some(args)) => args,
+            Ok(None) => panic!("{args:?} printed the usage"),
+            Err(e) => panic!("{args:?} failed to parse: {e}"),
+        }
+    }
+
+    fn parse_err(args: &[&str]) -> String {
+        match parse(args) {
+            Err(e) => e,
+            Ok(_) => panic!("{args:?} should have been rejected"),
+        }
+    }
+
+    #[test]
+    fn positional_ports() {
+        let args = parse_ok(&["34254", "45243"]);
+        assert_eq!(args.primary_port, Some(34254));
+        assert_eq!(args.secondary_port, Some(45243));
+
+        let args = parse_ok(&["34254"]);
+        assert_eq!(args.primary_port, Some(34254));
+        assert_eq!(args.secondary_port, None);
+
+        parse_err(&["34254", "45243", "45244"]);
+        parse_err(&["65536"]);
+    }
+
+    #[test]
+    fn named_ports() {
+        let args = parse_ok(&["--secondary-port", "45243", "--primary-port", "34254"]);
+        assert_eq!(args.primary_port, Some(34254));
+        assert_eq!(args.secondary_port, Some(45243));
+
+        // named and positional ports can be mixed, with the last one winning
+        let args = parse_ok(&["--primary-port", "1", "34254"]);
+        assert_eq!(args.primary_port, Some(34254));
+
+        assert_eq!(parse_err(&["--primary-port"]), "Missing a value after --primary-port");
+    }
+
+    #[test]
+    fn key_equals_value() {
+        let args = parse_ok(&[
+            "--primary-port=34254",
+            "--host=::1",
+            "--transport=TCP",
+            "--port-retry=45244-45254",
+            "--settings=other settings.txt",
+        ]);
+
+        assert_eq!(args.primary_port, Some(34254));
+        assert_eq!(args.host, Some(IpAddr::V6(std::net::Ipv6Addr::LOCALHOST)));
+        assert!(args.transport == Transport::Tcp);
+        assert_eq!(args.port_retry, Some(45244..=45254));
+        assert_eq!(args.settings, Some(PathBuf::from("other settings.txt")));
+    }
+
+    #[test]
+    fn invalid_port_retry() {
+        for range in ["45244", "45244-", "abc-45254", "45244-65536", "45254-45244"] {
+            let e = parse_err(&["--port-retry", range]);
+            assert!(e.starts_with("Invalid port range"), "{range}: {e}");
+        }
+    }
+
+    #[test]
+    fn flags_and_help() {
+        let args = parse_ok(&["--headless", "--new-instance", "--take-over"]);
+        assert!(args.headless && args.new_instance && args.take_over);
+
+        assert!(matches!(parse(&["--primary-port", "34254", "-h"]), Ok(None)));
+        assert_eq!(parse_err(&["--unknown"]), "Unknown argument --unknown");
+        // `=` only splits options, not positional arguments
+        parse_err(&["34254=45243"]);
+    }
+}
@@ -0,0 +1,203 @@
+use crate::{mesh::FieldMeshInfo, settings::options::ShowCrowd};
+use bevy::{
+    asset::load_internal_asset,
+    prelude::*,
+    render::{
+        mesh::{Indices, PrimitiveTopology, VertexAttributeValues},
+        render_asset::RenderAssetUsages,
+        render_resource::{AsBindGroup, ShaderRef},
+        view::NoFrustumCulling,
+    },
+};
+
+pub struct CrowdPlugin;
+
+impl Plugin for CrowdPlugin {
+    fn build(&self, app: &mut App) {
+        load_internal_asset!(app, CROWD_SHADER_HANDLE, "crowd.wgsl", Shader::from_wgsl);
+
+        app.add_plugins(MaterialPlugin::<CrowdMaterial> {
+            // the billboards are moved in the vertex shader, which the prepass and shadows wouldn't know about
+            prepass_enabled: false,
+            shadows_enabled: false,
+            ..default()
+        })
+        .add_systems(Update, update_crowds);
+    }
+}
+
+const CROWD_SHADER_HANDLE: Handle<Shader> = Handle::weak_from_u128(0x6f1b_52a4_93c0_4e8d_b7a1_2d54_c9e3_0a17);
+
+/// The mesh in the stadium layout that covers the stands where the crowd would be
+const CROWD_SPAWNER_MESH: &str = "CrowdSpawnerMesh";
+
+/// How many crowd members there are for every square unit of the stands
+const CROWD_DENSITY: f32 = 1. / (110. * 110.);
+const MEMBER_HEIGHT: f32 = 150.;
+/// How much taller or shorter a crowd member can be, as a fraction of `MEMBER_HEIGHT`
+const MEMBER_HEIGHT_VARIATION: f32 = 0.15;
+/// How much darker a crowd member's shirt can be than the color painted on the spawner mesh
+const SHIRT_SHADE_VARIATION: f32 = 0.4;
+/// How many times per second, in radians, the wave moves around the stadium
+const WAVE_SPEED: f32 = 1.5;
+/// So the same crowd is spawned every time
+const CROWD_SEED: u32 = 0x2545_f491;
+
+#[derive(Asset, TypePath, AsBindGroup, Clone)]
+pub struct CrowdMaterial {
+    #[uniform(0)]
+    wave_speed: f32,
+}
+
+impl Material for CrowdMaterial {
+    fn vertex_shader() -> ShaderRef {
+        CROWD_SHADER_HANDLE.into()
+    }
+
+    fn fragment_shader() -> ShaderRef {
+        CROWD_SHADER_HANDLE.into()
+    }
+}
+
+/// The crowd billboards that stand on a spawner mesh
+#[derive(Component)]
+struct Crowd {
+    spawner: Entity,
+}
+
+/// A tiny xorshift generator, so the crowd doesn't need a dependency for randomness
+struct XorShift(u32);
+
+impl XorShift {
+    /// A random number from 0 to 1
+    fn next(&mut self) -> f32 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 17;
+        self.0 ^= self.0 << 5;
+        self.0 as f32 / u32::MAX as f32
+    }
+}
+
+fn update_crowds(
+    mut commands: Commands,
+    show_crowd: Res<ShowCrowd>,
+    spawners: Query<(Entity, &FieldMeshInfo, &Mesh3d, &Transform)>,
+    new_spawners: Query<(), Added<FieldMeshInfo>>,
+    crowds: Query<(Entity, &Crowd)>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<CrowdMaterial>>,
+    mut material: Local<Option<Handle<CrowdMaterial>>>,
+) {
+    if !show_crowd.is_changed() && new_spawners.is_empty() {
+        return;
+    }
+
+    // remove the crowds of spawners that were despawned when the field was reloaded, or all of them if they're turned off
+    for (entity, crowd) in &crowds {
+        if !show_crowd.0 || !spawners.contains(crowd.spawner) {
+            commands.entity(entity).despawn();
+        }
+    }
+
+    if !show_crowd.0 {
+        return;
+    }
+
+    let material = material.get_or_insert_with(|| materials.add(CrowdMaterial { wave_speed: WAVE_SPEED }));
+
+    for (entity, info, spawner_mesh, transform) in &spawners {
+        if !info.mesh.contains(CROWD_SPAWNER_MESH) || crowds.iter().any(|(_, crowd)| crowd.spawner == entity) {
+            continue;
+        }
+
+        let Some(crowd_mesh) = meshes.get(&spawner_mesh.0).and_then(build_crowd_mesh) else {
+            warn!("Couldn't spawn a crowd on {}", info.mesh);
+            continue;
+        };
+
+        commands.spawn((
+            Mesh3d(meshes.add(crowd_mesh)),
+            MeshMaterial3d(material.clone()),
+            *transform,
+            // the billboards stick out above the spawner mesh, so its bounds are too small
+            NoFrustumCulling,
+            Crowd { spawner: entity },
+        ));
+    }
+}
+
+/// Scatters crowd members across the triangles of the spawner mesh, with a billboard for each of them
+fn build_crowd_mesh(spawner: &Mesh) -> Option<Mesh> {
+    let positions = spawner.attribute(Mesh::ATTRIBUTE_POSITION)?.as_float3()?;
+    let colors = match spawner.attribute(Mesh::ATTRIBUTE_COLOR) {
+        Some(VertexAttributeValues::Float32x4(colors)) => Some(colors),
+        _ => None,
+    };
+
+    let indices = spawner.indices().map_or_else(
+        || (0..positions.len()).collect::<Vec<_>>(),
+        |indices| indices.iter().collect(),
+    );
+
+    let mut rng = XorShift(CROWD_SEED);
+    let mut members = Vec::new();
+
+    for triangle in indices.chunks_exact(3) {
+        let [a, b, c] = [triangle[0], triangle[1], triangle[2]].map(|i| Vec3::from(positions[i]));
+        let area = (b - a).cross(c - a).length() / 2.;
+
+        // the fractional part of a crowd member is spawned by chance, so small triangles still get some
+        let count = (area * CROWD_DENSITY + rng.next()) as usize;
+
+        for _ in 0..count {
+            let (mut u, mut v) = (rng.next(), rng.next());
+            if u + v > 1. {
+                (u, v) = (1. - u, 1. - v);
+            }
+
+            let color = colors.map_or(Vec4::ONE, |colors| {
+                let [ca, cb, cc] = [triangle[0], triangle[1], triangle[2]].map(|i| Vec4::from(colors[i]));
+                ca + (cb - ca) * u + (cc - ca) * v
+            });
+            let shade = 1. - rng.next() * SHIRT_SHADE_VARIATION;
+
+            members.push((
+                a + (b - a) * u + (c - a) * v,
+                (color.truncate() * shade).extend(1.),
+                rng.next(),
+                MEMBER_HEIGHT * (1. + (rng.next() * 2. - 1.) * MEMBER_HEIGHT_VARIATION),
+            ));
+        }
+    }
+
+    if members.is_empty() {
+        return None;
+    }
+
+    let mut feet = Vec::with_capacity(members.len() * 4);
+    let mut corners = Vec::<[f32; 2]>::with_capacity(members.len() * 4);
+    let mut member_info = Vec::with_capacity(members.len() * 4);
+    let mut member_colors = Vec::with_capacity(members.len() * 4);
+    let mut quads = Vec::with_capacity(members.len() * 6);
+
+    for (i, (pos, color, offset, height)) in members.into_iter().enumerate() {
+        for corner in [[0., 0.], [1., 0.], [1., 1.], [0., 1.]] {
+            feet.push(pos.to_array());
+            corners.push(corner);
+            member_info.push([offset, height]);
+            member_colors.push(color.to_array());
+        }
+
+        let first = i as u32 * 4;
+        quads.extend([first, first + 1, first + 2, first, first + 2, first + 3]);
+    }
+
+    Some(
+        Mesh::new(PrimitiveTopology::TriangleList, RenderAssetUsages::RENDER_WORLD)
+            .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, feet)
+            .with_inserted_attribute(Mesh::ATTRIBUTE_UV_0, corners)
+            .with_inserted_attribute(Mesh::ATTRIBUTE_UV_1, member_info)
+            .with_inserted_attribute(Mesh::ATTRIBUTE_COLOR, member_colors)
+            .with_inserted_indices(Indices::U32(quads)),
+    )
+}
@@ -0,0 +1,79 @@
+#import bevy_pbr::{
+    mesh_functions,
+    mesh_view_bindings::{globals, view},
+    view_transformations::position_world_to_clip,
+}
+
+@group(2) @binding(0) var<uniform> wave_speed: f32;
+
+// How high a crowd member jumps at the crest of the wave, relative to their height
+const WAVE_HEIGHT: f32 = 0.35;
+// How many times the wave goes around the stadium at once
+const WAVE_COUNT: f32 = 2.0;
+// How much the crowd members bob up and down while they wait for the wave
+const IDLE_HEIGHT: f32 = 0.03;
+const TAU: f32 = 6.28318530718;
+
+struct Vertex {
+    @builtin(instance_index) instance_index: u32,
+    // Where the crowd member is standing, the same for all 4 corners
+    @location(0) position: vec3<f32>,
+    // Which corner of the billboard this is, (0, 0) being the bottom left
+    @location(2) corner: vec2<f32>,
+    // A random offset for when the crowd member moves, and their height
+    @location(3) member: vec2<f32>,
+    @location(5) color: vec4<f32>,
+}
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) corner: vec2<f32>,
+    @location(1) color: vec4<f32>,
+    @location(2) skin: f32,
+}
+
+@vertex
+fn vertex(vertex: Vertex) -> VertexOutput {
+    let world_from_local = mesh_functions::get_world_from_local(vertex.instance_index);
+    let feet = mesh_functions::mesh_position_local_to_world(world_from_local, vec4<f32>(vertex.position, 1.0)).xyz;
+    let height = vertex.member.y;
+
+    // a stadium wave that travels around the field, with the whole crowd bobbing a little in between
+    let angle = atan2(feet.x, feet.z) * WAVE_COUNT;
+    let wave = pow(max(sin(globals.time * wave_speed - angle), 0.0), 8.0) * WAVE_HEIGHT;
+    let idle = (sin(globals.time * 3.0 + vertex.member.x * TAU) * 0.5 + 0.5) * IDLE_HEIGHT;
+
+    // turn to face the camera, but stay upright
+    let camera_right = view.world_from_view[0].xyz;
+    let right = normalize(vec3<f32>(camera_right.x, 0.0, camera_right.z));
+
+    let offset = right * (vertex.corner.x - 0.5) * height * 0.5
+        + vec3<f32>(0.0, (vertex.corner.y + wave + idle) * height, 0.0);
+
+    var out: VertexOutput;
+    out.position = position_world_to_clip(feet + offset);
+    out.corner = vertex.corner;
+    out.color = vertex.color;
+    out.skin = fract(vertex.member.x * 7.0);
+    return out;
+}
+
+@fragment
+fn fragment(in: VertexOutput) -> @location(0) vec4<f32> {
+    // the billboard is twice as tall as it is wide, so stretch x to keep the head round
+    let p = vec2<f32>((in.corner.x - 0.5) * 0.5, in.corner.y);
+
+    let head = length(p - vec2<f32>(0.0, 0.84)) < 0.13;
+    let body = in.corner.y < 0.68 && abs(p.x) < 0.2 - max(in.corner.y - 0.55, 0.0) * 0.8;
+
+    if !head && !body {
+        discard;
+    }
+
+    if head {
+        return vec4<f32>(mix(vec3<f32>(0.3, 0.2, 0.13), vec3<f32>(0.9, 0.72, 0.58), in.skin), 1.0);
+    }
+
+    // the shirts get darker towards the bottom so the crowd doesn't look flat
+    return vec4<f32>(in.color.rgb * mix(0.55, 1.0, in.corner.y / 0.68), 1.0);
+}
@@ -0,0 +1,168 @@
+use crate::{
+    hoops,
+    rocketsim::{BallState, CarInfo, GameMode, GameState},
+    udp::ToBevyVec,
+};
+use bevy::{math::Vec3A, prelude::*};
+
+/// How long to stay on a car when nothing interesting is happening, in seconds
+const IDLE_SHOT_LENGTH: f32 = 12.;
+/// Cuts that come sooner than this after the last one are ignored, so the camera doesn't flicker between cars
+const MIN_SHOT_LENGTH: f32 = 2.;
+
+/// Speed the ball needs to be going towards a goal for it to count as a shot
+const SHOT_MIN_SPEED: f32 = 1500.;
+/// How far into the future the ball is checked for reaching a goal, in seconds
+const SHOT_LOOKAHEAD: f32 = 1.5;
+/// Half the width of the goals, with some leeway for shots that just miss
+const SHOT_GOAL_HALF_WIDTH: f32 = 1100.;
+const GOAL_LINE_Y: f32 = 5120.;
+
+/// Height the ball needs to be above for a challenge to count as an aerial
+const AERIAL_MIN_HEIGHT: f32 = 500.;
+/// How close to the ball a car needs to be to be part of an aerial challenge
+const AERIAL_MAX_DIST: f32 = 800.;
+
+/// How long the camera takes to turn towards or away from the rim during close shots in hoops, in seconds
+const RIM_FOCUS_TIME: f32 = 0.4;
+/// How much of the way the camera turns from where it's looking towards the rim
+const RIM_FOCUS_AMOUNT: f32 = 0.6;
+
+/// Picks which car the Director camera follows
+#[derive(Resource)]
+pub struct Director {
+    target: u32,
+    since_cut: f32,
+    /// The rim of the last close shot in hoops
+    rim: Vec3A,
+    /// How far the camera has turned towards the rim, from 0 to 1
+    rim_focus: f32,
+}
+
+impl Default for Director {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            target: 0,
+            since_cut: IDLE_SHOT_LENGTH,
+            rim: Vec3A::ZERO,
+            rim_focus: 0.,
+        }
+    }
+}
+
+/// Whether the ball is heading towards a goal fast enough to be a shot
+fn is_shot_on_goal(ball: &BallState) -> bool {
+    if ball.vel.y.abs() < SHOT_MIN_SPEED {
+        return false;
+    }
+
+    let time_to_goal = (GOAL_LINE_Y * ball.vel.y.signum() - ball.pos.y) / ball.vel.y;
+    if !(0. ..=SHOT_LOOKAHEAD).contains(&time_to_goal) {
+        return false;
+    }
+
+    let x_at_goal = ball.pos.x + ball.vel.x * time_to_goal;
+    x_at_goal.abs() <= SHOT_GOAL_HALF_WIDTH
+}
+
+/// The car that touched the ball last, if the ball is on its way into a goal
+fn find_shooter(state: &GameState) -> Option<u32> {
+    let is_shot = match state.game_mode {
+        // hoops has a different kind of goal, and the void has none
+        GameMode::Hoops => hoops::close_shot_rim(state).is_some(),
+        GameMode::TheVoid => false,
+        _ => is_shot_on_goal(&state.ball),
+    };
+
+    if !is_shot {
+        return None;
+    }
+
+    state
+        .cars
+        .iter()
+        .filter(|car| car.state.ball_hit_info.is_valid)
+        .max_by_key(|car| car.state.ball_hit_info.tick_count_when_hit)
+        .map(|car| car.id)
+}
+
+/// The airborne car closest to the ball, if the ball is high enough up for it to be an aerial
+fn find_aerial_challenger(state: &GameState) -> Option<u32> {
+    if state.ball.pos.z < AERIAL_MIN_HEIGHT {
+        return None;
+    }
+
+    closest_car_to_ball(
+        state,
+        state.cars.iter().filter(|car| {
+            !car.state.is_on_ground && car.state.pos.distance_squared(state.ball.pos) < AERIAL_MAX_DIST * AERIAL_MAX_DIST
+        }),
+    )
+}
+
+fn closest_car_to_ball<'a>(state: &GameState, cars: impl Iterator<Item = &'a CarInfo>) -> Option<u32> {
+    cars.filter(|car| !car.state.is_demoed)
+        .min_by(|a, b| {
+            let a = a.state.pos.distance_squared(state.ball.pos);
+            let b = b.state.pos.distance_squared(state.ball.pos);
+            a.total_cmp(&b)
+        })
+        .map(|car| car.id)
+}
+
+impl Director {
+    /// Advances the director by `delta` seconds and returns the id of the car that should be followed
+    pub fn update(&mut self, state: &GameState, delta: f32) -> u32 {
+        self.since_cut += delta;
+
+        let close_shot_rim = hoops::close_shot_rim(state);
+        if let Some(rim) = close_shot_rim {
+            self.rim = rim;
+        }
+
+        let focus_step = delta / RIM_FOCUS_TIME;
+        self.rim_focus = if close_shot_rim.is_some() {
+            self.rim_focus + focus_step
+        } else {
+            self.rim_focus - focus_step
+        }
+        .clamp(0., 1.);
+
+        let target_exists = state.cars.iter().any(|car| car.id == self.target);
+        let new_target = if !target_exists {
+            closest_car_to_ball(state, state.cars.iter())
+        } else if self.since_cut < MIN_SHOT_LENGTH {
+            None
+        } else {
+            find_shooter(state).or_else(|| find_aerial_challenger(state)).or_else(|| {
+                (self.since_cut >= IDLE_SHOT_LENGTH)
+                    .then(|| closest_car_to_ball(state, state.cars.iter()))
+                    .flatten()
+            })
+        };
+
+        if let Some(new_target) = new_target.filter(|&new_target| new_target != self.target) {
+            self.target = new_target;
+            self.since_cut = 0.;
+        } else if new_target.is_some() {
+            // the same car was picked again, so it counts as a fresh shot of it
+            self.since_cut = 0.;
+        }
+
+        self.target
+    }
+
+    /// Turns the camera up towards the rim during close shots in hoops
+    pub fn look_at_rim(&self, camera: &mut Transform) {
+        if self.rim_focus <= 0. {
+            return;
+        }
+
+        // ease in and out
+        let t = self.rim_focus * self.rim_focus * (3. - 2. * self.rim_focus);
+
+        let target = camera.looking_at(self.rim.to_bevy(), Vec3::Y).rotation;
+        camera.rotation = camera.rotation.slerp(target, t * RIM_FOCUS_AMOUNT);
+    }
+}
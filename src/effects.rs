@@ -0,0 +1,582 @@
+use crate::{
+    camera::PrimaryCamera,
+    goal::GoalScored,
+    rocketsim::Team,
+    settings::{
+        gui::Toast,
+        keybinds::{Action, Keybinds},
+        options::{GameSpeed, GoalExplosion, ImpactCameraShake, MenuFocused, Options, PacketSmoothing, ReplayOnGoal},
+    },
+    udp::{get_color_from_team, Connection, GameStates, LastPacketTimesElapsed, SendableUdp, ToBevyVec},
+};
+use bevy::{
+    color::palettes::css,
+    pbr::{NotShadowCaster, NotShadowReceiver},
+    prelude::*,
+    transform::TransformSystem,
+    utils::HashMap,
+};
+use std::f32::consts::PI;
+
+pub struct EffectsPlugin;
+
+impl Plugin for EffectsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(CameraShake::default())
+            .insert_resource(GoalSlowMotion::default())
+            .insert_resource(BulletTime::default())
+            .add_systems(Startup, setup)
+            .add_systems(First, remove_camera_shake)
+            .add_systems(
+                Update,
+                (
+                    pad_pickup_bursts,
+                    demo_explosions,
+                    goal_explosions.run_if(on_event::<GoalScored>),
+                    impact_camera_shake,
+                    update_particles,
+                    update_goal_flashes,
+                    end_goal_slow_motion
+                        .run_if(|slow_motion: Res<GoalSlowMotion>| slow_motion.is_active())
+                        .run_if(resource_exists::<Connection>),
+                    (
+                        toggle_bullet_time.run_if(resource_equals(MenuFocused(false))),
+                        ramp_bullet_time.run_if(|bullet_time: Res<BulletTime>| bullet_time.is_active()),
+                    )
+                        .chain()
+                        .run_if(resource_exists::<Connection>),
+                ),
+            )
+            .add_systems(
+                PostUpdate,
+                apply_camera_shake
+                    .run_if(|shake: Res<CameraShake>| shake.strength > 0.)
+                    .before(TransformSystem::TransformPropagate),
+            );
+    }
+}
+
+#[derive(Resource)]
+struct ParticleAssets {
+    mesh: Handle<Mesh>,
+    small_pad: Handle<StandardMaterial>,
+    big_pad: Handle<StandardMaterial>,
+    /// Blue, then orange
+    team: [Handle<StandardMaterial>; 2],
+}
+
+#[derive(Component)]
+#[require(Mesh3d, MeshMaterial3d<StandardMaterial>, NotShadowCaster, NotShadowReceiver)]
+struct Particle {
+    velocity: Vec3,
+    scale: f32,
+    lifetime: Timer,
+}
+
+/// How close a car has to be to a pad for it to be the one that picked it up
+const PICKUP_DISTANCE: f32 = 300.;
+const PAD_PARTICLES: usize = 12;
+const PAD_PARTICLE_SPEED: f32 = 400.;
+const PAD_PARTICLE_LIFETIME: f32 = 0.4;
+const DEMO_PARTICLES: usize = 40;
+const DEMO_PARTICLE_SPEED: f32 = 1200.;
+const DEMO_PARTICLE_LIFETIME: f32 = 0.8;
+const GOAL_PARTICLES: usize = 120;
+const GOAL_PARTICLE_SPEED: f32 = 3000.;
+const GOAL_PARTICLE_LIFETIME: f32 = 1.5;
+const PARTICLE_GRAVITY: f32 = -650.;
+const PARTICLE_DRAG: f32 = 2.;
+
+fn setup(mut commands: Commands, mut meshes: ResMut<Assets<Mesh>>, mut materials: ResMut<Assets<StandardMaterial>>) {
+    let mut unlit = |color: Color| {
+        materials.add(StandardMaterial {
+            base_color: color,
+            unlit: true,
+            ..default()
+        })
+    };
+
+    commands.insert_resource(ParticleAssets {
+        mesh: meshes.add(Sphere::new(1.).mesh().ico(1).unwrap()),
+        small_pad: unlit(Color::from(css::LIGHT_YELLOW)),
+        big_pad: unlit(Color::from(css::ORANGE)),
+        team: [
+            unlit(get_color_from_team(Team::Blue)),
+            unlit(get_color_from_team(Team::Orange)),
+        ],
+    });
+}
+
+/// Evenly spreads `count` directions over a sphere, so bursts look the same every time
+fn burst_directions(count: usize) -> impl Iterator<Item = Vec3> {
+    // the golden angle
+    let angle_step = PI * (3. - 5f32.sqrt());
+
+    (0..count).map(move |i| {
+        let y = 1. - (i as f32 + 0.5) / count as f32 * 2.;
+        let radius = (1. - y * y).sqrt();
+        let theta = angle_step * i as f32;
+
+        Vec3::new(theta.cos() * radius, y, theta.sin() * radius)
+    })
+}
+
+fn spawn_burst(
+    commands: &mut Commands,
+    assets: &ParticleAssets,
+    material: &Handle<StandardMaterial>,
+    pos: Vec3,
+    count: usize,
+    speed: f32,
+    scale: f32,
+    lifetime: f32,
+) {
+    for direction in burst_directions(count) {
+        commands.spawn((
+            Mesh3d(assets.mesh.clone()),
+            MeshMaterial3d(material.clone()),
+            Transform::from_translation(pos).with_scale(Vec3::splat(scale)),
+            Particle {
+                velocity: direction * speed,
+                scale,
+                lifetime: Timer::from_seconds(lifetime, TimerMode::Once),
+            },
+        ));
+    }
+}
+
+fn pad_pickup_bursts(
+    mut commands: Commands,
+    states: Res<GameStates>,
+    assets: Res<ParticleAssets>,
+    mut was_active: Local<Vec<bool>>,
+) {
+    let pads = &states.current.pads;
+
+    // the field changed, so the old pads don't mean anything anymore
+    if was_active.len() != pads.len() {
+        *was_active = pads.iter().map(|pad| pad.state.is_active).collect();
+        return;
+    }
+
+    for (pad, was_active) in pads.iter().zip(was_active.iter_mut()) {
+        let picked_up = *was_active && !pad.state.is_active;
+        *was_active = pad.state.is_active;
+
+        if !picked_up {
+            continue;
+        }
+
+        // state setting can also deactivate pads, so only show pickups by a car
+        let cars = &states.current.cars;
+        if !cars.iter().any(|car| car.state.pos.distance(pad.position) < PICKUP_DISTANCE) {
+            continue;
+        }
+
+        let (material, scale) = if pad.is_big {
+            (&assets.big_pad, 12.)
+        } else {
+            (&assets.small_pad, 6.)
+        };
+
+        spawn_burst(
+            &mut commands,
+            &assets,
+            material,
+            pad.position.to_bevy(),
+            PAD_PARTICLES,
+            PAD_PARTICLE_SPEED,
+            scale,
+            PAD_PARTICLE_LIFETIME,
+        );
+    }
+}
+
+fn demo_explosions(
+    mut commands: Commands,
+    states: Res<GameStates>,
+    assets: Res<ParticleAssets>,
+    mut was_demoed: Local<Vec<(u32, bool)>>,
+) {
+    for car in &*states.current.cars {
+        let is_demoed = car.state.is_demoed;
+
+        let was = match was_demoed.iter_mut().find(|(id, _)| *id == car.id) {
+            Some((_, was)) => was,
+            None => {
+                was_demoed.push((car.id, is_demoed));
+                continue;
+            }
+        };
+
+        if is_demoed && !*was {
+            spawn_burst(
+                &mut commands,
+                &assets,
+                &assets.team[car.team as usize],
+                car.state.pos.to_bevy(),
+                DEMO_PARTICLES,
+                DEMO_PARTICLE_SPEED,
+                20.,
+                DEMO_PARTICLE_LIFETIME,
+            );
+        }
+
+        *was = is_demoed;
+    }
+
+    was_demoed.retain(|(id, _)| states.current.cars.iter().any(|car| car.id == *id));
+}
+
+fn update_particles(time: Res<Time>, mut commands: Commands, mut particles: Query<(Entity, &mut Transform, &mut Particle)>) {
+    let delta = time.delta_secs();
+
+    for (entity, mut transform, mut particle) in &mut particles {
+        if particle.lifetime.tick(time.delta()).finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+
+        particle.velocity.y += PARTICLE_GRAVITY * delta;
+        particle.velocity *= 1. - (PARTICLE_DRAG * delta).min(1.);
+
+        transform.translation += particle.velocity * delta;
+        transform.scale = Vec3::splat(particle.scale * particle.lifetime.fraction_remaining());
+    }
+}
+
+/// Where the light that flashes over the field after a goal is
+const GOAL_FLASH_POS: Vec3 = Vec3::new(0., 4000., 0.);
+const GOAL_FLASH_INTENSITY: f32 = 5e12;
+const GOAL_FLASH_SECS: f32 = 0.6;
+/// How long the strongest shake takes to die down, weaker ones are over sooner
+const CAMERA_SHAKE_SECS: f32 = 0.8;
+/// How far the camera moves away from where it should be at the start of the strongest shake
+const CAMERA_SHAKE_STRENGTH: f32 = 40.;
+/// Impacts further than this from the camera don't shake it
+const CAMERA_SHAKE_RANGE: f32 = 4000.;
+const DEMO_CAMERA_SHAKE: f32 = 0.5;
+const BALL_HIT_CAMERA_SHAKE: f32 = 0.3;
+/// Hits that add less than this to the ball's velocity don't shake the camera
+const MIN_SHAKE_HIT_VEL: f32 = 600.;
+/// Hits that add this much shake the camera the most, RocketSim's extra hit velocity tops out not far above it
+const MAX_SHAKE_HIT_VEL: f32 = 1300.;
+/// How long the simulation is slowed down for, in real time
+const GOAL_SLOW_MOTION_SECS: f32 = 1.5;
+/// The game speed during slow motion, relative to the one set in the menu
+const GOAL_SLOW_MOTION_SPEED: f32 = 0.25;
+
+#[derive(Component)]
+struct GoalFlash(Timer);
+
+#[derive(Resource, Default)]
+struct CameraShake {
+    /// From 0 to 1, going down to 0 over `CAMERA_SHAKE_SECS`
+    strength: f32,
+    /// How far the camera was moved for the current frame, so it can be moved back before anything else uses it
+    offset: Vec3,
+}
+
+impl CameraShake {
+    /// Starts a shake, unless a stronger one is already going
+    fn shake(&mut self, strength: f32) {
+        self.strength = self.strength.max(strength);
+    }
+}
+
+/// The position of the camera if it's following a car, since that's when impacts near it should be felt
+fn following_camera_pos(camera: &Query<(&PrimaryCamera, &Transform)>) -> Option<Vec3> {
+    match camera.get_single() {
+        Ok((PrimaryCamera::TrackCar(_) | PrimaryCamera::Director(_), transform)) => Some(transform.translation),
+        _ => None,
+    }
+}
+
+/// How much of a shake is felt by the camera at `camera_pos`, from 1 right on top of it to 0 at `CAMERA_SHAKE_RANGE`
+fn camera_shake_falloff(camera_pos: Vec3, pos: Vec3) -> f32 {
+    (1. - camera_pos.distance(pos) / CAMERA_SHAKE_RANGE).max(0.).powi(2)
+}
+
+/// Counts down the slow motion after a goal, after which the game speed from the menu is restored
+#[derive(Resource, Default)]
+pub struct GoalSlowMotion(Option<Timer>);
+
+impl GoalSlowMotion {
+    pub const fn is_active(&self) -> bool {
+        self.0.is_some()
+    }
+}
+
+fn goal_explosions(
+    mut commands: Commands,
+    mut events: EventReader<GoalScored>,
+    states: Res<GameStates>,
+    assets: Res<ParticleAssets>,
+    goal_explosion: Res<GoalExplosion>,
+    replay_on_goal: Res<ReplayOnGoal>,
+    options: Res<Options>,
+    socket: Option<Res<Connection>>,
+    mut shake: ResMut<CameraShake>,
+    mut slow_motion: ResMut<GoalSlowMotion>,
+    mut game_speed: ResMut<GameSpeed>,
+    bullet_time: Res<BulletTime>,
+    camera: Query<(&PrimaryCamera, &Transform)>,
+) {
+    for event in events.read() {
+        if goal_explosion.flash {
+            commands.spawn((
+                PointLight {
+                    color: get_color_from_team(event.team),
+                    intensity: GOAL_FLASH_INTENSITY,
+                    range: 20000.,
+                    ..default()
+                },
+                Transform::from_translation(GOAL_FLASH_POS),
+                GoalFlash(Timer::from_seconds(GOAL_FLASH_SECS, TimerMode::Once)),
+            ));
+        }
+
+        if goal_explosion.particles {
+            spawn_burst(
+                &mut commands,
+                &assets,
+                &assets.team[event.team as usize],
+                states.current.ball.pos.to_bevy(),
+                GOAL_PARTICLES,
+                GOAL_PARTICLE_SPEED,
+                30.,
+                GOAL_PARTICLE_LIFETIME,
+            );
+        }
+
+        if goal_explosion.camera_shake {
+            // the free camera and orbit aren't anywhere in particular, so they always get the full shake
+            shake.shake(following_camera_pos(&camera).map_or(1., |camera_pos| {
+                camera_shake_falloff(camera_pos, states.current.ball.pos.to_bevy())
+            }));
+        }
+
+        // slowing down the simulation would only slow down the live game that's hidden behind the replay
+        if goal_explosion.slow_motion && !replay_on_goal.0 && !game_speed.paused && !bullet_time.is_active() {
+            if let Some(socket) = &socket {
+                game_speed.speed = (options.game_speed * GOAL_SLOW_MOTION_SPEED).max(GameSpeed::MIN);
+                socket.send(SendableUdp::Speed(game_speed.speed)).unwrap();
+                slow_motion.0 = Some(Timer::from_seconds(GOAL_SLOW_MOTION_SECS, TimerMode::Once));
+            }
+        }
+    }
+}
+
+fn update_goal_flashes(
+    time: Res<Time>,
+    mut commands: Commands,
+    mut flashes: Query<(Entity, &mut PointLight, &mut GoalFlash)>,
+) {
+    for (entity, mut light, mut flash) in &mut flashes {
+        if flash.0.tick(time.delta()).finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+
+        light.intensity = GOAL_FLASH_INTENSITY * flash.0.fraction_remaining();
+    }
+}
+
+struct LastImpacts {
+    tick_count_when_hit: u64,
+    is_demoed: bool,
+}
+
+fn impact_camera_shake(
+    states: Res<GameStates>,
+    impact_camera_shake: Res<ImpactCameraShake>,
+    camera: Query<(&PrimaryCamera, &Transform)>,
+    mut shake: ResMut<CameraShake>,
+    mut last_impacts: Local<HashMap<u32, LastImpacts>>,
+) {
+    last_impacts.retain(|id, _| states.current.cars.iter().any(|car| car.id == *id));
+
+    // the last impacts are still kept track of while it's off, so turning it on doesn't shake for old ones
+    let camera_pos = following_camera_pos(&camera).filter(|_| impact_camera_shake.0);
+
+    for car in &*states.current.cars {
+        let state = &car.state;
+        let hit_info = &state.ball_hit_info;
+
+        let Some(last) = last_impacts.get_mut(&car.id) else {
+            last_impacts.insert(
+                car.id,
+                LastImpacts {
+                    tick_count_when_hit: hit_info.tick_count_when_hit,
+                    is_demoed: state.is_demoed,
+                },
+            );
+            continue;
+        };
+
+        if let Some(camera_pos) = camera_pos {
+            if state.is_demoed && !last.is_demoed {
+                shake.shake(DEMO_CAMERA_SHAKE * camera_shake_falloff(camera_pos, state.pos.to_bevy()));
+            }
+
+            if hit_info.is_valid && hit_info.tick_count_when_hit != last.tick_count_when_hit {
+                let hardness = ((hit_info.extra_hit_vel.length() - MIN_SHAKE_HIT_VEL)
+                    / (MAX_SHAKE_HIT_VEL - MIN_SHAKE_HIT_VEL))
+                    .clamp(0., 1.);
+                shake
+                    .shake(BALL_HIT_CAMERA_SHAKE * hardness * camera_shake_falloff(camera_pos, hit_info.ball_pos.to_bevy()));
+            }
+        }
+
+        last.tick_count_when_hit = hit_info.tick_count_when_hit;
+        last.is_demoed = state.is_demoed;
+    }
+}
+
+fn remove_camera_shake(mut shake: ResMut<CameraShake>, mut camera: Query<&mut Transform, With<PrimaryCamera>>) {
+    if shake.offset == Vec3::ZERO {
+        return;
+    }
+
+    if let Ok(mut transform) = camera.get_single_mut() {
+        transform.translation -= shake.offset;
+    }
+
+    shake.offset = Vec3::ZERO;
+}
+
+fn apply_camera_shake(
+    time: Res<Time>,
+    mut shake: ResMut<CameraShake>,
+    mut camera: Query<&mut Transform, With<PrimaryCamera>>,
+) {
+    shake.strength = (shake.strength - time.delta_secs() / CAMERA_SHAKE_SECS).max(0.);
+    if shake.strength == 0. {
+        return;
+    }
+
+    // a few sine waves that don't line up look random enough without needing an rng
+    let t = time.elapsed_secs();
+    let wobble = Vec3::new((t * 53.).sin(), (t * 71.).sin(), (t * 61.).cos());
+    let offset = wobble * CAMERA_SHAKE_STRENGTH * shake.strength;
+
+    if let Ok(mut transform) = camera.get_single_mut() {
+        transform.translation += offset;
+        shake.offset = offset;
+    }
+}
+
+/// Puts the game speed from the menu back once the slow motion after a goal is over
+fn end_goal_slow_motion(
+    time: Res<Time<Real>>,
+    options: Res<Options>,
+    socket: Res<Connection>,
+    mut slow_motion: ResMut<GoalSlowMotion>,
+    mut game_speed: ResMut<GameSpeed>,
+) {
+    let Some(timer) = &mut slow_motion.0 else {
+        return;
+    };
+
+    if !timer.tick(time.delta()).finished() {
+        return;
+    }
+
+    slow_motion.0 = None;
+    game_speed.speed = options.game_speed;
+    socket.send(SendableUdp::Speed(game_speed.speed)).unwrap();
+}
+
+/// The game speed that bullet time slows down to
+const BULLET_TIME_SPEED: f32 = 0.1;
+/// How long it takes to slow down to and speed back up from bullet time, in real time
+const BULLET_TIME_RAMP_SECS: f32 = 1.;
+/// How often the game speed is sent to the simulation during a ramp, in seconds
+const BULLET_TIME_SEND_INTERVAL: f32 = 1. / 15.;
+
+/// Smoothly slows the whole simulation down to `BULLET_TIME_SPEED` and back up to the game speed from the menu
+///
+/// Packets are interpolated while it's on, regardless of the packet smoothing setting,
+/// so the slow motion looks fluid even though the simulation sends fewer packets
+#[derive(Resource, Default)]
+pub struct BulletTime {
+    /// Whether it's slowing down or staying slowed down, instead of speeding back up
+    engaged: bool,
+    /// How far into the slow down it is, from 0 at the game speed from the menu to 1 at `BULLET_TIME_SPEED`
+    progress: f32,
+    since_sent: f32,
+}
+
+impl BulletTime {
+    pub fn is_active(&self) -> bool {
+        self.engaged || self.progress > 0.
+    }
+}
+
+fn toggle_bullet_time(
+    key: Res<ButtonInput<KeyCode>>,
+    keybinds: Res<Keybinds>,
+    slow_motion: Res<GoalSlowMotion>,
+    mut bullet_time: ResMut<BulletTime>,
+    mut toast: ResMut<Toast>,
+) {
+    if !keybinds.just_pressed(&key, Action::BulletTime) {
+        return;
+    }
+
+    if slow_motion.is_active() {
+        toast.show(String::from("Wait for the slow motion after the goal to end"));
+        return;
+    }
+
+    bullet_time.engaged = !bullet_time.engaged;
+    // send the first speed right away so the slow down starts on this frame
+    bullet_time.since_sent = BULLET_TIME_SEND_INTERVAL;
+    toast.show(String::from(if bullet_time.engaged {
+        "Bullet time"
+    } else {
+        "Back to normal speed"
+    }));
+}
+
+fn ramp_bullet_time(
+    time: Res<Time<Real>>,
+    options: Res<Options>,
+    socket: Res<Connection>,
+    mut bullet_time: ResMut<BulletTime>,
+    mut game_speed: ResMut<GameSpeed>,
+    mut packet_smoothing: ResMut<PacketSmoothing>,
+    mut last_packet_times: ResMut<LastPacketTimesElapsed>,
+) {
+    let step = time.delta_secs() / BULLET_TIME_RAMP_SECS;
+    let last_progress = bullet_time.progress;
+    let (progress, ramping, finished) = if bullet_time.engaged {
+        let progress = (last_progress + step).min(1.);
+        (progress, last_progress < 1., progress >= 1.)
+    } else {
+        let progress = (last_progress - step).max(0.);
+        (progress, last_progress > 0., progress <= 0.)
+    };
+    bullet_time.progress = progress;
+
+    if progress > 0. {
+        packet_smoothing.set_if_neq(PacketSmoothing::Interpolate);
+    } else {
+        // back to normal, so put everything back how the menu has it
+        packet_smoothing.set_if_neq(PacketSmoothing::from_usize(options.packet_smoothing));
+    }
+
+    bullet_time.since_sent += time.delta_secs();
+    if !ramping || (bullet_time.since_sent < BULLET_TIME_SEND_INTERVAL && !finished) {
+        return;
+    }
+
+    // ease in and out, and ramp exponentially so each step of the way feels the same
+    let eased = progress * progress * (3. - 2. * progress);
+    let speed = options.game_speed * (BULLET_TIME_SPEED / options.game_speed).powf(eased);
+
+    bullet_time.since_sent = 0.;
+    game_speed.speed = speed.clamp(GameSpeed::MIN, GameSpeed::MAX);
+    last_packet_times.reset();
+    socket.send(SendableUdp::Speed(game_speed.speed)).unwrap();
+}
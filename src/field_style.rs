@@ -0,0 +1,142 @@
+use crate::{mesh::StaticFieldEntity, settings::options::FieldStyle};
+use bevy::{
+    pbr::wireframe::{Wireframe, WireframeConfig, WireframePlugin},
+    prelude::*,
+    render::{renderer::RenderDevice, settings::WgpuFeatures},
+    utils::HashMap,
+};
+
+pub struct FieldStylePlugin;
+
+impl Plugin for FieldStylePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(WireframePlugin)
+            .insert_resource(WireframeConfig {
+                global: false,
+                default_color: WIREFRAME_COLOR,
+            })
+            .insert_resource(OriginalFieldMaterials::default())
+            .add_systems(
+                Update,
+                (
+                    apply_field_style.run_if(|style: Res<FieldStyle>| *style != FieldStyle::Normal),
+                    restore_field_materials.run_if(|style: Res<FieldStyle>, originals: Res<OriginalFieldMaterials>| {
+                        *style == FieldStyle::Normal && originals.applied.is_some()
+                    }),
+                ),
+            );
+    }
+}
+
+const FLAT_COLOR: Color = Color::srgb(0.55, 0.55, 0.6);
+const WIREFRAME_SURFACE_COLOR: Color = Color::srgb(0.04, 0.04, 0.05);
+const WIREFRAME_COLOR: Color = Color::srgb(0.3, 0.8, 1.);
+
+#[derive(Resource, Default)]
+struct OriginalFieldMaterials {
+    materials: HashMap<AssetId<StandardMaterial>, StandardMaterial>,
+    applied: Option<FieldStyle>,
+}
+
+fn set_field_material(material: &mut StandardMaterial, original: &StandardMaterial, style: FieldStyle) {
+    // alpha is driven by other systems (wall transparency) so it's left untouched
+    let alpha = material.base_color.alpha();
+    let alpha_mode = material.alpha_mode;
+
+    *material = original.clone();
+    material.base_color_texture = None;
+    material.normal_map_texture = None;
+    material.metallic_roughness_texture = None;
+    material.emissive = LinearRgba::BLACK;
+    material.emissive_texture = None;
+
+    match style {
+        FieldStyle::Normal => unreachable!(),
+        FieldStyle::FlatShaded => {
+            material.base_color = FLAT_COLOR;
+            material.perceptual_roughness = 1.;
+            material.metallic = 0.;
+        }
+        FieldStyle::Wireframe => {
+            material.base_color = WIREFRAME_SURFACE_COLOR;
+            material.unlit = true;
+        }
+    }
+
+    material.base_color.set_alpha(alpha);
+    material.alpha_mode = alpha_mode;
+}
+
+fn apply_field_style(
+    style: Res<FieldStyle>,
+    field: Query<(Entity, &MeshMaterial3d<StandardMaterial>, Has<Wireframe>), With<StaticFieldEntity>>,
+    render_device: Option<Res<RenderDevice>>,
+    mut originals: ResMut<OriginalFieldMaterials>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut commands: Commands,
+) {
+    let mut style = *style;
+    if style == FieldStyle::Wireframe
+        && !render_device.is_some_and(|device| device.features().contains(WgpuFeatures::POLYGON_MODE_LINE))
+    {
+        if originals.applied != Some(FieldStyle::FlatShaded) {
+            warn!("This GPU can't draw wireframes, using flat shading instead");
+        }
+        style = FieldStyle::FlatShaded;
+    }
+
+    let originals = originals.as_mut();
+    let changed = originals.applied != Some(style);
+    originals.applied = Some(style);
+
+    let wireframe = style == FieldStyle::Wireframe;
+
+    for (entity, material, has_wireframe) in &field {
+        if wireframe != has_wireframe {
+            if wireframe {
+                commands.entity(entity).insert(Wireframe);
+            } else {
+                commands.entity(entity).remove::<Wireframe>();
+            }
+        }
+
+        // only touch materials that are new (or all of them if the style changed) to avoid re-uploading every frame
+        let id = material.id();
+        if !changed && originals.materials.contains_key(&id) {
+            continue;
+        }
+
+        let Some(material) = materials.get_mut(id) else {
+            continue;
+        };
+
+        let original = originals.materials.entry(id).or_insert_with(|| material.clone());
+        set_field_material(material, original, style);
+    }
+}
+
+fn restore_field_materials(
+    wireframes: Query<Entity, (With<Wireframe>, With<StaticFieldEntity>)>,
+    mut originals: ResMut<OriginalFieldMaterials>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut commands: Commands,
+) {
+    for entity in &wireframes {
+        commands.entity(entity).remove::<Wireframe>();
+    }
+
+    for (id, original) in originals.materials.drain() {
+        let Some(material) = materials.get_mut(id) else {
+            continue;
+        };
+
+        let alpha = material.base_color.alpha();
+        let alpha_mode = material.alpha_mode;
+
+        *material = original;
+        material.base_color.set_alpha(alpha);
+        material.alpha_mode = alpha_mode;
+    }
+
+    originals.applied = None;
+}
@@ -0,0 +1,79 @@
+use crate::{
+    capture, hoops,
+    replay::not_replaying,
+    rocketsim::{GameMode, Team},
+    settings::options::ScreenshotOnGoal,
+    udp::GameStates,
+};
+use bevy::{math::Vec3A, prelude::*};
+
+pub struct GoalPlugin;
+
+impl Plugin for GoalPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<GoalScored>().add_systems(
+            Update,
+            (
+                detect_goals.run_if(not_replaying),
+                screenshot_goal
+                    .run_if(on_event::<GoalScored>.and(|screenshot_on_goal: Res<ScreenshotOnGoal>| screenshot_on_goal.0)),
+            )
+                .chain(),
+        );
+    }
+}
+
+#[derive(Event, Clone, Copy)]
+pub struct GoalScored {
+    pub team: Team,
+    pub tick_count: u64,
+}
+
+/// The distance from the center of the field to the goal line
+const GOAL_LINE_Y: f32 = 5124.25;
+
+/// The ball has to be below this height to count as being through a hoops net
+const HOOPS_GOAL_SCORE_THRESHOLD_Z: f32 = 270.;
+
+/// Whether the ball is fully inside of either goal, or `None` if the game mode has no goals
+fn is_ball_in_goal(game_mode: GameMode, pos: Vec3A) -> Option<bool> {
+    match game_mode {
+        GameMode::Soccar | GameMode::HeatSeeker => Some(pos.y.abs() > GOAL_LINE_Y + 91.25),
+        GameMode::Snowday => Some(pos.y.abs() > GOAL_LINE_Y + 114.25),
+        GameMode::Hoops => Some(pos.z < HOOPS_GOAL_SCORE_THRESHOLD_Z && hoops::is_in_net_circle(pos)),
+        GameMode::TheVoid => None,
+    }
+}
+
+fn detect_goals(states: Res<GameStates>, mut goal_scored: EventWriter<GoalScored>, mut in_goal: Local<bool>) {
+    let ball_pos = states.current.ball.pos;
+    let Some(is_in_goal) = is_ball_in_goal(states.current.game_mode, ball_pos) else {
+        *in_goal = false;
+        return;
+    };
+
+    // only report the goal once, until the ball is back on the field (usually at kickoff)
+    if is_in_goal && !*in_goal {
+        goal_scored.send(GoalScored {
+            // blue defends the negative side of the field
+            team: if ball_pos.y > 0. { Team::Blue } else { Team::Orange },
+            tick_count: states.current.tick_count,
+        });
+    }
+
+    *in_goal = is_in_goal;
+}
+
+fn screenshot_goal(mut commands: Commands, mut events: EventReader<GoalScored>) {
+    for event in events.read() {
+        capture::save_screenshot(
+            &mut commands,
+            &format!(
+                "goal_{}_tick{}_{}",
+                if event.team == Team::Blue { "blue" } else { "orange" },
+                event.tick_count,
+                capture::unix_timestamp()
+            ),
+        );
+    }
+}
@@ -0,0 +1,112 @@
+use crate::{
+    rocketsim::GameState,
+    udp::{self, Connection, SendableUdp, UdpUpdate, UdpUpdateStream, PROTOCOL_VERSION},
+    ServerPort,
+};
+use bevy::{app::ScheduleRunnerPlugin, log::LogPlugin, prelude::*};
+use std::{sync::Arc, time::Duration};
+
+/// How long to wait between checking for new packets
+const UPDATE_INTERVAL: Duration = Duration::from_millis(5);
+/// How often a summary of the game state is printed
+const SUMMARY_INTERVAL: f32 = 1.;
+
+/// Runs without a window or GPU, only decoding packets and tracking the game state
+///
+/// Useful for checking protocol compatibility in CI
+pub fn run(port: ServerPort) {
+    App::new()
+        .insert_resource(port)
+        .insert_resource(HeadlessStats::default())
+        .insert_resource(SummaryTimer(Timer::from_seconds(SUMMARY_INTERVAL, TimerMode::Repeating)))
+        .add_plugins((
+            MinimalPlugins.set(ScheduleRunnerPlugin::run_loop(UPDATE_INTERVAL)),
+            LogPlugin::default(),
+        ))
+        .add_systems(Startup, connect)
+        .add_systems(Update, (apply_updates, print_summary).chain())
+        .run();
+}
+
+#[derive(Resource, Default)]
+struct HeadlessStats {
+    state: Arc<GameState>,
+    states: u64,
+    renders: u64,
+    car_metadata: u64,
+    messages: u64,
+}
+
+impl HeadlessStats {
+    fn summary(&self) -> String {
+        let ball = self.state.ball.pos;
+
+        format!(
+            "tick {} | {:?} | {} cars, {} pads | ball at ({:.0}, {:.0}, {:.0}) | received {} states, {} renders, {} car metadata, {} messages",
+            self.state.tick_count,
+            self.state.game_mode,
+            self.state.cars.len(),
+            self.state.pads.len(),
+            ball.x,
+            ball.y,
+            ball.z,
+            self.states,
+            self.renders,
+            self.car_metadata,
+            self.messages,
+        )
+    }
+}
+
+#[derive(Resource)]
+struct SummaryTimer(Timer);
+
+fn connect(port: Res<ServerPort>, mut commands: Commands) {
+    udp::start_connection(&port, None, &mut commands).unwrap();
+}
+
+fn apply_updates(
+    udp_updates: Res<UdpUpdateStream>,
+    socket: Res<Connection>,
+    mut stats: ResMut<HeadlessStats>,
+    mut exit: EventWriter<AppExit>,
+) {
+    for update in udp_updates.try_iter() {
+        match update {
+            UdpUpdate::State(state, _) => {
+                stats.state = state;
+                stats.states += 1;
+            }
+            UdpUpdate::Render(_) => stats.renders += 1,
+            UdpUpdate::CarMetadata(_) => stats.car_metadata += 1,
+            UdpUpdate::Message(_) => stats.messages += 1,
+            UdpUpdate::Speed(_) | UdpUpdate::Paused(_) => {}
+            UdpUpdate::Hello(version) => {
+                socket.send(SendableUdp::Hello(PROTOCOL_VERSION)).unwrap();
+
+                // fail loudly so CI catches it
+                if version != PROTOCOL_VERSION {
+                    error!("Simulation uses protocol version {version}, but only version {PROTOCOL_VERSION} is supported");
+                    exit.send(AppExit::error());
+                    return;
+                }
+            }
+            UdpUpdate::Connection => {
+                // let the simulation run the same as it would with a fresh window
+                socket.send(SendableUdp::Paused(false)).unwrap();
+                socket.send(SendableUdp::Speed(1.)).unwrap();
+            }
+            UdpUpdate::Exit => {
+                println!("{}", stats.summary());
+                exit.send(AppExit::Success);
+                return;
+            }
+        }
+    }
+}
+
+fn print_summary(time: Res<Time>, stats: Res<HeadlessStats>, mut timer: ResMut<SummaryTimer>) {
+    if timer.0.tick(time.delta()).just_finished() {
+        println!("{}", stats.summary());
+    }
+}
@@ -0,0 +1,83 @@
+use crate::{
+    camera::GLOW_STRENGTH,
+    rocketsim::GameMode,
+    udp::{Ball, GameStates, ToBevyVec},
+};
+use bevy::{math::Vec3A, prelude::*};
+
+pub struct HeatseekerPlugin;
+
+impl Plugin for HeatseekerPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, (draw_target, tint_ball));
+    }
+}
+
+/// Where the ball seeks towards in the targeted net, matching RocketSim
+const TARGET_Y: f32 = 5120.;
+const TARGET_Z: f32 = 320.;
+
+/// The target speed the ball starts at, and the most it can reach after being hit many times
+const MIN_TARGET_SPEED: f32 = 2900.;
+const MAX_TARGET_SPEED: f32 = 4600.;
+
+const BALL_RADIUS: f32 = 91.25;
+const SLOW_COLOR: LinearRgba = LinearRgba::rgb(0.8, 0.3, 0.);
+const FAST_COLOR: LinearRgba = LinearRgba::rgb(1.5, 0.05, 0.);
+
+/// How far between the starting and max target speed the ball currently is
+fn target_speed_fraction(speed: f32) -> f32 {
+    ((speed - MIN_TARGET_SPEED) / (MAX_TARGET_SPEED - MIN_TARGET_SPEED)).clamp(0., 1.)
+}
+
+fn target_color(speed: f32) -> LinearRgba {
+    SLOW_COLOR.mix(&FAST_COLOR, target_speed_fraction(speed))
+}
+
+fn draw_target(states: Res<GameStates>, mut gizmos: Gizmos) {
+    if states.current.game_mode != GameMode::HeatSeeker {
+        return;
+    }
+
+    let hs_info = &states.current.ball.hs_info;
+    if hs_info.y_target_dir == 0. {
+        return;
+    }
+
+    let ball_pos = states.current.ball.pos;
+    let target = Vec3A::new(0., TARGET_Y * hs_info.y_target_dir.signum(), TARGET_Z);
+    let Some(dir) = (target - ball_pos).try_normalize() else {
+        return;
+    };
+
+    // start at the surface of the ball so the arrow isn't hidden inside of it
+    let start = (ball_pos + dir * BALL_RADIUS).to_bevy();
+    gizmos.arrow(start, target.to_bevy(), Color::from(target_color(hs_info.cur_target_speed)));
+}
+
+fn tint_ball(
+    states: Res<GameStates>,
+    ball: Query<&MeshMaterial3d<StandardMaterial>, With<Ball>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut tinted: Local<bool>,
+) {
+    let Ok(material) = ball.get_single() else {
+        return;
+    };
+
+    let is_heatseeker = states.current.game_mode == GameMode::HeatSeeker;
+    if !is_heatseeker && !*tinted {
+        return;
+    }
+
+    let Some(material) = materials.get_mut(material.id()) else {
+        return;
+    };
+
+    material.emissive = if is_heatseeker {
+        target_color(states.current.ball.hs_info.cur_target_speed) * GLOW_STRENGTH
+    } else {
+        LinearRgba::BLACK
+    };
+    *tinted = is_heatseeker;
+}
@@ -0,0 +1,190 @@
+use crate::{
+    goal::GoalScored,
+    mesh::FieldMeshInfo,
+    rocketsim::{GameMode, GameState, Team},
+    udp::{get_color_from_team, GameStates, ToBevyVec},
+};
+use bevy::{math::Vec3A, prelude::*};
+use std::f32::consts::TAU;
+
+pub struct HoopsPlugin;
+
+impl Plugin for HoopsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                (shake_net, show_score_popup).run_if(on_event::<GoalScored>),
+                update_net_shakes,
+                update_score_popups,
+            ),
+        );
+    }
+}
+
+// The nets are circles that are slightly squashed along the y axis, matching RocketSim
+const NET_SCALE_Y: f32 = 0.9;
+const NET_OFFSET_Y: f32 = 2770.;
+const NET_RADIUS: f32 = 716.;
+/// Roughly where the top of the rims are
+const RIM_HEIGHT: f32 = 395.;
+
+/// How close to a rim the ball has to be for it to be a close shot
+const CLOSE_SHOT_DISTANCE: f32 = 1200.;
+/// Close shots are only interesting while the ball is still above the part of the net it scores in
+const CLOSE_SHOT_MIN_HEIGHT: f32 = 270.;
+
+const NET_SHAKE_SECS: f32 = 1.2;
+const NET_SHAKE_FREQUENCY: f32 = 5.;
+/// How much the net stretches downwards at the start of the shake
+const NET_SHAKE_STRETCH: f32 = 0.25;
+
+const SCORE_POPUP_SECS: f32 = 2.;
+const SCORE_POPUP_FONT_SIZE: f32 = 80.;
+/// How far the popup floats up the screen while it fades out, in pixels
+const SCORE_POPUP_RISE: f32 = 60.;
+
+/// The center of the top of the rim on the side of the field that `y` is on
+fn rim_center(y: f32) -> Vec3A {
+    Vec3A::new(0., NET_OFFSET_Y / NET_SCALE_Y * y.signum(), RIM_HEIGHT)
+}
+
+/// Whether the ball is in the net's circle, as seen from above
+pub fn is_in_net_circle(pos: Vec3A) -> bool {
+    let dy = pos.y.abs() * NET_SCALE_Y - NET_OFFSET_Y;
+    pos.x * pos.x + dy * dy < NET_RADIUS * NET_RADIUS
+}
+
+/// The center of the top of the rim and the half size of the net's circle around it, on the side of the field that `y` is on
+pub fn net_ellipse(y: f32) -> (Vec3A, Vec2) {
+    (rim_center(y), Vec2::new(NET_RADIUS, NET_RADIUS / NET_SCALE_Y))
+}
+
+/// The rim the ball is about to go through or bounce off of, if it's close to one
+pub fn close_shot_rim(state: &GameState) -> Option<Vec3A> {
+    if state.game_mode != GameMode::Hoops {
+        return None;
+    }
+
+    let ball = &state.ball;
+    if ball.pos.z < CLOSE_SHOT_MIN_HEIGHT {
+        return None;
+    }
+
+    let rim = rim_center(ball.pos.y);
+    let to_rim = (rim - ball.pos).truncate();
+    if to_rim.length() > CLOSE_SHOT_DISTANCE {
+        return None;
+    }
+
+    // the ball has to be heading towards the rim or dropping onto it
+    (ball.vel.truncate().dot(to_rim) > 0. || ball.vel.z < 0.).then_some(rim)
+}
+
+/// Makes a net bounce after the ball goes through it
+#[derive(Component)]
+struct NetShake {
+    base: Transform,
+    timer: Timer,
+}
+
+fn shake_net(
+    mut commands: Commands,
+    mut events: EventReader<GoalScored>,
+    states: Res<GameStates>,
+    nets: Query<(Entity, &FieldMeshInfo, &Transform, Option<&NetShake>)>,
+) {
+    if states.current.game_mode != GameMode::Hoops || events.read().count() == 0 {
+        return;
+    }
+
+    let ball_side = states.current.ball.pos.to_bevy().z.signum();
+    for (entity, info, transform, shake) in &nets {
+        if !info.mesh.contains("BBall_HoopNet") || transform.translation.z.signum() != ball_side {
+            continue;
+        }
+
+        // the net might still be shaking from the last basket, so start from where it was before that
+        let base = shake.map_or(*transform, |shake| shake.base);
+        commands.entity(entity).insert(NetShake {
+            base,
+            timer: Timer::from_seconds(NET_SHAKE_SECS, TimerMode::Once),
+        });
+    }
+}
+
+fn update_net_shakes(time: Res<Time>, mut commands: Commands, mut nets: Query<(Entity, &mut Transform, &mut NetShake)>) {
+    for (entity, mut transform, mut shake) in &mut nets {
+        if shake.timer.tick(time.delta()).finished() {
+            *transform = shake.base;
+            commands.entity(entity).remove::<NetShake>();
+            continue;
+        }
+
+        // a spring that settles down, stretching the net down and squeezing it in, then the other way around
+        let t = shake.timer.elapsed_secs();
+        let stretch = NET_SHAKE_STRETCH * (t * NET_SHAKE_FREQUENCY * TAU).cos() * shake.timer.fraction_remaining();
+
+        let squeeze = 1. / (1. + stretch).sqrt();
+        transform.scale = shake.base.scale * Vec3::new(squeeze, 1. + stretch, squeeze);
+    }
+}
+
+#[derive(Component)]
+struct ScorePopup(Timer);
+
+fn show_score_popup(mut commands: Commands, mut events: EventReader<GoalScored>, states: Res<GameStates>) {
+    if states.current.game_mode != GameMode::Hoops {
+        return;
+    }
+
+    for event in events.read() {
+        let team = match event.team {
+            Team::Blue => "Blue",
+            Team::Orange => "Orange",
+        };
+
+        commands
+            .spawn(Node {
+                width: Val::Percent(100.),
+                position_type: PositionType::Absolute,
+                top: Val::Percent(30.),
+                justify_content: JustifyContent::Center,
+                ..default()
+            })
+            .insert(ScorePopup(Timer::from_seconds(SCORE_POPUP_SECS, TimerMode::Once)))
+            .with_children(|parent| {
+                parent.spawn((
+                    Text::new(format!("{team} scores!")),
+                    TextFont {
+                        font_size: SCORE_POPUP_FONT_SIZE,
+                        ..default()
+                    },
+                    TextColor(get_color_from_team(event.team)),
+                ));
+            });
+    }
+}
+
+fn update_score_popups(
+    time: Res<Time>,
+    mut commands: Commands,
+    mut popups: Query<(Entity, &mut Node, &mut ScorePopup, &Children)>,
+    mut texts: Query<&mut TextColor>,
+) {
+    for (entity, mut node, mut popup, children) in &mut popups {
+        if popup.0.tick(time.delta()).finished() {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+
+        node.margin.top = Val::Px(-SCORE_POPUP_RISE * popup.0.fraction());
+
+        let alpha = popup.0.fraction_remaining().sqrt();
+        for &child in children {
+            if let Ok(mut color) = texts.get_mut(child) {
+                color.0.set_alpha(alpha);
+            }
+        }
+    }
+}
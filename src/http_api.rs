@@ -0,0 +1,210 @@
+use crate::{
+    camera::PrimaryCamera,
+    nametags::CarNames,
+    settings::{
+        options::{ClampStateInputs, GameSpeed, Options},
+        state_script::StateAction,
+    },
+    udp::{Connection, GameStates, SendableUdp},
+};
+use bevy::prelude::*;
+use crossbeam_channel::Receiver;
+use std::thread;
+use tiny_http::{Header, Method, Request, Response, Server};
+
+/// Only reachable from this machine, since anything that can reach it can control the game
+const HTTP_API_ADDR: &str = "127.0.0.1:8080";
+/// The `Host` headers that requests to `HTTP_API_ADDR` are allowed to have
+const HTTP_API_HOSTS: [&str; 2] = ["127.0.0.1:8080", "localhost:8080"];
+
+/// Lets other programs control the visualizer over HTTP without speaking the UDP protocol
+///
+/// | Method | Path | Body |
+/// | --- | --- | --- |
+/// | `GET` | `/state` | |
+/// | `POST` | `/camera` | `spectator`, `director`, `car <id or name>` or `orbit <id or name>` |
+/// | `POST` | `/pause` | |
+/// | `POST` | `/resume` | |
+/// | `POST` | `/speed` | The new game speed |
+/// | `POST` | `/state` | State setting actions, one per line, in the same format as state scripts |
+pub struct HttpApiPlugin;
+
+impl Plugin for HttpApiPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, start_server)
+            .add_systems(Update, handle_requests.run_if(resource_exists::<HttpRequests>));
+    }
+}
+
+/// Requests along with their body, which is read on the server thread so a slow client can't stall a frame
+#[derive(Resource)]
+struct HttpRequests(Receiver<(Request, String)>);
+
+fn start_server(mut commands: Commands) {
+    let server = match Server::http(HTTP_API_ADDR) {
+        Ok(server) => server,
+        Err(e) => {
+            error!("Failed to start the HTTP API on {HTTP_API_ADDR} due to: {e}");
+            return;
+        }
+    };
+
+    info!("HTTP API listening on http://{HTTP_API_ADDR}");
+
+    let (tx, rx) = crossbeam_channel::unbounded();
+
+    // requests need access to the world, so they're answered by `handle_requests`
+    thread::Builder::new()
+        .name(String::from("rlviser-http-api"))
+        .spawn(move || {
+            for mut request in server.incoming_requests() {
+                let mut body = String::new();
+                if let Err(e) = request.as_reader().read_to_string(&mut body) {
+                    drop(request.respond(Response::from_string(format!("Couldn't read body: {e}")).with_status_code(400)));
+                    continue;
+                }
+
+                if tx.send((request, body)).is_err() {
+                    break;
+                }
+            }
+        })
+        .unwrap();
+
+    commands.insert_resource(HttpRequests(rx));
+}
+
+type ApiResult = Result<Option<String>, (u16, String)>;
+
+/// Browsers attach an `Origin` header to cross-site requests, and other programs have no reason to,
+/// so rejecting it keeps web pages from controlling the game through the user's browser
+fn is_from_browser(request: &Request) -> bool {
+    request.headers().iter().any(|header| header.field.equiv("Origin"))
+}
+
+/// A web page can point its own domain at 127.0.0.1 (DNS rebinding) to get around `is_from_browser`,
+/// but the browser still sends that domain as the `Host`, so anything that didn't ask for this machine is turned away
+fn has_local_host(request: &Request) -> bool {
+    request
+        .headers()
+        .iter()
+        .find(|header| header.field.equiv("Host"))
+        .is_some_and(|header| {
+            HTTP_API_HOSTS
+                .iter()
+                .any(|host| header.value.as_str().eq_ignore_ascii_case(host))
+        })
+}
+
+fn parse_camera(body: &str, car_names: &CarNames) -> Option<PrimaryCamera> {
+    let (mode, target) = match body.split_once(char::is_whitespace) {
+        Some((mode, target)) => (mode, Some(target.trim())),
+        None => (body, None),
+    };
+
+    // cars can be picked by their id or by their name
+    let car_id = |target: &str| {
+        target.parse().ok().or_else(|| {
+            car_names
+                .0
+                .iter()
+                .find(|(_, name)| name.as_str() == target)
+                .map(|(&id, _)| id)
+        })
+    };
+
+    match (mode, target) {
+        ("spectator", None) => Some(PrimaryCamera::Spectator),
+        ("director", None) => Some(PrimaryCamera::Director(0)),
+        ("car", Some(target)) => car_id(target).filter(|&id| id != 0).map(PrimaryCamera::TrackCar),
+        ("orbit", None) => Some(PrimaryCamera::Orbit(0)),
+        ("orbit", Some(target)) => car_id(target).map(PrimaryCamera::Orbit),
+        _ => None,
+    }
+}
+
+fn handle_requests(
+    requests: Res<HttpRequests>,
+    socket: Option<Res<Connection>>,
+    mut game_states: ResMut<GameStates>,
+    mut options: ResMut<Options>,
+    mut game_speed: ResMut<GameSpeed>,
+    car_names: Res<CarNames>,
+    clamp: Res<ClampStateInputs>,
+) {
+    for (request, body) in requests.0.try_iter() {
+        let connection = || socket.as_deref().ok_or((503, String::from("Not connected to a simulation")));
+
+        let result: ApiResult = match (request.method(), request.url()) {
+            _ if !has_local_host(&request) => Err((403, format!("The Host header must be one of {HTTP_API_HOSTS:?}"))),
+            (Method::Post, _) if is_from_browser(&request) => {
+                Err((403, String::from("Requests from web pages aren't allowed")))
+            }
+            (Method::Get, "/state") => serde_json::to_string(&game_states.current)
+                .map(Some)
+                .map_err(|e| (500, e.to_string())),
+            (Method::Post, "/camera") => parse_camera(body.trim(), &car_names).map_or_else(
+                || {
+                    Err((
+                        400,
+                        String::from("Expected spectator, director, car <id or name> or orbit <id or name>"),
+                    ))
+                },
+                |camera| {
+                    options.camera_state = camera;
+                    Ok(None)
+                },
+            ),
+            (Method::Post, path @ ("/pause" | "/resume")) => connection().map(|socket| {
+                let paused = path == "/pause";
+                options.paused = paused;
+                game_speed.paused = paused;
+                socket.send(SendableUdp::Paused(paused)).unwrap();
+                None
+            }),
+            (Method::Post, "/speed") => match body.trim().parse::<f32>() {
+                Ok(speed) if (GameSpeed::MIN..=GameSpeed::MAX).contains(&speed) => connection().map(|socket| {
+                    options.game_speed = speed;
+                    game_speed.speed = speed;
+                    socket.send(SendableUdp::Speed(speed)).unwrap();
+                    None
+                }),
+                _ => Err((
+                    400,
+                    format!("Expected a game speed between {} and {}", GameSpeed::MIN, GameSpeed::MAX),
+                )),
+            },
+            (Method::Post, "/state") => connection().and_then(|socket| {
+                let actions = body
+                    .lines()
+                    .map(str::trim)
+                    .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                    .map(|line| {
+                        line.parse::<StateAction>()
+                            .map_err(|()| (400, format!("Invalid action: {line}")))
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                for action in actions {
+                    action.apply(&mut game_states, &clamp);
+                }
+
+                socket.send(SendableUdp::State(game_states.next.clone())).unwrap();
+                Ok(None)
+            }),
+            (_, "/state" | "/camera" | "/pause" | "/resume" | "/speed") => Err((405, String::from("Method not allowed"))),
+            _ => Err((404, String::from("Not found"))),
+        };
+
+        let response = match result {
+            Ok(Some(json)) => Response::from_string(json)
+                .with_header(Header::from_bytes(&b"Content-Type"[..], &b"application/json"[..]).unwrap()),
+            Ok(None) => Response::from_string(String::new()).with_status_code(204),
+            Err((status, message)) => Response::from_string(message).with_status_code(status),
+        };
+
+        if let Err(e) = request.respond(response) {
+            warn!("Failed to respond to an HTTP API request due to: {e}");
+        }
+    }
+}
@@ -0,0 +1,178 @@
+use crate::{
+    camera::PrimaryCamera,
+    nametags::CarNames,
+    settings::options::{ShowInputViewer, UiOverlayScale},
+    udp::{get_color_from_team, GameStates},
+};
+use bevy::{prelude::*, window::PrimaryWindow};
+use bevy_vector_shapes::prelude::*;
+use std::f32::consts::FRAC_PI_2;
+
+pub struct InputViewerPlugin;
+
+impl Plugin for InputViewerPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, setup).add_systems(Update, draw_input_viewer);
+    }
+}
+
+/// How far the center of the viewer is from the bottom left corner of the window
+const VIEWER_POS: Vec2 = Vec2::new(140., 90.);
+const VIEWER_SIZE: Vec2 = Vec2::new(240., 120.);
+const NAME_FONT_SIZE: f32 = 18.;
+
+const WHEEL_OFFSET: f32 = -65.;
+const WHEEL_RADIUS: f32 = 38.;
+/// How far the wheel turns at full steer, either way
+const WHEEL_MAX_ANGLE: f32 = FRAC_PI_2;
+
+const BAR_SIZE: Vec2 = Vec2::new(14., 84.);
+const THROTTLE_OFFSET: f32 = 5.;
+const BOOST_OFFSET: f32 = 35.;
+
+const BUTTON_OFFSET: Vec2 = Vec2::new(80., 22.);
+const BUTTON_RADIUS: f32 = 13.;
+
+const BACKGROUND_COLOR: Color = Color::srgba(0.075, 0.075, 0.15, 0.8);
+const EMPTY_COLOR: Color = Color::srgba(1., 1., 1., 0.15);
+const WHEEL_COLOR: Color = Color::srgb(0.85, 0.85, 0.85);
+const FORWARD_COLOR: Color = Color::srgb(0.2, 0.85, 0.3);
+const REVERSE_COLOR: Color = Color::srgb(0.9, 0.25, 0.2);
+const BOOST_COLOR: Color = Color::srgb(1., 0.84, 0.);
+const BOOST_IDLE_COLOR: Color = Color::srgb(0.5, 0.42, 0.);
+const JUMP_COLOR: Color = Color::srgb(0.3, 0.6, 1.);
+const HANDBRAKE_COLOR: Color = Color::srgb(1., 0.5, 0.1);
+
+#[derive(Component)]
+struct InputViewerName;
+
+fn setup(mut commands: Commands) {
+    commands.spawn((
+        Text::new(""),
+        TextFont {
+            font_size: NAME_FONT_SIZE,
+            ..default()
+        },
+        Node {
+            position_type: PositionType::Absolute,
+            ..default()
+        },
+        InputViewerName,
+    ));
+}
+
+/// Shows the inputs of the car the camera is following, like an input viewer
+fn draw_input_viewer(
+    states: Res<GameStates>,
+    show_input_viewer: Res<ShowInputViewer>,
+    ui_scale: Res<UiOverlayScale>,
+    car_names: Res<CarNames>,
+    camera: Query<&PrimaryCamera>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    mut name: Query<(&mut Text, &mut Node, &mut TextFont, &mut TextColor), With<InputViewerName>>,
+    mut painter: ShapePainter,
+) {
+    let Ok((mut name_text, mut name_node, mut name_font, mut name_color)) = name.get_single_mut() else {
+        return;
+    };
+
+    let car_info = camera
+        .get_single()
+        .ok()
+        .filter(|_| show_input_viewer.0)
+        .and_then(|primary_camera| primary_camera.car_id())
+        .and_then(|id| states.current.cars.iter().find(|car_info| car_info.id == id));
+
+    let (Some(car_info), Ok(window)) = (car_info, windows.get_single()) else {
+        if !name_text.0.is_empty() {
+            name_text.0.clear();
+        }
+
+        return;
+    };
+
+    let scale = ui_scale.scale;
+    let controls = &car_info.state.last_controls;
+
+    name_text.0 = car_names
+        .0
+        .get(&car_info.id)
+        .cloned()
+        .unwrap_or_else(|| format!("Car {}", car_info.id));
+    name_font.font_size = NAME_FONT_SIZE * scale;
+    name_color.0 = get_color_from_team(car_info.team);
+    name_node.left = Val::Px((VIEWER_POS.x - VIEWER_SIZE.x / 2.) * scale);
+    name_node.bottom = Val::Px((VIEWER_POS.y + VIEWER_SIZE.y / 2. + 4.) * scale);
+
+    // the painter's origin is the center of the window, with y going up
+    let window_res = Vec2::new(window.width(), window.height());
+    let center = -window_res / 2. + VIEWER_POS * scale;
+    let at = |offset: Vec2| (center + offset * scale).extend(0.);
+
+    painter.set_translation(at(Vec2::ZERO));
+    painter.color = BACKGROUND_COLOR;
+    painter.rect(VIEWER_SIZE * scale);
+
+    // steering wheel, with a spoke that turns with the steering input
+    painter.set_translation(at(Vec2::new(WHEEL_OFFSET, 0.)));
+    painter.hollow = true;
+    painter.thickness = 6. * scale;
+    painter.color = WHEEL_COLOR;
+    painter.circle(WHEEL_RADIUS * scale);
+    painter.hollow = false;
+
+    let spoke = Vec2::from_angle(FRAC_PI_2 - controls.steer.clamp(-1., 1.) * WHEEL_MAX_ANGLE) * WHEEL_RADIUS * scale;
+    painter.set_translation(Vec3::ZERO);
+    painter.thickness = 4. * scale;
+    painter.line(
+        at(Vec2::new(WHEEL_OFFSET, 0.)),
+        at(Vec2::new(WHEEL_OFFSET, 0.)) + spoke.extend(0.),
+    );
+
+    // throttle fills up from the middle for going forwards, and down for reversing
+    let throttle = controls.throttle.clamp(-1., 1.);
+    draw_bar_background(&mut painter, at(Vec2::new(THROTTLE_OFFSET, 0.)), scale);
+    if throttle != 0. {
+        let height = BAR_SIZE.y / 2. * throttle;
+        painter.set_translation(at(Vec2::new(THROTTLE_OFFSET, height / 2.)));
+        painter.color = if throttle > 0. { FORWARD_COLOR } else { REVERSE_COLOR };
+        painter.rect(Vec2::new(BAR_SIZE.x, height.abs()) * scale);
+    }
+
+    // boost amount, which lights up while boost is held
+    let boost = car_info.state.boost.clamp(0., 100.) / 100.;
+    draw_bar_background(&mut painter, at(Vec2::new(BOOST_OFFSET, 0.)), scale);
+    if boost > 0. {
+        let height = BAR_SIZE.y * boost;
+        painter.set_translation(at(Vec2::new(BOOST_OFFSET, (height - BAR_SIZE.y) / 2.)));
+        painter.color = if controls.boost { BOOST_COLOR } else { BOOST_IDLE_COLOR };
+        painter.rect(Vec2::new(BAR_SIZE.x, height) * scale);
+    }
+
+    draw_button(&mut painter, at(BUTTON_OFFSET), controls.jump, JUMP_COLOR, scale);
+    draw_button(
+        &mut painter,
+        at(BUTTON_OFFSET * Vec2::new(1., -1.)),
+        controls.handbrake,
+        HANDBRAKE_COLOR,
+        scale,
+    );
+
+    painter.reset();
+}
+
+fn draw_bar_background(painter: &mut ShapePainter, pos: Vec3, scale: f32) {
+    painter.set_translation(pos);
+    painter.color = EMPTY_COLOR;
+    painter.rect(BAR_SIZE * scale);
+}
+
+/// A circle that's filled in while the button is held
+fn draw_button(painter: &mut ShapePainter, pos: Vec3, pressed: bool, color: Color, scale: f32) {
+    painter.set_translation(pos);
+    painter.color = color;
+    painter.hollow = !pressed;
+    painter.thickness = 3. * scale;
+    painter.circle(BUTTON_RADIUS * scale);
+    painter.hollow = false;
+}
@@ -0,0 +1,266 @@
+use crate::{settings::options::Options, udp::Reconnect, ServerPort};
+use bevy::{prelude::*, window::PrimaryWindow};
+use crossbeam_channel::{Receiver, Sender};
+use std::{
+    io::{self, BufRead, BufReader, Read, Write},
+    net::{IpAddr, Ipv4Addr, SocketAddr, TcpListener, TcpStream},
+    thread,
+    time::Duration,
+};
+
+/// The local port the running instance listens on for launches to hand off to it
+const INSTANCE_PORT: u16 = 45242;
+/// How long a new launch waits to connect to the running instance before starting anyways
+const HANDOFF_TIMEOUT: Duration = Duration::from_secs(1);
+/// The running instance answers from its next frame, which can take a while when the field is still loading
+const REPLY_TIMEOUT: Duration = Duration::from_secs(5);
+/// Handoffs are a single short line, so anything longer isn't from another instance
+const MAX_HANDOFF_LEN: u64 = 128;
+
+/// Listens for later launches if this is the running instance, `handoffs` is `None` otherwise
+pub struct SingleInstancePlugin {
+    pub handoffs: Option<InstanceHandoffs>,
+}
+
+impl Plugin for SingleInstancePlugin {
+    fn build(&self, app: &mut App) {
+        if let Some(handoffs) = self.handoffs.clone() {
+            app.insert_resource(handoffs).add_systems(Update, apply_handoffs);
+        }
+    }
+}
+
+/// Where a later launch wanted to connect to
+#[derive(Debug, PartialEq, Eq)]
+pub struct Handoff {
+    primary_port: u16,
+    secondary_port: u16,
+    /// `None` if it wasn't given `--host`, so the host in the settings is kept
+    host: Option<IpAddr>,
+    /// Whether the running instance should switch to these ports even if it's connected to different ones
+    take_over: bool,
+}
+
+impl Handoff {
+    /// `take-over` or `focus`, the primary and secondary ports, and then optionally the host, separated by spaces
+    fn to_line(&self) -> String {
+        let mode = if self.take_over { "take-over" } else { "focus" };
+
+        match self.host {
+            Some(host) => format!("{mode} {} {} {host}\n", self.primary_port, self.secondary_port),
+            None => format!("{mode} {} {}\n", self.primary_port, self.secondary_port),
+        }
+    }
+
+    fn from_line(line: &str) -> Option<Self> {
+        let mut parts = line.split_whitespace();
+        let take_over = match parts.next()? {
+            "take-over" => true,
+            "focus" => false,
+            _ => return None,
+        };
+        let primary_port = parts.next()?.parse().ok()?;
+        let secondary_port = parts.next()?.parse().ok()?;
+        let host = match parts.next() {
+            Some(host) => Some(host.parse().ok()?),
+            None => None,
+        };
+
+        if parts.next().is_some() {
+            return None;
+        }
+
+        Some(Self {
+            primary_port,
+            secondary_port,
+            host,
+            take_over,
+        })
+    }
+
+    /// Whether this instance is already connected to where the launch wanted to
+    fn matches(&self, port: &ServerPort, current_host: Option<IpAddr>) -> bool {
+        self.primary_port == port.primary_port
+            && self.secondary_port == port.secondary_port
+            && self.host.is_none_or(|host| Some(host) == current_host)
+    }
+}
+
+/// Launches that were handed off to this instance, along with the stream to answer them on
+#[derive(Resource, Deref, Clone)]
+pub struct InstanceHandoffs(Receiver<(Handoff, TcpStream)>);
+
+pub enum InstanceLock {
+    /// This is the only instance, and it's listening for later launches
+    Acquired(InstanceHandoffs),
+    /// Another instance is running and is now connected to the given ports
+    HandedOff,
+    /// Another instance is running but connected to different ports, and `--take-over` wasn't given
+    Declined,
+    /// Something else is using the instance port, so this instance can't tell if it's the only one
+    Unavailable,
+}
+
+/// Becomes the running instance, or hands the ports to connect to off to the one that already is
+///
+/// The running instance only switches to different ports if `take_over` is set, so a second launch for another simulation doesn't disconnect it
+pub fn lock(port: &ServerPort, take_over: bool) -> InstanceLock {
+    let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), INSTANCE_PORT);
+
+    match TcpListener::bind(addr) {
+        Ok(listener) => {
+            let (tx, rx) = crossbeam_channel::unbounded();
+            listen_for_handoffs(listener, tx);
+            InstanceLock::Acquired(InstanceHandoffs(rx))
+        }
+        Err(e) if e.kind() == io::ErrorKind::AddrInUse => {
+            let handoff = Handoff {
+                primary_port: port.primary_port,
+                secondary_port: port.secondary_port,
+                host: port.host,
+                take_over,
+            };
+
+            match hand_off(addr, &handoff) {
+                Ok(true) => InstanceLock::HandedOff,
+                Ok(false) => InstanceLock::Declined,
+                Err(e) => {
+                    eprintln!("Port {INSTANCE_PORT} is in use, but not by another instance of RLViser ({e})");
+                    InstanceLock::Unavailable
+                }
+            }
+        }
+        Err(e) => {
+            eprintln!("Failed to check for other instances of RLViser due to: {e}");
+            InstanceLock::Unavailable
+        }
+    }
+}
+
+/// Whether the running instance accepted the handoff
+fn hand_off(addr: SocketAddr, handoff: &Handoff) -> io::Result<bool> {
+    let mut stream = TcpStream::connect_timeout(&addr, HANDOFF_TIMEOUT)?;
+    stream.set_read_timeout(Some(REPLY_TIMEOUT))?;
+    stream.write_all(handoff.to_line().as_bytes())?;
+
+    // the running instance answers so we know it wasn't some other program
+    let mut reply = String::new();
+    BufReader::new(stream.take(MAX_HANDOFF_LEN)).read_line(&mut reply)?;
+    match reply.trim() {
+        "ok" => Ok(true),
+        "busy" => Ok(false),
+        _ => Err(io::Error::new(io::ErrorKind::InvalidData, "unexpected reply")),
+    }
+}
+
+fn listen_for_handoffs(listener: TcpListener, tx: Sender<(Handoff, TcpStream)>) {
+    thread::Builder::new()
+        .name(String::from("rlviser-instance"))
+        .spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(stream) = stream else {
+                    continue;
+                };
+
+                drop(stream.set_read_timeout(Some(HANDOFF_TIMEOUT)));
+
+                let mut line = String::new();
+                let Ok(reader) = stream.try_clone() else {
+                    continue;
+                };
+                if BufReader::new(reader.take(MAX_HANDOFF_LEN)).read_line(&mut line).is_err() {
+                    continue;
+                }
+
+                let Some(handoff) = Handoff::from_line(&line) else {
+                    continue;
+                };
+
+                // `apply_handoffs` answers, since only it knows where this instance is connected to
+                if tx.send((handoff, stream)).is_err() {
+                    return;
+                }
+            }
+        })
+        .unwrap();
+}
+
+/// Brings the window to the front for launches that wanted to connect to the same ports,
+/// or connects to wherever the latest launch with `--take-over` wanted to
+fn apply_handoffs(
+    handoffs: Res<InstanceHandoffs>,
+    mut port: ResMut<ServerPort>,
+    mut options: ResMut<Options>,
+    mut reconnect: EventWriter<Reconnect>,
+    mut windows: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    for (handoff, mut stream) in handoffs.try_iter() {
+        let current_host = port.host.or_else(|| options.remote_host.parse().ok());
+
+        if handoff.matches(&port, current_host) {
+            info!("Another launch for the same simulation was handed off to this instance");
+        } else if handoff.take_over {
+            port.primary_port = handoff.primary_port;
+            port.secondary_port = handoff.secondary_port;
+            if let Some(host) = handoff.host {
+                options.remote_host = host.to_string();
+            }
+
+            info!("Another launch took over this instance");
+            reconnect.send(Reconnect);
+        } else {
+            drop(stream.write_all(b"busy\n"));
+            continue;
+        }
+
+        drop(stream.write_all(b"ok\n"));
+
+        if let Ok(mut window) = windows.get_single_mut() {
+            window.focused = true;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn handoff_without_host() {
+        let handoff = Handoff::from_line("focus 34254 45243\n").unwrap();
+
+        assert_eq!(
+            handoff,
+            Handoff {
+                primary_port: 34254,
+                secondary_port: 45243,
+                host: None,
+                take_over: false,
+            }
+        );
+        assert_eq!(handoff.to_line(), "focus 34254 45243\n");
+    }
+
+    #[test]
+    fn handoff_with_host() {
+        let handoff = Handoff::from_line("take-over 34254 45243 ::1\n").unwrap();
+
+        assert_eq!(handoff.host, Some(IpAddr::V6(std::net::Ipv6Addr::LOCALHOST)));
+        assert!(handoff.take_over);
+        assert_eq!(Handoff::from_line(&handoff.to_line()), Some(handoff));
+    }
+
+    #[test]
+    fn invalid_handoffs() {
+        for line in [
+            "",
+            "34254 45243",
+            "focus 34254",
+            "focus 34254 65536",
+            "focus 34254 45243 localhost",
+            "focus 34254 45243 127.0.0.1 extra",
+        ] {
+            assert_eq!(Handoff::from_line(line), None, "{line:?}");
+        }
+    }
+}
@@ -1,17 +1,66 @@
-#![allow(clippy::too_many_arguments, clippy::needless_pass_by_value, clippy::module_name_repetitions)]
+#![allow(
+    clippy::too_many_arguments,
+    clippy::needless_pass_by_value,
+    clippy::module_name_repetitions,
+    clippy::type_complexity
+)]
 
 mod assets;
+mod ball_spin;
+mod blueprint;
 mod bytes;
 mod camera;
+mod camera_path;
+mod capture;
+mod cli;
+mod crowd;
+mod director;
+mod effects;
+mod field_style;
+mod goal;
+mod headless;
+mod heatseeker;
+mod hoops;
+#[cfg(feature = "http_api")]
+mod http_api;
+mod input_viewer;
+mod instance;
 mod mesh;
+mod message_feed;
 mod morton;
+mod nametags;
+mod physics_debug;
+#[cfg(debug_assertions)]
+mod precook;
+mod prediction;
 mod renderer;
+mod replay;
+#[cfg(feature = "replay_import")]
+mod replay_import;
+#[cfg(feature = "rlbot")]
+mod rlbot;
 mod rocketsim;
+mod scoreboard;
 mod settings;
+#[cfg(feature = "sound")]
+mod sound;
 mod spectator;
+mod split_screen;
+mod state_gizmos;
+mod team_hud;
+mod trails;
 mod udp;
 
-use std::env;
+#[cfg(feature = "replay_import")]
+use std::path::PathBuf;
+use std::{
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+    ops::RangeInclusive,
+    process,
+    str::FromStr,
+};
+#[cfg(feature = "threaded")]
+use std::{num::NonZeroUsize, thread};
 
 use bevy::{
     diagnostic::LogDiagnosticsPlugin,
@@ -19,7 +68,8 @@ use bevy::{
     prelude::*,
     window::PresentMode,
 };
-use settings::{cache_handler, gui};
+use instance::InstanceLock;
+use settings::{cache_handler, gui, options::Options, profiles::StartupProfile, state_script::StartupStateScript};
 
 #[derive(Clone, Eq, PartialEq, Debug, Hash, Default, States)]
 enum GameLoadState {
@@ -32,46 +82,183 @@ enum GameLoadState {
     None,
 }
 
+/// How many threads to keep out of Bevy's task pools, so the UDP receive and decode threads don't have to wait for a core
+///
+/// The send thread is idle between the few packets it sends, so it isn't counted.
+/// Machines with 4 threads or less only give up one, since Bevy needs the rest more than networking does.
+#[cfg(feature = "threaded")]
+const fn reserved_udp_threads(available_threads: usize) -> usize {
+    if available_threads <= 4 {
+        1
+    } else {
+        2
+    }
+}
+
+/// The port to bind the UDP socket to when it isn't given with `--secondary-port` or as the second argument
+const DEFAULT_SECONDARY_PORT: u16 = 45243;
+
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+pub enum Transport {
+    #[default]
+    Udp,
+    /// Length-prefixed packets over a TCP stream, for when UDP packets get lost (e.g. over Wi-Fi)
+    Tcp,
+    /// RLBot's flatbuffer socket, to watch an RLBot match instead of a RocketSim simulation
+    #[cfg(feature = "rlbot")]
+    RLBot,
+    /// A Rocket League replay given with `--replay`, played back as if it were a simulation
+    #[cfg(feature = "replay_import")]
+    ReplayFile,
+}
+
+impl Transport {
+    /// The port to connect to when it isn't given with `--primary-port` or as the first argument
+    const fn default_port(self) -> u16 {
+        match self {
+            Self::Udp | Self::Tcp => 34254,
+            #[cfg(feature = "rlbot")]
+            Self::RLBot => rlbot::RLBOT_PORT,
+            // nothing is connected to
+            #[cfg(feature = "replay_import")]
+            Self::ReplayFile => 0,
+        }
+    }
+}
+
+impl FromStr for Transport {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "udp" => Ok(Self::Udp),
+            "tcp" => Ok(Self::Tcp),
+            #[cfg(feature = "rlbot")]
+            "rlbot" => Ok(Self::RLBot),
+            #[cfg(not(feature = "rlbot"))]
+            "rlbot" => Err(String::from("RLViser was built without the rlbot feature")),
+            other => Err(format!("Unknown transport {other}, expected udp, tcp or rlbot")),
+        }
+    }
+}
+
 #[derive(Resource)]
 pub struct ServerPort {
+    /// The IP of the simulation, which comes from the settings if it wasn't given with `--host`
+    host: Option<IpAddr>,
     primary_port: u16,
     secondary_port: u16,
+    /// Ports to try binding the UDP socket to if the secondary port is already in use
+    port_retry: Option<RangeInclusive<u16>>,
+    transport: Transport,
+    /// The replay that's played with `Transport::ReplayFile`
+    #[cfg(feature = "replay_import")]
+    replay_file: PathBuf,
+}
+
+impl ServerPort {
+    fn out_addr(&self) -> SocketAddr {
+        SocketAddr::new(self.host.unwrap_or(IpAddr::V4(Ipv4Addr::LOCALHOST)), self.primary_port)
+    }
 }
 
 fn main() {
-    let mut args = env::args();
+    let args = cli::Args::from_env();
+
+    if args.precook {
+        #[cfg(debug_assertions)]
+        let result = precook::run();
+        // release builds embed the cache.zip that this makes, so they can't make their own
+        #[cfg(not(debug_assertions))]
+        let result = Err(std::io::Error::other("--precook is only available in debug builds"));
+
+        if let Err(e) = result {
+            eprintln!("Failed to precook assets: {e}");
+            process::exit(1);
+        }
 
-    // read the first argument and treat it as the port to connect to (u16)
-    let primary_port = args.nth(1).and_then(|s| s.parse::<u16>().ok()).unwrap_or(34254);
-    // read the second argument and treat it as the port to bind the UDP socket to (u16)
-    let secondary_port = args.next().and_then(|s| s.parse::<u16>().ok()).unwrap_or(45243);
+        return;
+    }
+
+    if let Some(path) = args.settings {
+        Options::set_path(path);
+    }
+
+    let primary_port = args.primary_port.unwrap_or(args.transport.default_port());
+    let server_port = ServerPort {
+        host: args.host,
+        primary_port,
+        secondary_port: args.secondary_port.unwrap_or(DEFAULT_SECONDARY_PORT),
+        port_retry: args.port_retry,
+        transport: args.transport,
+        #[cfg(feature = "replay_import")]
+        replay_file: args.replay_file.unwrap_or_default(),
+    };
+
+    if args.headless {
+        headless::run(server_port);
+        return;
+    }
+
+    let handoffs = if args.new_instance {
+        None
+    } else {
+        match instance::lock(&server_port, args.take_over) {
+            InstanceLock::Acquired(handoffs) => Some(handoffs),
+            InstanceLock::HandedOff => {
+                println!("RLViser is already running and connected to port {primary_port}");
+                return;
+            }
+            InstanceLock::Declined => {
+                println!("RLViser is already running for another simulation, starting a separate one");
+                None
+            }
+            InstanceLock::Unavailable => None,
+        }
+    };
 
     #[cfg(debug_assertions)]
     assets::umodel::uncook().unwrap();
 
-    App::new()
-        .insert_resource(ServerPort {
-            primary_port,
-            secondary_port,
+    let default_plugins = DefaultPlugins
+        .set(ImagePlugin {
+            default_sampler: ImageSamplerDescriptor {
+                address_mode_u: ImageAddressMode::Repeat,
+                address_mode_v: ImageAddressMode::Repeat,
+                address_mode_w: ImageAddressMode::Repeat,
+                ..default()
+            },
         })
+        .set(WindowPlugin {
+            primary_window: Some(Window {
+                title: "RLViser-rs".into(),
+                present_mode: PresentMode::AutoNoVsync,
+                ..default()
+            }),
+            ..default()
+        });
+
+    // Leave a thread or two free for the UDP threads so they don't contend with Bevy's task pools
+    #[cfg(feature = "threaded")]
+    let default_plugins = {
+        let available_threads = thread::available_parallelism().map_or(1, NonZeroUsize::get);
+
+        default_plugins.set(TaskPoolPlugin {
+            task_pool_options: TaskPoolOptions {
+                max_total_threads: available_threads
+                    .saturating_sub(reserved_udp_threads(available_threads))
+                    .max(1),
+                ..default()
+            },
+        })
+    };
+
+    App::new()
+        .insert_resource(server_port)
+        .insert_resource(StartupProfile(args.profile))
+        .insert_resource(StartupStateScript(args.replay))
         .add_plugins((
-            DefaultPlugins
-                .set(ImagePlugin {
-                    default_sampler: ImageSamplerDescriptor {
-                        address_mode_u: ImageAddressMode::Repeat,
-                        address_mode_v: ImageAddressMode::Repeat,
-                        address_mode_w: ImageAddressMode::Repeat,
-                        ..default()
-                    },
-                })
-                .set(WindowPlugin {
-                    primary_window: Some(Window {
-                        title: "RLViser-rs".into(),
-                        present_mode: PresentMode::AutoNoVsync,
-                        ..default()
-                    }),
-                    ..default()
-                }),
+            default_plugins,
             LogDiagnosticsPlugin {
                 debug: cfg!(feature = "debug"),
                 ..default()
@@ -82,6 +269,35 @@ fn main() {
             mesh::FieldLoaderPlugin,
             udp::RocketSimPlugin,
             assets::AssetsLoaderPlugin,
+            blueprint::BlueprintPlugin,
+            prediction::BallPredictionPlugin,
+            (
+                goal::GoalPlugin,
+                scoreboard::ScoreboardPlugin,
+                replay::ReplayPlugin,
+                #[cfg(feature = "sound")]
+                sound::SoundPlugin,
+                capture::CapturePlugin,
+                field_style::FieldStylePlugin,
+                heatseeker::HeatseekerPlugin,
+                hoops::HoopsPlugin,
+                split_screen::SplitScreenPlugin,
+                nametags::NameTagPlugin,
+                team_hud::TeamHudPlugin,
+                effects::EffectsPlugin,
+                physics_debug::PhysicsDebugPlugin,
+                state_gizmos::StateGizmoPlugin,
+                instance::SingleInstancePlugin { handoffs },
+            ),
+            trails::TrailPlugin,
+            (
+                input_viewer::InputViewerPlugin,
+                message_feed::MessageFeedPlugin,
+                ball_spin::BallSpinPlugin,
+            ),
+            crowd::CrowdPlugin,
+            #[cfg(feature = "http_api")]
+            http_api::HttpApiPlugin,
         ))
         .init_state::<GameLoadState>()
         .run();
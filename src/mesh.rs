@@ -21,24 +21,35 @@ use bevy::{
 use include_flate::flate;
 use serde::{Deserialize, Serialize};
 use std::{
+    borrow::Cow,
     cmp::Ordering,
-    fs::{create_dir_all, File},
+    fs::{self, create_dir_all, File},
     io::{self, Read},
-    path::Path,
+    path::{Path, PathBuf},
     rc::Rc,
     str::Utf8Error,
+    time::SystemTime,
 };
 use thiserror::Error;
 
 use crate::{
     camera::{HighlightedEntity, PrimaryCamera},
-    settings::state_setting::{EnableBallInfo, EnableCarInfo, EnablePadInfo, UserCarStates, UserPadStates},
+    prediction::arena_bounds,
+    settings::{
+        field_inspector::InspectedFieldMesh,
+        options::{WallTransparency, WallTransparencyMode},
+        state_setting::{EnableBallInfo, EnableCarInfo, EnablePadInfo, UserCarStates, UserPadStates},
+    },
+    state_gizmos::{GizmoTarget, StateSetGizmo},
     udp::{BoostPadI, Car, Connection, GameStates, SendableUdp},
 };
 use std::time::Duration;
 
 #[cfg(feature = "team_goal_barriers")]
-use crate::udp::{BLUE_COLOR, ORANGE_COLOR};
+use crate::{
+    camera::GLOW_STRENGTH,
+    udp::{BLUE_COLOR, ORANGE_COLOR},
+};
 
 #[cfg(debug_assertions)]
 use crate::camera::EntityName;
@@ -53,6 +64,8 @@ impl Plugin for FieldLoaderPlugin {
                 .add_event::<BallClicked>()
                 .add_event::<CarClicked>()
                 .add_event::<BoostPadClicked>()
+                .add_event::<FieldMeshClicked>()
+                .add_event::<ReloadField>()
                 .insert_resource(StateSetTime::default())
                 .add_systems(
                     Update,
@@ -60,30 +73,35 @@ impl Plugin for FieldLoaderPlugin {
                         handle_ball_clicked.run_if(on_event::<BallClicked>),
                         handle_car_clicked.run_if(on_event::<CarClicked>),
                         handle_boost_pad_clicked.run_if(on_event::<BoostPadClicked>),
+                        handle_field_mesh_clicked.run_if(on_event::<FieldMeshClicked>),
                         (
                             advance_stopwatch,
                             (
                                 change_ball_pos.run_if(on_event::<ChangeBallPos>),
                                 change_car_pos.run_if(on_event::<ChangeCarPos>),
                             )
-                                .run_if(|last_state_set: Res<StateSetTime>| {
-                                    // Limit state setting to avoid bogging down the simulation with state setting requests
-                                    last_state_set.0.elapsed() >= Duration::from_secs_f32(1. / 60.)
-                                }),
+                                .run_if(resource_exists::<Connection>)
+                                .run_if(|last_state_set: Res<StateSetTime>| can_state_set(&last_state_set))
+                                .run_if(|gizmo: Res<StateSetGizmo>| !gizmo.is_dragging()),
                         )
                             .chain(),
                     ),
                 );
         }
 
-        app.insert_resource(LargeBoostPadLocRots::default()).add_systems(
-            Update,
-            (
-                despawn_old_field.run_if(in_state(GameLoadState::Despawn)),
-                load_field.run_if(in_state(GameLoadState::Field)),
-                load_extra_field.run_if(in_state(GameLoadState::FieldExtra)),
-            ),
-        );
+        app.insert_resource(LargeBoostPadLocRots::default())
+            .insert_resource(StadiumLayout::default())
+            .add_systems(
+                Update,
+                (
+                    (watch_stadium_layout, reload_field.run_if(on_event::<ReloadField>))
+                        .run_if(in_state(GameLoadState::None)),
+                    despawn_old_field.run_if(in_state(GameLoadState::Despawn)),
+                    load_field.run_if(in_state(GameLoadState::Field)),
+                    load_extra_field.run_if(in_state(GameLoadState::FieldExtra)),
+                    fade_walls,
+                ),
+            );
     }
 }
 
@@ -101,7 +119,18 @@ impl From<Pointer<Drag>> for ChangeBallPos {
 }
 
 #[derive(Resource, Default)]
-struct StateSetTime(Stopwatch);
+pub struct StateSetTime(Stopwatch);
+
+impl StateSetTime {
+    pub fn reset(&mut self) {
+        self.0.reset();
+    }
+}
+
+/// Limit state setting to avoid bogging down the simulation with state setting requests
+pub fn can_state_set(last_state_set: &StateSetTime) -> bool {
+    last_state_set.0.elapsed() >= Duration::from_secs_f32(1. / 60.)
+}
 
 fn change_ball_pos(
     windows: Query<&Window, With<PrimaryWindow>>,
@@ -118,7 +147,7 @@ fn change_ball_pos(
 
     events.clear();
 
-    let Some([cam_pos, cursor_dir, plane_normal]) = project_ray_to_plane(camera, windows) else {
+    let Some([cam_pos, cursor_dir, plane_normal]) = project_ray_to_plane(&camera, &windows) else {
         return;
     };
 
@@ -149,7 +178,7 @@ fn change_car_pos(
     camera: Query<(&Camera, &GlobalTransform), With<PrimaryCamera>>,
     mut last_state_set: ResMut<StateSetTime>,
 ) {
-    let Some([cam_pos, cursor_dir, plane_normal]) = project_ray_to_plane(camera, windows) else {
+    let Some([cam_pos, cursor_dir, plane_normal]) = project_ray_to_plane(&camera, &windows) else {
         events.clear();
         return;
     };
@@ -190,17 +219,24 @@ impl From<Pointer<Click>> for CarClicked {
     }
 }
 
-fn handle_car_clicked(mut events: EventReader<CarClicked>, mut enable_car_info: ResMut<EnableCarInfo>, cars: Query<&Car>) {
-    let toggle_car_ids = events
-        .read()
-        .filter_map(|event| {
-            if event.0 != PointerButton::Secondary {
-                return None;
-            }
+fn handle_car_clicked(
+    mut events: EventReader<CarClicked>,
+    mut enable_car_info: ResMut<EnableCarInfo>,
+    mut gizmo: ResMut<StateSetGizmo>,
+    cars: Query<&Car>,
+) {
+    let mut toggle_car_ids = Vec::new();
+    for event in events.read() {
+        let Ok(car_id) = cars.get(event.1).map(Car::id) else {
+            continue;
+        };
 
-            cars.get(event.1).map(Car::id).ok()
-        })
-        .collect::<Vec<_>>();
+        match event.0 {
+            PointerButton::Secondary => toggle_car_ids.push(car_id),
+            PointerButton::Middle => gizmo.toggle(GizmoTarget::Car(car_id)),
+            PointerButton::Primary => {}
+        }
+    }
 
     for car in cars.iter() {
         if toggle_car_ids.contains(&car.id()) {
@@ -209,9 +245,9 @@ fn handle_car_clicked(mut events: EventReader<CarClicked>, mut enable_car_info:
     }
 }
 
-fn project_ray_to_plane(
-    camera: Query<(&Camera, &GlobalTransform), With<PrimaryCamera>>,
-    windows: Query<&Window, With<PrimaryWindow>>,
+pub fn project_ray_to_plane(
+    camera: &Query<(&Camera, &GlobalTransform), With<PrimaryCamera>>,
+    windows: &Query<&Window, With<PrimaryWindow>>,
 ) -> Option<[Vec3A; 3]> {
     let (camera, global_transform) = camera.single();
     let cursor_coords = windows.single().cursor_position()?;
@@ -228,7 +264,7 @@ fn project_ray_to_plane(
     Some([cam_pos, cursor_dir, plane_normal])
 }
 
-fn get_move_object_target(cam_pos: Vec3A, cursor_dir: Vec3A, plane_normal: Vec3A, plane_point: Vec3A) -> Vec3A {
+pub fn get_move_object_target(cam_pos: Vec3A, cursor_dir: Vec3A, plane_normal: Vec3A, plane_point: Vec3A) -> Vec3A {
     // get projection factor
     let lambda = (plane_point - cam_pos).dot(plane_normal) / plane_normal.dot(cursor_dir);
 
@@ -245,14 +281,29 @@ impl From<Pointer<Click>> for BallClicked {
     }
 }
 
-fn handle_ball_clicked(mut events: EventReader<BallClicked>, mut enable_ball_info: ResMut<EnableBallInfo>) {
-    // ensure that it was an odd amount of right clicks
+fn handle_ball_clicked(
+    mut events: EventReader<BallClicked>,
+    mut enable_ball_info: ResMut<EnableBallInfo>,
+    mut gizmo: ResMut<StateSetGizmo>,
+) {
+    let (mut right_clicks, mut middle_clicks) = (0, 0);
+    for event in events.read() {
+        match event.0 {
+            PointerButton::Secondary => right_clicks += 1,
+            PointerButton::Middle => middle_clicks += 1,
+            PointerButton::Primary => {}
+        }
+    }
+
+    // ensure that it was an odd amount of clicks
     // e.x. right click -> open then right click -> close (an event amount of clicks) wouldn't change the state
-    if events.read().filter(|event| event.0 == PointerButton::Secondary).count() % 2 == 0 {
-        return;
+    if right_clicks % 2 == 1 {
+        enable_ball_info.toggle();
     }
 
-    enable_ball_info.toggle();
+    if middle_clicks % 2 == 1 {
+        gizmo.toggle(GizmoTarget::Ball);
+    }
 }
 
 #[derive(Event)]
@@ -280,6 +331,23 @@ fn handle_boost_pad_clicked(
     }
 }
 
+#[derive(Event)]
+pub struct FieldMeshClicked(PointerButton, Entity);
+
+impl From<Pointer<Click>> for FieldMeshClicked {
+    fn from(event: Pointer<Click>) -> Self {
+        Self(event.button, event.target)
+    }
+}
+
+fn handle_field_mesh_clicked(mut events: EventReader<FieldMeshClicked>, mut inspected: ResMut<InspectedFieldMesh>) {
+    for event in events.read() {
+        if event.0 == PointerButton::Secondary {
+            inspected.toggle(event.1);
+        }
+    }
+}
+
 fn load_extra_field(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
@@ -434,6 +502,34 @@ const BLACKLIST_MESH_MATS: [&str; 8] = [
 
 const NO_SHADOWS: [&str; 1] = ["Proto_BBall.SM.Net_Collision"];
 
+/// Meshes that make up the walls, glass and ceiling around the field
+const WALL_MESHES: [&str; 15] = [
+    "Field_STD_Frame",
+    "Field_STD_Glass",
+    "Field_STD_Trim",
+    "Field_STD_TrimB",
+    "FieldFrame_Outer",
+    "Goal_STD_Glass",
+    "Goal_STD_Glass_Outer",
+    "FFCage_Full",
+    "FF_Goal",
+    "FF_Roof",
+    "FF_Side",
+    "BBall_Walls_03",
+    "BBall_OuterWalls_01",
+    "BBall_OriginalLattice",
+    "BBall_CornerLattice_01",
+];
+
+/// Part of the walls around the field, which can be faded out so it doesn't block the view
+///
+/// Each wall has its own copy of its material so that fading it doesn't affect anything else
+#[derive(Component)]
+pub struct FieldWall {
+    alpha: f32,
+    alpha_mode: AlphaMode,
+}
+
 #[derive(Resource, Default)]
 pub struct LargeBoostPadLocRots {
     pub locs: Vec<Vec2>,
@@ -444,9 +540,79 @@ pub struct LargeBoostPadLocRots {
 #[require(Mesh3d, MeshMaterial3d<StandardMaterial>, NotShadowCaster, NotShadowReceiver)]
 pub struct StaticFieldEntity;
 
+/// Where a field mesh came from, shown when it's right clicked
+#[derive(Component)]
+pub struct FieldMeshInfo {
+    pub mesh: Box<str>,
+    pub material: Box<str>,
+}
+
 flate!(pub static STADIUM_P_LAYOUT: str from "stadiums/Stadium_P_MeshObjects.json");
 flate!(pub static HOOPS_STADIUM_P_LAYOUT: str from "stadiums/HoopsStadium_P_MeshObjects.json");
 
+/// Despawns the field and loads it again, picking up any changes to the layout on disk
+#[derive(Event)]
+pub struct ReloadField;
+
+/// Tracks the layout file in the `stadiums` folder, which is used instead of the built-in layout if it exists
+/// so stadium modders can see their changes without recompiling
+#[derive(Resource, Default)]
+pub struct StadiumLayout {
+    /// When the loaded layout was last modified, `None` if the built-in one was used
+    loaded_modified: Option<SystemTime>,
+    /// The file on disk is different from the layout that's loaded
+    pub changed: bool,
+}
+
+impl StadiumLayout {
+    const FOLDER: &'static str = "stadiums";
+
+    fn path(game_mode: GameMode) -> Option<PathBuf> {
+        let file_name = match game_mode {
+            GameMode::TheVoid => return None,
+            GameMode::Hoops => "HoopsStadium_P_MeshObjects.json",
+            _ => "Stadium_P_MeshObjects.json",
+        };
+
+        Some(Path::new(Self::FOLDER).join(file_name))
+    }
+
+    fn modified(path: &Path) -> Option<SystemTime> {
+        fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+    }
+}
+
+/// How often the layout file is checked for changes, in seconds
+const LAYOUT_CHECK_INTERVAL: f32 = 1.;
+
+fn watch_stadium_layout(
+    time: Res<Time>,
+    game_mode: Res<GameMode>,
+    mut since_check: Local<f32>,
+    mut stadium_layout: ResMut<StadiumLayout>,
+) {
+    *since_check += time.delta_secs();
+    if *since_check < LAYOUT_CHECK_INTERVAL {
+        return;
+    }
+
+    *since_check = 0.;
+    let Some(path) = StadiumLayout::path(*game_mode) else {
+        return;
+    };
+
+    let changed = StadiumLayout::modified(&path) != stadium_layout.loaded_modified;
+    if changed && !stadium_layout.changed {
+        info!("{} changed, reload the field to see the changes", path.display());
+    }
+
+    stadium_layout.changed = changed;
+}
+
+fn reload_field(mut state: ResMut<NextState<GameLoadState>>) {
+    state.set(GameLoadState::Despawn);
+}
+
 fn despawn_old_field(
     mut commands: Commands,
     mut state: ResMut<NextState<GameLoadState>>,
@@ -464,6 +630,10 @@ fn despawn_old_field(
     state.set(GameLoadState::Field);
 }
 
+/// The goals cover a lot of the screen, so they glow dimmer than the boost pads to not wash it out with bloom
+#[cfg(feature = "team_goal_barriers")]
+const GOAL_GLOW_STRENGTH: f32 = GLOW_STRENGTH / 2.;
+
 #[cfg(feature = "team_goal_barriers")]
 fn load_goals(
     game_mode: GameMode,
@@ -477,12 +647,8 @@ fn load_goals(
                 .spawn((
                     Mesh3d(meshes.add(Rectangle::from_size(Vec2::splat(1000.)))),
                     MeshMaterial3d(materials.add(StandardMaterial {
-                        base_color: {
-                            let mut color = BLUE_COLOR.with_alpha(0.8);
-                            color.blue *= 2.;
-                            Color::Srgba(color)
-                        },
-                        emissive: LinearRgba::from(BLUE_COLOR.with_alpha(0.5)),
+                        base_color: Color::BLACK.with_alpha(0.8),
+                        emissive: LinearRgba::from(BLUE_COLOR) * GOAL_GLOW_STRENGTH,
                         double_sided: true,
                         cull_mode: None,
                         alpha_mode: AlphaMode::Add,
@@ -504,12 +670,8 @@ fn load_goals(
                 .spawn((
                     Mesh3d(meshes.add(Rectangle::from_size(Vec2::splat(1000.)))),
                     MeshMaterial3d(materials.add(StandardMaterial {
-                        base_color: {
-                            let mut color = ORANGE_COLOR.with_alpha(0.8);
-                            color.red *= 2.;
-                            Color::Srgba(color)
-                        },
-                        emissive: LinearRgba::from(ORANGE_COLOR.with_alpha(0.5)),
+                        base_color: Color::BLACK.with_alpha(0.8),
+                        emissive: LinearRgba::from(ORANGE_COLOR) * GOAL_GLOW_STRENGTH,
                         double_sided: true,
                         cull_mode: None,
                         alpha_mode: AlphaMode::Add,
@@ -532,7 +694,7 @@ fn load_goals(
     }
 }
 
-fn load_field(
+pub fn load_field(
     mut commands: Commands,
     mut materials: ResMut<Assets<StandardMaterial>>,
     mut meshes: ResMut<Assets<Mesh>>,
@@ -542,8 +704,9 @@ fn load_field(
     game_mode: Res<GameMode>,
     render_device: Option<Res<RenderDevice>>,
     asset_server: Res<AssetServer>,
+    mut stadium_layout: ResMut<StadiumLayout>,
 ) {
-    let layout: &str = match *game_mode {
+    let builtin_layout: &str = match *game_mode {
         GameMode::TheVoid => {
             state.set(GameLoadState::None);
             return;
@@ -552,7 +715,30 @@ fn load_field(
         _ => &STADIUM_P_LAYOUT,
     };
 
-    let (_pickup_boost, structures, the_world): (Section, Node, Node) = serde_json::from_str(layout).unwrap();
+    let path = StadiumLayout::path(*game_mode).unwrap();
+    stadium_layout.loaded_modified = StadiumLayout::modified(&path);
+    stadium_layout.changed = false;
+
+    let layout = match fs::read_to_string(&path) {
+        Ok(layout) => {
+            info!("Loading field layout from {}", path.display());
+            Cow::Owned(layout)
+        }
+        Err(e) => {
+            if e.kind() != io::ErrorKind::NotFound {
+                error!("Failed to read {} due to: {e}", path.display());
+            }
+
+            Cow::Borrowed(builtin_layout)
+        }
+    };
+
+    let (_pickup_boost, structures, the_world): (Section, Node, Node) = serde_json::from_str(&layout)
+        .or_else(|e| {
+            error!("Failed to parse {} due to: {e}, using the built-in layout", path.display());
+            serde_json::from_str(builtin_layout)
+        })
+        .unwrap();
     #[cfg(debug_assertions)]
     {
         // this double-layer of debug_assertion checks is because 'name' won't be present in release mode
@@ -665,7 +851,24 @@ fn process_info_node(
             mat.as_ref()
         };
 
-        let material = get_material(mat_name, materials, asset_server, None, side, images, render_device);
+        let mut material = get_material(mat_name, materials, asset_server, None, side, images, render_device);
+
+        let is_wall = node
+            .static_mesh
+            .rsplit_once('.')
+            .is_some_and(|(_, name)| WALL_MESHES.contains(&name));
+        let wall = if is_wall {
+            materials.get(&material).cloned().map(|wall_material| {
+                let wall = FieldWall {
+                    alpha: wall_material.base_color.alpha(),
+                    alpha_mode: wall_material.alpha_mode,
+                };
+                material = materials.add(wall_material);
+                wall
+            })
+        } else {
+            None
+        };
 
         let mut transform = node.get_transform();
 
@@ -687,13 +890,69 @@ fn process_info_node(
             #[cfg(debug_assertions)]
             EntityName::from(format!("{} | {mat}", node.static_mesh)),
             StaticFieldEntity,
+            FieldMeshInfo {
+                mesh: Box::from(node.static_mesh.as_ref()),
+                material: Box::from(mat_name),
+            },
         ));
         obj.observe(target_insert::<Pointer<Over>>(HighlightedEntity))
-            .observe(target_remove::<Pointer<Out>, HighlightedEntity>);
+            .observe(target_remove::<Pointer<Out>, HighlightedEntity>)
+            .observe(send_event::<Pointer<Click>, FieldMeshClicked>);
 
         if NO_SHADOWS.contains(&node.static_mesh.as_ref()) {
             obj.insert(NotShadowCaster).insert(NotShadowReceiver);
         }
+
+        if let Some(wall) = wall {
+            obj.insert(wall);
+        }
+    }
+}
+
+/// How long it takes for the walls to fade in or out, in seconds
+const WALL_FADE_TIME: f32 = 0.3;
+
+fn fade_walls(
+    wall_transparency: Res<WallTransparency>,
+    game_states: Res<GameStates>,
+    camera: Query<&Transform, With<PrimaryCamera>>,
+    walls: Query<(&MeshMaterial3d<StandardMaterial>, &FieldWall)>,
+    new_walls: Query<(), Added<FieldWall>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    time: Res<Time>,
+    mut fade: Local<f32>,
+) {
+    let faded = match wall_transparency.mode {
+        WallTransparencyMode::Off => false,
+        WallTransparencyMode::Always => true,
+        WallTransparencyMode::WhenOutside => camera.get_single().is_ok_and(|camera| {
+            let pos = camera.translation;
+            arena_bounds(game_states.current.game_mode)
+                .is_some_and(|bounds| pos.x.abs() > bounds.x || pos.z.abs() > bounds.y || pos.y > bounds.z || pos.y < 0.)
+        }),
+    };
+
+    let last_fade = *fade;
+    let step = time.delta_secs() / WALL_FADE_TIME;
+    *fade = if faded {
+        (*fade + step).min(1.)
+    } else {
+        (*fade - step).max(0.)
+    };
+
+    if *fade == last_fade && !wall_transparency.is_changed() && new_walls.is_empty() {
+        return;
+    }
+
+    let opacity = 1. - *fade * (1. - wall_transparency.opacity);
+
+    for (material, wall) in &walls {
+        let Some(material) = materials.get_mut(material) else {
+            continue;
+        };
+
+        material.base_color.set_alpha(wall.alpha * opacity);
+        material.alpha_mode = if opacity < 1. { AlphaMode::Blend } else { wall.alpha_mode };
     }
 }
 
@@ -0,0 +1,172 @@
+use crate::{
+    nametags::CarNames,
+    settings::options::{ShowMessageFeed, UiOverlayScale},
+    udp::{get_color_from_team, GameStates},
+};
+use bevy::prelude::*;
+
+pub struct MessageFeedPlugin;
+
+impl Plugin for MessageFeedPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<BotMessage>()
+            .add_systems(Startup, setup)
+            .add_systems(Update, (add_messages, fade_messages).chain());
+    }
+}
+
+/// A line of text sent by the simulation in a `Message` packet, like a bot narrating what it's doing
+#[derive(Event, Clone, Debug)]
+pub struct BotMessage {
+    /// The car the message is about, or 0 if it isn't about any car
+    pub car_id: u32,
+    pub text: String,
+}
+
+const FEED_MARGIN: f32 = 20.;
+const FEED_TOP: f32 = 200.;
+const FEED_FONT_SIZE: f32 = 16.;
+const ROW_PADDING: f32 = 4.;
+/// Older messages are removed once there are more than this many on screen
+const MAX_MESSAGES: usize = 8;
+/// How long a message stays on screen, in seconds
+const MESSAGE_SECS: f32 = 6.;
+/// How long it takes a message to fade out at the end of its time on screen, in seconds
+const FADE_SECS: f32 = 1.;
+
+const BACKGROUND_ALPHA: f32 = 0.5;
+
+#[derive(Component)]
+struct MessageFeedRoot;
+
+#[derive(Component)]
+struct FeedMessage {
+    age: f32,
+}
+
+fn setup(mut commands: Commands) {
+    commands.spawn((
+        Node {
+            position_type: PositionType::Absolute,
+            right: Val::Px(FEED_MARGIN),
+            top: Val::Px(FEED_TOP),
+            flex_direction: FlexDirection::Column,
+            align_items: AlignItems::FlexEnd,
+            row_gap: Val::Px(ROW_PADDING),
+            ..default()
+        },
+        MessageFeedRoot,
+    ));
+}
+
+fn add_messages(
+    mut commands: Commands,
+    mut events: EventReader<BotMessage>,
+    states: Res<GameStates>,
+    car_names: Res<CarNames>,
+    show_message_feed: Res<ShowMessageFeed>,
+    ui_scale: Res<UiOverlayScale>,
+    root: Query<Entity, With<MessageFeedRoot>>,
+    messages: Query<(Entity, &FeedMessage)>,
+) {
+    let Ok(root) = root.get_single() else {
+        events.clear();
+        return;
+    };
+
+    if !show_message_feed.0 {
+        events.clear();
+        for (entity, _) in &messages {
+            commands.entity(entity).despawn_recursive();
+        }
+
+        return;
+    }
+
+    let new_messages = events.read().collect::<Vec<_>>();
+    if new_messages.is_empty() {
+        return;
+    }
+
+    // make room by removing the oldest messages first
+    let mut old_messages = messages.iter().collect::<Vec<_>>();
+    old_messages.sort_unstable_by(|(_, a), (_, b)| b.age.total_cmp(&a.age));
+    let num_to_remove = (old_messages.len() + new_messages.len()).saturating_sub(MAX_MESSAGES);
+    for (entity, _) in old_messages.into_iter().take(num_to_remove) {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    let scale = ui_scale.scale;
+    let font = TextFont {
+        font_size: FEED_FONT_SIZE * scale,
+        ..default()
+    };
+
+    commands.entity(root).with_children(|parent| {
+        for message in new_messages.iter().skip(new_messages.len().saturating_sub(MAX_MESSAGES)) {
+            // the name of the car the message is about, in the color of its team
+            let sender = (message.car_id != 0).then(|| {
+                let name = car_names
+                    .0
+                    .get(&message.car_id)
+                    .cloned()
+                    .unwrap_or_else(|| format!("Car {}", message.car_id));
+                let color = states
+                    .current
+                    .cars
+                    .iter()
+                    .find(|car_info| car_info.id == message.car_id)
+                    .map_or(Color::WHITE, |car_info| get_color_from_team(car_info.team));
+
+                (name, color)
+            });
+
+            parent
+                .spawn((
+                    Node {
+                        column_gap: Val::Px(ROW_PADDING * 2. * scale),
+                        padding: UiRect::all(Val::Px(ROW_PADDING * scale)),
+                        ..default()
+                    },
+                    BackgroundColor(Color::BLACK.with_alpha(BACKGROUND_ALPHA)),
+                    FeedMessage { age: 0. },
+                ))
+                .with_children(|row| {
+                    if let Some((name, color)) = sender {
+                        row.spawn((Text::new(name), font.clone(), TextColor(color)));
+                    }
+
+                    row.spawn((Text::new(message.text.clone()), font.clone(), TextColor(Color::WHITE)));
+                });
+        }
+    });
+}
+
+/// Fades messages out at the end of their time on screen, and then removes them
+fn fade_messages(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut messages: Query<(Entity, &mut FeedMessage, &mut BackgroundColor, &Children)>,
+    mut texts: Query<&mut TextColor>,
+) {
+    for (entity, mut message, mut background, children) in &mut messages {
+        message.age += time.delta_secs();
+
+        if message.age >= MESSAGE_SECS {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+
+        let alpha = ((MESSAGE_SECS - message.age) / FADE_SECS).min(1.);
+        if alpha >= 1. {
+            continue;
+        }
+
+        background.0.set_alpha(alpha * BACKGROUND_ALPHA);
+
+        let mut iter = texts.iter_many_mut(children);
+        while let Some(mut color) = iter.fetch_next() {
+            color.0.set_alpha(alpha);
+        }
+    }
+}
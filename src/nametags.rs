@@ -0,0 +1,114 @@
+use crate::{camera::PrimaryCamera, settings::options::ShowCarNames, udp::Car};
+use bevy::{color::palettes::css, prelude::*, utils::HashMap};
+
+pub struct NameTagPlugin;
+
+impl Plugin for NameTagPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(CarNames::default()).add_systems(Update, update_name_tags);
+    }
+}
+
+/// Names for the cars, sent by the simulation in a `CarMetadata` packet
+#[derive(Clone, Debug)]
+pub struct CarMetadata(pub Vec<(u32, String)>);
+
+#[derive(Resource, Default)]
+pub struct CarNames(pub HashMap<u32, String>);
+
+#[derive(Component)]
+struct NameTag(u32);
+
+const NAME_TAG_FONT_SIZE: f32 = 20.;
+/// How far above the center of the car the name is drawn
+const NAME_TAG_HEIGHT: f32 = 120.;
+/// Name tags start fading out past this distance...
+const FADE_START_DISTANCE: f32 = 2000.;
+/// ...and are fully transparent past this distance
+const FADE_END_DISTANCE: f32 = 8000.;
+
+fn update_name_tags(
+    mut commands: Commands,
+    car_names: Res<CarNames>,
+    show_car_names: Res<ShowCarNames>,
+    cars: Query<(&Car, &GlobalTransform, &Visibility)>,
+    camera: Query<(&Camera, &GlobalTransform), With<PrimaryCamera>>,
+    mut tags: Query<
+        (
+            Entity,
+            &NameTag,
+            &mut Text,
+            &mut TextColor,
+            &mut Node,
+            &ComputedNode,
+            &mut Visibility,
+        ),
+        Without<Car>,
+    >,
+) {
+    let cars = cars
+        .iter()
+        .map(|(car, transform, visibility)| (car.id(), (transform.translation(), visibility)))
+        .collect::<HashMap<_, _>>();
+
+    let mut tagged = Vec::with_capacity(cars.len());
+    let (camera, camera_transform) = camera.single();
+
+    for (entity, tag, mut text, mut color, mut node, computed, mut visibility) in &mut tags {
+        let (Some(name), Some(&(pos, car_visibility))) = (car_names.0.get(&tag.0), cars.get(&tag.0)) else {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        };
+
+        tagged.push(tag.0);
+
+        if !show_car_names.0 || *car_visibility == Visibility::Hidden {
+            *visibility = Visibility::Hidden;
+            continue;
+        }
+
+        let pos = pos + Vec3::Y * NAME_TAG_HEIGHT;
+        let Ok(screen_pos) = camera.world_to_viewport(camera_transform, pos) else {
+            // behind the camera
+            *visibility = Visibility::Hidden;
+            continue;
+        };
+
+        *visibility = Visibility::Inherited;
+
+        if text.0 != *name {
+            text.0.clone_from(name);
+        }
+
+        // center the text above the car
+        let size = computed.size() * computed.inverse_scale_factor();
+        node.left = Val::Px(screen_pos.x - size.x / 2.);
+        node.top = Val::Px(screen_pos.y - size.y / 2.);
+
+        let distance = camera_transform.translation().distance(pos);
+        let alpha = 1. - ((distance - FADE_START_DISTANCE) / (FADE_END_DISTANCE - FADE_START_DISTANCE)).clamp(0., 1.);
+        color.0.set_alpha(alpha);
+    }
+
+    for (&id, name) in &car_names.0 {
+        if !cars.contains_key(&id) || tagged.contains(&id) {
+            continue;
+        }
+
+        commands.spawn((
+            Text::new(name.clone()),
+            TextFont {
+                font_size: NAME_TAG_FONT_SIZE,
+                ..default()
+            },
+            TextColor(Color::from(css::WHITE)),
+            Node {
+                position_type: PositionType::Absolute,
+                ..default()
+            },
+            // hidden until it has been positioned
+            Visibility::Hidden,
+            NameTag(id),
+        ));
+    }
+}
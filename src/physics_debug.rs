@@ -0,0 +1,286 @@
+use crate::{
+    hoops,
+    rocketsim::{GameMode, Team},
+    settings::options::PhysicsDebug,
+    udp::{get_color_from_team, GameStates, ToBevyMat, ToBevyVec},
+};
+use bevy::{color::palettes::css, math::Vec3A, prelude::*, utils::HashMap};
+
+pub struct PhysicsDebugPlugin;
+
+impl Plugin for PhysicsDebugPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                draw_hit_markers.run_if(|physics_debug: Res<PhysicsDebug>| physics_debug.hit_markers),
+                draw_contact_normals.run_if(|physics_debug: Res<PhysicsDebug>| physics_debug.contact_normals),
+                flash_car_bumps.run_if(|physics_debug: Res<PhysicsDebug>| physics_debug.bump_flashes),
+                draw_field_bounds.run_if(|physics_debug: Res<PhysicsDebug>| physics_debug.field_bounds),
+                draw_goal_lines.run_if(|physics_debug: Res<PhysicsDebug>| physics_debug.goal_lines),
+            ),
+        );
+    }
+}
+
+/// How long markers and flashes stay on screen after a hit or bump, in seconds
+const FADE_SECS: f32 = 1.5;
+const HIT_MARKER_RADIUS: f32 = 15.;
+const HIT_MARKER_COLOR: Srgba = css::LIME;
+const CONTACT_NORMAL_LENGTH: f32 = 200.;
+const CONTACT_NORMAL_COLOR: Srgba = css::AQUA;
+const BUMP_FLASH_COLOR: Srgba = css::WHITE;
+
+/// How visible something that happened `age` seconds ago still is
+fn fade(age: f32) -> f32 {
+    (1. - age / FADE_SECS).max(0.)
+}
+
+struct HitMarker {
+    tick_count_when_hit: u64,
+    pos: Vec3,
+    age: f32,
+}
+
+/// Marks where on the ball each car last hit it, at the position the hit happened
+fn draw_hit_markers(
+    time: Res<Time>,
+    states: Res<GameStates>,
+    mut gizmos: Gizmos,
+    mut markers: Local<HashMap<u32, HitMarker>>,
+) {
+    markers.retain(|id, _| states.current.cars.iter().any(|car| car.id == *id));
+
+    for car in &*states.current.cars {
+        let hit_info = &car.state.ball_hit_info;
+        if !hit_info.is_valid {
+            continue;
+        }
+
+        // compared with != instead of > so hits show up again during replays
+        if markers
+            .get(&car.id)
+            .is_some_and(|marker| marker.tick_count_when_hit == hit_info.tick_count_when_hit)
+        {
+            continue;
+        }
+
+        // cars that just showed up shouldn't mark hits from before
+        let age = if markers.contains_key(&car.id) { 0. } else { FADE_SECS };
+        markers.insert(
+            car.id,
+            HitMarker {
+                tick_count_when_hit: hit_info.tick_count_when_hit,
+                pos: (hit_info.ball_pos + hit_info.relative_pos_on_ball).to_bevy(),
+                age,
+            },
+        );
+    }
+
+    for marker in markers.values_mut() {
+        marker.age += time.delta_secs();
+
+        let alpha = fade(marker.age);
+        if alpha <= 0. {
+            continue;
+        }
+
+        gizmos.sphere(marker.pos, HIT_MARKER_RADIUS, HIT_MARKER_COLOR.with_alpha(alpha));
+    }
+}
+
+/// Points from each car that's touching the world along the normal of the surface it's on
+fn draw_contact_normals(states: Res<GameStates>, mut gizmos: Gizmos) {
+    for car in &*states.current.cars {
+        let world_contact = &car.state.world_contact;
+        if !world_contact.has_contact || car.state.is_demoed {
+            continue;
+        }
+
+        let start = car.state.pos.to_bevy();
+        let end = start + world_contact.contact_normal.to_bevy() * CONTACT_NORMAL_LENGTH;
+        gizmos.arrow(start, end, CONTACT_NORMAL_COLOR);
+    }
+}
+
+struct LastBump {
+    cooldown_timer: f32,
+    /// Seconds since this car was last part of a bump, `None` if it hasn't been
+    age: Option<f32>,
+}
+
+/// Outlines the hitboxes of both cars involved in a bump for a moment afterwards
+fn flash_car_bumps(
+    time: Res<Time>,
+    states: Res<GameStates>,
+    mut gizmos: Gizmos,
+    mut last_bumps: Local<HashMap<u32, LastBump>>,
+) {
+    last_bumps.retain(|id, _| states.current.cars.iter().any(|car| car.id == *id));
+
+    for last_bump in last_bumps.values_mut() {
+        if let Some(age) = &mut last_bump.age {
+            *age += time.delta_secs();
+        }
+    }
+
+    let mut bumped = Vec::new();
+    for car in &*states.current.cars {
+        let car_contact = &car.state.car_contact;
+        let last_bump = last_bumps.entry(car.id).or_insert_with(|| LastBump {
+            cooldown_timer: car_contact.cooldown_timer,
+            age: None,
+        });
+
+        // the cooldown starts over whenever the car bumps into another one
+        if car_contact.other_car_id != 0 && car_contact.cooldown_timer > last_bump.cooldown_timer {
+            bumped.extend([car.id, car_contact.other_car_id]);
+        }
+
+        last_bump.cooldown_timer = car_contact.cooldown_timer;
+    }
+
+    for id in bumped {
+        if let Some(last_bump) = last_bumps.get_mut(&id) {
+            last_bump.age = Some(0.);
+        }
+    }
+
+    for car in &*states.current.cars {
+        let Some(age) = last_bumps.get(&car.id).and_then(|last_bump| last_bump.age) else {
+            continue;
+        };
+
+        let alpha = fade(age);
+        if alpha <= 0. {
+            continue;
+        }
+
+        let rotation = car.state.rot_mat.to_bevy();
+        let translation = car.state.pos.to_bevy() + rotation * car.config.hitbox_pos_offset.to_bevy();
+        let transform = Transform {
+            translation,
+            rotation,
+            scale: car.config.hitbox_size.to_bevy(),
+        };
+
+        gizmos.cuboid(transform, BUMP_FLASH_COLOR.with_alpha(alpha));
+    }
+}
+
+// The extents of the arenas in RocketSim/RLBot coordinates
+const SOCCAR_SIDE_WALL_X: f32 = 4096.;
+const SOCCAR_BACK_WALL_Y: f32 = 5120.;
+const SOCCAR_CEILING_Z: f32 = 2044.;
+/// The corners are cut off where `|x| + |y|` goes over this
+const SOCCAR_CORNER_DISTANCE: f32 = 8064.;
+const SOCCAR_GOAL_HALF_WIDTH: f32 = 892.755;
+const SOCCAR_GOAL_HEIGHT: f32 = 642.775;
+/// Roughly the size of the kickoff circle painted on the field
+const CENTER_CIRCLE_RADIUS: f32 = 1000.;
+
+const HOOPS_SIDE_WALL_X: f32 = 2966.;
+const HOOPS_BACK_WALL_Y: f32 = 3581.;
+const HOOPS_CEILING_Z: f32 = 1820.;
+
+const SIDE_WALL_COLOR: Srgba = css::YELLOW;
+const BACK_WALL_COLOR: Srgba = css::FUCHSIA;
+const CORNER_COLOR: Srgba = css::GRAY;
+const CEILING_COLOR: Srgba = css::AQUA;
+const CENTER_CIRCLE_COLOR: Srgba = css::WHITE;
+
+/// Draws a wall as a rectangle going from the floor up to the ceiling, between two points on the floor
+fn draw_wall(gizmos: &mut Gizmos, start: Vec3A, end: Vec3A, height: f32, color: Srgba) {
+    let up = Vec3A::Z * height;
+    gizmos.linestrip([start, end, end + up, start + up, start].map(ToBevyVec::to_bevy), color);
+}
+
+/// Outlines the walls, the ceiling and the center circle of the arena, exactly where they are in RocketSim
+fn draw_field_bounds(states: Res<GameStates>, mut gizmos: Gizmos) {
+    let (side_wall_x, back_wall_y, ceiling_z) = match states.current.game_mode {
+        GameMode::TheVoid => return,
+        GameMode::Hoops => (HOOPS_SIDE_WALL_X, HOOPS_BACK_WALL_Y, HOOPS_CEILING_Z),
+        GameMode::Soccar | GameMode::HeatSeeker | GameMode::Snowday => {
+            (SOCCAR_SIDE_WALL_X, SOCCAR_BACK_WALL_Y, SOCCAR_CEILING_Z)
+        }
+    };
+
+    // the hoops arena has rounded corners, which are left out
+    let corner_offset = if states.current.game_mode == GameMode::Hoops {
+        0.
+    } else {
+        side_wall_x + back_wall_y - SOCCAR_CORNER_DISTANCE
+    };
+
+    for (x, y) in [(1., 1.), (-1., 1.), (1., -1.), (-1., -1.)] {
+        let side_wall_end = Vec3A::new(side_wall_x * x, (back_wall_y - corner_offset) * y, 0.);
+        let back_wall_end = Vec3A::new((side_wall_x - corner_offset) * x, back_wall_y * y, 0.);
+
+        // each wall is drawn in halves from the middle out to the corners
+        draw_wall(
+            &mut gizmos,
+            Vec3A::new(side_wall_x * x, 0., 0.),
+            side_wall_end,
+            ceiling_z,
+            SIDE_WALL_COLOR,
+        );
+        draw_wall(
+            &mut gizmos,
+            Vec3A::new(0., back_wall_y * y, 0.),
+            back_wall_end,
+            ceiling_z,
+            BACK_WALL_COLOR,
+        );
+
+        if corner_offset > 0. {
+            draw_wall(&mut gizmos, side_wall_end, back_wall_end, ceiling_z, CORNER_COLOR);
+        }
+    }
+
+    // a cross over the ceiling so it can be told apart from the tops of the walls
+    gizmos.line(
+        Vec3A::new(-side_wall_x, 0., ceiling_z).to_bevy(),
+        Vec3A::new(side_wall_x, 0., ceiling_z).to_bevy(),
+        CEILING_COLOR,
+    );
+    gizmos.line(
+        Vec3A::new(0., -back_wall_y, ceiling_z).to_bevy(),
+        Vec3A::new(0., back_wall_y, ceiling_z).to_bevy(),
+        CEILING_COLOR,
+    );
+
+    gizmos.circle(
+        Isometry3d::new(Vec3::ZERO, Quat::from_rotation_arc(Vec3::Z, Vec3::Y)),
+        CENTER_CIRCLE_RADIUS,
+        CENTER_CIRCLE_COLOR,
+    );
+}
+
+/// Outlines the openings of both goals in the color of the team that defends them
+fn draw_goal_lines(states: Res<GameStates>, mut gizmos: Gizmos) {
+    for (team, y) in [(Team::Blue, -1.), (Team::Orange, 1.)] {
+        let color = get_color_from_team(team);
+
+        match states.current.game_mode {
+            GameMode::TheVoid => {}
+            GameMode::Hoops => {
+                let (center, half_size) = hoops::net_ellipse(y);
+                gizmos.ellipse(
+                    Isometry3d::new(center.to_bevy(), Quat::from_rotation_arc(Vec3::Z, Vec3::Y)),
+                    half_size,
+                    color,
+                );
+            }
+            GameMode::Soccar | GameMode::HeatSeeker | GameMode::Snowday => {
+                let post = Vec3A::new(SOCCAR_GOAL_HALF_WIDTH, SOCCAR_BACK_WALL_Y * y, 0.);
+                let crossbar = Vec3A::Z * SOCCAR_GOAL_HEIGHT;
+                let other_post = post * Vec3A::new(-1., 1., 1.);
+
+                gizmos.linestrip(
+                    [post, post + crossbar, other_post + crossbar, other_post, post].map(ToBevyVec::to_bevy),
+                    color,
+                );
+            }
+        }
+    }
+}
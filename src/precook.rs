@@ -0,0 +1,129 @@
+use crate::{
+    assets::{get_mesh_info, load_assets, umodel},
+    mesh::{load_field, LargeBoostPadLocRots, StadiumLayout},
+    rocketsim::{GameMode, Team},
+    udp::{get_car_mesh_materials, CAR_BODIES},
+    GameLoadState,
+};
+use bevy::{
+    ecs::system::RunSystemOnce, log::LogPlugin, prelude::*, render::renderer::RenderDevice, state::app::StatesPlugin,
+};
+use std::{
+    fs::File,
+    io::{self, Write},
+    path::Path,
+};
+use walkdir::WalkDir;
+use zip::{write::SimpleFileOptions, CompressionMethod, ZipWriter};
+
+const ASSETS_DIR: &str = "assets";
+const CACHE_DIR: &str = "cache";
+const CACHE_ZIP: &str = "cache.zip";
+
+/// Uncooks the assets, builds the caches for everything the visualizer loads and zips them up into `cache.zip`,
+/// which release builds embed, all without opening a window
+pub fn run() -> io::Result<()> {
+    umodel::uncook()?;
+    if !Path::new(ASSETS_DIR).is_dir() {
+        return Err(io::Error::other(
+            "Couldn't find any uncooked assets, is UModel next to the executable?",
+        ));
+    }
+
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        // textures are only copied into the cache, so there's nothing to load them with
+        LogPlugin {
+            filter: String::from("wgpu=error,bevy_asset=off"),
+            ..default()
+        },
+        AssetPlugin::default(),
+        StatesPlugin,
+    ))
+    .init_asset::<Mesh>()
+    .init_asset::<Image>()
+    .init_asset::<StandardMaterial>()
+    .init_state::<GameLoadState>()
+    .init_resource::<LargeBoostPadLocRots>()
+    .init_resource::<StadiumLayout>();
+    app.finish();
+    app.cleanup();
+
+    // the same systems as a normal launch, so the caches have exactly what it would load
+    let world = app.world_mut();
+    run_system(world, load_assets)?;
+
+    for game_mode in [GameMode::Soccar, GameMode::Hoops] {
+        info!("Cooking the {game_mode:?} field...");
+        world.insert_resource(game_mode);
+        run_system(world, load_field)?;
+    }
+
+    info!("Cooking car bodies...");
+    run_system(world, cook_car_bodies)?;
+
+    zip_cache()?;
+    info!("Wrote {CACHE_ZIP}");
+
+    Ok(())
+}
+
+fn run_system<M>(world: &mut World, system: impl IntoSystem<(), (), M>) -> io::Result<()> {
+    world.run_system_once(system).map_err(|e| io::Error::other(e.to_string()))
+}
+
+fn cook_car_bodies(
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut images: ResMut<Assets<Image>>,
+    asset_server: Res<AssetServer>,
+    render_device: Option<Res<RenderDevice>>,
+) {
+    for body in CAR_BODIES {
+        if get_mesh_info(body, &mut meshes).is_none() {
+            warn!("Failed to cook car body {body}");
+            continue;
+        }
+
+        // car materials are only loaded with full_load, so they're only cooked with it too
+        if cfg!(feature = "full_load") {
+            get_car_mesh_materials(
+                body,
+                &mut materials,
+                &asset_server,
+                Color::WHITE,
+                Team::Blue,
+                &mut images,
+                render_device.as_deref(),
+            );
+        }
+    }
+}
+
+/// Zips up the `cache` folder in the same layout that release builds read it from
+fn zip_cache() -> io::Result<()> {
+    let mut zip = ZipWriter::new(File::create(CACHE_ZIP)?);
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+
+    for entry in WalkDir::new(CACHE_DIR).sort_by_file_name() {
+        let entry = entry?;
+        let path = entry.path();
+
+        // zip paths always use forward slashes
+        let name = path
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+
+        if entry.file_type().is_dir() {
+            zip.add_directory(name, options)?;
+        } else {
+            zip.start_file(name, options)?;
+            io::copy(&mut File::open(path)?, &mut zip)?;
+        }
+    }
+
+    zip.finish()?.flush()
+}
@@ -0,0 +1,140 @@
+use crate::{
+    rocketsim::GameMode,
+    settings::options::{ShowBallPath, ShowBouncePoint},
+    udp::{GameStates, ToBevyVec},
+};
+use bevy::{math::Vec3A, prelude::*};
+
+pub struct BallPredictionPlugin;
+
+impl Plugin for BallPredictionPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                draw_bounce_point.run_if(|show_bounce_point: Res<ShowBouncePoint>| show_bounce_point.0),
+                draw_ball_path.run_if(|show_ball_path: Res<ShowBallPath>| show_ball_path.enabled),
+            ),
+        );
+    }
+}
+
+const GRAVITY_Z: f32 = -650.;
+const BOUNCE_MARKER_COLOR: Color = Color::srgb(1., 0.3, 0.9);
+const BALL_PATH_COLOR: Color = Color::srgb(0.3, 0.9, 1.);
+
+const BALL_PATH_TICK_RATE: f32 = 120.;
+/// Only every few ticks are drawn, the line is smooth enough without the rest
+const BALL_PATH_TICKS_PER_POINT: usize = 4;
+const BALL_DRAG: f32 = 0.0305;
+const BALL_MAX_SPEED: f32 = 6000.;
+const BALL_RESTITUTION: f32 = 0.6;
+
+/// Distance from the center of the ball to the ground when it's touching the floor
+const fn ball_floor_offset(game_mode: GameMode) -> f32 {
+    match game_mode {
+        GameMode::Hoops => 96.3831,
+        // half of the puck's height
+        GameMode::Snowday => 41.25,
+        _ => 91.25,
+    }
+}
+
+/// Half the width and length of the arena, and the height of the ceiling, or `None` if there's nothing to bounce off of
+pub const fn arena_bounds(game_mode: GameMode) -> Option<Vec3A> {
+    match game_mode {
+        GameMode::TheVoid => None,
+        GameMode::Hoops => Some(Vec3A::new(2966., 3581., 1820.)),
+        _ => Some(Vec3A::new(4096., 5120., 2044.)),
+    }
+}
+
+/// Steps the ball forward with gravity, drag, and bounces off the arena treated as a box.
+///
+/// Spin, friction, goals and the curved parts of the arena are ignored, so this is only meant as a rough baseline.
+fn predict_ball_path(mut pos: Vec3A, mut vel: Vec3A, game_mode: GameMode, seconds: f32) -> Vec<Vec3A> {
+    const DELTA_TIME: f32 = 1. / BALL_PATH_TICK_RATE;
+
+    let radius = ball_floor_offset(game_mode);
+    let bounds = arena_bounds(game_mode).map(|bounds| (Vec3A::new(-bounds.x, -bounds.y, 0.) + radius, bounds - radius));
+
+    let num_ticks = (seconds * BALL_PATH_TICK_RATE) as usize;
+    let mut path = Vec::with_capacity(num_ticks / BALL_PATH_TICKS_PER_POINT + 1);
+    path.push(pos);
+
+    for tick in 1..=num_ticks {
+        vel.z += GRAVITY_Z * DELTA_TIME;
+        vel *= 1. - BALL_DRAG * DELTA_TIME;
+        vel = vel.clamp_length_max(BALL_MAX_SPEED);
+        pos += vel * DELTA_TIME;
+
+        if let Some((min, max)) = bounds {
+            for axis in 0..3 {
+                if pos[axis] < min[axis] {
+                    pos[axis] = min[axis];
+                    vel[axis] = vel[axis].abs() * BALL_RESTITUTION;
+                } else if pos[axis] > max[axis] {
+                    pos[axis] = max[axis];
+                    vel[axis] = -vel[axis].abs() * BALL_RESTITUTION;
+                }
+            }
+        }
+
+        if tick % BALL_PATH_TICKS_PER_POINT == 0 {
+            path.push(pos);
+        }
+    }
+
+    path
+}
+
+/// Solves the ball's ballistic trajectory for the first point it touches the ground.
+///
+/// Walls, the ceiling and air drag are ignored.
+fn predict_bounce_point(pos: Vec3A, vel: Vec3A, floor_offset: f32) -> Option<Vec3A> {
+    let height = pos.z - floor_offset;
+
+    // the ball is already rolling along the ground
+    if height < 1. && vel.z.abs() < 1. {
+        return None;
+    }
+
+    // height + vel.z * t + GRAVITY_Z / 2 * t^2 = 0
+    let discriminant = vel.z * vel.z - 2. * GRAVITY_Z * height;
+    if discriminant < 0. {
+        return None;
+    }
+
+    let time = (-vel.z - discriminant.sqrt()) / GRAVITY_Z;
+    if time <= 0. {
+        return None;
+    }
+
+    Some(Vec3A::new(pos.x + vel.x * time, pos.y + vel.y * time, floor_offset))
+}
+
+fn draw_bounce_point(states: Res<GameStates>, mut gizmos: Gizmos) {
+    let ball = &states.current.ball;
+    let floor_offset = ball_floor_offset(states.current.game_mode);
+
+    let Some(bounce_point) = predict_bounce_point(ball.pos, ball.vel, floor_offset) else {
+        return;
+    };
+
+    let ball_center = bounce_point.to_bevy();
+    let ground = Vec3::new(ball_center.x, 1., ball_center.z);
+
+    gizmos.sphere(Isometry3d::from_translation(ball_center), floor_offset, BOUNCE_MARKER_COLOR);
+    gizmos.circle(
+        Isometry3d::new(ground, Quat::from_rotation_arc(Vec3::Z, Vec3::Y)),
+        floor_offset / 3.,
+        BOUNCE_MARKER_COLOR,
+    );
+}
+
+fn draw_ball_path(states: Res<GameStates>, show_ball_path: Res<ShowBallPath>, mut gizmos: Gizmos) {
+    let ball = &states.current.ball;
+    let path = predict_ball_path(ball.pos, ball.vel, states.current.game_mode, show_ball_path.seconds);
+
+    gizmos.linestrip(path.into_iter().map(ToBevyVec::to_bevy), BALL_PATH_COLOR);
+}
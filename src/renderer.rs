@@ -1,5 +1,19 @@
-use crate::udp::ToBevyVec;
-use bevy::{prelude::*, utils::HashMap};
+use crate::{
+    camera::{PrimaryCamera, DEFAULT_FOV},
+    settings::{
+        gui::Toast,
+        keybinds::{Action, Keybinds},
+        options::{MenuFocused, Options},
+    },
+    spectator::{BookmarkFlight, Spectator},
+    udp::ToBevyVec,
+};
+use bevy::{
+    math::bounding::{Aabb3d, BoundingVolume},
+    prelude::*,
+    utils::{HashMap, HashSet},
+};
+use bevy_vector_shapes::prelude::*;
 
 #[derive(Clone, Copy, Debug)]
 pub struct CustomColor {
@@ -27,21 +41,99 @@ pub enum Render {
     Line2D { start: Vec2, end: Vec2, color: CustomColor },
     Line { start: Vec3, end: Vec3, color: CustomColor },
     LineStrip { positions: Vec<Vec3>, color: CustomColor },
+    Sphere { pos: Vec3, radius: f32, color: CustomColor },
+    Box { pos: Vec3, size: Vec3, color: CustomColor },
+    Text { pos: Vec3, text: String, color: CustomColor },
+    FilledRect2D { pos: Vec2, size: Vec2, color: CustomColor },
+    Polygon { positions: Vec<Vec2>, color: CustomColor },
+    Circle { pos: Vec2, radius: f32, color: CustomColor },
 }
 
 #[derive(Clone, Debug)]
 pub enum RenderMessage {
     AddRender(i32, Vec<Render>),
     RemoveRender(i32),
+    NameGroup(i32, String),
 }
 
 #[derive(Resource, Default)]
 pub struct RenderGroups {
     pub groups: HashMap<i32, Vec<Render>>,
+    /// Names given to groups by the simulation, kept when a group is removed so it has the same name when it comes back
+    pub names: HashMap<i32, String>,
+    /// Groups that were hidden in the rendering manager, which stay hidden while the simulation keeps updating them
+    pub hidden: HashSet<i32>,
+    /// The bounding box of the 3D renders in each group that has any
+    bounds: HashMap<i32, Aabb3d>,
+    /// The group with 3D renders that was sent most recently, which the camera can be pointed at
+    latest: Option<i32>,
 }
 
-fn render_gizmos(mut renders: ResMut<RenderGroups>, mut gizmos: Gizmos) {
-    for (_, renders) in renders.groups.iter_mut() {
+impl RenderGroups {
+    pub fn visible(&self) -> impl Iterator<Item = (&i32, &Vec<Render>)> {
+        self.groups.iter().filter(|(id, _)| !self.hidden.contains(*id))
+    }
+
+    pub fn insert(&mut self, id: i32, renders: Vec<Render>) {
+        if let Some(bounds) = render_bounds(&renders) {
+            self.bounds.insert(id, bounds);
+            self.latest = Some(id);
+        } else {
+            self.bounds.remove(&id);
+        }
+
+        self.groups.insert(id, renders);
+    }
+
+    pub fn remove(&mut self, id: i32) {
+        self.groups.remove(&id);
+        self.bounds.remove(&id);
+
+        if self.latest == Some(id) {
+            self.latest = None;
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.groups.clear();
+        self.bounds.clear();
+        self.latest = None;
+    }
+
+    #[inline]
+    pub fn bounds(&self, id: i32) -> Option<Aabb3d> {
+        self.bounds.get(&id).copied()
+    }
+
+    /// The name of the group, or its id if the simulation didn't name it
+    pub fn name(&self, id: i32) -> String {
+        self.names.get(&id).cloned().unwrap_or_else(|| format!("group {id}"))
+    }
+}
+
+/// The box around every 3D render in a group, in Bevy's coordinates
+fn render_bounds(renders: &[Render]) -> Option<Aabb3d> {
+    renders
+        .iter()
+        .filter_map(|render| match render {
+            Render::Line { start, end, .. } => Some(Aabb3d::new(
+                (start.to_bevy() + end.to_bevy()) / 2.,
+                (start.to_bevy() - end.to_bevy()).abs() / 2.,
+            )),
+            Render::LineStrip { positions, .. } if !positions.is_empty() => Some(Aabb3d::from_point_cloud(
+                Isometry3d::IDENTITY,
+                positions.iter().copied().map(ToBevyVec::to_bevy),
+            )),
+            Render::Sphere { pos, radius, .. } => Some(Aabb3d::new(pos.to_bevy(), Vec3::splat(radius.abs()))),
+            Render::Box { pos, size, .. } => Some(Aabb3d::new(pos.to_bevy(), size.to_bevy().abs() / 2.)),
+            Render::Text { pos, .. } => Some(Aabb3d::new(pos.to_bevy(), Vec3::ZERO)),
+            _ => None,
+        })
+        .reduce(|a, b| a.merge(&b))
+}
+
+fn render_gizmos(renders: Res<RenderGroups>, mut gizmos: Gizmos) {
+    for (_, renders) in renders.visible() {
         for render in renders.iter() {
             match render {
                 Render::Line2D { start, end, color } => {
@@ -53,11 +145,233 @@ fn render_gizmos(mut renders: ResMut<RenderGroups>, mut gizmos: Gizmos) {
                 Render::LineStrip { positions, color } => {
                     gizmos.linestrip(positions.iter().copied().map(ToBevyVec::to_bevy), *color);
                 }
+                Render::Sphere { pos, radius, color } => {
+                    gizmos.sphere(Isometry3d::from_translation(pos.to_bevy()), *radius, *color);
+                }
+                Render::Box { pos, size, color } => {
+                    gizmos.cuboid(Transform::from_translation(pos.to_bevy()).with_scale(size.to_bevy()), *color);
+                }
+                // text and filled shapes can't be drawn with gizmos, see `render_text` and `render_shapes`
+                Render::Text { .. } | Render::FilledRect2D { .. } | Render::Polygon { .. } | Render::Circle { .. } => {}
             }
         }
     }
 }
 
+/// How far apart each render group's shapes are, so groups with a higher id are drawn on top
+const RENDER_GROUP_Z_STEP: f32 = 0.1;
+
+/// Draws the filled 2D shapes, which use the same screen space as `Render::Line2D`
+fn render_shapes(renders: Res<RenderGroups>, mut painter: ShapePainter) {
+    let mut groups = renders.visible().collect::<Vec<_>>();
+    radsort::sort_by_key(&mut groups, |(id, _)| **id);
+
+    for (i, (_, group)) in groups.into_iter().enumerate() {
+        let z = i as f32 * RENDER_GROUP_Z_STEP;
+
+        for render in group {
+            match render {
+                Render::FilledRect2D { pos, size, color } => {
+                    painter.set_translation(pos.extend(z));
+                    painter.color = (*color).into();
+                    painter.rect(*size);
+                }
+                Render::Polygon { positions, color } => {
+                    // filled in as a fan of triangles from the first point, so it has to be convex
+                    let [first, rest @ ..] = positions.as_slice() else {
+                        continue;
+                    };
+
+                    painter.set_translation(Vec3::Z * z);
+                    painter.color = (*color).into();
+                    for edge in rest.windows(2) {
+                        painter.triangle(*first, edge[0], edge[1]);
+                    }
+                }
+                Render::Circle { pos, radius, color } => {
+                    painter.set_translation(pos.extend(z));
+                    painter.color = (*color).into();
+                    painter.circle(*radius);
+                }
+                // everything else is drawn by `render_gizmos` and `render_text`
+                _ => {}
+            }
+        }
+    }
+
+    painter.reset();
+}
+
+#[derive(Component)]
+struct RenderText;
+
+const RENDER_TEXT_FONT_SIZE: f32 = 20.;
+
+/// Keeps one text entity for every `Render::Text`, reusing them between frames
+fn render_text(
+    mut commands: Commands,
+    renders: Res<RenderGroups>,
+    do_rendering: Res<DoRendering>,
+    camera: Query<(&Camera, &GlobalTransform), With<PrimaryCamera>>,
+    mut texts: Query<(Entity, &mut Text, &mut TextColor, &mut Node, &ComputedNode, &mut Visibility), With<RenderText>>,
+) {
+    let mut text_renders = renders
+        .visible()
+        .flat_map(|(_, group)| group)
+        .filter_map(|render| match render {
+            Render::Text { pos, text, color } if do_rendering.0 => Some((pos, text, color)),
+            _ => None,
+        });
+
+    let (camera, camera_transform) = camera.single();
+
+    for (entity, mut text, mut text_color, mut node, computed, mut visibility) in &mut texts {
+        let Some((pos, new_text, color)) = text_renders.next() else {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        };
+
+        if text.0 != *new_text {
+            text.0.clone_from(new_text);
+        }
+
+        text_color.0 = (*color).into();
+
+        let Ok(screen_pos) = camera.world_to_viewport(camera_transform, pos.to_bevy()) else {
+            *visibility = Visibility::Hidden;
+            continue;
+        };
+
+        *visibility = Visibility::Inherited;
+
+        let size = computed.size() * computed.inverse_scale_factor();
+        node.left = Val::Px(screen_pos.x - size.x / 2.);
+        node.top = Val::Px(screen_pos.y - size.y / 2.);
+    }
+
+    for (_, text, color) in text_renders {
+        commands.spawn((
+            Text::new(text.clone()),
+            TextFont {
+                font_size: RENDER_TEXT_FONT_SIZE,
+                ..default()
+            },
+            TextColor((*color).into()),
+            Node {
+                position_type: PositionType::Absolute,
+                ..default()
+            },
+            // hidden until it has been positioned
+            Visibility::Hidden,
+            RenderText,
+        ));
+    }
+}
+
+/// Points the free camera at the 3D renders in a group
+#[derive(Event)]
+pub struct FocusRenderGroup(pub i32);
+
+/// The smallest area the camera will zoom in to when looking at a render group
+const MIN_FOCUS_RADIUS: f32 = 300.;
+/// How much space is left around a render group when looking at it
+const FOCUS_MARGIN: f32 = 1.2;
+
+fn listen(
+    key: Res<ButtonInput<KeyCode>>,
+    keybinds: Res<Keybinds>,
+    renders: Res<RenderGroups>,
+    mut focus: EventWriter<FocusRenderGroup>,
+    mut toast: ResMut<Toast>,
+) {
+    if !keybinds.just_pressed(&key, Action::FocusRenderGroup) {
+        return;
+    }
+
+    match renders.latest {
+        Some(id) => {
+            focus.send(FocusRenderGroup(id));
+        }
+        None => toast.show(String::from("There aren't any 3D renders to look at")),
+    }
+}
+
+/// Lets the user know how to look at the newest render group when it's sent somewhere the camera can't see
+fn offer_focus(
+    renders: Res<RenderGroups>,
+    keybinds: Res<Keybinds>,
+    camera: Query<(&Camera, &GlobalTransform), With<PrimaryCamera>>,
+    mut toast: ResMut<Toast>,
+    mut offered: Local<Option<i32>>,
+) {
+    let Some((id, bounds)) = renders.latest.and_then(|id| Some((id, renders.bounds(id)?))) else {
+        return;
+    };
+
+    let (camera, camera_transform) = camera.single();
+    let in_view = camera.logical_viewport_size().is_some_and(|size| {
+        camera
+            .world_to_viewport(camera_transform, bounds.center().into())
+            .is_ok_and(|pos| pos.cmpge(Vec2::ZERO).all() && pos.cmple(size).all())
+    });
+
+    if in_view {
+        *offered = None;
+    } else if *offered != Some(id) && !renders.hidden.contains(&id) {
+        *offered = Some(id);
+
+        if let Some(key) = keybinds.label(Action::FocusRenderGroup) {
+            toast.show(format!("Press {key} to look at {}", renders.name(id)));
+        }
+    }
+}
+
+/// Flies the free camera to where it can see the whole group, keeping the direction it was looking in
+fn focus_render_group(
+    mut events: EventReader<FocusRenderGroup>,
+    renders: Res<RenderGroups>,
+    mut options: ResMut<Options>,
+    mut flight: ResMut<BookmarkFlight>,
+    cameras: Query<(&PrimaryCamera, &Transform, &Projection), With<Spectator>>,
+    mut pending: Local<Option<i32>>,
+) {
+    if let Some(event) = events.read().last() {
+        *pending = Some(event.0);
+    }
+
+    let Some(id) = *pending else {
+        return;
+    };
+
+    let Ok((&primary_camera, transform, projection)) = cameras.get_single() else {
+        return;
+    };
+
+    // the flight would be cancelled if the camera isn't free yet, so wait for it to switch over
+    if primary_camera != PrimaryCamera::Spectator {
+        options.camera_state = PrimaryCamera::Spectator;
+        return;
+    }
+
+    *pending = None;
+
+    let Some(bounds) = renders.bounds(id) else {
+        return;
+    };
+
+    let fov = match projection {
+        Projection::Perspective(perspective) => perspective.fov,
+        Projection::Orthographic(_) => DEFAULT_FOV,
+    };
+
+    let center = Vec3::from(bounds.center());
+    let radius = bounds.half_size().length().max(MIN_FOCUS_RADIUS) * FOCUS_MARGIN;
+    let distance = radius / (fov / 2.).sin();
+
+    let target = Transform::from_translation(center - transform.forward() * distance).looking_at(center, Vec3::Y);
+    flight.start(transform, (&target).into());
+}
+
 #[derive(Resource)]
 pub struct DoRendering(pub bool);
 
@@ -67,6 +381,19 @@ impl Plugin for UdpRendererPlugin {
     fn build(&self, app: &mut App) {
         app.insert_resource(RenderGroups::default())
             .insert_resource(DoRendering(true))
-            .add_systems(Update, render_gizmos.run_if(|do_rendering: Res<DoRendering>| do_rendering.0));
+            .add_event::<FocusRenderGroup>()
+            .add_systems(
+                Update,
+                (
+                    (render_gizmos, render_shapes).run_if(|do_rendering: Res<DoRendering>| do_rendering.0),
+                    render_text,
+                    (
+                        listen.run_if(resource_equals(MenuFocused(false))),
+                        offer_focus.run_if(|do_rendering: Res<DoRendering>| do_rendering.0),
+                        focus_render_group,
+                    )
+                        .chain(),
+                ),
+            );
     }
 }
@@ -0,0 +1,299 @@
+use crate::{
+    camera::TIME_DISPLAY_POS,
+    goal::GoalScored,
+    rocketsim::GameState,
+    settings::{
+        keybinds::{Action, Keybinds},
+        options::{GameSpeed, MenuFocused, ReplayOnGoal},
+    },
+};
+use bevy::{color::palettes::css, prelude::*};
+use bevy_egui::{egui, EguiContexts};
+use std::{collections::VecDeque, sync::Arc};
+
+pub struct ReplayPlugin;
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Replay::default())
+            .add_systems(Startup, setup)
+            .add_systems(
+                Update,
+                (
+                    replay_goals.run_if(on_event::<GoalScored>.and(|replay_on_goal: Res<ReplayOnGoal>| replay_on_goal.0)),
+                    listen.run_if(resource_equals(MenuFocused(false))),
+                    update_replay_text,
+                    timeline
+                        .run_if(|game_speed: Res<GameSpeed>, replay: Res<Replay>| game_speed.paused && !replay.is_playing()),
+                ),
+            );
+    }
+}
+
+/// How many seconds of game states are kept for replays
+const REPLAY_LENGTH: f32 = 5.;
+/// How fast replays are played back, compared to the live game
+const REPLAY_SPEED: f32 = 0.25;
+
+struct Playback {
+    states: VecDeque<Arc<GameState>>,
+    start_tick: u64,
+    tick_rate: f32,
+    elapsed: f32,
+}
+
+/// The last few seconds of live game states, and the replay of them that's being shown if there is one
+#[derive(Resource, Default)]
+pub struct Replay {
+    /// Oldest first, shared with the rest of the app so recording a state doesn't copy it
+    buffer: VecDeque<Arc<GameState>>,
+    playback: Option<Playback>,
+    /// The tick picked on the timeline while paused, instead of showing the newest state
+    scrub_tick: Option<u64>,
+    /// If the state that should be shown changed since it was last taken
+    scrub_changed: bool,
+}
+
+impl Replay {
+    #[inline]
+    pub const fn is_playing(&self) -> bool {
+        self.playback.is_some()
+    }
+
+    /// Adds a live game state to the buffer, dropping the ones that are too old to be replayed
+    pub fn record(&mut self, state: &Arc<GameState>) {
+        // a new game was started, so the old states aren't part of it
+        if self.buffer.back().is_some_and(|last| last.tick_count > state.tick_count) {
+            self.buffer.clear();
+        }
+
+        self.buffer.push_back(Arc::clone(state));
+
+        let max_ticks = (REPLAY_LENGTH * state.tick_rate) as u64;
+        while self
+            .buffer
+            .front()
+            .is_some_and(|first| state.tick_count - first.tick_count > max_ticks)
+        {
+            self.buffer.pop_front();
+        }
+    }
+
+    fn start(&mut self) {
+        let Some(first) = self.buffer.front() else {
+            return;
+        };
+
+        self.playback = Some(Playback {
+            start_tick: first.tick_count,
+            tick_rate: first.tick_rate,
+            states: self.buffer.clone(),
+            elapsed: 0.,
+        });
+    }
+
+    fn stop(&mut self) {
+        self.playback = None;
+    }
+
+    #[inline]
+    pub const fn is_scrubbing(&self) -> bool {
+        self.scrub_tick.is_some()
+    }
+
+    /// The index in the buffer of the newest state at or before `tick`
+    fn buffer_index(&self, tick: u64) -> usize {
+        self.buffer
+            .partition_point(|state| state.tick_count <= tick)
+            .saturating_sub(1)
+    }
+
+    /// Shows the buffered state at `tick` instead of the newest one
+    fn scrub_to(&mut self, tick: u64) {
+        let Some(state) = self.buffer.get(self.buffer_index(tick)) else {
+            return;
+        };
+
+        if self.scrub_tick != Some(state.tick_count) {
+            self.scrub_tick = Some(state.tick_count);
+            self.scrub_changed = true;
+        }
+    }
+
+    /// Moves the timeline by `steps` buffered states, going back to the newest one once it's stepped past
+    fn scrub_by(&mut self, steps: isize) {
+        let Some(last) = self.buffer.len().checked_sub(1) else {
+            return;
+        };
+
+        let current = self.scrub_tick.map_or(last, |tick| self.buffer_index(tick));
+        let next = current.saturating_add_signed(steps).min(last);
+
+        if next == last && steps > 0 {
+            self.stop_scrubbing();
+        } else {
+            self.scrub_to(self.buffer[next].tick_count);
+        }
+    }
+
+    /// Goes back to showing the newest state
+    pub fn stop_scrubbing(&mut self) {
+        if self.scrub_tick.take().is_some() {
+            self.scrub_changed = true;
+        }
+    }
+
+    /// The state picked on the timeline, or the newest one if scrubbing just stopped,
+    /// if it's different from the one that was last shown
+    pub fn take_scrubbed_state(&mut self) -> Option<Arc<GameState>> {
+        if !std::mem::take(&mut self.scrub_changed) {
+            return None;
+        }
+
+        match self.scrub_tick {
+            Some(tick) => self.buffer.get(self.buffer_index(tick)).cloned(),
+            None => self.buffer.back().cloned(),
+        }
+    }
+
+    /// Advances the replay by `delta` seconds of real time,
+    /// returning the newest game state that should now be shown
+    ///
+    /// Live viewing resumes once the end of the replay is reached
+    pub fn next_state(&mut self, delta: f32) -> Option<Arc<GameState>> {
+        let playback = self.playback.as_mut()?;
+        playback.elapsed += delta * REPLAY_SPEED;
+
+        let current_tick = playback.start_tick + (playback.elapsed * playback.tick_rate) as u64;
+
+        let mut next_state = None;
+        while playback.states.front().is_some_and(|state| state.tick_count <= current_tick) {
+            next_state = playback.states.pop_front();
+        }
+
+        if playback.states.is_empty() {
+            self.playback = None;
+        }
+
+        next_state
+    }
+}
+
+/// Systems that react to the game state, like goal detection, shouldn't count things that happen during a replay twice
+pub fn not_replaying(replay: Res<Replay>) -> bool {
+    !replay.is_playing() && !replay.is_scrubbing()
+}
+
+#[derive(Component)]
+struct ReplayText;
+
+fn setup(mut commands: Commands) {
+    commands.spawn((
+        Text::new("REPLAY"),
+        TextFont {
+            font_size: 30.0,
+            ..default()
+        },
+        TextColor(Color::from(css::YELLOW)),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(TIME_DISPLAY_POS.x + 50.),
+            width: Val::Percent(100.),
+            ..default()
+        },
+        TextLayout::new_with_justify(JustifyText::Center),
+        Visibility::Hidden,
+        ReplayText,
+    ));
+}
+
+fn replay_goals(mut events: EventReader<GoalScored>, mut replay: ResMut<Replay>) {
+    events.clear();
+
+    if !replay.is_playing() {
+        replay.start();
+    }
+}
+
+fn listen(key: Res<ButtonInput<KeyCode>>, keybinds: Res<Keybinds>, game_speed: Res<GameSpeed>, mut replay: ResMut<Replay>) {
+    if game_speed.paused && !replay.is_playing() {
+        if keybinds.just_pressed(&key, Action::ScrubBack) {
+            replay.scrub_by(-1);
+        }
+
+        if keybinds.just_pressed(&key, Action::ScrubForward) {
+            replay.scrub_by(1);
+        }
+    }
+
+    if !keybinds.just_pressed(&key, Action::InstantReplay) {
+        return;
+    }
+
+    if replay.is_playing() {
+        replay.stop();
+    } else {
+        replay.stop_scrubbing();
+        replay.start();
+    }
+}
+
+fn update_replay_text(replay: Res<Replay>, mut text: Query<&mut Visibility, With<ReplayText>>) {
+    text.single_mut().set_if_neq(if replay.is_playing() {
+        Visibility::Inherited
+    } else {
+        Visibility::Hidden
+    });
+}
+
+/// A timeline of the buffered states that can be scrubbed through tick by tick while paused
+fn timeline(mut contexts: EguiContexts, menu_focused: Res<MenuFocused>, mut replay: ResMut<Replay>) {
+    let (Some(first), Some(last)) = (replay.buffer.front(), replay.buffer.back()) else {
+        return;
+    };
+
+    let (first_tick, last_tick, tick_rate) = (first.tick_count, last.tick_count, last.tick_rate);
+    if first_tick == last_tick {
+        return;
+    }
+
+    let mut tick = replay.scrub_tick.unwrap_or(last_tick);
+    let mut steps = 0;
+    let mut go_live = false;
+
+    egui::Area::new(egui::Id::new("replay_timeline"))
+        .anchor(egui::Align2::CENTER_BOTTOM, [0., -80.])
+        .interactable(menu_focused.0)
+        .show(contexts.ctx_mut(), |ui| {
+            egui::Frame::popup(ui.style()).show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    if ui.button("<").on_hover_text("Go back one tick").clicked() {
+                        steps = -1;
+                    }
+
+                    ui.spacing_mut().slider_width = 400.;
+                    ui.add(egui::Slider::new(&mut tick, first_tick..=last_tick).show_value(false))
+                        .on_hover_text("Scrub through the last few seconds, or use , and . while the menu is closed");
+
+                    if ui.button(">").on_hover_text("Go forward one tick").clicked() {
+                        steps = 1;
+                    }
+
+                    ui.label(format!("-{:.2}s", (last_tick - tick) as f32 / tick_rate));
+
+                    go_live = ui
+                        .add_enabled(replay.is_scrubbing(), egui::Button::new("Live"))
+                        .on_hover_text("Go back to the newest state")
+                        .clicked();
+                });
+            });
+        });
+
+    if go_live {
+        replay.stop_scrubbing();
+    } else if steps != 0 {
+        replay.scrub_by(steps);
+    } else if tick != replay.scrub_tick.unwrap_or(last_tick) {
+        replay.scrub_to(tick);
+    }
+}
@@ -0,0 +1,460 @@
+use crate::{
+    nametags::CarMetadata,
+    rocketsim::{BallState, CarConfig, CarControls, CarInfo, CarState, GameMode, GameState, Team},
+    udp::{SendableUdp, UdpUpdate},
+};
+use bevy::{
+    log::{error, info},
+    math::{Mat3A as RotMat, Quat, Vec3A as Vec3},
+};
+use boxcars::{ActorId, Attribute, Frame, NewActor, ParserBuilder, RigidBody, UpdatedAttribute};
+use crossbeam_channel::{Receiver, RecvTimeoutError, Sender};
+use std::{
+    collections::HashMap,
+    fs, io,
+    path::{Path, PathBuf},
+    sync::Arc,
+    thread,
+    time::{Duration, Instant},
+};
+
+/// Replays are recorded at a lower rate than the game's physics, but ticks are still counted at 120Hz
+const TICK_RATE: f32 = 120.;
+
+/// How much boost is used per second while boosting, in the same 0 to 100 as `CarState::boost`
+///
+/// The game only sends the boost amount every so often, so it's drained between updates
+const BOOST_USED_PER_SECOND: f32 = 100. / 3.;
+const SUPERSONIC_SPEED: f32 = 2200.;
+
+const CAR_OBJECT: &str = "Archetypes.Car.Car_Default";
+const PLAYER_OBJECT: &str = "TAGame.Default__PRI_TA";
+const BLUE_TEAM_OBJECT: &str = "Archetypes.Teams.Team0";
+const ORANGE_TEAM_OBJECT: &str = "Archetypes.Teams.Team1";
+const BOOST_OBJECT: &str = "Archetypes.CarComponents.CarComponent_Boost";
+const JUMP_OBJECT: &str = "Archetypes.CarComponents.CarComponent_Jump";
+const DOUBLE_JUMP_OBJECT: &str = "Archetypes.CarComponents.CarComponent_DoubleJump";
+const DODGE_OBJECT: &str = "Archetypes.CarComponents.CarComponent_Dodge";
+
+const RIGID_BODY_KEY: &str = "TAGame.RBActor_TA:ReplicatedRBState";
+const CAR_PLAYER_KEY: &str = "Engine.Pawn:PlayerReplicationInfo";
+const THROTTLE_KEY: &str = "TAGame.Vehicle_TA:ReplicatedThrottle";
+const STEER_KEY: &str = "TAGame.Vehicle_TA:ReplicatedSteer";
+const HANDBRAKE_KEY: &str = "TAGame.Vehicle_TA:bReplicatedHandbrake";
+const PLAYER_NAME_KEY: &str = "Engine.PlayerReplicationInfo:PlayerName";
+const PLAYER_TEAM_KEY: &str = "Engine.PlayerReplicationInfo:Team";
+const COMPONENT_CAR_KEY: &str = "TAGame.CarComponent_TA:Vehicle";
+const COMPONENT_ACTIVE_KEY: &str = "TAGame.CarComponent_TA:ReplicatedActive";
+const BOOST_KEY: &str = "TAGame.CarComponent_Boost_TA:ReplicatedBoost";
+/// Older replays send the boost amount on its own instead
+const BOOST_AMOUNT_KEY: &str = "TAGame.CarComponent_Boost_TA:ReplicatedBoostAmount";
+
+/// Reads the replay and plays it back in real time, as if it were a simulation
+///
+/// Pausing and changing the game speed work like they do with RocketSim, but state setting is ignored
+pub fn start_replay_file_handlers(path: PathBuf, outgoing: Receiver<SendableUdp>, updates: Sender<UdpUpdate>) {
+    thread::Builder::new()
+        .name(String::from("rlviser-replay-file"))
+        .spawn(move || {
+            match import(&path) {
+                Ok((states, names)) => {
+                    info!("Playing {} frames from {}", states.len(), path.display());
+
+                    // the visualizer answers with its current pause and speed
+                    let started = [UdpUpdate::CarMetadata(names), UdpUpdate::Connection]
+                        .into_iter()
+                        .all(|update| updates.send(update).is_ok());
+
+                    if started {
+                        play(&states, &outgoing, &updates);
+                    }
+                }
+                Err(e) => error!("Failed to import {} due to: {e}", path.display()),
+            }
+
+            // the last state stays up instead of going back to waiting for a simulation
+            while outgoing.recv().is_ok() {}
+        })
+        .unwrap();
+}
+
+/// Sends each state once it's due, until the end of the replay is reached
+fn play(states: &[Arc<GameState>], outgoing: &Receiver<SendableUdp>, updates: &Sender<UdpUpdate>) {
+    let mut paused = false;
+    let mut speed = 1.;
+    let mut elapsed = 0.;
+    let mut last_time = Instant::now();
+    let mut next = 0;
+
+    loop {
+        while let Some(state) = states
+            .get(next)
+            .filter(|state| state.tick_count as f32 / TICK_RATE <= elapsed)
+        {
+            if updates.send(UdpUpdate::State(Arc::clone(state), Instant::now())).is_err() {
+                return;
+            }
+
+            next += 1;
+        }
+
+        let Some(next_state) = states.get(next) else {
+            info!("Reached the end of the replay");
+            return;
+        };
+
+        let message = if paused {
+            outgoing.recv().map_err(|_| RecvTimeoutError::Disconnected)
+        } else {
+            let wait = (next_state.tick_count as f32 / TICK_RATE - elapsed) / speed;
+            outgoing.recv_timeout(Duration::from_secs_f32(wait.max(0.)))
+        };
+
+        match message {
+            Ok(SendableUdp::Paused(new_paused)) => paused = new_paused,
+            Ok(SendableUdp::Speed(new_speed)) => speed = new_speed.max(f32::EPSILON),
+            // a recording can't be changed
+            Ok(SendableUdp::Hello(_) | SendableUdp::State(_)) | Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => return,
+        }
+
+        let now = Instant::now();
+        if !paused {
+            elapsed += now.duration_since(last_time).as_secs_f32() * speed;
+        }
+        last_time = now;
+    }
+}
+
+/// Turns every network frame of the replay into a game state, along with the names of every player in it
+fn import(path: &Path) -> io::Result<(Vec<Arc<GameState>>, CarMetadata)> {
+    let data = fs::read(path)?;
+    let replay = ParserBuilder::new(&data)
+        .must_parse_network_data()
+        .on_error_check_crc()
+        .parse()
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
+
+    let frames = replay
+        .network_frames
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "the replay has no network frames"))?
+        .frames;
+
+    let mut converter = ReplayConverter {
+        objects: replay.objects,
+        ..Default::default()
+    };
+
+    let mut states = Vec::with_capacity(frames.len());
+    let mut elapsed = 0.;
+    let mut last_tick = None;
+
+    for frame in &frames {
+        elapsed += frame.delta;
+        converter.apply_frame(frame);
+
+        // frames can be closer together than a tick, but ticks have to keep going up
+        let tick_count = (elapsed * TICK_RATE).round() as u64;
+        let tick_count = last_tick.map_or(tick_count, |last_tick: u64| tick_count.max(last_tick + 1));
+        last_tick = Some(tick_count);
+
+        states.push(Arc::new(converter.game_state(tick_count)));
+    }
+
+    let mut names = converter.names.into_iter().collect::<Vec<_>>();
+    names.sort_unstable_by_key(|&(id, _)| id);
+
+    Ok((states, CarMetadata(names)))
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum ComponentKind {
+    Boost,
+    Jump,
+    DoubleJump,
+    Dodge,
+}
+
+/// The actors in the replay that end up in a `GameState`, everything else is skipped
+enum Actor {
+    Ball(Option<RigidBody>),
+    Car {
+        player: Option<ActorId>,
+        body: Option<RigidBody>,
+        controls: CarControls,
+    },
+    Player {
+        id: u32,
+        team: Option<ActorId>,
+    },
+    Team(Team),
+    Component {
+        kind: ComponentKind,
+        car: Option<ActorId>,
+        active: bool,
+        boost: f32,
+    },
+}
+
+/// Keeps track of every actor as the frames are applied one by one
+#[derive(Default)]
+struct ReplayConverter {
+    /// The names of the objects that new actors and attributes refer to by index
+    objects: Vec<String>,
+    actors: HashMap<ActorId, Actor>,
+    game_mode: GameMode,
+    names: HashMap<u32, String>,
+    next_car_id: u32,
+}
+
+impl ReplayConverter {
+    fn apply_frame(&mut self, frame: &Frame) {
+        // actor ids are reused, so a new actor can replace one that was deleted in the same frame
+        for actor_id in &frame.deleted_actors {
+            self.actors.remove(actor_id);
+        }
+
+        for new_actor in &frame.new_actors {
+            if let Some(actor) = self.new_actor(new_actor) {
+                self.actors.insert(new_actor.actor_id, actor);
+            }
+        }
+
+        for update in &frame.updated_actors {
+            self.update_actor(update);
+        }
+
+        for actor in self.actors.values_mut() {
+            if let Actor::Component {
+                kind: ComponentKind::Boost,
+                active: true,
+                boost,
+                ..
+            } = actor
+            {
+                *boost = (*boost - BOOST_USED_PER_SECOND * frame.delta).max(0.);
+            }
+        }
+    }
+
+    fn new_actor(&mut self, new_actor: &NewActor) -> Option<Actor> {
+        let object = self.objects.get(usize::from(new_actor.object_id))?;
+
+        let component = |kind| Actor::Component {
+            kind,
+            car: None,
+            active: false,
+            boost: 0.,
+        };
+
+        Some(match object.as_str() {
+            CAR_OBJECT => Actor::Car {
+                player: None,
+                body: None,
+                controls: CarControls::default(),
+            },
+            PLAYER_OBJECT => {
+                // car ids start at 1 so 0 can mean "no car", and stay with the player when their car is demolished
+                self.next_car_id += 1;
+                Actor::Player {
+                    id: self.next_car_id,
+                    team: None,
+                }
+            }
+            BLUE_TEAM_OBJECT => Actor::Team(Team::Blue),
+            ORANGE_TEAM_OBJECT => Actor::Team(Team::Orange),
+            BOOST_OBJECT => component(ComponentKind::Boost),
+            JUMP_OBJECT => component(ComponentKind::Jump),
+            DOUBLE_JUMP_OBJECT => component(ComponentKind::DoubleJump),
+            DODGE_OBJECT => component(ComponentKind::Dodge),
+            _ => {
+                self.game_mode = match object.strip_prefix("Archetypes.Ball.")? {
+                    "Ball_Basketball" => GameMode::Hoops,
+                    "Ball_Puck" => GameMode::Snowday,
+                    _ => GameMode::Soccar,
+                };
+
+                Actor::Ball(None)
+            }
+        })
+    }
+
+    fn update_actor(&mut self, update: &UpdatedAttribute) {
+        let Some(actor) = self.actors.get_mut(&update.actor_id) else {
+            return;
+        };
+
+        let Some(key) = self.objects.get(usize::from(update.object_id)) else {
+            return;
+        };
+
+        match (actor, key.as_str(), &update.attribute) {
+            (Actor::Ball(body) | Actor::Car { body, .. }, RIGID_BODY_KEY, Attribute::RigidBody(new_body)) => {
+                *body = Some(*new_body);
+            }
+            (Actor::Car { player, .. }, CAR_PLAYER_KEY, Attribute::ActiveActor(active_actor)) => {
+                *player = Some(active_actor.actor);
+            }
+            (Actor::Car { controls, .. }, THROTTLE_KEY, &Attribute::Byte(throttle)) => {
+                controls.throttle = byte_to_axis(throttle);
+            }
+            (Actor::Car { controls, .. }, STEER_KEY, &Attribute::Byte(steer)) => {
+                controls.steer = byte_to_axis(steer);
+            }
+            (Actor::Car { controls, .. }, HANDBRAKE_KEY, &Attribute::Boolean(handbrake)) => {
+                controls.handbrake = handbrake;
+            }
+            (Actor::Player { id, .. }, PLAYER_NAME_KEY, Attribute::String(name)) => {
+                self.names.insert(*id, name.clone());
+            }
+            (Actor::Player { team, .. }, PLAYER_TEAM_KEY, Attribute::ActiveActor(active_actor)) => {
+                *team = Some(active_actor.actor);
+            }
+            (Actor::Component { car, .. }, COMPONENT_CAR_KEY, Attribute::ActiveActor(active_actor)) => {
+                *car = Some(active_actor.actor);
+            }
+            // the lowest bit is if it's active, the rest counts how many times it's been activated
+            (Actor::Component { active, .. }, COMPONENT_ACTIVE_KEY, &Attribute::Byte(state)) => {
+                *active = state % 2 == 1;
+            }
+            (Actor::Component { boost, .. }, BOOST_KEY, Attribute::ReplicatedBoost(replicated)) => {
+                *boost = byte_to_boost(replicated.boost_amount);
+            }
+            (Actor::Component { boost, .. }, BOOST_AMOUNT_KEY, &Attribute::Byte(amount)) => {
+                *boost = byte_to_boost(amount);
+            }
+            _ => {}
+        }
+    }
+
+    fn game_state(&self, tick_count: u64) -> GameState {
+        let ball = self
+            .actors
+            .values()
+            .find_map(|actor| match actor {
+                Actor::Ball(Some(body)) => Some(body),
+                _ => None,
+            })
+            .map_or_else(BallState::default, |body| BallState {
+                update_counter: tick_count,
+                pos: vec3(body.location),
+                rot_mat: rot_mat(body),
+                vel: body.linear_velocity.map_or(Vec3::ZERO, vec3),
+                ang_vel: body.angular_velocity.map_or(Vec3::ZERO, vec3),
+                ..BallState::default()
+            });
+
+        let mut cars = self
+            .actors
+            .iter()
+            .filter_map(|(&actor_id, actor)| self.car_info(actor_id, actor, tick_count))
+            .collect::<Vec<_>>();
+        cars.sort_unstable_by_key(|car| car.id);
+
+        GameState {
+            tick_count,
+            tick_rate: TICK_RATE,
+            game_mode: self.game_mode,
+            ball,
+            // the pads are never sent, only when they're picked up
+            pads: Box::default(),
+            cars: cars.into_boxed_slice(),
+        }
+    }
+
+    /// Cars are only shown once it's known which player is driving them
+    fn car_info(&self, actor_id: ActorId, actor: &Actor, tick_count: u64) -> Option<CarInfo> {
+        let Actor::Car {
+            player,
+            body: Some(body),
+            controls,
+        } = actor
+        else {
+            return None;
+        };
+
+        let Some(&Actor::Player { id, team }) = self.actors.get(&(*player)?) else {
+            return None;
+        };
+
+        let team = match team.and_then(|team| self.actors.get(&team)) {
+            Some(&Actor::Team(team)) => team,
+            _ => Team::default(),
+        };
+
+        let mut state = CarState {
+            pos: vec3(body.location),
+            rot_mat: rot_mat(body),
+            vel: body.linear_velocity.map_or(Vec3::ZERO, vec3),
+            ang_vel: body.angular_velocity.map_or(Vec3::ZERO, vec3),
+            update_counter: tick_count,
+            last_controls: *controls,
+            ..Default::default()
+        };
+        state.is_supersonic = state.vel.length() >= SUPERSONIC_SPEED;
+
+        for component in self.actors.values() {
+            let &Actor::Component {
+                kind,
+                car: Some(car),
+                active,
+                boost,
+            } = component
+            else {
+                continue;
+            };
+
+            if car != actor_id {
+                continue;
+            }
+
+            match kind {
+                ComponentKind::Boost => {
+                    state.boost = boost;
+                    state.last_controls.boost = active;
+                }
+                ComponentKind::Jump => {
+                    state.is_jumping = active;
+                    state.has_jumped |= active;
+                    state.last_controls.jump |= active;
+                }
+                ComponentKind::DoubleJump => state.has_double_jumped = active,
+                ComponentKind::Dodge => {
+                    state.is_flipping = active;
+                    state.has_flipped = active;
+                }
+            }
+        }
+
+        Some(CarInfo {
+            id,
+            team,
+            state,
+            // replays don't say which car each player is using, so every car gets the Octane's hitbox
+            config: CarConfig::OCTANE,
+        })
+    }
+}
+
+/// Throttle and steer are sent as a byte, with 128 in the middle
+#[inline]
+fn byte_to_axis(byte: u8) -> f32 {
+    ((f32::from(byte) - 128.) / 127.).clamp(-1., 1.)
+}
+
+#[inline]
+fn byte_to_boost(byte: u8) -> f32 {
+    f32::from(byte) / 255. * 100.
+}
+
+#[inline]
+fn vec3(vec: boxcars::Vector3f) -> Vec3 {
+    Vec3::new(vec.x, vec.y, vec.z)
+}
+
+/// The quaternion is in the same coordinates as RocketSim, so its columns are forward, right, and up
+#[inline]
+fn rot_mat(body: &RigidBody) -> RotMat {
+    let rotation = body.rotation;
+    RotMat::from_quat(Quat::from_xyzw(rotation.x, rotation.y, rotation.z, rotation.w).normalize())
+}
@@ -0,0 +1,337 @@
+use crate::{
+    nametags::CarMetadata,
+    rocketsim::{
+        BallState, BoostPad, BoostPadState, CarConfig, CarControls, CarInfo, CarState, GameMode, GameState, Team,
+        WheelPairConfig,
+    },
+    udp::{SendableUdp, UdpUpdate},
+};
+use bevy::{
+    log::{error, info, warn},
+    math::{Mat3A as RotMat, Vec3A as Vec3},
+};
+use crossbeam_channel::{Receiver, RecvTimeoutError, Sender};
+use rlbot_flat::{
+    flat,
+    planus::{self, ReadAsRoot},
+};
+use std::{
+    io::{self, Read, Write},
+    net::{Shutdown, SocketAddr, TcpStream},
+    sync::Arc,
+    thread,
+    time::{Duration, Instant},
+};
+
+/// The port RLBot's core listens on for its flatbuffer socket
+pub const RLBOT_PORT: u16 = 23234;
+
+/// The game always runs physics at 120Hz, and RLBot doesn't send the tick rate
+const TICK_RATE: f32 = 120.;
+
+/// How long it takes a boost pad to come back after being picked up, in seconds
+const BIG_PAD_RESPAWN_SECS: f32 = 10.;
+const SMALL_PAD_RESPAWN_SECS: f32 = 4.;
+
+fn read_packet(stream: &mut TcpStream, buf: &mut Vec<u8>) -> io::Result<flat::CorePacket> {
+    let mut len = [0; 2];
+    stream.read_exact(&mut len)?;
+
+    buf.resize(usize::from(u16::from_be_bytes(len)), 0);
+    stream.read_exact(buf)?;
+
+    flat::CorePacketRef::read_as_root(buf)
+        .and_then(flat::CorePacket::try_from)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+fn write_packet(stream: &mut TcpStream, builder: &mut planus::Builder, message: flat::InterfaceMessage) -> io::Result<()> {
+    builder.clear();
+    let bytes = builder.finish(flat::InterfacePacket { message }, None);
+    let len = u16::try_from(bytes.len()).map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "packet is too big"))?;
+
+    stream.write_all(&len.to_be_bytes())?;
+    stream.write_all(bytes)
+}
+
+/// Connects to RLBot's socket as a viewer and turns the packets it sends into `UdpUpdate`s
+///
+/// Nothing is sent back to RLBot, so pausing, changing the game speed, and state setting only affect the visualizer
+pub fn start_rlbot_handlers(out_addr: SocketAddr, outgoing: Receiver<SendableUdp>, updates: Sender<UdpUpdate>) {
+    thread::Builder::new()
+        .name(String::from("rlviser-rlbot-send"))
+        .spawn(move || {
+            // RLBot might not be running yet, so keep trying like the TCP transport does
+            let mut stream = loop {
+                match TcpStream::connect(out_addr) {
+                    Ok(stream) => break stream,
+                    // waiting on the channel instead of sleeping lets a reconnect stop this thread
+                    Err(_) => {
+                        if matches!(
+                            outgoing.recv_timeout(Duration::from_secs(1)),
+                            Err(RecvTimeoutError::Disconnected)
+                        ) {
+                            return;
+                        }
+                    }
+                }
+            };
+
+            info!("Connected to RLBot at {out_addr}");
+            drop(stream.set_nodelay(true));
+
+            let mut builder = planus::Builder::new();
+            let connection_settings = flat::ConnectionSettings {
+                agent_id: String::from("rlviser"),
+                wants_ball_predictions: false,
+                wants_comms: false,
+                close_between_matches: false,
+            };
+
+            let handshake = write_packet(
+                &mut stream,
+                &mut builder,
+                flat::InterfaceMessage::ConnectionSettings(Box::new(connection_settings)),
+            )
+            .and_then(|()| {
+                write_packet(
+                    &mut stream,
+                    &mut builder,
+                    flat::InterfaceMessage::InitComplete(Box::default()),
+                )
+            });
+
+            if let Err(e) = handshake {
+                error!("Failed to connect to RLBot due to: {e}");
+                while outgoing.recv().is_ok() {}
+                return;
+            }
+
+            let mut recv_stream = stream.try_clone().unwrap();
+            thread::Builder::new()
+                .name(String::from("rlviser-rlbot-recv"))
+                .spawn(move || {
+                    let mut buf = Vec::new();
+                    let mut converter = PacketConverter::default();
+
+                    loop {
+                        let packet = match read_packet(&mut recv_stream, &mut buf) {
+                            Ok(packet) => packet,
+                            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return,
+                            Err(e) => {
+                                error!("Failed to read RLBot packet due to: {e}");
+                                return;
+                            }
+                        };
+
+                        for update in converter.convert(packet) {
+                            let is_exit = matches!(update, UdpUpdate::Exit);
+                            if updates.send(update).is_err() || is_exit {
+                                return;
+                            }
+                        }
+                    }
+                })
+                .unwrap();
+
+            // keep the channel open until the connection is replaced so sending never fails
+            while outgoing.recv().is_ok() {}
+
+            // the connection was replaced, so stop the receiving thread too
+            drop(stream.shutdown(Shutdown::Both));
+        })
+        .unwrap();
+}
+
+/// Keeps what RLBot only sends once per match, which is needed to fill in every `GameState`
+#[derive(Default)]
+struct PacketConverter {
+    game_mode: GameMode,
+    /// Where each boost pad is and if it's a big one, in the same order as the states in each `GamePacket`
+    pads: Vec<(Vec3, bool)>,
+    names: Vec<String>,
+}
+
+impl PacketConverter {
+    fn convert(&mut self, packet: flat::CorePacket) -> Vec<UdpUpdate> {
+        match packet.message {
+            flat::CoreMessage::DisconnectSignal(_) => vec![UdpUpdate::Exit],
+            flat::CoreMessage::FieldInfo(field_info) => {
+                self.pads = field_info
+                    .boost_pads
+                    .iter()
+                    .map(|pad| (vec3(&pad.location), pad.is_full_boost))
+                    .collect();
+                Vec::new()
+            }
+            flat::CoreMessage::MatchConfiguration(match_config) => {
+                self.game_mode = match match_config.game_mode {
+                    flat::GameMode::Soccar | flat::GameMode::Rumble => GameMode::Soccar,
+                    flat::GameMode::Hoops => GameMode::Hoops,
+                    flat::GameMode::Heatseeker => GameMode::HeatSeeker,
+                    flat::GameMode::Snowday => GameMode::Snowday,
+                    other => {
+                        warn!("RLViser can't show the field for {other:?}");
+                        GameMode::TheVoid
+                    }
+                };
+                Vec::new()
+            }
+            flat::CoreMessage::GamePacket(game_packet) => {
+                let mut updates = Vec::with_capacity(2);
+
+                // the names only change when players join or leave
+                if game_packet.players.len() != self.names.len()
+                    || game_packet
+                        .players
+                        .iter()
+                        .zip(&self.names)
+                        .any(|(player, name)| player.name != *name)
+                {
+                    self.names = game_packet.players.iter().map(|player| player.name.clone()).collect();
+                    updates.push(UdpUpdate::CarMetadata(CarMetadata(
+                        self.names
+                            .iter()
+                            .cloned()
+                            .enumerate()
+                            .map(|(i, name)| (car_id(i), name))
+                            .collect(),
+                    )));
+                }
+
+                updates.push(UdpUpdate::State(Arc::new(self.game_state(&game_packet)), Instant::now()));
+                updates
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    fn game_state(&self, packet: &flat::GamePacket) -> GameState {
+        let tick_count = u64::from(packet.match_info.frame_num);
+
+        let ball = packet.balls.first().map_or_else(BallState::default, |ball| BallState {
+            update_counter: tick_count,
+            pos: vec3(&ball.physics.location),
+            rot_mat: rot_mat(&ball.physics.rotation),
+            vel: vec3(&ball.physics.velocity),
+            ang_vel: vec3(&ball.physics.angular_velocity),
+            ..BallState::default()
+        });
+
+        let pads = self
+            .pads
+            .iter()
+            .zip(&packet.boost_pads)
+            .map(|(&(position, is_big), state)| {
+                let respawn_secs = if is_big {
+                    BIG_PAD_RESPAWN_SECS
+                } else {
+                    SMALL_PAD_RESPAWN_SECS
+                };
+
+                BoostPad {
+                    is_big,
+                    position,
+                    state: BoostPadState {
+                        is_active: state.is_active,
+                        // RLBot counts up from when the pad was picked up instead of down to when it comes back
+                        cooldown: if state.is_active {
+                            0.
+                        } else {
+                            (respawn_secs - state.timer).max(0.)
+                        },
+                        ..Default::default()
+                    },
+                }
+            })
+            .collect();
+
+        let cars = packet
+            .players
+            .iter()
+            .enumerate()
+            .map(|(i, player)| car_info(car_id(i), player, tick_count))
+            .collect();
+
+        GameState {
+            tick_count,
+            tick_rate: TICK_RATE,
+            game_mode: self.game_mode,
+            ball,
+            pads,
+            cars,
+        }
+    }
+}
+
+/// RLBot's players are identified by their index, but car ids start at 1 so 0 can mean "no car"
+#[inline]
+fn car_id(index: usize) -> u32 {
+    index as u32 + 1
+}
+
+fn car_info(id: u32, player: &flat::PlayerInfo, tick_count: u64) -> CarInfo {
+    let physics = &player.physics;
+    let controls = &player.last_input;
+    let is_demoed = player.demolished_timeout > 0.;
+
+    CarInfo {
+        id,
+        team: if player.team == 0 { Team::Blue } else { Team::Orange },
+        state: CarState {
+            pos: vec3(&physics.location),
+            rot_mat: rot_mat(&physics.rotation),
+            vel: vec3(&physics.velocity),
+            ang_vel: vec3(&physics.angular_velocity),
+            update_counter: tick_count,
+            is_on_ground: player.air_state == flat::AirState::OnGround,
+            wheels_with_contact: [player.air_state == flat::AirState::OnGround; 4],
+            has_jumped: player.has_jumped,
+            has_double_jumped: player.has_double_jumped,
+            has_flipped: player.has_dodged,
+            is_jumping: player.air_state == flat::AirState::Jumping,
+            is_flipping: player.air_state == flat::AirState::Dodging,
+            boost: player.boost,
+            is_supersonic: player.is_supersonic,
+            is_demoed,
+            demo_respawn_timer: if is_demoed { player.demolished_timeout } else { 0. },
+            last_controls: CarControls {
+                throttle: controls.throttle,
+                steer: controls.steer,
+                pitch: controls.pitch,
+                yaw: controls.yaw,
+                roll: controls.roll,
+                boost: controls.boost,
+                jump: controls.jump,
+                handbrake: controls.handbrake,
+            },
+            ..Default::default()
+        },
+        config: CarConfig {
+            hitbox_size: Vec3::new(player.hitbox.length, player.hitbox.width, player.hitbox.height),
+            hitbox_pos_offset: vec3(&player.hitbox_offset),
+            // RLBot doesn't send the wheels of each car, so every car gets the Octane's
+            front_wheels: WheelPairConfig::OCTANE_FRONT,
+            back_wheels: WheelPairConfig::OCTANE_BACK,
+            dodge_deadzone: 0.5,
+        },
+    }
+}
+
+#[inline]
+fn vec3(vec: &flat::Vector3) -> Vec3 {
+    Vec3::new(vec.x, vec.y, vec.z)
+}
+
+/// The same rotation matrix RocketSim makes from a pitch, yaw, and roll, with forward, right, and up as the columns
+fn rot_mat(rotator: &flat::Rotator) -> RotMat {
+    let (sp, cp) = rotator.pitch.sin_cos();
+    let (sy, cy) = rotator.yaw.sin_cos();
+    let (sr, cr) = rotator.roll.sin_cos();
+
+    RotMat::from_cols(
+        Vec3::new(cp * cy, cp * sy, sp),
+        Vec3::new(cy * sp * sr - cr * sy, sy * sp * sr + cr * cy, -cp * sr),
+        Vec3::new(-cr * cy * sp - sr * sy, -cr * sy * sp + sr * cy, cp * cr),
+    )
+}
@@ -2,9 +2,10 @@ use bevy::{
     math::{Mat3A as RotMat, Vec3A as Vec3},
     prelude::*,
 };
+use serde::{Deserialize, Serialize};
 
 #[repr(u8)]
-#[derive(Resource, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub enum GameMode {
     Soccar = 0,
     Hoops,
@@ -14,7 +15,7 @@ pub enum GameMode {
     TheVoid,
 }
 
-#[derive(Clone, Copy, Default, Debug)]
+#[derive(Clone, Copy, Default, Debug, Serialize, Deserialize)]
 pub struct BallHitInfo {
     pub is_valid: bool,
     pub relative_pos_on_ball: Vec3,
@@ -24,7 +25,7 @@ pub struct BallHitInfo {
     pub tick_count_when_extra_impulse_applied: u64,
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
 pub struct HeatseekerInfo {
     /// Which net the ball should seek towards;
     /// When 0, no net
@@ -44,7 +45,7 @@ impl Default for HeatseekerInfo {
     }
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
 pub struct BallState {
     pub update_counter: u64,
     pub pos: Vec3,
@@ -69,21 +70,37 @@ impl Default for BallState {
 }
 
 #[repr(u8)]
-#[derive(Clone, Copy, Default, Debug, PartialEq, Eq, Hash)]
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum Team {
     #[default]
     Blue,
     Orange,
 }
 
-#[derive(Clone, Copy, Default, Debug)]
+#[derive(Clone, Copy, Default, Debug, Serialize, Deserialize)]
 pub struct WheelPairConfig {
     pub wheel_radius: f32,
     pub suspension_rest_length: f32,
     pub connection_point_offset: Vec3,
 }
 
-#[derive(Clone, Copy, Default, Debug)]
+// only sources that don't send the car's config need these
+#[cfg(any(feature = "rlbot", feature = "replay_import"))]
+impl WheelPairConfig {
+    pub const OCTANE_FRONT: Self = Self {
+        wheel_radius: 12.5,
+        suspension_rest_length: 38.755,
+        connection_point_offset: Vec3::new(51.25, 25.9, 20.755),
+    };
+
+    pub const OCTANE_BACK: Self = Self {
+        wheel_radius: 15.,
+        suspension_rest_length: 37.055,
+        connection_point_offset: Vec3::new(-33.75, 29.5, 20.755),
+    };
+}
+
+#[derive(Clone, Copy, Default, Debug, Serialize, Deserialize)]
 pub struct CarConfig {
     pub hitbox_size: Vec3,
     pub hitbox_pos_offset: Vec3,
@@ -92,7 +109,19 @@ pub struct CarConfig {
     pub dodge_deadzone: f32,
 }
 
-#[derive(Clone, Copy, Debug, Default)]
+#[cfg(feature = "replay_import")]
+impl CarConfig {
+    /// For when the source of the game state doesn't say which car each player is using
+    pub const OCTANE: Self = Self {
+        hitbox_size: Vec3::new(120.507, 86.6994, 38.6591),
+        hitbox_pos_offset: Vec3::new(13.8757, 0., 20.755),
+        front_wheels: WheelPairConfig::OCTANE_FRONT,
+        back_wheels: WheelPairConfig::OCTANE_BACK,
+        dodge_deadzone: 0.5,
+    };
+}
+
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize)]
 pub struct CarControls {
     pub throttle: f32,
     pub steer: f32,
@@ -104,20 +133,20 @@ pub struct CarControls {
     pub handbrake: bool,
 }
 
-#[derive(Clone, Copy, Debug, Default)]
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize)]
 pub struct WorldContact {
     pub has_contact: bool,
     pub contact_normal: Vec3,
 }
 
-#[derive(Clone, Copy, Debug, Default)]
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize)]
 pub struct CarContact {
     pub other_car_id: u32,
     pub cooldown_timer: f32,
 }
 
 #[allow(clippy::struct_excessive_bools)]
-#[derive(Clone, Copy, Debug, Default)]
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize)]
 pub struct CarState {
     pub pos: Vec3,
     pub rot_mat: RotMat,
@@ -152,7 +181,7 @@ pub struct CarState {
     pub last_controls: CarControls,
 }
 
-#[derive(Clone, Copy, Default, Debug)]
+#[derive(Clone, Copy, Default, Debug, Serialize, Deserialize)]
 pub struct CarInfo {
     pub id: u32,
     pub team: Team,
@@ -160,7 +189,7 @@ pub struct CarInfo {
     pub config: CarConfig,
 }
 
-#[derive(Clone, Copy, Default, Debug)]
+#[derive(Clone, Copy, Default, Debug, Serialize, Deserialize)]
 pub struct BoostPadState {
     pub is_active: bool,
     pub cooldown: f32,
@@ -168,14 +197,14 @@ pub struct BoostPadState {
     pub prev_locked_car_id: u32,
 }
 
-#[derive(Clone, Copy, Default, Debug)]
+#[derive(Clone, Copy, Default, Debug, Serialize, Deserialize)]
 pub struct BoostPad {
     pub is_big: bool,
     pub position: Vec3,
     pub state: BoostPadState,
 }
 
-#[derive(Clone, Resource, Default, Debug)]
+#[derive(Clone, Resource, Default, Debug, Serialize, Deserialize)]
 pub struct GameState {
     pub tick_count: u64,
     pub tick_rate: f32,
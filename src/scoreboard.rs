@@ -0,0 +1,99 @@
+use crate::{
+    camera::TIME_DISPLAY_POS,
+    goal::GoalScored,
+    replay::not_replaying,
+    rocketsim::{GameMode, Team},
+    udp::{get_color_from_team, GameStates},
+};
+use bevy::prelude::*;
+
+pub struct ScoreboardPlugin;
+
+impl Plugin for ScoreboardPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Score::default()).add_systems(Startup, setup).add_systems(
+            Update,
+            (
+                reset_score.run_if(not_replaying),
+                count_goals.run_if(on_event::<GoalScored>),
+                update_scoreboard.run_if(resource_changed::<Score>),
+            )
+                .chain(),
+        );
+    }
+}
+
+/// The score, as inferred from the ball entering the goals
+#[derive(Resource, Default)]
+pub struct Score {
+    pub blue: u32,
+    pub orange: u32,
+    game_mode: GameMode,
+}
+
+#[derive(Component)]
+struct ScoreDisplay(Team);
+
+/// The gap between the two scores that the time display sits in
+const CLOCK_GAP: f32 = 220.;
+
+fn setup(mut commands: Commands) {
+    commands
+        .spawn(Node {
+            width: Val::Percent(100.),
+            position_type: PositionType::Absolute,
+            top: Val::Px(TIME_DISPLAY_POS.x),
+            justify_content: JustifyContent::Center,
+            column_gap: Val::Px(CLOCK_GAP),
+            ..default()
+        })
+        .with_children(|parent| {
+            for team in [Team::Blue, Team::Orange] {
+                parent.spawn((
+                    Text::new(""),
+                    TextFont {
+                        font_size: 40.0,
+                        ..default()
+                    },
+                    TextColor(get_color_from_team(team)),
+                    ScoreDisplay(team),
+                ));
+            }
+        });
+}
+
+/// Starts the score over when the game mode changes or the tick count goes backwards (a new game was started)
+fn reset_score(states: Res<GameStates>, mut score: ResMut<Score>, mut last_tick_count: Local<u64>) {
+    let tick_count = states.current.tick_count;
+    let game_mode = states.current.game_mode;
+
+    if tick_count < *last_tick_count || game_mode != score.game_mode {
+        *score = Score { game_mode, ..default() };
+    }
+
+    *last_tick_count = tick_count;
+}
+
+fn count_goals(mut events: EventReader<GoalScored>, mut score: ResMut<Score>) {
+    for event in events.read() {
+        match event.team {
+            Team::Blue => score.blue += 1,
+            Team::Orange => score.orange += 1,
+        }
+    }
+}
+
+fn update_scoreboard(score: Res<Score>, mut displays: Query<(&mut Text, &ScoreDisplay)>) {
+    for (mut text, display) in &mut displays {
+        **text = if score.game_mode == GameMode::TheVoid {
+            // there are no goals to score in the void
+            String::new()
+        } else {
+            match display.0 {
+                Team::Blue => score.blue,
+                Team::Orange => score.orange,
+            }
+            .to_string()
+        };
+    }
+}
@@ -0,0 +1,157 @@
+use crate::udp::Car;
+use bevy::{prelude::*, utils::HashMap};
+use std::{fs, io, time::SystemTime};
+
+/// The hitbox presets, in the same order as the built-in car bodies
+pub const CAR_PRESETS: [&str; 6] = ["octane", "dominus", "plank", "breakout", "hybrid", "merc"];
+
+/// How a car should look, where anything that's `None` is left as the default
+#[derive(Clone, Default)]
+pub struct CarCustomization {
+    pub body: Option<Box<str>>,
+    pub primary_color: Option<Color>,
+    pub secondary_color: Option<Color>,
+    /// Path to an image in the assets folder that replaces the texture of the car's paint
+    pub decal: Option<Box<str>>,
+}
+
+impl CarCustomization {
+    /// Fills in anything that isn't set with the values from `other`
+    fn or(mut self, other: &Self) -> Self {
+        self.body = self.body.or_else(|| other.body.clone());
+        self.primary_color = self.primary_color.or(other.primary_color);
+        self.secondary_color = self.secondary_color.or(other.secondary_color);
+        self.decal = self.decal.or_else(|| other.decal.clone());
+        self
+    }
+}
+
+/// User provided car bodies, colors and decals that replace the built-in ones
+///
+/// Each line of the file is `key=mesh` or `key.field=value`, where `key` is either a hitbox preset name or a car id.
+/// `mesh` is the name of an uncooked skeletal mesh, e.g. `Body_Octane.SkeletalMesh3.Body_Octane_SK`,
+/// and `field` is one of `body`, `primary`, `secondary` (hex colors), or `decal` (an image in the assets folder).
+///
+/// The file is reloaded whenever it changes, and the cars are respawned with the new settings.
+#[derive(Resource, Default)]
+pub struct CarBodies {
+    presets: [CarCustomization; CAR_PRESETS.len()],
+    cars: HashMap<u32, CarCustomization>,
+    last_modified: Option<SystemTime>,
+}
+
+impl CarBodies {
+    const FILE_NAME: &'static str = "car_bodies.txt";
+
+    #[inline]
+    pub fn default_read_file() -> Self {
+        match Self::read_from_file() {
+            Ok(car_bodies) => car_bodies,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Self::default(),
+            Err(e) => {
+                error!("Failed to read {} due to: {e}", Self::FILE_NAME);
+                Self {
+                    last_modified: Self::modified(),
+                    ..default()
+                }
+            }
+        }
+    }
+
+    fn modified() -> Option<SystemTime> {
+        fs::metadata(Self::FILE_NAME).and_then(|metadata| metadata.modified()).ok()
+    }
+
+    fn read_from_file() -> io::Result<Self> {
+        let mut car_bodies = Self {
+            last_modified: Self::modified(),
+            ..default()
+        };
+
+        let file = fs::read_to_string(Self::FILE_NAME)?;
+
+        for line in file.lines() {
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+
+            let (key, value) = (key.trim(), value.trim());
+            if value.is_empty() {
+                continue;
+            }
+
+            let (key, field) = key.split_once('.').unwrap_or((key, "body"));
+
+            let customization = if let Some(index) = CAR_PRESETS.iter().position(|&preset| preset == key) {
+                &mut car_bodies.presets[index]
+            } else if let Ok(id) = key.parse() {
+                car_bodies.cars.entry(id).or_default()
+            } else {
+                warn!(
+                    "Unknown key {key} in {}, expected a car id or one of {CAR_PRESETS:?}",
+                    Self::FILE_NAME
+                );
+                continue;
+            };
+
+            let parse_color = |value: &str| match Srgba::hex(value) {
+                Ok(color) => Some(Color::from(color)),
+                Err(e) => {
+                    warn!("Invalid color {value} for {key} in {} due to: {e}", Self::FILE_NAME);
+                    None
+                }
+            };
+
+            match field {
+                "body" => customization.body = Some(value.into()),
+                "primary" => customization.primary_color = parse_color(value),
+                "secondary" => customization.secondary_color = parse_color(value),
+                "decal" => customization.decal = Some(value.into()),
+                _ => warn!(
+                    "Unknown field {field} for {key} in {}, expected body, primary, secondary or decal",
+                    Self::FILE_NAME
+                ),
+            }
+        }
+
+        Ok(car_bodies)
+    }
+
+    /// How a car should look, preferring the settings for its id over the ones for its hitbox preset
+    pub fn get(&self, car_id: u32, preset: usize) -> CarCustomization {
+        let preset = &self.presets[preset];
+
+        self.cars
+            .get(&car_id)
+            .map_or_else(|| preset.clone(), |car| car.clone().or(preset))
+    }
+}
+
+/// How often the file is checked for changes, in seconds
+const RELOAD_CHECK_INTERVAL: f32 = 1.;
+
+/// Reloads the file when it has changed, and despawns the cars so they get spawned again with the new settings
+pub fn reload_car_bodies(
+    time: Res<Time>,
+    mut since_check: Local<f32>,
+    mut car_bodies: ResMut<CarBodies>,
+    cars: Query<Entity, With<Car>>,
+    mut commands: Commands,
+) {
+    *since_check += time.delta_secs();
+    if *since_check < RELOAD_CHECK_INTERVAL {
+        return;
+    }
+
+    *since_check = 0.;
+    if CarBodies::modified() == car_bodies.last_modified {
+        return;
+    }
+
+    info!("Reloading {}", CarBodies::FILE_NAME);
+    *car_bodies = CarBodies::default_read_file();
+
+    for entity in &cars {
+        commands.entity(entity).despawn_recursive();
+    }
+}
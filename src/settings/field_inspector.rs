@@ -0,0 +1,112 @@
+use super::options::MenuFocused;
+use crate::mesh::{FieldMeshInfo, StaticFieldEntity};
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+
+pub struct FieldInspectorPlugin;
+
+impl Plugin for FieldInspectorPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(InspectedFieldMesh::default()).add_systems(
+            Update,
+            update_field_inspector
+                .run_if(|inspected: Res<InspectedFieldMesh>| inspected.0.is_some())
+                .run_if(resource_equals(MenuFocused::default())),
+        );
+    }
+}
+
+/// The field mesh that was last right clicked, if its window is open
+#[derive(Resource, Default)]
+pub struct InspectedFieldMesh(Option<Entity>);
+
+impl InspectedFieldMesh {
+    pub fn toggle(&mut self, entity: Entity) {
+        self.0 = if self.0 == Some(entity) { None } else { Some(entity) };
+    }
+}
+
+fn update_field_inspector(
+    mut contexts: EguiContexts,
+    mut inspected: ResMut<InspectedFieldMesh>,
+    meshes: Query<(&FieldMeshInfo, &Transform)>,
+    mut field: Query<(Entity, &mut Visibility), With<StaticFieldEntity>>,
+) {
+    let entity = inspected.0.unwrap();
+
+    // the field was reloaded
+    let Ok((info, transform)) = meshes.get(entity) else {
+        inspected.0 = None;
+        return;
+    };
+
+    let mut open = true;
+    egui::Window::new("Field mesh")
+        .open(&mut open)
+        .show(contexts.ctx_mut(), |ui| {
+            egui::Grid::new("field_mesh_info").num_columns(2).show(ui, |ui| {
+                ui.label("Mesh");
+                ui.label(info.mesh.as_ref());
+                ui.end_row();
+
+                ui.label("Material");
+                ui.label(info.material.as_ref());
+                ui.end_row();
+
+                let pos = transform.translation;
+                ui.label("Translation");
+                ui.label(format!("[{:.1}, {:.1}, {:.1}]", pos.x, pos.y, pos.z));
+                ui.end_row();
+
+                let (yaw, pitch, roll) = transform.rotation.to_euler(EulerRot::YXZ);
+                ui.label("Rotation").on_hover_text("Yaw, pitch and roll in degrees");
+                ui.label(format!(
+                    "[{:.1}, {:.1}, {:.1}]",
+                    yaw.to_degrees(),
+                    pitch.to_degrees(),
+                    roll.to_degrees()
+                ));
+                ui.end_row();
+
+                let scale = transform.scale;
+                ui.label("Scale");
+                ui.label(format!("[{:.2}, {:.2}, {:.2}]", scale.x, scale.y, scale.z));
+                ui.end_row();
+            });
+
+            ui.horizontal(|ui| {
+                let is_hidden = field
+                    .get(entity)
+                    .is_ok_and(|(_, visibility)| *visibility == Visibility::Hidden);
+                if ui.button(if is_hidden { "Show" } else { "Hide" }).clicked() {
+                    if let Ok((_, mut visibility)) = field.get_mut(entity) {
+                        *visibility = if is_hidden {
+                            Visibility::Inherited
+                        } else {
+                            Visibility::Hidden
+                        };
+                    }
+                }
+
+                if ui.button("Isolate").on_hover_text("Hide every other field mesh").clicked() {
+                    for (other, mut visibility) in &mut field {
+                        visibility.set_if_neq(if other == entity {
+                            Visibility::Inherited
+                        } else {
+                            Visibility::Hidden
+                        });
+                    }
+                }
+
+                if ui.button("Show all").clicked() {
+                    for (_, mut visibility) in &mut field {
+                        visibility.set_if_neq(Visibility::Inherited);
+                    }
+                }
+            });
+        });
+
+    if !open {
+        inspected.0 = None;
+    }
+}
@@ -1,27 +1,69 @@
 use super::options::{
-    BallCam, CalcBallRot, GameOptions, GameSpeed, MenuFocused, Options, PacketSmoothing, ShowTime, UiOverlayScale,
+    BallCam, BallTrail, Blueprint, BoostTrailQuality, CalcBallRot, CarLodDistance, ClampStateInputs, FieldStyle,
+    GameOptions, GameSpeed, GoalExplosion, HideDemoedCars, ImpactCameraShake, LightingPreset, MenuFocused, NudgeStep,
+    Options, PacketSmoothing, PhysicsDebug, PovCamera, RenderDelay, ReplayOnGoal, ScreenshotOnGoal, ShowBallPath,
+    ShowBallSpin, ShowBouncePoint, ShowCarHitboxes, ShowCarNames, ShowCrowd, ShowInputViewer, ShowMessageFeed,
+    ShowPadCooldowns, ShowTeamHud, ShowTime, SmoothBoostMeter, SplitScreen, SplitScreenLayout, UiOverlayScale,
+    WallTransparency, WallTransparencyMode,
 };
 use crate::{
-    camera::{DaylightOffset, PrimaryCamera, Sun},
-    renderer::{DoRendering, RenderGroups},
-    spectator::SpectatorSettings,
-    udp::{Connection, LastPacketTimesElapsed, PausedUpdate, SendableUdp, SpeedUpdate},
+    camera::{CameraTransition, CarSlots, DaylightOffset, PrimaryCamera, Sun},
+    camera_path::{CameraPath, CAMERA_PATH_FILE},
+    capture::Recording,
+    effects::{BulletTime, GoalSlowMotion},
+    mesh::{ReloadField, StadiumLayout},
+    nametags::CarNames,
+    renderer::{DoRendering, FocusRenderGroup, RenderGroups},
+    spectator::{BookmarkFlight, Spectator, SpectatorSettings},
+    udp::{
+        Connection, DumpGameState, GameStates, LastPacketTimesElapsed, PausedUpdate, ProtocolMismatch, Reconnect,
+        SendableUdp, SpeedUpdate, StepQueue, WaitingForSimulator, PROTOCOL_VERSION,
+    },
+    ServerPort, Transport,
 };
+#[cfg(feature = "sound")]
+use bevy::audio::Volume;
 use bevy::{
-    pbr::DirectionalLightShadowMap,
+    core_pipeline::{
+        bloom::Bloom,
+        experimental::taa::TemporalAntiAliasing,
+        prepass::{DepthPrepass, MotionVectorPrepass, NormalPrepass},
+    },
+    pbr::{
+        DirectionalLightShadowMap, ScreenSpaceAmbientOcclusion, ScreenSpaceAmbientOcclusionQualityLevel,
+        ShadowFilteringMethod,
+    },
     prelude::*,
+    render::camera::TemporalJitter,
     time::Stopwatch,
-    window::{CursorGrabMode, PrimaryWindow},
+    window::{CursorGrabMode, Monitor, MonitorSelection, PrimaryWindow, WindowMode, WindowPosition, WindowResized},
+};
+use std::{
+    net::IpAddr,
+    time::{Duration, SystemTime},
 };
-use std::time::Duration;
 
-use super::state_setting::StateSettingInterface;
+use super::{
+    field_inspector::FieldInspectorPlugin,
+    keybinds::{Action, EnableKeybindsMenu, Keybinds, KeybindsPlugin},
+    material_editor::{EnableMaterialEditor, MaterialEditorPlugin},
+    network_stats::{EnableNetworkStats, NetworkStatsPlugin},
+    packet_log::{PacketLog, PacketLogPlugin},
+    profiles::{apply_profile, profile_path, save_profile, ProfilesPlugin, SettingsProfiles},
+    session_stats::{EnableSessionStats, SessionStatsPlugin},
+    snapshot_diff::{EnableSnapshotDiff, SnapshotDiffPlugin},
+    state_script::{RunStateScript, StateScriptPlayback, StateScriptPlugin, StateScriptRecorder},
+    state_setting::{EnableStateSnapshots, StateSettingInterface},
+    telemetry::{EnableTelemetryMenu, TelemetryPlugin},
+};
 use bevy_egui::{
     egui::{self, CollapsingHeader},
     EguiContexts, EguiPlugin,
 };
 use bevy_framepace::{FramepaceSettings, Limiter};
 
+#[cfg(debug_assertions)]
+use super::paused_systems::{DebugSystem, PausedSystems};
 #[cfg(debug_assertions)]
 use crate::camera::{EntityName, HighlightedEntity};
 
@@ -29,45 +71,109 @@ pub struct DebugOverlayPlugin;
 
 impl Plugin for DebugOverlayPlugin {
     fn build(&self, app: &mut App) {
-        app.add_plugins((GameOptions, EguiPlugin, StateSettingInterface))
-            .insert_resource(RenderInfo::default())
-            .insert_resource(UpdateRenderInfoTime::default())
-            .insert_resource(PacketSendTime::default())
-            .add_systems(
-                Update,
+        app.add_plugins((
+            GameOptions,
+            EguiPlugin,
+            StateSettingInterface,
+            FieldInspectorPlugin,
+            SnapshotDiffPlugin,
+            StateScriptPlugin,
+            KeybindsPlugin,
+            NetworkStatsPlugin,
+            PacketLogPlugin,
+            TelemetryPlugin,
+            SessionStatsPlugin,
+            MaterialEditorPlugin,
+            ProfilesPlugin,
+        ))
+        .insert_resource(RenderInfo::default())
+        .insert_resource(UpdateRenderInfoTime::default())
+        .insert_resource(PacketSendTime::default())
+        .insert_resource(Toast::default())
+        .add_systems(
+            Update,
+            (
+                listen,
+                (read_speed_update_event, read_paused_update_event),
+                ui_system.run_if(resource_equals(MenuFocused::default())),
                 (
-                    listen,
-                    (read_speed_update_event, read_paused_update_event),
+                    toggle_vsync,
+                    toggle_ballcam,
+                    toggle_show_time,
+                    update_daytime,
+                    update_msaa,
+                    update_sensitivity,
+                    update_allow_rendering,
+                    update_render_info,
+                    // bullet time interpolates packets no matter what, and puts the setting back when it's done
+                    update_packet_smoothing.run_if(|bullet_time: Res<BulletTime>| !bullet_time.is_active()),
                     (
-                        advance_time,
-                        ui_system,
-                        toggle_vsync,
-                        toggle_ballcam,
-                        toggle_show_time,
-                        update_daytime,
-                        #[cfg(not(feature = "ssao"))]
-                        update_msaa,
-                        update_ui_scale,
-                        update_shadows,
-                        update_sensitivity,
-                        update_allow_rendering,
-                        update_render_info,
-                        update_packet_smoothing,
                         update_calc_ball_rot,
-                        (
-                            update_speed
-                                .run_if(|options: Res<Options>, last: Res<GameSpeed>| options.game_speed != last.speed),
-                            update_paused
-                                .run_if(|options: Res<Options>, last: Res<GameSpeed>| options.paused != last.paused),
-                        )
-                            .run_if(resource_exists::<Connection>),
-                    )
-                        .run_if(resource_equals(MenuFocused::default())),
-                    update_camera_state,
-                    write_settings_to_file,
+                        update_field_style,
+                        update_split_screen,
+                        update_show_team_hud,
+                        update_show_input_viewer,
+                        update_show_message_feed,
+                        update_camera_transition,
+                        update_lighting_preset,
+                        update_render_delay,
+                        update_ambient_occlusion,
+                        update_bloom,
+                        update_show_ball_spin,
+                        update_physics_debug,
+                        update_goal_explosion,
+                        update_impact_camera_shake,
+                    ),
+                    (
+                        update_smooth_boost_meter,
+                        update_blueprint,
+                        update_clamp_state_inputs,
+                        update_nudge_step,
+                        update_show_bounce_point,
+                        update_hide_demoed_cars,
+                        update_screenshot_on_goal,
+                        update_show_car_hitboxes,
+                        update_car_lod_distance,
+                        update_show_car_names,
+                        update_boost_trail_quality,
+                        update_ball_trail,
+                        update_pov_camera,
+                        update_replay_on_goal,
+                        update_show_pad_cooldowns,
+                        update_show_ball_path,
+                        #[cfg(feature = "sound")]
+                        update_volume,
+                        update_wall_transparency,
+                        update_show_crowd,
+                    ),
+                    update_window_mode,
                 )
-                    .chain(),
-            );
+                    // the settings file can also be edited while the menu is closed
+                    .run_if(resource_equals(MenuFocused::default()).or(resource_changed::<Options>)),
+                // these can also be changed through hotkeys while the menu is closed
+                (update_ui_scale, update_shadows).run_if(resource_changed::<Options>),
+                speed_scrubber,
+                (
+                    advance_time,
+                    (
+                        update_speed
+                            .run_if(|options: Res<Options>, last: Res<GameSpeed>| options.game_speed != last.speed)
+                            .run_if(|slow_motion: Res<GoalSlowMotion>| !slow_motion.is_active())
+                            .run_if(|bullet_time: Res<BulletTime>| !bullet_time.is_active()),
+                        update_paused.run_if(|options: Res<Options>, last: Res<GameSpeed>| options.paused != last.paused),
+                    )
+                        .run_if(resource_exists::<Connection>),
+                ),
+                dump_game_state.run_if(on_event::<DumpGameState>),
+                show_toast.run_if(|toast: Res<Toast>| toast.0.is_some()),
+                protocol_mismatch_dialog.run_if(|mismatch: Res<ProtocolMismatch>| mismatch.0.is_some()),
+                waiting_for_simulator_overlay.run_if(resource_equals(WaitingForSimulator(true))),
+                update_camera_state,
+                save_window_size.run_if(on_event::<WindowResized>),
+                sync_settings_file,
+            )
+                .chain(),
+        );
 
         #[cfg(debug_assertions)]
         app.add_systems(Update, debug_ui);
@@ -86,6 +192,7 @@ fn debug_ui(
     mut contexts: EguiContexts,
     heq: Query<(&Transform, &EntityName), With<HighlightedEntity>>,
     cam_pos: Query<&Transform, With<PrimaryCamera>>,
+    mut paused_systems: ResMut<PausedSystems>,
 ) {
     let ctx = contexts.ctx_mut();
     let camera_pos = cam_pos.single().translation;
@@ -102,6 +209,15 @@ fn debug_ui(
         ));
         ui.label(format!("HE position: [{:.0}, {:.0}, {:.0}]", he_pos.x, he_pos.y, he_pos.z));
         ui.label(format!("Highlighted entity: {highlighted_entity_name}"));
+
+        ui.collapsing("Systems", |ui| {
+            for system in DebugSystem::ALL {
+                let mut enabled = !paused_systems.0.contains(&system);
+                if ui.checkbox(&mut enabled, system.name()).changed() {
+                    paused_systems.set_paused(system, !enabled);
+                }
+            }
+        });
     });
 }
 
@@ -112,6 +228,8 @@ struct UpdateRenderInfoTime(Stopwatch);
 struct RenderInfo {
     groups: usize,
     items: usize,
+    /// The id and number of items of every group, sorted by id
+    group_items: Vec<(i32, usize)>,
 }
 
 fn update_render_info(
@@ -129,19 +247,58 @@ fn update_render_info(
 
     render_info.groups = renders.groups.len();
     render_info.items = renders.groups.values().map(Vec::len).sum();
+
+    render_info.group_items.clear();
+    render_info
+        .group_items
+        .extend(renders.groups.iter().map(|(&id, group)| (id, group.len())));
+    radsort::sort_by_key(&mut render_info.group_items, |(id, _)| *id);
 }
 
 fn ui_system(
     mut menu_focused: ResMut<MenuFocused>,
     mut options: ResMut<Options>,
     mut contexts: EguiContexts,
-    render_info: Res<RenderInfo>,
+    (render_info, mut render_groups, mut focus_group): (
+        Res<RenderInfo>,
+        ResMut<RenderGroups>,
+        EventWriter<FocusRenderGroup>,
+    ),
     time: Res<Time>,
+    mut dump_state: EventWriter<DumpGameState>,
+    mut snapshot_diff: ResMut<EnableSnapshotDiff>,
+    mut keybinds_menu: ResMut<EnableKeybindsMenu>,
+    (mut network_stats, mut packet_log, mut telemetry_menu, mut session_stats, mut material_editor): (
+        ResMut<EnableNetworkStats>,
+        ResMut<PacketLog>,
+        ResMut<EnableTelemetryMenu>,
+        ResMut<EnableSessionStats>,
+        ResMut<EnableMaterialEditor>,
+    ),
+    mut state_snapshots: ResMut<EnableStateSnapshots>,
+    mut script_recorder: ResMut<StateScriptRecorder>,
+    mut script_playback: ResMut<StateScriptPlayback>,
+    mut run_script: EventWriter<RunStateScript>,
+    (mut recording, mut step_queue, mut camera_path): (ResMut<Recording>, ResMut<StepQueue>, ResMut<CameraPath>),
+    (mut server_port, mut reconnect): (ResMut<ServerPort>, EventWriter<Reconnect>),
+    (mut reload_field, stadium_layout, monitors, mut profiles, mut toast): (
+        EventWriter<ReloadField>,
+        Res<StadiumLayout>,
+        Query<&Monitor>,
+        ResMut<SettingsProfiles>,
+        ResMut<Toast>,
+    ),
 ) {
-    #[cfg(not(feature = "ssao"))]
     const MSAA_NAMES: [&str; 4] = ["Off", "2x", "4x", "8x"];
+    const AMBIENT_OCCLUSION_NAMES: [&str; 5] = ["Off", "Low", "Medium", "High", "Ultra"];
     const SHADOW_NAMES: [&str; 4] = ["Off", "0.5x", "1x", "1.5x"];
     const SMOOTHING_NAMES: [&str; 3] = ["None", "Interpolate", "Extrapolate"];
+    const BOOST_TRAIL_NAMES: [&str; 4] = ["Off", "Low", "Medium", "High"];
+    const WALL_TRANSPARENCY_NAMES: [&str; 3] = ["Off", "Always", "When outside"];
+    const FIELD_STYLE_NAMES: [&str; 3] = ["Normal", "Flat shaded", "Wireframe"];
+    const LIGHTING_NAMES: [&str; 5] = ["Day cycle", "Noon", "Sunset", "Night", "Studio"];
+    const SPLIT_SCREEN_NAMES: [&str; 4] = ["Off", "Side by side", "Stacked", "Quad"];
+    const WINDOW_MODE_NAMES: [&str; 3] = ["Windowed", "Borderless fullscreen", "Fullscreen"];
 
     let ctx = contexts.ctx_mut();
 
@@ -166,12 +323,83 @@ fn ui_system(
             ui.label(format!("FPS: {fps:.0}"));
 
             ui.collapsing("Graphics", |ui| {
+                ui.horizontal(|ui| {
+                    let mut chosen = None;
+                    egui::ComboBox::from_label("Profile")
+                        .width(100.)
+                        .selected_text("Apply…")
+                        .show_ui(ui, |ui| {
+                            for name in &profiles.names {
+                                if ui.selectable_label(false, name).clicked() {
+                                    chosen = Some(name.clone());
+                                }
+                            }
+                        })
+                        .response
+                        .on_hover_text("Switch the graphics settings to a saved profile");
+
+                    if let Some(name) = chosen {
+                        match apply_profile(&name, &mut options) {
+                            Ok(()) => toast.show(format!("Switched to the {name} profile")),
+                            Err(e) => toast.show(format!("Failed to read {} due to: {e}", profile_path(&name).display())),
+                        }
+                    }
+
+                    ui.add(
+                        egui::TextEdit::singleline(&mut profiles.new_name)
+                            .hint_text("Profile name")
+                            .desired_width(100.),
+                    );
+
+                    let name = profiles.new_name.trim().to_owned();
+                    let valid_name = !name.is_empty() && name.chars().all(|c| c.is_alphanumeric() || c == '-' || c == '_');
+                    if ui
+                        .add_enabled(valid_name, egui::Button::new("Save"))
+                        .on_hover_text("Save the current graphics settings as a profile, replacing it if it already exists")
+                        .clicked()
+                    {
+                        match save_profile(&name, &options) {
+                            Ok(()) => {
+                                toast.show(format!("Saved the {name} profile to {}", profile_path(&name).display()));
+                                profiles.new_name.clear();
+                                profiles.refresh();
+                            }
+                            Err(e) => toast.show(format!("Failed to save {} due to: {e}", profile_path(&name).display())),
+                        }
+                    }
+                });
+
                 ui.horizontal(|ui| {
                     ui.checkbox(&mut options.vsync, "vsync");
                     ui.checkbox(&mut options.uncap_fps, "Uncap FPS");
                     ui.add(egui::DragValue::new(&mut options.fps_limit).speed(5.).range(30..=600));
                 });
 
+                ui.horizontal(|ui| {
+                    egui::ComboBox::from_label("Window").width(150.).show_index(
+                        ui,
+                        &mut options.window_mode,
+                        WINDOW_MODE_NAMES.len(),
+                        |i| WINDOW_MODE_NAMES[i],
+                    );
+
+                    let selected = if options.window_monitor.is_empty() {
+                        "Primary monitor"
+                    } else {
+                        options.window_monitor.as_str()
+                    };
+
+                    egui::ComboBox::from_label("Monitor")
+                        .width(150.)
+                        .selected_text(selected.to_owned())
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(&mut options.window_monitor, String::new(), "Primary monitor");
+                            for name in monitors.iter().filter_map(|monitor| monitor.name.clone()) {
+                                ui.selectable_value(&mut options.window_monitor, name.clone(), name);
+                            }
+                        });
+                });
+
                 ui.horizontal(|ui| {
                     egui::ComboBox::from_label("Shadows").width(50.).show_index(
                         ui,
@@ -179,31 +407,155 @@ fn ui_system(
                         SHADOW_NAMES.len(),
                         |i| SHADOW_NAMES[i],
                     );
-                    #[cfg(not(feature = "ssao"))]
-                    egui::ComboBox::from_label("MSAA")
-                        .width(40.)
-                        .show_index(ui, &mut options.msaa, MSAA_NAMES.len(), |i| MSAA_NAMES[i]);
+                    ui.add_enabled_ui(options.ambient_occlusion == 0, |ui| {
+                        egui::ComboBox::from_label("MSAA").width(40.).show_index(
+                            ui,
+                            &mut options.msaa,
+                            MSAA_NAMES.len(),
+                            |i| MSAA_NAMES[i],
+                        );
+                    });
+                });
+
+                egui::ComboBox::from_label("Ambient occlusion")
+                    .width(70.)
+                    .show_index(ui, &mut options.ambient_occlusion, AMBIENT_OCCLUSION_NAMES.len(), |i| {
+                        AMBIENT_OCCLUSION_NAMES[i]
+                    })
+                    .on_hover_text("Darkens creases and corners, at the cost of performance. Turns off MSAA");
+
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut options.bloom, "Bloom")
+                        .on_hover_text("Makes boost pads, goals, and other glowing things light up their surroundings");
+                    ui.add_enabled_ui(options.bloom, |ui| {
+                        ui.add(egui::Slider::new(&mut options.bloom_intensity, 0.01..=0.5).text("Intensity"));
+                    });
+                });
+
+                egui::ComboBox::from_label("Boost trails").width(70.).show_index(
+                    ui,
+                    &mut options.boost_trail_quality,
+                    BOOST_TRAIL_NAMES.len(),
+                    |i| BOOST_TRAIL_NAMES[i],
+                );
+
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut options.show_ball_trail, "Ball trail")
+                        .on_hover_text("Colored by the team that touched the ball last");
+                    ui.add_enabled_ui(options.show_ball_trail, |ui| {
+                        ui.add(
+                            egui::Slider::new(&mut options.ball_trail_length, 0.2..=5.0)
+                                .text("Length")
+                                .suffix("s"),
+                        );
+                        ui.add(egui::Slider::new(&mut options.ball_trail_width, 10.0..=150.0).text("Width"));
+                    });
+                });
+
+                egui::ComboBox::from_label("Lighting")
+                    .width(100.)
+                    .show_index(ui, &mut options.lighting_preset, LIGHTING_NAMES.len(), |i| LIGHTING_NAMES[i])
+                    .on_hover_text("Night adds floodlights around the stadium");
+
+                egui::ComboBox::from_label("Field style")
+                    .width(100.)
+                    .show_index(ui, &mut options.field_style, FIELD_STYLE_NAMES.len(), |i| {
+                        FIELD_STYLE_NAMES[i]
+                    })
+                    .on_hover_text("Simpler looks for the field that are easier on the eyes and the GPU");
+
+                ui.horizontal(|ui| {
+                    if ui
+                        .button("Reload field")
+                        .on_hover_text("Load the field again, using the layout in the stadiums folder if there is one")
+                        .clicked()
+                    {
+                        reload_field.send(ReloadField);
+                    }
+
+                    if stadium_layout.changed {
+                        ui.label("Layout changed on disk");
+                    }
+                });
+
+                ui.horizontal(|ui| {
+                    egui::ComboBox::from_label("Transparent walls")
+                        .width(100.)
+                        .show_index(ui, &mut options.wall_transparency, WALL_TRANSPARENCY_NAMES.len(), |i| {
+                            WALL_TRANSPARENCY_NAMES[i]
+                        })
+                        .on_hover_text("Fade out the walls and ceiling of the arena so they don't hide the field");
+                    ui.add_enabled(
+                        options.wall_transparency != 0,
+                        egui::Slider::new(&mut options.wall_opacity, 0.0..=1.0).text("Opacity"),
+                    );
                 });
 
+                ui.checkbox(&mut options.show_crowd, "Show crowd")
+                    .on_hover_text("Fill the stands with an animated crowd, turn off for better performance");
+
                 egui::ComboBox::from_label("Packet smoothing").width(100.).show_index(
                     ui,
                     &mut options.packet_smoothing as &mut usize,
                     SMOOTHING_NAMES.len(),
                     |i| SMOOTHING_NAMES[i],
                 );
+                ui.add(
+                    egui::Slider::new(&mut options.render_delay, 0.0..=250.)
+                        .text("Render delay")
+                        .suffix(" ms")
+                        .fixed_decimals(0),
+                )
+                .on_hover_text("Hold back received game states to smooth out jittery connections, 0 to turn off");
                 ui.checkbox(&mut options.calc_ball_rot, "Ignore packet ball rotation");
+
+                #[cfg(feature = "sound")]
+                ui.horizontal(|ui| {
+                    ui.add_enabled(
+                        !options.mute,
+                        egui::Slider::new(&mut options.master_volume, 0.0..=1.0).text("Volume"),
+                    );
+                    ui.checkbox(&mut options.mute, "Mute");
+                });
+
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut options.blueprint, "Blueprint mode");
+                    ui.color_edit_button_rgb(&mut options.blueprint_color)
+                        .on_hover_text("Geometry color");
+                    ui.color_edit_button_rgb(&mut options.blueprint_background)
+                        .on_hover_text("Background color");
+                });
             });
 
             CollapsingHeader::new("World settings").default_open(true).show(ui, |ui| {
                 ui.horizontal(|ui| {
-                    ui.label("Game speed");
+                    // the speed and pause packets go to the replay's own clock instead of a simulation
+                    #[cfg(feature = "replay_import")]
+                    let is_replay = server_port.transport == Transport::ReplayFile;
+                    #[cfg(not(feature = "replay_import"))]
+                    let is_replay = false;
+
+                    if is_replay {
+                        ui.label("Playback speed")
+                            .on_hover_text("How fast the replay is played back, nothing is sent to a simulation");
+                    } else {
+                        ui.label("Game speed");
+                    }
+
                     ui.add(
-                        egui::DragValue::new(&mut options.game_speed)
-                            .range(0.1..=10.0)
-                            .speed(0.02)
-                            .fixed_decimals(1),
+                        egui::Slider::new(&mut options.game_speed, GameSpeed::MIN..=GameSpeed::MAX)
+                            .logarithmic(true)
+                            .max_decimals(2)
+                            .suffix("x"),
                     );
                     ui.checkbox(&mut options.paused, "Paused");
+                    if ui
+                        .add_enabled(options.paused, egui::Button::new("Step"))
+                        .on_hover_text("Show the next game state, one tick at a time")
+                        .clicked()
+                    {
+                        step_queue.step();
+                    }
                 });
 
                 ui.add_space(15.);
@@ -212,15 +564,367 @@ fn ui_system(
                     ui.checkbox(&mut options.show_time, "In-game time");
                     ui.checkbox(&mut options.ball_cam, "Ball cam");
                 });
+                ui.checkbox(&mut options.smooth_boost_meter, "Smooth boost meter");
+                ui.checkbox(&mut options.show_bounce_point, "Show ball bounce point")
+                    .on_hover_text("Mark where the ball will next touch the ground");
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut options.show_ball_path, "Show ball path")
+                        .on_hover_text("Draw a simple prediction of the ball's path to compare against your own");
+                    ui.add_enabled(
+                        options.show_ball_path,
+                        egui::Slider::new(&mut options.ball_path_seconds, 0.5..=6.0).suffix("s"),
+                    );
+                });
+                ui.checkbox(&mut options.show_ball_spin, "Show ball spin")
+                    .on_hover_text("Paint seams on the ball and draw an arrow along the axis it's spinning around");
+                ui.checkbox(&mut options.show_pad_cooldowns, "Show boost pad timers")
+                    .on_hover_text("Draw a shrinking ring above boost pads until they respawn");
+                ui.checkbox(&mut options.hide_demoed_cars, "Hide demolished cars");
+                ui.checkbox(&mut options.show_car_hitboxes, "Show car hitboxes")
+                    .on_hover_text("Draw cars as their collision hitbox instead of their body");
+                ui.horizontal(|ui| {
+                    ui.label("Car LOD distance:");
+                    ui.add(egui::Slider::new(&mut options.car_lod_distance, 0.0..=20000.0).step_by(500.))
+                        .on_hover_text("Draw cars further than this from the camera as their hitbox, 0 turns it off");
+                });
+                ui.checkbox(&mut options.show_car_names, "Show car names")
+                    .on_hover_text("Only cars that have been given a name by the simulation will have one");
+                ui.checkbox(&mut options.show_team_hud, "Show team HUD").on_hover_text(
+                    "List every car with its boost, supersonic state and demo timer on the side of the screen",
+                );
+                ui.checkbox(&mut options.show_input_viewer, "Show input viewer").on_hover_text(
+                    "Show the steering, throttle, boost, jump and handbrake inputs of the car the camera is following",
+                );
+                ui.checkbox(&mut options.show_message_feed, "Show bot messages")
+                    .on_hover_text("Show text events sent by the simulation, like what a bot is about to do");
+                ui.checkbox(&mut options.screenshot_on_goal, "Screenshot goals")
+                    .on_hover_text("Save a screenshot to the screenshots folder whenever a goal is scored");
+                ui.checkbox(&mut options.replay_on_goal, "Replay goals")
+                    .on_hover_text("Show the last few seconds before a goal again in slow motion");
+                ui.horizontal(|ui| {
+                    ui.label("Goal effects:");
+                    ui.checkbox(&mut options.goal_flash, "Flash")
+                        .on_hover_text("Light up the field in the scoring team's color");
+                    ui.checkbox(&mut options.goal_particles, "Particles");
+                    ui.checkbox(&mut options.goal_camera_shake, "Camera shake")
+                        .on_hover_text("Shake the camera, less the further it is from the ball while following a car");
+                    ui.checkbox(&mut options.goal_slow_motion, "Slow motion").on_hover_text(
+                        "Briefly slow down the simulation, unless goals are being replayed. Requires the simulation to support changing the game speed",
+                    );
+                });
+                ui.checkbox(&mut options.impact_camera_shake, "Impact camera shake").on_hover_text(
+                    "While following a car, shake the camera a little when a car nearby is demolished or the ball is hit hard",
+                );
                 ui.add(egui::Slider::new(&mut options.ui_scale, 0.4..=4.0).text("UI scale"));
                 ui.label("Mouse sensitivity:");
                 ui.add(egui::Slider::new(&mut options.mouse_sensitivity, 0.01..=4.0));
 
+                ui.checkbox(&mut options.spectator_collision, "Free camera collision")
+                    .on_hover_text("Stop the free camera from flying through the floor and walls");
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut options.spectator_follow_ball, "Follow ball")
+                        .on_hover_text("Loosely turn the free camera towards the ball and keep it within range");
+                    ui.add_enabled(
+                        options.spectator_follow_ball,
+                        egui::Slider::new(&mut options.spectator_follow_smoothing, 0.0..=3.0)
+                            .text("Smoothing")
+                            .suffix("s"),
+                    );
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Camera path:");
+
+                    let label = if camera_path.is_recording() { "Stop recording" } else { "Record" };
+                    if ui
+                        .button(label)
+                        .on_hover_text("Record the free camera as it moves, to play it back smoothly for cinematics")
+                        .clicked()
+                    {
+                        if camera_path.is_recording() {
+                            camera_path.stop();
+                        } else {
+                            camera_path.record();
+                            options.camera_state = PrimaryCamera::Spectator;
+                        }
+                    }
+
+                    let label = if camera_path.is_playing() { "Stop" } else { "Play" };
+                    if ui
+                        .add_enabled(
+                            camera_path.is_playable() && !camera_path.is_recording(),
+                            egui::Button::new(label),
+                        )
+                        .clicked()
+                    {
+                        if camera_path.is_playing() {
+                            camera_path.stop();
+                        } else if camera_path.play() {
+                            options.camera_state = PrimaryCamera::Spectator;
+                        }
+                    }
+
+                    if ui
+                        .add_enabled(camera_path.is_playable(), egui::Button::new("Save"))
+                        .on_hover_text(format!("Save the camera path to {CAMERA_PATH_FILE}"))
+                        .clicked()
+                    {
+                        if let Err(e) = camera_path.save() {
+                            error!("Failed to save {CAMERA_PATH_FILE} due to: {e}");
+                        }
+                    }
+
+                    if ui
+                        .button("Load")
+                        .on_hover_text(format!("Load the camera path from {CAMERA_PATH_FILE}"))
+                        .clicked()
+                    {
+                        if let Err(e) = camera_path.load() {
+                            error!("Failed to load {CAMERA_PATH_FILE} due to: {e}");
+                        }
+                    }
+
+                    if camera_path.is_playable() {
+                        ui.label(format!("{:.1}s", camera_path.duration()));
+                    }
+                });
+
                 ui.add_space(15.);
 
-                ui.checkbox(&mut options.stop_day, "Stop day cycle");
-                ui.add(egui::Slider::new(&mut options.daytime, 0.0..=150.0).text("Daytime"));
-                ui.add(egui::Slider::new(&mut options.day_speed, 0.0..=10.0).text("Day speed"));
+                ui.checkbox(&mut options.pov_camera, "Rocket League camera")
+                    .on_hover_text("Follow cars with the same camera settings as a player in Rocket League");
+                ui.add_enabled_ui(options.pov_camera, |ui| {
+                    ui.add(egui::Slider::new(&mut options.camera_fov, 60.0..=110.0).text("FOV"));
+                    ui.add(egui::Slider::new(&mut options.camera_distance, 100.0..=400.0).text("Distance"));
+                    ui.add(egui::Slider::new(&mut options.camera_height, 40.0..=200.0).text("Height"));
+                    ui.add(egui::Slider::new(&mut options.camera_angle, -15.0..=0.0).text("Angle"));
+                    ui.add(egui::Slider::new(&mut options.camera_stiffness, 0.0..=1.0).text("Stiffness"));
+                    ui.add(egui::Slider::new(&mut options.camera_swivel_speed, 1.0..=10.0).text("Swivel speed"));
+                });
+
+                ui.add(
+                    egui::Slider::new(&mut options.camera_transition_time, 0.0..=2.0)
+                        .text("Camera transitions")
+                        .suffix("s"),
+                )
+                .on_hover_text("How long the camera takes to glide over when it starts following something else, 0 to cut straight there");
+                ui.add_enabled(
+                    options.camera_transition_time > 0.,
+                    egui::Checkbox::new(&mut options.smooth_director_cuts, "Smooth Director cuts"),
+                )
+                .on_hover_text("Also glide over when the Director switches to another car");
+
+                ui.add_space(15.);
+
+                egui::ComboBox::from_label("Split screen")
+                    .width(100.)
+                    .show_index(ui, &mut options.split_screen, SPLIT_SCREEN_NAMES.len(), |i| {
+                        SPLIT_SCREEN_NAMES[i]
+                    })
+                    .on_hover_text("Show more cameras at once, the first viewport uses the current camera");
+                let num_views = SplitScreenLayout::from_usize(options.split_screen).num_viewports() - 1;
+                for (i, view) in options.split_screen_views.iter_mut().take(num_views).enumerate() {
+                    ui.horizontal(|ui| {
+                        ui.add(egui::DragValue::new(view).range(0..=8).custom_formatter(|n, _| {
+                            if n == 0. {
+                                String::from("Director")
+                            } else {
+                                format!("Car key {n}")
+                            }
+                        }));
+                        ui.label(format!("Viewport {}", i + 2));
+                    });
+                }
+
+                ui.add_space(15.);
+
+                ui.add_enabled_ui(options.lighting_preset == 0, |ui| {
+                    ui.checkbox(&mut options.stop_day, "Stop day cycle");
+                    ui.add(egui::Slider::new(&mut options.daytime, 0.0..=150.0).text("Daytime"));
+                    ui.add(egui::Slider::new(&mut options.day_speed, 0.0..=10.0).text("Day speed"));
+                })
+                .response
+                .on_disabled_hover_text("Only used by the day cycle lighting");
+
+                ui.add_space(15.);
+
+                ui.checkbox(&mut options.clamp_state_inputs, "Clamp state setting inputs")
+                    .on_hover_text("Limit entered positions, velocities, boost and timers to sensible ranges");
+                ui.horizontal(|ui| {
+                    ui.label("Nudge step");
+                    ui.add(
+                        egui::DragValue::new(&mut options.nudge_step)
+                            .speed(1.)
+                            .range(0.1..=1000.)
+                            .suffix(" uu"),
+                    )
+                    .on_hover_text(
+                        "How far the arrow keys and Page Up/Down move the ball or car whose info window is focused. Hold Shift for 10x, or Alt for 0.1x",
+                    );
+                });
+                if ui
+                    .button("Dump game state")
+                    .on_hover_text("Print the current game state as JSON and copy it to the clipboard")
+                    .clicked()
+                {
+                    dump_state.send(DumpGameState);
+                }
+
+                if ui
+                    .button("State snapshots")
+                    .on_hover_text("Save the current game state under a name and load it again later")
+                    .clicked()
+                {
+                    state_snapshots.toggle();
+                }
+
+                if ui
+                    .button("Snapshot diff")
+                    .on_hover_text("Compare two saved game states, or what changed in the last tick")
+                    .clicked()
+                {
+                    snapshot_diff.toggle();
+                }
+
+                if ui
+                    .button("Keybinds")
+                    .on_hover_text("Change the keys used for each action")
+                    .clicked()
+                {
+                    keybinds_menu.toggle();
+                }
+
+                if ui
+                    .button("Network stats")
+                    .on_hover_text("Show packet rates, loss and timing for the connection to the simulation")
+                    .clicked()
+                {
+                    network_stats.toggle();
+                }
+
+                if ui
+                    .button("Packet log")
+                    .on_hover_text("List the most recent packets from the simulation and save them as hex")
+                    .clicked()
+                {
+                    packet_log.toggle();
+                }
+
+                if ui
+                    .button("Telemetry")
+                    .on_hover_text("Export ball and car kinematics to CSV or JSON lines for offline analysis")
+                    .clicked()
+                {
+                    telemetry_menu.toggle();
+                }
+
+                if ui
+                    .button("Session stats")
+                    .on_hover_text("Count touches, goals, demos, boost used and time supersonic for each car")
+                    .clicked()
+                {
+                    session_stats.toggle();
+                }
+
+                if ui
+                    .button("Materials")
+                    .on_hover_text("Tweak the colors and lighting of the loaded materials, and save the changes to materials.toml")
+                    .clicked()
+                {
+                    material_editor.toggle();
+                }
+
+                ui.add_space(15.);
+
+                ui.horizontal(|ui| {
+                    let label = if recording.is_recording() {
+                        "Stop recording"
+                    } else {
+                        "Start recording"
+                    };
+
+                    if ui
+                        .button(label)
+                        .on_hover_text("Record the window to an MP4 if ffmpeg is installed, or to PNG frames otherwise")
+                        .clicked()
+                    {
+                        recording.toggle(&options.capture_path, options.capture_fps);
+                    }
+
+                    ui.add(egui::DragValue::new(&mut options.capture_fps).range(1..=240).suffix(" fps"));
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Save to");
+                    ui.add(egui::TextEdit::singleline(&mut options.capture_path).desired_width(150.));
+                });
+                ui.add(
+                    egui::Slider::new(&mut options.screenshot_scale, 2..=4)
+                        .text("Screenshot scale")
+                        .suffix("x"),
+                )
+                .on_hover_text("Screenshots taken with F12 render the 3D view at this many times the window's resolution");
+
+                ui.add_space(15.);
+
+                let mut recording = script_recorder.is_recording();
+                if ui
+                    .checkbox(&mut recording, "Record state setting")
+                    .on_hover_text("Save every state setting action to a script in the state_scripts folder")
+                    .changed()
+                {
+                    script_recorder.toggle();
+                }
+
+                ui.horizontal(|ui| {
+                    ui.add(egui::TextEdit::singleline(&mut script_playback.path).desired_width(150.));
+                    if ui
+                        .button("Run script")
+                        .on_hover_text("Replay a recorded state setting script")
+                        .clicked()
+                    {
+                        run_script.send(RunStateScript(script_playback.path.clone()));
+                    }
+                });
+            });
+
+            ui.collapsing("Physics debug", |ui| {
+                ui.checkbox(&mut options.show_hit_markers, "Show ball hits")
+                    .on_hover_text("Mark where on the ball each car last hit it");
+                ui.checkbox(&mut options.show_contact_normals, "Show contact normals")
+                    .on_hover_text("Draw an arrow along the surface normal under cars touching the world");
+                ui.checkbox(&mut options.flash_car_bumps, "Flash car bumps")
+                    .on_hover_text("Outline the hitboxes of both cars involved in a bump");
+                ui.checkbox(&mut options.show_field_bounds, "Show field bounds")
+                    .on_hover_text("Outline the walls, ceiling and center circle at their exact RocketSim coordinates");
+                ui.checkbox(&mut options.show_goal_lines, "Show goal lines")
+                    .on_hover_text("Outline the goal openings at their exact RocketSim coordinates");
+            });
+
+            ui.collapsing("Connection", |ui| {
+                let out_addr = server_port.out_addr();
+                ui.label(match server_port.transport {
+                    Transport::Udp => format!("Connected to {out_addr} over UDP"),
+                    Transport::Tcp => format!("Connected to {out_addr} over TCP"),
+                    #[cfg(feature = "rlbot")]
+                    Transport::RLBot => format!("Connected to {out_addr} over RLBot's socket"),
+                    #[cfg(feature = "replay_import")]
+                    Transport::ReplayFile => format!("Playing {}", server_port.replay_file.display()),
+                });
+
+                ui.horizontal(|ui| {
+                    ui.label("Host");
+                    ui.add(egui::TextEdit::singleline(&mut options.remote_host).desired_width(120.))
+                        .on_hover_text("IPv4 or IPv6 address of the simulation");
+                    ui.label("Port");
+                    ui.add(egui::DragValue::new(&mut server_port.primary_port));
+                });
+
+                ui.checkbox(&mut options.close_on_quit, "Close when the simulation quits")
+                    .on_hover_text("Otherwise, wait for the simulation to start again");
+
+                let valid_host = options.remote_host.parse::<IpAddr>().is_ok();
+                if ui.add_enabled(valid_host, egui::Button::new("Reconnect")).clicked() {
+                    reconnect.send(Reconnect);
+                }
             });
 
             ui.collapsing("Rendering manager", |ui| {
@@ -230,20 +934,212 @@ fn ui_system(
 
                 ui.label(format!("Groups: {}", render_info.groups));
                 ui.label(format!("Items: {}", render_info.items));
+
+                if !render_info.group_items.is_empty() {
+                    ui.add_space(10.);
+
+                    egui::Grid::new("render_groups").num_columns(5).striped(true).show(ui, |ui| {
+                        ui.label("Shown");
+                        ui.label("Group");
+                        ui.label("Name");
+                        ui.label("Items");
+                        ui.label("");
+                        ui.end_row();
+
+                        for &(id, items) in &render_info.group_items {
+                            let mut shown = !render_groups.hidden.contains(&id);
+                            if ui.checkbox(&mut shown, "").changed() {
+                                if shown {
+                                    render_groups.hidden.remove(&id);
+                                } else {
+                                    render_groups.hidden.insert(id);
+                                }
+                            }
+
+                            ui.label(id.to_string());
+                            ui.label(render_groups.names.get(&id).map_or("", String::as_str));
+                            ui.label(items.to_string());
+
+                            if render_groups.bounds(id).is_some() {
+                                if ui.button("Look").on_hover_text("Fly the free camera to where it can see the group").clicked() {
+                                    focus_group.send(FocusRenderGroup(id));
+                                }
+                            } else {
+                                ui.label("");
+                            }
+                            ui.end_row();
+                        }
+                    });
+                }
+
+                if ui
+                    .button("Clear all")
+                    .on_hover_text("Remove every group, the simulation will send the ones it still uses again")
+                    .clicked()
+                {
+                    render_groups.clear();
+                }
+            });
+        });
+}
+
+const UI_SCALE_STEP: f32 = 0.2;
+const TOAST_SECS: f32 = 1.5;
+
+/// A short message shown at the top of the screen, e.g. after changing a setting with a hotkey
+#[derive(Resource, Default)]
+pub struct Toast(Option<(String, Timer)>);
+
+impl Toast {
+    pub fn show(&mut self, message: String) {
+        self.0 = Some((message, Timer::from_seconds(TOAST_SECS, TimerMode::Once)));
+    }
+}
+
+fn show_toast(mut toast: ResMut<Toast>, time: Res<Time>, mut contexts: EguiContexts) {
+    let (message, timer) = toast.0.as_mut().unwrap();
+    if timer.tick(time.delta()).finished() {
+        toast.0 = None;
+        return;
+    }
+
+    egui::Area::new(egui::Id::new("toast"))
+        .anchor(egui::Align2::CENTER_TOP, [0., 40.])
+        .interactable(false)
+        .show(contexts.ctx_mut(), |ui| {
+            egui::Frame::popup(ui.style()).show(ui, |ui| {
+                ui.label(message.as_str());
+            });
+        });
+}
+
+/// How long the speed scrubber stays on screen after the speed is changed while the menu is closed
+const SPEED_SCRUBBER_SECS: f32 = 2.;
+
+/// A game speed slider at the bottom of the screen, which is always there while the menu is open
+fn speed_scrubber(
+    mut options: ResMut<Options>,
+    keybinds: Res<Keybinds>,
+    menu_focused: Res<MenuFocused>,
+    time: Res<Time>,
+    mut contexts: EguiContexts,
+    mut last_speed: Local<Option<f32>>,
+    mut shown_for: Local<f32>,
+) {
+    match *last_speed {
+        Some(speed) if speed == options.game_speed => *shown_for += time.delta_secs(),
+        Some(_) => *shown_for = 0.,
+        // don't pop up on startup
+        None => *shown_for = SPEED_SCRUBBER_SECS,
+    }
+
+    *last_speed = Some(options.game_speed);
+
+    if !menu_focused.0 && *shown_for >= SPEED_SCRUBBER_SECS {
+        return;
+    }
+
+    let mut speed = options.game_speed;
+    egui::Area::new(egui::Id::new("speed_scrubber"))
+        .anchor(egui::Align2::CENTER_BOTTOM, [0., -20.])
+        .interactable(menu_focused.0)
+        .show(contexts.ctx_mut(), |ui| {
+            egui::Frame::popup(ui.style()).show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    if ui.button("1x").on_hover_text("Reset the game speed").clicked() {
+                        speed = 1.;
+                    }
+
+                    ui.add(
+                        egui::Slider::new(&mut speed, GameSpeed::MIN..=GameSpeed::MAX)
+                            .logarithmic(true)
+                            .max_decimals(2)
+                            .suffix("x")
+                            .text("Game speed"),
+                    )
+                    .on_hover_ui(|ui| {
+                        let modifier = keybinds.label(Action::SpeedScrubModifier).unwrap_or_default();
+                        ui.label(format!(
+                            "{modifier} + scroll while the menu is closed to scrub through the speed"
+                        ));
+                    });
+                });
+            });
+        });
+
+    // only touch the options if the speed was actually changed, so they aren't saved every frame
+    if speed != options.game_speed {
+        options.game_speed = speed;
+    }
+}
+
+fn waiting_for_simulator_overlay(port: Res<ServerPort>, mut contexts: EguiContexts) {
+    egui::Area::new(egui::Id::new("waiting_for_simulator"))
+        .anchor(egui::Align2::CENTER_CENTER, [0., 0.])
+        .interactable(false)
+        .show(contexts.ctx_mut(), |ui| {
+            egui::Frame::popup(ui.style()).show(ui, |ui| {
+                ui.heading(format!("Waiting for simulator at {}…", port.out_addr()));
             });
         });
 }
 
+fn protocol_mismatch_dialog(mismatch: Res<ProtocolMismatch>, mut contexts: EguiContexts, mut dismissed: Local<Option<u32>>) {
+    if *dismissed == mismatch.0 {
+        return;
+    }
+
+    let version = mismatch.0.unwrap();
+
+    egui::Window::new("Protocol version mismatch")
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, [0., 0.])
+        .show(contexts.ctx_mut(), |ui| {
+            ui.colored_label(
+                egui::Color32::RED,
+                format!(
+                    "The simulation uses protocol version {version}, but rlviser only supports version {PROTOCOL_VERSION}."
+                ),
+            );
+            ui.label("Game states, renders and car names will be ignored until rlviser or the simulation is updated.");
+
+            if ui.button("Dismiss").clicked() {
+                *dismissed = mismatch.0;
+            }
+        });
+}
+
+fn dump_game_state(mut events: EventReader<DumpGameState>, game_states: Res<GameStates>, mut contexts: EguiContexts) {
+    events.clear();
+
+    match serde_json::to_string_pretty(&game_states.current) {
+        Ok(json) => {
+            println!("{json}");
+            contexts.ctx_mut().copy_text(json);
+        }
+        Err(e) => error!("Failed to serialize game state due to: {e}"),
+    }
+}
+
 fn update_allow_rendering(options: Res<Options>, mut do_rendering: ResMut<DoRendering>, mut renders: ResMut<RenderGroups>) {
     if !options.allow_rendering {
-        renders.groups.clear();
+        renders.clear();
     }
 
     do_rendering.0 = options.allow_rendering;
 }
 
+fn update_camera_transition(options: Res<Options>, mut transition: ResMut<CameraTransition>) {
+    transition.duration = options.camera_transition_time;
+    transition.smooth_cuts = options.smooth_director_cuts;
+}
+
 fn update_sensitivity(options: Res<Options>, mut settings: ResMut<SpectatorSettings>) {
     settings.sensitivity = SpectatorSettings::default().sensitivity * options.mouse_sensitivity;
+    settings.collision = options.spectator_collision;
+    settings.follow_ball = options.spectator_follow_ball;
+    settings.follow_smoothing = options.spectator_follow_smoothing;
 }
 
 fn read_speed_update_event(
@@ -272,20 +1168,27 @@ fn update_packet_smoothing(options: Res<Options>, mut packet_smoothing: ResMut<P
     *packet_smoothing = PacketSmoothing::from_usize(options.packet_smoothing);
 }
 
+fn update_render_delay(options: Res<Options>, mut render_delay: ResMut<RenderDelay>) {
+    render_delay.0 = options.render_delay / 1000.;
+}
+
+fn update_boost_trail_quality(options: Res<Options>, mut boost_trail_quality: ResMut<BoostTrailQuality>) {
+    *boost_trail_quality = BoostTrailQuality::from_usize(options.boost_trail_quality);
+}
+
 fn update_speed(
     options: Res<Options>,
     socket: Res<Connection>,
     mut last_packet_send: ResMut<PacketSendTime>,
     mut last_packet_times: ResMut<LastPacketTimesElapsed>,
-    time: Res<Time>,
     mut global: ResMut<GameSpeed>,
 ) {
-    last_packet_send.0.tick(time.delta());
+    // scrubbing changes the speed every frame, so only the latest speed is sent a few times a second
     if last_packet_send.0.elapsed() < Duration::from_secs_f32(1. / 15.) {
-        last_packet_send.0.reset();
         return;
     }
 
+    last_packet_send.0.reset();
     last_packet_times.reset();
     socket.send(SendableUdp::Speed(options.game_speed)).unwrap();
     global.speed = options.game_speed;
@@ -324,20 +1227,221 @@ fn toggle_vsync(options: Res<Options>, mut framepace: ResMut<FramepaceSettings>)
     };
 }
 
+fn update_field_style(options: Res<Options>, mut field_style: ResMut<FieldStyle>) {
+    *field_style = FieldStyle::from_usize(options.field_style);
+}
+
+fn update_lighting_preset(options: Res<Options>, mut lighting_preset: ResMut<LightingPreset>) {
+    // only mark it as changed when it actually changes, the lights are only updated then
+    lighting_preset.set_if_neq(LightingPreset::from_usize(options.lighting_preset));
+}
+
+fn update_split_screen(options: Res<Options>, mut split_screen: ResMut<SplitScreen>) {
+    split_screen.layout = SplitScreenLayout::from_usize(options.split_screen);
+    split_screen.views = options.split_screen_views;
+}
+
 fn update_calc_ball_rot(options: Res<Options>, mut calc_ball_rot: ResMut<CalcBallRot>) {
     calc_ball_rot.0 = options.calc_ball_rot;
 }
 
-#[cfg(not(feature = "ssao"))]
+fn update_smooth_boost_meter(options: Res<Options>, mut smooth_boost_meter: ResMut<SmoothBoostMeter>) {
+    smooth_boost_meter.0 = options.smooth_boost_meter;
+}
+
+fn update_blueprint(options: Res<Options>, mut blueprint: ResMut<Blueprint>) {
+    blueprint.enabled = options.blueprint;
+    blueprint.color = options.blueprint_color;
+    blueprint.background = options.blueprint_background;
+}
+
+fn update_clamp_state_inputs(options: Res<Options>, mut clamp_state_inputs: ResMut<ClampStateInputs>) {
+    clamp_state_inputs.0 = options.clamp_state_inputs;
+}
+
+fn update_nudge_step(options: Res<Options>, mut nudge_step: ResMut<NudgeStep>) {
+    nudge_step.0 = options.nudge_step;
+}
+
+fn update_show_bounce_point(options: Res<Options>, mut show_bounce_point: ResMut<ShowBouncePoint>) {
+    show_bounce_point.0 = options.show_bounce_point;
+}
+
+fn update_show_pad_cooldowns(options: Res<Options>, mut show_pad_cooldowns: ResMut<ShowPadCooldowns>) {
+    show_pad_cooldowns.0 = options.show_pad_cooldowns;
+}
+
+fn update_show_ball_spin(options: Res<Options>, mut show_ball_spin: ResMut<ShowBallSpin>) {
+    // the seam's visibility is only updated when this changes
+    show_ball_spin.set_if_neq(ShowBallSpin(options.show_ball_spin));
+}
+
+fn update_show_ball_path(options: Res<Options>, mut show_ball_path: ResMut<ShowBallPath>) {
+    show_ball_path.enabled = options.show_ball_path;
+    show_ball_path.seconds = options.ball_path_seconds;
+}
+
+#[cfg(feature = "sound")]
+fn update_volume(options: Res<Options>, mut global_volume: ResMut<GlobalVolume>) {
+    // only affects sounds that start playing afterwards, but they're all short anyways
+    global_volume.volume = Volume::new(if options.mute { 0. } else { options.master_volume });
+}
+
+fn update_wall_transparency(options: Res<Options>, mut wall_transparency: ResMut<WallTransparency>) {
+    let mode = WallTransparencyMode::from_usize(options.wall_transparency);
+    if wall_transparency.mode != mode || wall_transparency.opacity != options.wall_opacity {
+        wall_transparency.mode = mode;
+        wall_transparency.opacity = options.wall_opacity;
+    }
+}
+
+/// Moves the window between monitors and in or out of fullscreen, and gives it the size it had last time on startup
+fn update_window_mode(
+    options: Res<Options>,
+    monitors: Query<(Entity, &Monitor)>,
+    mut windows: Query<&mut Window, With<PrimaryWindow>>,
+    mut last_monitor: Local<Option<String>>,
+) {
+    let Ok(mut window) = windows.get_single_mut() else {
+        return;
+    };
+
+    // an unplugged monitor falls back to the primary one
+    let monitor = monitors
+        .iter()
+        .find(|(_, monitor)| monitor.name.as_ref() == Some(&options.window_monitor))
+        .map_or(MonitorSelection::Primary, |(entity, _)| MonitorSelection::Entity(entity));
+
+    let mode = match options.window_mode {
+        1 => WindowMode::BorderlessFullscreen(monitor),
+        2 => WindowMode::Fullscreen(monitor),
+        _ => WindowMode::Windowed,
+    };
+
+    if last_monitor.is_none() {
+        window.resolution.set(options.window_width, options.window_height);
+    }
+
+    if window.mode != mode {
+        window.mode = mode;
+    }
+
+    if last_monitor.as_ref() != Some(&options.window_monitor) {
+        if last_monitor.is_some() && mode == WindowMode::Windowed {
+            window.position = WindowPosition::Centered(monitor);
+        }
+
+        *last_monitor = Some(options.window_monitor.clone());
+    }
+}
+
+/// Remembers the size of the window so it can be restored next time, but not while it's fullscreen
+fn save_window_size(windows: Query<&Window, With<PrimaryWindow>>, mut options: ResMut<Options>) {
+    let Ok(window) = windows.get_single() else {
+        return;
+    };
+
+    if window.mode != WindowMode::Windowed {
+        return;
+    }
+
+    let (width, height) = (window.width(), window.height());
+    if options.window_width != width || options.window_height != height {
+        options.window_width = width;
+        options.window_height = height;
+    }
+}
+
+fn update_show_crowd(options: Res<Options>, mut show_crowd: ResMut<ShowCrowd>) {
+    // the crowd is rebuilt whenever this changes
+    if show_crowd.0 != options.show_crowd {
+        show_crowd.0 = options.show_crowd;
+    }
+}
+
+fn update_hide_demoed_cars(options: Res<Options>, mut hide_demoed_cars: ResMut<HideDemoedCars>) {
+    hide_demoed_cars.0 = options.hide_demoed_cars;
+}
+
+fn update_screenshot_on_goal(options: Res<Options>, mut screenshot_on_goal: ResMut<ScreenshotOnGoal>) {
+    screenshot_on_goal.0 = options.screenshot_on_goal;
+}
+
+fn update_show_car_hitboxes(options: Res<Options>, mut show_car_hitboxes: ResMut<ShowCarHitboxes>) {
+    show_car_hitboxes.0 = options.show_car_hitboxes;
+}
+
+fn update_car_lod_distance(options: Res<Options>, mut car_lod_distance: ResMut<CarLodDistance>) {
+    car_lod_distance.0 = options.car_lod_distance;
+}
+
+fn update_show_car_names(options: Res<Options>, mut show_car_names: ResMut<ShowCarNames>) {
+    show_car_names.0 = options.show_car_names;
+}
+
+fn update_physics_debug(options: Res<Options>, mut physics_debug: ResMut<PhysicsDebug>) {
+    physics_debug.hit_markers = options.show_hit_markers;
+    physics_debug.contact_normals = options.show_contact_normals;
+    physics_debug.bump_flashes = options.flash_car_bumps;
+    physics_debug.field_bounds = options.show_field_bounds;
+    physics_debug.goal_lines = options.show_goal_lines;
+}
+
+fn update_ball_trail(options: Res<Options>, mut ball_trail: ResMut<BallTrail>) {
+    ball_trail.enabled = options.show_ball_trail;
+    ball_trail.length = options.ball_trail_length;
+    ball_trail.width = options.ball_trail_width;
+}
+
+fn update_goal_explosion(options: Res<Options>, mut goal_explosion: ResMut<GoalExplosion>) {
+    goal_explosion.flash = options.goal_flash;
+    goal_explosion.particles = options.goal_particles;
+    goal_explosion.camera_shake = options.goal_camera_shake;
+    goal_explosion.slow_motion = options.goal_slow_motion;
+}
+
+fn update_impact_camera_shake(options: Res<Options>, mut impact_camera_shake: ResMut<ImpactCameraShake>) {
+    impact_camera_shake.0 = options.impact_camera_shake;
+}
+
+fn update_show_team_hud(options: Res<Options>, mut show_team_hud: ResMut<ShowTeamHud>) {
+    show_team_hud.0 = options.show_team_hud;
+}
+
+fn update_show_input_viewer(options: Res<Options>, mut show_input_viewer: ResMut<ShowInputViewer>) {
+    show_input_viewer.0 = options.show_input_viewer;
+}
+
+fn update_show_message_feed(options: Res<Options>, mut show_message_feed: ResMut<ShowMessageFeed>) {
+    show_message_feed.0 = options.show_message_feed;
+}
+
+fn update_replay_on_goal(options: Res<Options>, mut replay_on_goal: ResMut<ReplayOnGoal>) {
+    replay_on_goal.0 = options.replay_on_goal;
+}
+
+fn update_pov_camera(options: Res<Options>, mut pov_camera: ResMut<PovCamera>) {
+    pov_camera.enabled = options.pov_camera;
+    pov_camera.fov = options.camera_fov;
+    pov_camera.distance = options.camera_distance;
+    pov_camera.height = options.camera_height;
+    pov_camera.angle = options.camera_angle;
+    pov_camera.stiffness = options.camera_stiffness;
+    pov_camera.swivel_speed = options.camera_swivel_speed;
+}
+
 fn update_msaa(options: Res<Options>, mut msaa_query: Query<&mut Msaa>) {
     const MSAA_SAMPLES: [u32; 4] = [1, 2, 4, 8];
 
+    // ambient occlusion and the temporal anti-aliasing that goes with it don't work with MSAA
+    let samples = if options.ambient_occlusion == 0 { options.msaa } else { 0 };
+
     for mut msaa in msaa_query.iter_mut() {
-        if MSAA_SAMPLES[options.msaa] == msaa.samples() {
+        if MSAA_SAMPLES[samples] == msaa.samples() {
             continue;
         }
 
-        *msaa = match options.msaa {
+        *msaa = match samples {
             0 => Msaa::Off,
             1 => Msaa::Sample2,
             2 => Msaa::Sample4,
@@ -347,6 +1451,83 @@ fn update_msaa(options: Res<Options>, mut msaa_query: Query<&mut Msaa>) {
     }
 }
 
+fn update_ambient_occlusion(
+    options: Res<Options>,
+    mut commands: Commands,
+    mut cameras: Query<(Entity, Option<&mut ScreenSpaceAmbientOcclusion>), With<Camera3d>>,
+) {
+    let quality_level = match options.ambient_occlusion {
+        0 => None,
+        1 => Some(ScreenSpaceAmbientOcclusionQualityLevel::Low),
+        2 => Some(ScreenSpaceAmbientOcclusionQualityLevel::Medium),
+        3 => Some(ScreenSpaceAmbientOcclusionQualityLevel::High),
+        4 => Some(ScreenSpaceAmbientOcclusionQualityLevel::Ultra),
+        _ => unreachable!(),
+    };
+
+    for (entity, ssao) in &mut cameras {
+        match (quality_level, ssao) {
+            (Some(quality_level), Some(mut ssao)) => {
+                if ssao.quality_level != quality_level {
+                    ssao.quality_level = quality_level;
+                }
+            }
+            (Some(quality_level), None) => {
+                // temporal anti-aliasing cleans up the noise that ambient occlusion leaves behind
+                commands.entity(entity).insert((
+                    ScreenSpaceAmbientOcclusion {
+                        quality_level,
+                        ..default()
+                    },
+                    TemporalAntiAliasing::default(),
+                    ShadowFilteringMethod::Temporal,
+                ));
+            }
+            (None, Some(_)) => {
+                // also remove the prepasses they needed, they're not free
+                commands
+                    .entity(entity)
+                    .remove::<(
+                        ScreenSpaceAmbientOcclusion,
+                        TemporalAntiAliasing,
+                        TemporalJitter,
+                        DepthPrepass,
+                        NormalPrepass,
+                        MotionVectorPrepass,
+                    )>()
+                    .insert(ShadowFilteringMethod::Gaussian);
+            }
+            (None, None) => {}
+        }
+    }
+}
+
+fn update_bloom(
+    options: Res<Options>,
+    mut commands: Commands,
+    mut cameras: Query<(Entity, Option<&mut Bloom>), With<Camera3d>>,
+) {
+    for (entity, bloom) in &mut cameras {
+        match (options.bloom, bloom) {
+            (true, Some(mut bloom)) => {
+                if bloom.intensity != options.bloom_intensity {
+                    bloom.intensity = options.bloom_intensity;
+                }
+            }
+            (true, None) => {
+                commands.entity(entity).insert(Bloom {
+                    intensity: options.bloom_intensity,
+                    ..default()
+                });
+            }
+            (false, Some(_)) => {
+                commands.entity(entity).remove::<Bloom>();
+            }
+            (false, None) => {}
+        }
+    }
+}
+
 fn toggle_show_time(options: Res<Options>, mut show_time: ResMut<ShowTime>) {
     show_time.enabled = options.show_time;
 }
@@ -365,11 +1546,13 @@ fn update_daytime(options: Res<Options>, mut daytime: ResMut<DaylightOffset>) {
     daytime.day_speed = options.day_speed;
 }
 
-fn write_settings_to_file(
+/// Saves the settings when they change, and applies changes made to the file by anything else, like a script
+fn sync_settings_file(
     time: Res<Time>,
-    options: Res<Options>,
+    mut options: ResMut<Options>,
     mut last_options: Local<Options>,
     mut last_time: Local<f32>,
+    mut last_modified: Local<Option<SystemTime>>,
 ) {
     // ensure the time difference is > 1 second
     let secs = time.elapsed_secs_wrapped();
@@ -379,12 +1562,33 @@ fn write_settings_to_file(
 
     *last_time = secs;
 
+    let modified = Options::file_modified();
+    if last_modified.is_some() && modified != *last_modified {
+        *last_modified = modified;
+
+        match Options::read_from_file() {
+            Ok(new_options) => {
+                info!("Reloaded {} after it was changed", Options::path().display());
+                *last_options = new_options.clone();
+                *options = new_options;
+            }
+            Err(e) => error!("Failed to reload {} due to: {e}", Options::path().display()),
+        }
+
+        return;
+    }
+
+    *last_modified = modified;
+
     if options.is_not_similar(&last_options) {
         *last_options = options.clone();
 
         if let Err(e) = options.write_options_to_file() {
             error!("Failed to write settings to file due to: {e}");
         }
+
+        // don't reload the settings that were just written
+        *last_modified = Options::file_modified();
     }
 }
 
@@ -402,11 +1606,16 @@ fn listen(
     mut windows: Query<&mut Window, With<PrimaryWindow>>,
     mut picking_state: ResMut<PickingPlugin>,
     key: Res<ButtonInput<KeyCode>>,
+    keybinds: Res<Keybinds>,
     mut menu_focused: ResMut<MenuFocused>,
     mut last_focus: Local<bool>,
     mut options: ResMut<Options>,
+    mut toast: ResMut<Toast>,
+    mut last_shadows: Local<usize>,
+    (car_slots, car_names): (Res<CarSlots>, Res<CarNames>),
+    (mut flight, cameras): (ResMut<BookmarkFlight>, Query<&Transform, With<Spectator>>),
 ) {
-    if key.just_pressed(KeyCode::Escape) {
+    if keybinds.just_pressed(&key, Action::ToggleMenu) {
         menu_focused.0 = !menu_focused.0;
     }
 
@@ -430,25 +1639,79 @@ fn listen(
         return;
     }
 
-    if key.just_pressed(KeyCode::Digit1) || key.just_pressed(KeyCode::Numpad1) {
-        options.camera_state = PrimaryCamera::TrackCar(1);
-    } else if key.just_pressed(KeyCode::Digit2) || key.just_pressed(KeyCode::Numpad2) {
-        options.camera_state = PrimaryCamera::TrackCar(2);
-    } else if key.just_pressed(KeyCode::Digit3) || key.just_pressed(KeyCode::Numpad3) {
-        options.camera_state = PrimaryCamera::TrackCar(3);
-    } else if key.just_pressed(KeyCode::Digit4) || key.just_pressed(KeyCode::Numpad4) {
-        options.camera_state = PrimaryCamera::TrackCar(4);
-    } else if key.just_pressed(KeyCode::Digit5) || key.just_pressed(KeyCode::Numpad5) {
-        options.camera_state = PrimaryCamera::TrackCar(5);
-    } else if key.just_pressed(KeyCode::Digit6) || key.just_pressed(KeyCode::Numpad2) {
-        options.camera_state = PrimaryCamera::TrackCar(6);
-    } else if key.just_pressed(KeyCode::Digit7) || key.just_pressed(KeyCode::Numpad7) {
-        options.camera_state = PrimaryCamera::TrackCar(7);
-    } else if key.just_pressed(KeyCode::Digit8) || key.just_pressed(KeyCode::Numpad8) {
-        options.camera_state = PrimaryCamera::TrackCar(8);
-    } else if key.just_pressed(KeyCode::Digit9) || key.just_pressed(KeyCode::Numpad9) {
+    let scale_change = if keybinds.just_pressed(&key, Action::IncreaseUiScale) {
+        UI_SCALE_STEP
+    } else if keybinds.just_pressed(&key, Action::DecreaseUiScale) {
+        -UI_SCALE_STEP
+    } else {
+        0.
+    };
+
+    if scale_change != 0. {
+        options.ui_scale = (options.ui_scale + scale_change).clamp(0.4, 4.);
+        toast.show(format!("UI scale: {:.1}", options.ui_scale));
+    }
+
+    if keybinds.just_pressed(&key, Action::ToggleShadows) {
+        if options.shadows == 0 {
+            // default to 1x if shadows have never been on
+            options.shadows = if *last_shadows == 0 { 2 } else { *last_shadows };
+            toast.show(String::from("Shadows on"));
+        } else {
+            *last_shadows = options.shadows;
+            options.shadows = 0;
+            toast.show(String::from("Shadows off"));
+        }
+    }
+
+    if keybinds.just_pressed(&key, Action::OrbitCamera) {
+        // cycle from the ball through each of the cars, in the order of their car keys
+        let ids = car_slots.car_ids().collect::<Vec<_>>();
+        let next = match options.camera_state {
+            PrimaryCamera::Orbit(0) => ids.first(),
+            PrimaryCamera::Orbit(id) => ids.iter().position(|&i| i == id).and_then(|i| ids.get(i + 1)),
+            _ => None,
+        };
+        let target = next.copied().unwrap_or_default();
+
+        options.camera_state = PrimaryCamera::Orbit(target);
+        toast.show(match car_names.0.get(&target) {
+            _ if target == 0 => String::from("Orbiting the ball"),
+            Some(name) => format!("Orbiting {name}"),
+            None => format!("Orbiting car {target}"),
+        });
+    } else if let Some(slot) = Action::TRACK_CARS
+        .into_iter()
+        .position(|action| keybinds.just_pressed(&key, action))
+    {
+        // in the free camera, the car keys fly to the bookmark in that slot if there is one
+        match options.camera_bookmarks[slot] {
+            Some(bookmark) if options.camera_state == PrimaryCamera::Spectator => {
+                if let Ok(camera) = cameras.get_single() {
+                    flight.start(camera, bookmark);
+                    toast.show(format!("Camera bookmark {}", slot + 1));
+                }
+            }
+            _ => match car_slots.car_id(slot) {
+                Some(id) => options.camera_state = PrimaryCamera::TrackCar(id),
+                None => toast.show(format!("There's no car on key {}", slot + 1)),
+            },
+        }
+    } else if keybinds.just_pressed(&key, Action::DirectorCamera) {
         options.camera_state = PrimaryCamera::Director(0);
-    } else if key.just_pressed(KeyCode::Digit0) || key.just_pressed(KeyCode::Numpad0) {
+    } else if keybinds.just_pressed(&key, Action::FreeCamera) {
         options.camera_state = PrimaryCamera::Spectator;
     }
+
+    if let Some(slot) = Action::SAVE_BOOKMARKS
+        .into_iter()
+        .position(|action| keybinds.just_pressed(&key, action))
+    {
+        if options.camera_state != PrimaryCamera::Spectator {
+            toast.show(String::from("Switch to the free camera to save a bookmark"));
+        } else if let Ok(camera) = cameras.get_single() {
+            options.camera_bookmarks[slot] = Some(camera.into());
+            toast.show(format!("Saved camera bookmark {}", slot + 1));
+        }
+    }
 }
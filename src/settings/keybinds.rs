@@ -0,0 +1,745 @@
+use super::options::MenuFocused;
+use bevy::{prelude::*, utils::HashMap};
+use bevy_egui::{egui, EguiContexts};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    io::{self, Write},
+};
+
+pub struct KeybindsPlugin;
+
+impl Plugin for KeybindsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Keybinds::default_read_file())
+            .insert_resource(EnableKeybindsMenu::default())
+            .add_systems(
+                Update,
+                (
+                    capture_rebind,
+                    update_keybinds_menu
+                        .run_if(resource_equals(EnableKeybindsMenu(true)))
+                        .run_if(resource_equals(MenuFocused::default())),
+                )
+                    .chain(),
+            );
+    }
+}
+
+/// Everything that can be done from the keyboard
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Action {
+    ToggleMenu,
+    MoveForward,
+    MoveBackward,
+    MoveLeft,
+    MoveRight,
+    MoveUp,
+    MoveDown,
+    MoveSlow,
+    TrackCar1,
+    TrackCar2,
+    TrackCar3,
+    TrackCar4,
+    TrackCar5,
+    TrackCar6,
+    TrackCar7,
+    TrackCar8,
+    DirectorCamera,
+    FreeCamera,
+    OrbitCamera,
+    ShootBall,
+    TogglePause,
+    StepFrame,
+    DumpGameState,
+    IncreaseSpeed,
+    DecreaseSpeed,
+    ResetSpeed,
+    IncreaseUiScale,
+    DecreaseUiScale,
+    ToggleShadows,
+    StepWheels,
+    InstantReplay,
+    ToggleRecording,
+    SaveBookmark1,
+    SaveBookmark2,
+    SaveBookmark3,
+    SaveBookmark4,
+    SaveBookmark5,
+    SaveBookmark6,
+    SaveBookmark7,
+    SaveBookmark8,
+    RecordCameraPath,
+    PlayCameraPath,
+    ScrubBack,
+    ScrubForward,
+    FocusRenderGroup,
+    BulletTime,
+    Screenshot,
+    NudgeLeft,
+    NudgeRight,
+    NudgeForward,
+    NudgeBackward,
+    NudgeUp,
+    NudgeDown,
+    NudgeCoarse,
+    NudgeFine,
+    SpeedScrubModifier,
+}
+
+impl Action {
+    pub const ALL: [Self; 56] = [
+        Self::ToggleMenu,
+        Self::MoveForward,
+        Self::MoveBackward,
+        Self::MoveLeft,
+        Self::MoveRight,
+        Self::MoveUp,
+        Self::MoveDown,
+        Self::MoveSlow,
+        Self::TrackCar1,
+        Self::TrackCar2,
+        Self::TrackCar3,
+        Self::TrackCar4,
+        Self::TrackCar5,
+        Self::TrackCar6,
+        Self::TrackCar7,
+        Self::TrackCar8,
+        Self::DirectorCamera,
+        Self::FreeCamera,
+        Self::OrbitCamera,
+        Self::ShootBall,
+        Self::TogglePause,
+        Self::StepFrame,
+        Self::DumpGameState,
+        Self::IncreaseSpeed,
+        Self::DecreaseSpeed,
+        Self::ResetSpeed,
+        Self::IncreaseUiScale,
+        Self::DecreaseUiScale,
+        Self::ToggleShadows,
+        Self::StepWheels,
+        Self::InstantReplay,
+        Self::ToggleRecording,
+        Self::SaveBookmark1,
+        Self::SaveBookmark2,
+        Self::SaveBookmark3,
+        Self::SaveBookmark4,
+        Self::SaveBookmark5,
+        Self::SaveBookmark6,
+        Self::SaveBookmark7,
+        Self::SaveBookmark8,
+        Self::RecordCameraPath,
+        Self::PlayCameraPath,
+        Self::ScrubBack,
+        Self::ScrubForward,
+        Self::FocusRenderGroup,
+        Self::BulletTime,
+        Self::Screenshot,
+        Self::NudgeLeft,
+        Self::NudgeRight,
+        Self::NudgeForward,
+        Self::NudgeBackward,
+        Self::NudgeUp,
+        Self::NudgeDown,
+        Self::NudgeCoarse,
+        Self::NudgeFine,
+        Self::SpeedScrubModifier,
+    ];
+
+    /// The track car actions, in order of the car they track
+    pub const TRACK_CARS: [Self; 8] = [
+        Self::TrackCar1,
+        Self::TrackCar2,
+        Self::TrackCar3,
+        Self::TrackCar4,
+        Self::TrackCar5,
+        Self::TrackCar6,
+        Self::TrackCar7,
+        Self::TrackCar8,
+    ];
+
+    /// The save bookmark actions, in order of the slot they save to
+    pub const SAVE_BOOKMARKS: [Self; 8] = [
+        Self::SaveBookmark1,
+        Self::SaveBookmark2,
+        Self::SaveBookmark3,
+        Self::SaveBookmark4,
+        Self::SaveBookmark5,
+        Self::SaveBookmark6,
+        Self::SaveBookmark7,
+        Self::SaveBookmark8,
+    ];
+
+    /// Whether holding a key bound to this action moves the spectator camera
+    const fn moves_camera(self) -> bool {
+        matches!(
+            self,
+            Self::MoveForward | Self::MoveBackward | Self::MoveLeft | Self::MoveRight | Self::MoveUp | Self::MoveDown
+        )
+    }
+
+    /// Whether the action only changes how other actions behave while it's held,
+    /// so it can share a key with other actions like it
+    const fn only_modifies(self) -> bool {
+        matches!(
+            self,
+            Self::MoveSlow | Self::NudgeCoarse | Self::NudgeFine | Self::SpeedScrubModifier
+        )
+    }
+
+    /// The name used in the keybinds file
+    const fn name(self) -> &'static str {
+        match self {
+            Self::ToggleMenu => "toggle_menu",
+            Self::MoveForward => "move_forward",
+            Self::MoveBackward => "move_backward",
+            Self::MoveLeft => "move_left",
+            Self::MoveRight => "move_right",
+            Self::MoveUp => "move_up",
+            Self::MoveDown => "move_down",
+            Self::MoveSlow => "move_slow",
+            Self::TrackCar1 => "track_car_1",
+            Self::TrackCar2 => "track_car_2",
+            Self::TrackCar3 => "track_car_3",
+            Self::TrackCar4 => "track_car_4",
+            Self::TrackCar5 => "track_car_5",
+            Self::TrackCar6 => "track_car_6",
+            Self::TrackCar7 => "track_car_7",
+            Self::TrackCar8 => "track_car_8",
+            Self::DirectorCamera => "director_camera",
+            Self::FreeCamera => "free_camera",
+            Self::OrbitCamera => "orbit_camera",
+            Self::ShootBall => "shoot_ball",
+            Self::TogglePause => "toggle_pause",
+            Self::StepFrame => "step_frame",
+            Self::DumpGameState => "dump_game_state",
+            Self::IncreaseSpeed => "increase_speed",
+            Self::DecreaseSpeed => "decrease_speed",
+            Self::ResetSpeed => "reset_speed",
+            Self::IncreaseUiScale => "increase_ui_scale",
+            Self::DecreaseUiScale => "decrease_ui_scale",
+            Self::ToggleShadows => "toggle_shadows",
+            Self::StepWheels => "step_wheels",
+            Self::InstantReplay => "instant_replay",
+            Self::ToggleRecording => "toggle_recording",
+            Self::SaveBookmark1 => "save_bookmark_1",
+            Self::SaveBookmark2 => "save_bookmark_2",
+            Self::SaveBookmark3 => "save_bookmark_3",
+            Self::SaveBookmark4 => "save_bookmark_4",
+            Self::SaveBookmark5 => "save_bookmark_5",
+            Self::SaveBookmark6 => "save_bookmark_6",
+            Self::SaveBookmark7 => "save_bookmark_7",
+            Self::SaveBookmark8 => "save_bookmark_8",
+            Self::RecordCameraPath => "record_camera_path",
+            Self::PlayCameraPath => "play_camera_path",
+            Self::ScrubBack => "scrub_back",
+            Self::ScrubForward => "scrub_forward",
+            Self::FocusRenderGroup => "focus_render_group",
+            Self::BulletTime => "bullet_time",
+            Self::Screenshot => "screenshot",
+            Self::NudgeLeft => "nudge_left",
+            Self::NudgeRight => "nudge_right",
+            Self::NudgeForward => "nudge_forward",
+            Self::NudgeBackward => "nudge_backward",
+            Self::NudgeUp => "nudge_up",
+            Self::NudgeDown => "nudge_down",
+            Self::NudgeCoarse => "nudge_coarse",
+            Self::NudgeFine => "nudge_fine",
+            Self::SpeedScrubModifier => "speed_scrub_modifier",
+        }
+    }
+
+    const fn label(self) -> &'static str {
+        match self {
+            Self::ToggleMenu => "Toggle menu",
+            Self::MoveForward => "Move forward",
+            Self::MoveBackward => "Move backward",
+            Self::MoveLeft => "Move left",
+            Self::MoveRight => "Move right",
+            Self::MoveUp => "Move up",
+            Self::MoveDown => "Move down",
+            Self::MoveSlow => "Move slowly",
+            Self::TrackCar1 => "Track car 1",
+            Self::TrackCar2 => "Track car 2",
+            Self::TrackCar3 => "Track car 3",
+            Self::TrackCar4 => "Track car 4",
+            Self::TrackCar5 => "Track car 5",
+            Self::TrackCar6 => "Track car 6",
+            Self::TrackCar7 => "Track car 7",
+            Self::TrackCar8 => "Track car 8",
+            Self::DirectorCamera => "Director camera",
+            Self::FreeCamera => "Free camera",
+            Self::OrbitCamera => "Orbit camera",
+            Self::ShootBall => "State set ball towards goal",
+            Self::TogglePause => "Toggle pause/play",
+            Self::StepFrame => "Step one tick while paused",
+            Self::DumpGameState => "Dump game state",
+            Self::IncreaseSpeed => "Increase game speed",
+            Self::DecreaseSpeed => "Decrease game speed",
+            Self::ResetSpeed => "Reset game speed",
+            Self::IncreaseUiScale => "Increase UI scale",
+            Self::DecreaseUiScale => "Decrease UI scale",
+            Self::ToggleShadows => "Toggle shadows",
+            Self::StepWheels => "Step wheels one tick per frame",
+            Self::InstantReplay => "Instant replay",
+            Self::ToggleRecording => "Start/stop recording",
+            Self::SaveBookmark1 => "Save camera bookmark 1",
+            Self::SaveBookmark2 => "Save camera bookmark 2",
+            Self::SaveBookmark3 => "Save camera bookmark 3",
+            Self::SaveBookmark4 => "Save camera bookmark 4",
+            Self::SaveBookmark5 => "Save camera bookmark 5",
+            Self::SaveBookmark6 => "Save camera bookmark 6",
+            Self::SaveBookmark7 => "Save camera bookmark 7",
+            Self::SaveBookmark8 => "Save camera bookmark 8",
+            Self::RecordCameraPath => "Start/stop recording a camera path",
+            Self::PlayCameraPath => "Play/stop the camera path",
+            Self::ScrubBack => "Go back one tick on the timeline while paused",
+            Self::ScrubForward => "Go forward one tick on the timeline while paused",
+            Self::FocusRenderGroup => "Look at the newest 3D render group",
+            Self::BulletTime => "Toggle bullet time",
+            Self::Screenshot => "Take a supersampled screenshot",
+            Self::NudgeLeft => "Nudge the focused ball or car along -X",
+            Self::NudgeRight => "Nudge the focused ball or car along +X",
+            Self::NudgeForward => "Nudge the focused ball or car along +Y",
+            Self::NudgeBackward => "Nudge the focused ball or car along -Y",
+            Self::NudgeUp => "Nudge the focused ball or car along +Z",
+            Self::NudgeDown => "Nudge the focused ball or car along -Z",
+            Self::NudgeCoarse => "Nudge 10x as far",
+            Self::NudgeFine => "Nudge 0.1x as far",
+            Self::SpeedScrubModifier => "Hold to scrub the game speed with the scroll wheel",
+        }
+    }
+
+    fn default_binds(self) -> Vec<KeyBind> {
+        let digit = |digit, numpad| vec![KeyBind::new(digit), KeyBind::new(numpad)];
+        let alt_digit = |digit, numpad| vec![KeyBind::alt(digit), KeyBind::alt(numpad)];
+
+        match self {
+            Self::ToggleMenu => vec![KeyBind::new(KeyCode::Escape)],
+            Self::MoveForward => vec![KeyBind::new(KeyCode::KeyW)],
+            Self::MoveBackward => vec![KeyBind::new(KeyCode::KeyS)],
+            Self::MoveLeft => vec![KeyBind::new(KeyCode::KeyA)],
+            Self::MoveRight => vec![KeyBind::new(KeyCode::KeyD)],
+            Self::MoveUp => vec![KeyBind::new(KeyCode::Space)],
+            Self::MoveDown => vec![KeyBind::new(KeyCode::ControlLeft)],
+            Self::MoveSlow => vec![KeyBind::new(KeyCode::ShiftLeft)],
+            Self::TrackCar1 => digit(KeyCode::Digit1, KeyCode::Numpad1),
+            Self::TrackCar2 => digit(KeyCode::Digit2, KeyCode::Numpad2),
+            Self::TrackCar3 => digit(KeyCode::Digit3, KeyCode::Numpad3),
+            Self::TrackCar4 => digit(KeyCode::Digit4, KeyCode::Numpad4),
+            Self::TrackCar5 => digit(KeyCode::Digit5, KeyCode::Numpad5),
+            Self::TrackCar6 => digit(KeyCode::Digit6, KeyCode::Numpad6),
+            Self::TrackCar7 => digit(KeyCode::Digit7, KeyCode::Numpad7),
+            Self::TrackCar8 => digit(KeyCode::Digit8, KeyCode::Numpad8),
+            Self::DirectorCamera => digit(KeyCode::Digit9, KeyCode::Numpad9),
+            Self::FreeCamera => digit(KeyCode::Digit0, KeyCode::Numpad0),
+            Self::OrbitCamera => vec![KeyBind::new(KeyCode::KeyO)],
+            Self::ShootBall => vec![KeyBind::new(KeyCode::KeyR)],
+            Self::TogglePause => vec![KeyBind::new(KeyCode::KeyP)],
+            Self::StepFrame => vec![KeyBind::new(KeyCode::KeyN)],
+            Self::DumpGameState => vec![KeyBind::new(KeyCode::KeyJ)],
+            Self::IncreaseSpeed => vec![KeyBind::new(KeyCode::NumpadAdd), KeyBind::shift(KeyCode::Equal)],
+            Self::DecreaseSpeed => vec![KeyBind::new(KeyCode::NumpadSubtract), KeyBind::new(KeyCode::Minus)],
+            Self::ResetSpeed => vec![KeyBind::new(KeyCode::NumpadEqual), KeyBind::new(KeyCode::Equal)],
+            Self::IncreaseUiScale => vec![KeyBind::alt(KeyCode::NumpadAdd), KeyBind::alt(KeyCode::Equal)],
+            Self::DecreaseUiScale => vec![KeyBind::alt(KeyCode::NumpadSubtract), KeyBind::alt(KeyCode::Minus)],
+            Self::ToggleShadows => vec![KeyBind::new(KeyCode::KeyK)],
+            Self::StepWheels => vec![KeyBind::new(KeyCode::KeyI)],
+            Self::InstantReplay => vec![KeyBind::new(KeyCode::KeyG)],
+            Self::ToggleRecording => vec![KeyBind::new(KeyCode::F9)],
+            Self::SaveBookmark1 => alt_digit(KeyCode::Digit1, KeyCode::Numpad1),
+            Self::SaveBookmark2 => alt_digit(KeyCode::Digit2, KeyCode::Numpad2),
+            Self::SaveBookmark3 => alt_digit(KeyCode::Digit3, KeyCode::Numpad3),
+            Self::SaveBookmark4 => alt_digit(KeyCode::Digit4, KeyCode::Numpad4),
+            Self::SaveBookmark5 => alt_digit(KeyCode::Digit5, KeyCode::Numpad5),
+            Self::SaveBookmark6 => alt_digit(KeyCode::Digit6, KeyCode::Numpad6),
+            Self::SaveBookmark7 => alt_digit(KeyCode::Digit7, KeyCode::Numpad7),
+            Self::SaveBookmark8 => alt_digit(KeyCode::Digit8, KeyCode::Numpad8),
+            Self::RecordCameraPath => vec![KeyBind::new(KeyCode::F7)],
+            Self::PlayCameraPath => vec![KeyBind::new(KeyCode::F8)],
+            Self::ScrubBack => vec![KeyBind::new(KeyCode::Comma)],
+            Self::ScrubForward => vec![KeyBind::new(KeyCode::Period)],
+            Self::FocusRenderGroup => vec![KeyBind::new(KeyCode::KeyF)],
+            Self::BulletTime => vec![KeyBind::new(KeyCode::KeyB)],
+            Self::Screenshot => vec![KeyBind::new(KeyCode::F12)],
+            Self::NudgeLeft => vec![KeyBind::new(KeyCode::ArrowLeft)],
+            Self::NudgeRight => vec![KeyBind::new(KeyCode::ArrowRight)],
+            Self::NudgeForward => vec![KeyBind::new(KeyCode::ArrowUp)],
+            Self::NudgeBackward => vec![KeyBind::new(KeyCode::ArrowDown)],
+            Self::NudgeUp => vec![KeyBind::new(KeyCode::PageUp)],
+            Self::NudgeDown => vec![KeyBind::new(KeyCode::PageDown)],
+            Self::NudgeCoarse => vec![KeyBind::new(KeyCode::ShiftLeft), KeyBind::new(KeyCode::ShiftRight)],
+            Self::NudgeFine => vec![KeyBind::new(KeyCode::AltLeft), KeyBind::new(KeyCode::AltRight)],
+            Self::SpeedScrubModifier => vec![KeyBind::new(KeyCode::AltLeft), KeyBind::new(KeyCode::AltRight)],
+        }
+    }
+}
+
+/// A key, plus the modifiers that have to be held with it
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct KeyBind {
+    key: KeyCode,
+    #[serde(default, skip_serializing_if = "is_false")]
+    shift: bool,
+    #[serde(default, skip_serializing_if = "is_false")]
+    ctrl: bool,
+    #[serde(default, skip_serializing_if = "is_false")]
+    alt: bool,
+}
+
+#[allow(clippy::trivially_copy_pass_by_ref)]
+const fn is_false(value: &bool) -> bool {
+    !*value
+}
+
+const SHIFT_KEYS: [KeyCode; 2] = [KeyCode::ShiftLeft, KeyCode::ShiftRight];
+const CTRL_KEYS: [KeyCode; 2] = [KeyCode::ControlLeft, KeyCode::ControlRight];
+const ALT_KEYS: [KeyCode; 2] = [KeyCode::AltLeft, KeyCode::AltRight];
+
+impl KeyBind {
+    const fn new(key: KeyCode) -> Self {
+        Self {
+            key,
+            shift: false,
+            ctrl: false,
+            alt: false,
+        }
+    }
+
+    const fn shift(key: KeyCode) -> Self {
+        Self {
+            shift: true,
+            ..Self::new(key)
+        }
+    }
+
+    const fn alt(key: KeyCode) -> Self {
+        Self {
+            alt: true,
+            ..Self::new(key)
+        }
+    }
+
+    fn is_modifier(key: KeyCode) -> bool {
+        SHIFT_KEYS.contains(&key) || CTRL_KEYS.contains(&key) || ALT_KEYS.contains(&key)
+    }
+
+    /// Whether the key was just pressed while holding at least the modifiers of this bind
+    ///
+    /// A modifier bound on its own fires no matter what else is held
+    fn just_pressed(self, keys: &ButtonInput<KeyCode>) -> bool {
+        keys.just_pressed(self.key)
+            && (Self::is_modifier(self.key)
+                || ((!self.shift || keys.any_pressed(SHIFT_KEYS))
+                    && (!self.ctrl || keys.any_pressed(CTRL_KEYS))
+                    && (!self.alt || keys.any_pressed(ALT_KEYS))))
+    }
+
+    /// How many modifiers have to be held with the key
+    fn modifiers(self) -> u8 {
+        u8::from(self.shift) + u8::from(self.ctrl) + u8::from(self.alt)
+    }
+
+    /// Whether the key has to be held as one of the modifiers of this bind
+    fn needs_held(self, key: KeyCode) -> bool {
+        (self.shift && SHIFT_KEYS.contains(&key))
+            || (self.ctrl && CTRL_KEYS.contains(&key))
+            || (self.alt && ALT_KEYS.contains(&key))
+    }
+
+    fn label(self) -> String {
+        let key = format!("{:?}", self.key);
+        let key = key.strip_prefix("Key").or_else(|| key.strip_prefix("Digit")).unwrap_or(&key);
+
+        let mut label = String::new();
+        for (held, modifier) in [(self.ctrl, "Ctrl+"), (self.alt, "Alt+"), (self.shift, "Shift+")] {
+            if held {
+                label.push_str(modifier);
+            }
+        }
+
+        label.push_str(key);
+        label
+    }
+}
+
+#[derive(Resource)]
+pub struct Keybinds {
+    binds: HashMap<Action, Vec<KeyBind>>,
+    /// The action that the next key press will be bound to
+    rebinding: Option<Action>,
+    /// Set on the frame a key is rebound so that the key press isn't also treated as an action
+    just_rebound: bool,
+}
+
+impl Default for Keybinds {
+    fn default() -> Self {
+        Self {
+            binds: Action::ALL
+                .into_iter()
+                .map(|action| (action, action.default_binds()))
+                .collect(),
+            rebinding: None,
+            just_rebound: false,
+        }
+    }
+}
+
+impl Keybinds {
+    const FILE_NAME: &'static str = "keybinds.txt";
+
+    #[inline]
+    fn default_read_file() -> Self {
+        match Self::read_from_file() {
+            Ok(keybinds) => keybinds,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Self::default(),
+            Err(e) => {
+                error!("Failed to read {} due to: {e}", Self::FILE_NAME);
+                Self::default()
+            }
+        }
+    }
+
+    fn read_from_file() -> io::Result<Self> {
+        fs::read_to_string(Self::FILE_NAME).map(|file| Self::parse(&file))
+    }
+
+    /// Reads `name=binds` lines, where the binds are a JSON list, keeping the default binds of anything that's missing or invalid
+    fn parse(file: &str) -> Self {
+        let mut keybinds = Self::default();
+
+        for line in file.lines() {
+            let Some((name, binds)) = line.split_once('=') else {
+                continue;
+            };
+
+            let Some(action) = Action::ALL.into_iter().find(|action| action.name() == name) else {
+                warn!("Unknown action {name} in {}", Self::FILE_NAME);
+                continue;
+            };
+
+            match serde_json::from_str(binds) {
+                Ok(binds) => {
+                    keybinds.binds.insert(action, binds);
+                }
+                Err(e) => warn!("Invalid keybinds for {name} in {} due to: {e}", Self::FILE_NAME),
+            }
+        }
+
+        keybinds
+    }
+
+    fn write_to_file(&self) -> io::Result<()> {
+        let mut file = fs::File::create(Self::FILE_NAME)?;
+
+        for action in Action::ALL {
+            file.write_fmt(format_args!(
+                "{}={}\n",
+                action.name(),
+                serde_json::to_string(self.binds(action))?
+            ))?;
+        }
+
+        Ok(())
+    }
+
+    fn binds(&self, action: Action) -> &[KeyBind] {
+        self.binds.get(&action).map_or(&[], Vec::as_slice)
+    }
+
+    #[inline]
+    const fn is_capturing(&self) -> bool {
+        self.rebinding.is_some() || self.just_rebound
+    }
+
+    /// Whether any of the keys bound to the action were just pressed, with their modifiers
+    ///
+    /// If another action has a bind on the same key that needs more of the held modifiers,
+    /// only that more specific bind fires, so `Ctrl+1` doesn't also trigger `1`
+    pub fn just_pressed(&self, keys: &ButtonInput<KeyCode>, action: Action) -> bool {
+        !self.is_capturing()
+            && self.binds(action).iter().any(|&bind| {
+                bind.just_pressed(keys)
+                    && !self.binds.values().flatten().any(|&other| {
+                        other.key == bind.key && other.modifiers() > bind.modifiers() && other.just_pressed(keys)
+                    })
+            })
+    }
+
+    /// Whether any of the keys bound to the action are being held down, ignoring modifiers
+    pub fn pressed(&self, keys: &ButtonInput<KeyCode>, action: Action) -> bool {
+        !self.is_capturing() && keys.any_pressed(self.binds(action).iter().map(|bind| bind.key))
+    }
+
+    /// Another action that can't be used alongside this one, either because they share a bind
+    /// or because one of them moves the camera with a key the other needs as a modifier
+    fn clash(&self, action: Action) -> Option<Action> {
+        Action::ALL.into_iter().filter(|&other| other != action).find(|&other| {
+            self.binds(action).iter().any(|&bind| {
+                self.binds(other).iter().any(|&other_bind| {
+                    (bind == other_bind && !(action.only_modifies() && other.only_modifies()))
+                        || (other.moves_camera() && bind.needs_held(other_bind.key))
+                        || (action.moves_camera() && other_bind.needs_held(bind.key))
+                })
+            })
+        })
+    }
+
+    /// The first key bound to the action, as it's shown in the keybinds menu
+    pub fn label(&self, action: Action) -> Option<String> {
+        self.binds(action).first().map(|bind| bind.label())
+    }
+}
+
+#[derive(Resource, Default, PartialEq, Eq)]
+pub struct EnableKeybindsMenu(bool);
+
+impl EnableKeybindsMenu {
+    pub fn toggle(&mut self) {
+        self.0 = !self.0;
+    }
+}
+
+fn capture_rebind(keys: Res<ButtonInput<KeyCode>>, mut keybinds: ResMut<Keybinds>) {
+    if keybinds.just_rebound {
+        keybinds.just_rebound = false;
+    }
+
+    let Some(action) = keybinds.rebinding else {
+        return;
+    };
+
+    let Some(&key) = keys.get_just_pressed().next() else {
+        return;
+    };
+
+    // modifiers can be bound on their own, but otherwise they're part of the bind
+    let bind = if KeyBind::is_modifier(key) {
+        KeyBind::new(key)
+    } else {
+        KeyBind {
+            key,
+            shift: keys.any_pressed(SHIFT_KEYS),
+            ctrl: keys.any_pressed(CTRL_KEYS),
+            alt: keys.any_pressed(ALT_KEYS),
+        }
+    };
+
+    keybinds.binds.insert(action, vec![bind]);
+    keybinds.rebinding = None;
+    keybinds.just_rebound = true;
+
+    if let Err(e) = keybinds.write_to_file() {
+        error!("Failed to write {} due to: {e}", Keybinds::FILE_NAME);
+    }
+}
+
+fn update_keybinds_menu(
+    mut contexts: EguiContexts,
+    mut enable_menu: ResMut<EnableKeybindsMenu>,
+    mut keybinds: ResMut<Keybinds>,
+) {
+    egui::Window::new("Keybinds")
+        .open(&mut enable_menu.0)
+        .show(contexts.ctx_mut(), |ui| {
+            ui.label("Click on an action, then press the key to bind to it");
+
+            egui::Grid::new("keybinds").striped(true).show(ui, |ui| {
+                for action in Action::ALL {
+                    ui.label(action.label());
+
+                    let text = if keybinds.rebinding == Some(action) {
+                        String::from("Press a key...")
+                    } else {
+                        keybinds
+                            .binds(action)
+                            .iter()
+                            .map(|bind| bind.label())
+                            .collect::<Vec<_>>()
+                            .join(" / ")
+                    };
+
+                    let button = match keybinds.clash(action) {
+                        Some(other) => ui
+                            .add(egui::Button::new(egui::RichText::new(text).color(ui.visuals().warn_fg_color)))
+                            .on_hover_text(format!("Clashes with \"{}\"", other.label())),
+                        None => ui.button(text),
+                    };
+
+                    if button.clicked() {
+                        keybinds.rebinding = Some(action);
+                    }
+
+                    ui.end_row();
+                }
+            });
+
+            if ui.button("Reset to defaults").clicked() {
+                *keybinds = Keybinds::default();
+
+                if let Err(e) = keybinds.write_to_file() {
+                    error!("Failed to write {} due to: {e}", Keybinds::FILE_NAME);
+                }
+            }
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_keybind_lines() {
+        let keybinds = Keybinds::parse(
+            "toggle_pause=[{\"key\":\"KeyP\"},{\"key\":\"Space\",\"shift\":true}]\n\
+             speed_scrub_modifier=[{\"key\":\"ControlLeft\"}]\n",
+        );
+
+        assert_eq!(
+            keybinds.binds(Action::TogglePause),
+            [KeyBind::new(KeyCode::KeyP), KeyBind::shift(KeyCode::Space)]
+        );
+        assert_eq!(
+            keybinds.binds(Action::SpeedScrubModifier),
+            [KeyBind::new(KeyCode::ControlLeft)]
+        );
+        // actions that aren't in the file keep their defaults
+        assert_eq!(keybinds.binds(Action::StepFrame), Action::StepFrame.default_binds());
+    }
+
+    #[test]
+    fn skip_invalid_keybind_lines() {
+        let keybinds = Keybinds::parse(
+            "not_an_action=[{\"key\":\"KeyP\"}]\n\
+             toggle_pause=[{\"key\":\"NotAKey\"}]\n\
+             step_frame\n\
+             dump_game_state=[]\n",
+        );
+
+        assert_eq!(keybinds.binds(Action::TogglePause), Action::TogglePause.default_binds());
+        assert_eq!(keybinds.binds(Action::StepFrame), Action::StepFrame.default_binds());
+        // an empty list unbinds the action
+        assert!(keybinds.binds(Action::DumpGameState).is_empty());
+    }
+
+    #[test]
+    fn keybind_lines_round_trip() {
+        let defaults = Keybinds::default();
+        let file = Action::ALL
+            .into_iter()
+            .map(|action| {
+                format!(
+                    "{}={}\n",
+                    action.name(),
+                    serde_json::to_string(defaults.binds(action)).unwrap()
+                )
+            })
+            .collect::<String>();
+        let keybinds = Keybinds::parse(&file);
+
+        for action in Action::ALL {
+            assert_eq!(keybinds.binds(action), defaults.binds(action), "{action:?}");
+        }
+    }
+}
@@ -0,0 +1,254 @@
+use super::options::MenuFocused;
+use crate::{assets::loaded_materials, rocketsim::Team};
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fs, io, sync::Mutex};
+
+pub struct MaterialEditorPlugin;
+
+impl Plugin for MaterialEditorPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(EnableMaterialEditor::default())
+            .insert_resource(MaterialEditor::default())
+            .add_systems(
+                Update,
+                update_material_editor
+                    .run_if(resource_equals(EnableMaterialEditor(true)))
+                    .run_if(resource_equals(MenuFocused::default())),
+            );
+    }
+}
+
+pub const MATERIALS_FILE: &str = "materials.toml";
+
+/// Changes to a material's look, saved to `materials.toml` and applied when the material is loaded
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct MaterialOverride {
+    /// sRGB with alpha
+    pub base_color: [u8; 4],
+    pub roughness: f32,
+    pub reflectance: f32,
+    /// Linear RGB, which can go above 1 to make it glow brighter
+    pub emissive: [f32; 3],
+}
+
+impl From<&StandardMaterial> for MaterialOverride {
+    fn from(material: &StandardMaterial) -> Self {
+        Self {
+            base_color: material.base_color.to_srgba().to_u8_array(),
+            roughness: material.perceptual_roughness,
+            reflectance: material.reflectance,
+            emissive: material.emissive.to_f32_array_no_alpha(),
+        }
+    }
+}
+
+impl MaterialOverride {
+    pub fn apply(&self, material: &mut StandardMaterial) {
+        let [r, g, b, a] = self.base_color;
+        material.base_color = Color::srgba_u8(r, g, b, a);
+        material.perceptual_roughness = self.roughness;
+        material.reflectance = self.reflectance;
+        material.emissive = LinearRgba::from_f32_array_no_alpha(self.emissive);
+    }
+}
+
+/// The name a material is saved under in `materials.toml`, with the team for materials that are colored by side
+pub fn override_key(name: &str, side: Option<Team>) -> String {
+    match side {
+        None => name.to_string(),
+        Some(Team::Blue) => format!("{name}:blue"),
+        Some(Team::Orange) => format!("{name}:orange"),
+    }
+}
+
+fn read_overrides() -> io::Result<BTreeMap<String, MaterialOverride>> {
+    let file = fs::read_to_string(MATERIALS_FILE)?;
+    toml::from_str(&file).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+fn write_overrides(overrides: &BTreeMap<String, MaterialOverride>) -> io::Result<()> {
+    let file = toml::to_string(overrides).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    fs::write(MATERIALS_FILE, file)
+}
+
+static OVERRIDES: Mutex<Option<BTreeMap<String, MaterialOverride>>> = Mutex::new(None);
+
+/// Runs `f` with the overrides saved in `materials.toml`, which is only read the first time they're needed
+fn with_saved_overrides<T>(f: impl FnOnce(&mut BTreeMap<String, MaterialOverride>) -> T) -> T {
+    let mut overrides_lock = OVERRIDES.lock().unwrap();
+    f(overrides_lock.get_or_insert_with(|| match read_overrides() {
+        Ok(overrides) => overrides,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => BTreeMap::new(),
+        Err(e) => {
+            error!("Failed to read {MATERIALS_FILE} due to: {e}");
+            BTreeMap::new()
+        }
+    }))
+}
+
+/// The saved changes to a material, if there are any
+pub fn material_override(name: &str, side: Option<Team>) -> Option<MaterialOverride> {
+    with_saved_overrides(|overrides| overrides.get(&override_key(name, side)).copied())
+}
+
+#[derive(Resource, Default, PartialEq, Eq)]
+pub struct EnableMaterialEditor(bool);
+
+impl EnableMaterialEditor {
+    pub fn toggle(&mut self) {
+        self.0 = !self.0;
+    }
+}
+
+#[derive(Resource, Default)]
+struct MaterialEditor {
+    filter: String,
+    /// The saved overrides and any changes made since, copied when the window is first opened
+    overrides: Option<BTreeMap<String, MaterialOverride>>,
+    unsaved: bool,
+    error: Option<String>,
+}
+
+impl MaterialEditor {
+    fn save(&mut self) {
+        let overrides = self.overrides.get_or_insert_with(BTreeMap::new);
+
+        match write_overrides(overrides) {
+            Ok(()) => {
+                info!("Saved material overrides to {MATERIALS_FILE}");
+                with_saved_overrides(|saved| saved.clone_from(overrides));
+                self.unsaved = false;
+                self.error = None;
+            }
+            Err(e) => self.error = Some(format!("Failed to write {MATERIALS_FILE} due to: {e}")),
+        }
+    }
+}
+
+fn update_material_editor(
+    mut contexts: EguiContexts,
+    mut enable_menu: ResMut<EnableMaterialEditor>,
+    mut editor: ResMut<MaterialEditor>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let editor = &mut *editor;
+
+    let overrides = editor
+        .overrides
+        .get_or_insert_with(|| with_saved_overrides(|overrides| overrides.clone()));
+
+    let mut save = false;
+
+    egui::Window::new("Materials")
+        .open(&mut enable_menu.0)
+        .show(contexts.ctx_mut(), |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Filter: ");
+                ui.add(egui::TextEdit::singleline(&mut editor.filter).desired_width(200.));
+            });
+
+            ui.separator();
+
+            let filter = editor.filter.to_lowercase();
+            let mut loaded = loaded_materials();
+            loaded.retain(|(key, _)| key.to_lowercase().contains(&filter));
+
+            if loaded.is_empty() {
+                ui.label("No loaded materials");
+            }
+
+            egui::ScrollArea::vertical().max_height(400.).show(ui, |ui| {
+                for (key, handle) in loaded {
+                    let Some(material) = materials.get(&handle) else {
+                        continue;
+                    };
+
+                    let original = MaterialOverride::from(material);
+                    let mut edited = original;
+
+                    let overridden = overrides.contains_key(&key);
+                    let title = if overridden { format!("{key} *") } else { key.clone() };
+
+                    egui::CollapsingHeader::new(title).id_salt(&key).show(ui, |ui| {
+                        egui::Grid::new(&key).num_columns(2).show(ui, |ui| {
+                            ui.label("Base color");
+                            ui.color_edit_button_srgba_unmultiplied(&mut edited.base_color);
+                            ui.end_row();
+
+                            ui.label("Roughness");
+                            ui.add(egui::Slider::new(&mut edited.roughness, 0.089..=1.));
+                            ui.end_row();
+
+                            ui.label("Reflectance");
+                            ui.add(egui::Slider::new(&mut edited.reflectance, 0.0..=1.));
+                            ui.end_row();
+
+                            // the color picker only goes up to 1, so anything brighter is split into a strength
+                            let mut strength = edited.emissive.into_iter().fold(1., f32::max);
+                            let mut emissive = edited.emissive.map(|c| c / strength);
+
+                            ui.label("Emissive");
+                            let emissive_changed = ui
+                                .horizontal(|ui| {
+                                    let picked = ui.color_edit_button_rgb(&mut emissive);
+                                    let dragged =
+                                        ui.add(egui::DragValue::new(&mut strength).speed(0.1).range(1.0..=100.).prefix("x"));
+                                    picked.changed() || dragged.changed()
+                                })
+                                .inner;
+                            ui.end_row();
+
+                            if emissive_changed {
+                                edited.emissive = emissive.map(|c| c * strength);
+                            }
+                        });
+
+                        if overridden
+                            && ui
+                                .button("Remove override")
+                                .on_hover_text("Go back to the default look the next time the material is loaded")
+                                .clicked()
+                        {
+                            overrides.remove(&key);
+                            editor.unsaved = true;
+                        }
+                    });
+
+                    if edited != original {
+                        if let Some(material) = materials.get_mut(&handle) {
+                            edited.apply(material);
+                        }
+
+                        overrides.insert(key, edited);
+                        editor.unsaved = true;
+                    }
+                }
+            });
+
+            ui.separator();
+
+            if let Some(error) = &editor.error {
+                ui.colored_label(egui::Color32::RED, error);
+            }
+
+            ui.horizontal(|ui| {
+                if ui
+                    .add_enabled(editor.unsaved, egui::Button::new("Save overrides"))
+                    .on_hover_text(format!(
+                        "Save the changed materials to {MATERIALS_FILE}, so they're used the next time they're loaded"
+                    ))
+                    .clicked()
+                {
+                    save = true;
+                }
+
+                ui.label(format!("{} overridden", overrides.len()));
+            });
+        });
+
+    if save {
+        editor.save();
+    }
+}
@@ -1,4 +1,16 @@
 pub mod cache_handler;
+pub mod car_bodies;
+pub mod field_inspector;
 pub mod gui;
+pub mod keybinds;
+pub mod material_editor;
+pub mod network_stats;
 pub mod options;
+pub mod packet_log;
+pub mod paused_systems;
+pub mod profiles;
+pub mod session_stats;
+pub mod snapshot_diff;
+pub mod state_script;
 pub mod state_setting;
+pub mod telemetry;
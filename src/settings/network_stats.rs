@@ -0,0 +1,91 @@
+use super::options::MenuFocused;
+use crate::udp::{NetworkStats, RecvError};
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+
+pub struct NetworkStatsPlugin;
+
+impl Plugin for NetworkStatsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(EnableNetworkStats::default()).add_systems(
+            Update,
+            update_network_stats_menu
+                .run_if(resource_equals(EnableNetworkStats(true)))
+                .run_if(resource_equals(MenuFocused::default())),
+        );
+    }
+}
+
+#[derive(Resource, Default, PartialEq, Eq)]
+pub struct EnableNetworkStats(bool);
+
+impl EnableNetworkStats {
+    pub fn toggle(&mut self) {
+        self.0 = !self.0;
+    }
+}
+
+fn update_network_stats_menu(
+    mut contexts: EguiContexts,
+    stats: Res<NetworkStats>,
+    mut enable_menu: ResMut<EnableNetworkStats>,
+) {
+    egui::Window::new("Network")
+        .open(&mut enable_menu.0)
+        .show(contexts.ctx_mut(), |ui| {
+            egui::Grid::new("network_stats").num_columns(2).show(ui, |ui| {
+                ui.label("Packets/sec");
+                ui.label(format!("{:.1}", stats.packets_per_sec));
+                ui.end_row();
+
+                ui.label("Bandwidth");
+                ui.label(format!("{:.1} KB/s", stats.bytes_per_sec / 1024.));
+                ui.end_row();
+
+                ui.label("Packet loss")
+                    .on_hover_text("Estimated from gaps in the tick count of the game states");
+                ui.label(format!("{:.1}%", stats.packet_loss * 100.));
+                ui.end_row();
+
+                ui.label("Jitter").on_hover_text("How much the time between packets varies");
+                ui.label(format!("{:.2} ms", stats.jitter * 1000.));
+                ui.end_row();
+
+                ui.label("Latency").on_hover_text(
+                    "How long game states wait between being read off of the socket and being picked up, \
+                     averaged over the last second",
+                );
+                ui.label(format!(
+                    "{:.2} ms (max {:.2} ms)",
+                    stats.latency * 1000.,
+                    stats.max_latency * 1000.
+                ));
+                ui.end_row();
+
+                ui.label("Since last packet");
+                let since_last_packet = format!("{:.2} s", stats.since_last_packet);
+                if stats.since_last_packet > 1. {
+                    ui.colored_label(egui::Color32::YELLOW, since_last_packet);
+                } else {
+                    ui.label(since_last_packet);
+                }
+                ui.end_row();
+            });
+
+            ui.separator();
+            ui.label("Dropped UDP packets")
+                .on_hover_text("Packets the visualizer skipped over instead of disconnecting, since it started");
+
+            egui::Grid::new("recv_errors").num_columns(2).show(ui, |ui| {
+                for (error, count) in RecvError::ALL.into_iter().zip(stats.recv_errors) {
+                    ui.label(error.name()).on_hover_text(error.description());
+                    if count > 0 {
+                        ui.colored_label(egui::Color32::YELLOW, count.to_string());
+                    } else {
+                        ui.label("0");
+                    }
+                    ui.end_row();
+                }
+            });
+        });
+}
@@ -1,8 +1,17 @@
-use crate::camera::PrimaryCamera;
+use super::car_bodies::{reload_car_bodies, CarBodies};
+use crate::{
+    camera::PrimaryCamera,
+    spectator::{CameraBookmark, BOOKMARK_SLOTS},
+};
 use bevy::prelude::*;
 use std::{
+    fmt::Display,
     fs,
     io::{self, Write},
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::OnceLock,
+    time::SystemTime,
 };
 
 pub struct GameOptions;
@@ -10,13 +19,44 @@ pub struct GameOptions;
 impl Plugin for GameOptions {
     fn build(&self, app: &mut App) {
         app.insert_resource(Options::default_read_file())
+            .insert_resource(CarBodies::default_read_file())
             .insert_resource(BallCam::default())
             .insert_resource(UiOverlayScale::default())
             .insert_resource(ShowTime::default())
             .insert_resource(GameSpeed::default())
             .insert_resource(MenuFocused::default())
             .insert_resource(CalcBallRot::default())
-            .insert_resource(PacketSmoothing::default());
+            .insert_resource(SmoothBoostMeter::default())
+            .insert_resource(Blueprint::default())
+            .insert_resource(ClampStateInputs::default())
+            .insert_resource(NudgeStep::default())
+            .insert_resource(ShowBouncePoint::default())
+            .insert_resource(ShowBallPath::default())
+            .insert_resource(ShowBallSpin::default())
+            .insert_resource(ShowPadCooldowns::default())
+            .insert_resource(HideDemoedCars::default())
+            .insert_resource(ScreenshotOnGoal::default())
+            .insert_resource(ImpactCameraShake::default())
+            .insert_resource(ShowCarHitboxes::default())
+            .insert_resource(CarLodDistance::default())
+            .insert_resource(ShowCarNames::default())
+            .insert_resource(ShowTeamHud::default())
+            .insert_resource(ShowInputViewer::default())
+            .insert_resource(ShowMessageFeed::default())
+            .insert_resource(PhysicsDebug::default())
+            .insert_resource(GoalExplosion::default())
+            .insert_resource(BoostTrailQuality::default())
+            .insert_resource(BallTrail::default())
+            .insert_resource(PovCamera::default())
+            .insert_resource(ReplayOnGoal::default())
+            .insert_resource(PacketSmoothing::default())
+            .insert_resource(RenderDelay::default())
+            .insert_resource(WallTransparency::default())
+            .insert_resource(ShowCrowd::default())
+            .insert_resource(FieldStyle::default())
+            .insert_resource(LightingPreset::default())
+            .insert_resource(SplitScreen::default())
+            .add_systems(Update, reload_car_bodies);
     }
 }
 
@@ -42,6 +82,76 @@ pub struct Options {
     pub allow_rendering: bool,
     pub packet_smoothing: usize,
     pub calc_ball_rot: bool,
+    pub smooth_boost_meter: bool,
+    pub blueprint: bool,
+    pub blueprint_color: [f32; 3],
+    pub blueprint_background: [f32; 3],
+    pub clamp_state_inputs: bool,
+    pub show_bounce_point: bool,
+    pub hide_demoed_cars: bool,
+    pub screenshot_on_goal: bool,
+    pub show_car_hitboxes: bool,
+    pub show_car_names: bool,
+    pub boost_trail_quality: usize,
+    pub pov_camera: bool,
+    pub camera_fov: f32,
+    pub camera_distance: f32,
+    pub camera_height: f32,
+    pub camera_angle: f32,
+    pub camera_stiffness: f32,
+    pub camera_swivel_speed: f32,
+    pub replay_on_goal: bool,
+    pub show_pad_cooldowns: bool,
+    pub show_ball_path: bool,
+    pub ball_path_seconds: f32,
+    pub master_volume: f32,
+    pub mute: bool,
+    pub capture_path: String,
+    pub capture_fps: u32,
+    pub wall_transparency: usize,
+    pub wall_opacity: f32,
+    pub remote_host: String,
+    pub close_on_quit: bool,
+    pub telemetry_format: usize,
+    pub field_style: usize,
+    pub spectator_collision: bool,
+    pub spectator_follow_ball: bool,
+    pub spectator_follow_smoothing: f32,
+    pub split_screen: usize,
+    pub split_screen_views: [u32; 3],
+    pub show_team_hud: bool,
+    pub lighting_preset: usize,
+    pub render_delay: f32,
+    pub ambient_occlusion: usize,
+    pub show_hit_markers: bool,
+    pub show_contact_normals: bool,
+    pub flash_car_bumps: bool,
+    pub goal_flash: bool,
+    pub goal_particles: bool,
+    pub goal_camera_shake: bool,
+    pub goal_slow_motion: bool,
+    pub nudge_step: f32,
+    pub camera_bookmarks: [Option<CameraBookmark>; BOOKMARK_SLOTS],
+    pub show_field_bounds: bool,
+    pub show_goal_lines: bool,
+    pub car_lod_distance: f32,
+    pub show_input_viewer: bool,
+    pub show_crowd: bool,
+    pub camera_transition_time: f32,
+    pub smooth_director_cuts: bool,
+    pub show_ball_trail: bool,
+    pub ball_trail_length: f32,
+    pub ball_trail_width: f32,
+    pub window_mode: usize,
+    pub window_monitor: String,
+    pub window_width: f32,
+    pub window_height: f32,
+    pub show_message_feed: bool,
+    pub bloom: bool,
+    pub bloom_intensity: f32,
+    pub show_ball_spin: bool,
+    pub screenshot_scale: u32,
+    pub impact_camera_shake: bool,
 }
 
 impl Default for Options {
@@ -67,23 +177,134 @@ impl Default for Options {
             allow_rendering: true,
             packet_smoothing: 1,
             calc_ball_rot: true,
+            smooth_boost_meter: false,
+            blueprint: false,
+            blueprint_color: [0.85, 0.92, 1.],
+            blueprint_background: [0.04, 0.11, 0.28],
+            clamp_state_inputs: false,
+            show_bounce_point: false,
+            hide_demoed_cars: false,
+            screenshot_on_goal: false,
+            show_car_hitboxes: false,
+            show_car_names: true,
+            boost_trail_quality: 2,
+            pov_camera: false,
+            camera_fov: 110.,
+            camera_distance: 270.,
+            camera_height: 100.,
+            camera_angle: -4.,
+            camera_stiffness: 0.5,
+            camera_swivel_speed: 5.,
+            replay_on_goal: false,
+            show_pad_cooldowns: false,
+            show_ball_path: false,
+            ball_path_seconds: 3.,
+            master_volume: 0.,
+            mute: false,
+            capture_path: String::from("recordings"),
+            capture_fps: 60,
+            wall_transparency: 0,
+            wall_opacity: 0.25,
+            remote_host: String::from("127.0.0.1"),
+            close_on_quit: false,
+            telemetry_format: 0,
+            field_style: 0,
+            spectator_collision: false,
+            spectator_follow_ball: false,
+            spectator_follow_smoothing: 0.5,
+            split_screen: 0,
+            split_screen_views: [1, 2, 3],
+            show_team_hud: false,
+            lighting_preset: 0,
+            render_delay: 0.,
+            ambient_occlusion: if cfg!(feature = "ssao") { 3 } else { 0 },
+            show_hit_markers: false,
+            show_contact_normals: false,
+            flash_car_bumps: false,
+            goal_flash: true,
+            goal_particles: true,
+            goal_camera_shake: true,
+            goal_slow_motion: false,
+            nudge_step: 50.,
+            camera_bookmarks: [None; BOOKMARK_SLOTS],
+            show_field_bounds: false,
+            show_goal_lines: false,
+            car_lod_distance: 0.,
+            show_input_viewer: false,
+            show_crowd: false,
+            camera_transition_time: 0.5,
+            smooth_director_cuts: true,
+            show_ball_trail: false,
+            ball_trail_length: 1.5,
+            ball_trail_width: 40.,
+            window_mode: 0,
+            window_monitor: String::new(),
+            window_width: 1280.,
+            window_height: 720.,
+            show_message_feed: true,
+            bloom: true,
+            bloom_intensity: 0.15,
+            show_ball_spin: false,
+            screenshot_scale: 2,
+            impact_camera_shake: true,
         }
     }
 }
 
+/// Parses a value from the settings file, so a bad value from editing it by hand is an error instead of a crash
+fn parse<T: FromStr>(key: &str, value: &str) -> io::Result<T>
+where
+    T::Err: Display,
+{
+    value
+        .parse()
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("Invalid value {value} for {key}: {e}")))
+}
+
+/// Where the settings are saved, which can be changed with `--settings <path>`
+static SETTINGS_PATH: OnceLock<PathBuf> = OnceLock::new();
+
 impl Options {
-    const FILE_NAME: &'static str = "settings.txt";
+    const DEFAULT_PATH: &'static str = "settings.txt";
+
+    /// Uses another settings file, which only works before the settings are first read
+    pub fn set_path(path: PathBuf) {
+        if SETTINGS_PATH.set(path).is_err() {
+            println!("The settings were already read from {}", Self::path().display());
+        }
+    }
+
+    pub fn path() -> &'static Path {
+        SETTINGS_PATH.get_or_init(|| PathBuf::from(Self::DEFAULT_PATH))
+    }
 
     #[inline]
     fn default_read_file() -> Self {
-        Self::read_from_file().unwrap_or_else(|_| Self::create_file_from_defualt())
+        match Self::read_from_file() {
+            Ok(options) => options,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Self::create_file_from_defualt(),
+            Err(e) => {
+                // don't overwrite a file that was edited by hand with the defaults, it'll be saved over once something changes
+                println!("Failed to read {} due to: {e}", Self::path().display());
+                Self::default()
+            }
+        }
     }
 
-    fn read_from_file() -> io::Result<Self> {
+    /// When the settings file was last changed, which could be by something other than the visualizer
+    pub fn file_modified() -> Option<SystemTime> {
+        fs::metadata(Self::path()).and_then(|metadata| metadata.modified()).ok()
+    }
+
+    pub fn read_from_file() -> io::Result<Self> {
         let mut options = Self::default();
+        options.apply_settings(&fs::read_to_string(Self::path())?)?;
 
-        let file = fs::read_to_string(Self::FILE_NAME)?;
+        Ok(options)
+    }
 
+    /// Sets every setting that's in `file`, which is in the same `key=value` format as the settings file
+    pub fn apply_settings(&mut self, file: &str) -> io::Result<()> {
         for line in file.lines() {
             let mut parts = line.split('=');
 
@@ -96,44 +317,117 @@ impl Options {
             };
 
             match key {
-                "vsync" => options.vsync = value.parse().unwrap(),
-                "uncap_fps" => options.uncap_fps = value.parse().unwrap(),
-                "fps_limit" => options.fps_limit = value.parse().unwrap(),
-                "ball_cam" => options.ball_cam = value.parse().unwrap(),
-                "stop_day" => options.stop_day = value.parse().unwrap(),
-                "daytime" => options.daytime = value.parse().unwrap(),
-                "day_speed" => options.day_speed = value.parse().unwrap(),
-                "msaa" => options.msaa = value.parse().unwrap(),
-                "camera_state" => options.camera_state = serde_json::from_str(value).unwrap(),
-                "show_time" => options.show_time = value.parse().unwrap(),
-                "ui_scale" => options.ui_scale = value.parse().unwrap(),
-                "shadows" => options.shadows = value.parse().unwrap(),
-                "game_speed" => options.game_speed = value.parse().unwrap(),
-                "paused" => options.paused = value.parse().unwrap(),
-                "mouse_sensitivity" => options.mouse_sensitivity = value.parse().unwrap(),
-                "allow_rendering" => options.allow_rendering = value.parse().unwrap(),
-                "packet_smoothing" => options.packet_smoothing = serde_json::from_str(value).unwrap(),
-                "calc_ball_rot" => options.calc_ball_rot = value.parse().unwrap(),
+                "vsync" => self.vsync = parse(key, value)?,
+                "uncap_fps" => self.uncap_fps = parse(key, value)?,
+                "fps_limit" => self.fps_limit = parse(key, value)?,
+                "ball_cam" => self.ball_cam = parse(key, value)?,
+                "stop_day" => self.stop_day = parse(key, value)?,
+                "daytime" => self.daytime = parse(key, value)?,
+                "day_speed" => self.day_speed = parse(key, value)?,
+                "msaa" => self.msaa = parse(key, value)?,
+                "camera_state" => self.camera_state = serde_json::from_str(value)?,
+                "show_time" => self.show_time = parse(key, value)?,
+                "ui_scale" => self.ui_scale = parse(key, value)?,
+                "shadows" => self.shadows = parse(key, value)?,
+                "game_speed" => self.game_speed = parse(key, value)?,
+                "paused" => self.paused = parse(key, value)?,
+                "mouse_sensitivity" => self.mouse_sensitivity = parse(key, value)?,
+                "allow_rendering" => self.allow_rendering = parse(key, value)?,
+                "packet_smoothing" => self.packet_smoothing = serde_json::from_str(value)?,
+                "calc_ball_rot" => self.calc_ball_rot = parse(key, value)?,
+                "smooth_boost_meter" => self.smooth_boost_meter = parse(key, value)?,
+                "blueprint" => self.blueprint = parse(key, value)?,
+                "blueprint_color" => self.blueprint_color = serde_json::from_str(value)?,
+                "blueprint_background" => self.blueprint_background = serde_json::from_str(value)?,
+                "clamp_state_inputs" => self.clamp_state_inputs = parse(key, value)?,
+                "show_bounce_point" => self.show_bounce_point = parse(key, value)?,
+                "hide_demoed_cars" => self.hide_demoed_cars = parse(key, value)?,
+                "screenshot_on_goal" => self.screenshot_on_goal = parse(key, value)?,
+                "show_car_hitboxes" => self.show_car_hitboxes = parse(key, value)?,
+                "show_car_names" => self.show_car_names = parse(key, value)?,
+                "boost_trail_quality" => self.boost_trail_quality = parse(key, value)?,
+                "pov_camera" => self.pov_camera = parse(key, value)?,
+                "camera_fov" => self.camera_fov = parse(key, value)?,
+                "camera_distance" => self.camera_distance = parse(key, value)?,
+                "camera_height" => self.camera_height = parse(key, value)?,
+                "camera_angle" => self.camera_angle = parse(key, value)?,
+                "camera_stiffness" => self.camera_stiffness = parse(key, value)?,
+                "camera_swivel_speed" => self.camera_swivel_speed = parse(key, value)?,
+                "replay_on_goal" => self.replay_on_goal = parse(key, value)?,
+                "show_pad_cooldowns" => self.show_pad_cooldowns = parse(key, value)?,
+                "show_ball_path" => self.show_ball_path = parse(key, value)?,
+                "ball_path_seconds" => self.ball_path_seconds = parse(key, value)?,
+                "master_volume" => self.master_volume = parse(key, value)?,
+                "mute" => self.mute = parse(key, value)?,
+                "capture_path" => self.capture_path = parse(key, value)?,
+                "capture_fps" => self.capture_fps = parse(key, value)?,
+                "wall_transparency" => self.wall_transparency = parse(key, value)?,
+                "wall_opacity" => self.wall_opacity = parse(key, value)?,
+                "remote_host" => self.remote_host = parse(key, value)?,
+                "close_on_quit" => self.close_on_quit = parse(key, value)?,
+                "telemetry_format" => self.telemetry_format = parse(key, value)?,
+                "field_style" => self.field_style = parse(key, value)?,
+                "spectator_collision" => self.spectator_collision = parse(key, value)?,
+                "spectator_follow_ball" => self.spectator_follow_ball = parse(key, value)?,
+                "spectator_follow_smoothing" => self.spectator_follow_smoothing = parse(key, value)?,
+                "split_screen" => self.split_screen = parse(key, value)?,
+                "split_screen_views" => self.split_screen_views = serde_json::from_str(value)?,
+                "show_team_hud" => self.show_team_hud = parse(key, value)?,
+                "lighting_preset" => self.lighting_preset = parse(key, value)?,
+                "render_delay" => self.render_delay = parse(key, value)?,
+                "ambient_occlusion" => self.ambient_occlusion = parse(key, value)?,
+                "show_hit_markers" => self.show_hit_markers = parse(key, value)?,
+                "show_contact_normals" => self.show_contact_normals = parse(key, value)?,
+                "flash_car_bumps" => self.flash_car_bumps = parse(key, value)?,
+                "goal_flash" => self.goal_flash = parse(key, value)?,
+                "goal_particles" => self.goal_particles = parse(key, value)?,
+                "goal_camera_shake" => self.goal_camera_shake = parse(key, value)?,
+                "goal_slow_motion" => self.goal_slow_motion = parse(key, value)?,
+                "nudge_step" => self.nudge_step = parse(key, value)?,
+                "camera_bookmarks" => self.camera_bookmarks = serde_json::from_str(value)?,
+                "show_field_bounds" => self.show_field_bounds = parse(key, value)?,
+                "show_goal_lines" => self.show_goal_lines = parse(key, value)?,
+                "car_lod_distance" => self.car_lod_distance = parse(key, value)?,
+                "show_input_viewer" => self.show_input_viewer = parse(key, value)?,
+                "show_crowd" => self.show_crowd = parse(key, value)?,
+                "camera_transition_time" => self.camera_transition_time = parse(key, value)?,
+                "smooth_director_cuts" => self.smooth_director_cuts = parse(key, value)?,
+                "show_ball_trail" => self.show_ball_trail = parse(key, value)?,
+                "ball_trail_length" => self.ball_trail_length = parse(key, value)?,
+                "ball_trail_width" => self.ball_trail_width = parse(key, value)?,
+                "window_mode" => self.window_mode = parse(key, value)?,
+                "window_monitor" => self.window_monitor = parse(key, value)?,
+                "window_width" => self.window_width = parse(key, value)?,
+                "window_height" => self.window_height = parse(key, value)?,
+                "show_message_feed" => self.show_message_feed = parse(key, value)?,
+                "bloom" => self.bloom = parse(key, value)?,
+                "bloom_intensity" => self.bloom_intensity = parse(key, value)?,
+                "show_ball_spin" => self.show_ball_spin = parse(key, value)?,
+                "screenshot_scale" => self.screenshot_scale = parse(key, value)?,
+                "impact_camera_shake" => self.impact_camera_shake = parse(key, value)?,
                 _ => println!("Unknown key {key} with value {value}"),
             }
         }
 
-        Ok(options)
+        Ok(())
     }
 
     fn create_file_from_defualt() -> Self {
         let options = Self::default();
 
         if let Err(e) = options.write_options_to_file() {
-            println!("Failed to create {} due to: {e}", Self::FILE_NAME);
+            println!("Failed to create {} due to: {e}", Self::path().display());
         }
 
         options
     }
 
     pub fn write_options_to_file(&self) -> io::Result<()> {
-        let mut file = fs::File::create(Self::FILE_NAME)?;
+        self.write_options(&mut fs::File::create(Self::path())?)
+    }
 
+    /// Writes every setting as `key=value` lines
+    pub fn write_options(&self, file: &mut impl Write) -> io::Result<()> {
         file.write_fmt(format_args!("vsync={}\n", self.vsync))?;
         file.write_fmt(format_args!("uncap_fps={}\n", self.uncap_fps))?;
         file.write_fmt(format_args!("fps_limit={}\n", self.fps_limit))?;
@@ -152,6 +446,91 @@ impl Options {
         file.write_fmt(format_args!("allow_rendering={}\n", self.allow_rendering))?;
         file.write_fmt(format_args!("packet_smoothing={}\n", self.packet_smoothing))?;
         file.write_fmt(format_args!("calc_ball_rot={}\n", self.calc_ball_rot))?;
+        file.write_fmt(format_args!("smooth_boost_meter={}\n", self.smooth_boost_meter))?;
+        file.write_fmt(format_args!("blueprint={}\n", self.blueprint))?;
+        file.write_fmt(format_args!(
+            "blueprint_color={}\n",
+            serde_json::to_string(&self.blueprint_color)?
+        ))?;
+        file.write_fmt(format_args!(
+            "blueprint_background={}\n",
+            serde_json::to_string(&self.blueprint_background)?
+        ))?;
+        file.write_fmt(format_args!("clamp_state_inputs={}\n", self.clamp_state_inputs))?;
+        file.write_fmt(format_args!("show_bounce_point={}\n", self.show_bounce_point))?;
+        file.write_fmt(format_args!("hide_demoed_cars={}\n", self.hide_demoed_cars))?;
+        file.write_fmt(format_args!("screenshot_on_goal={}\n", self.screenshot_on_goal))?;
+        file.write_fmt(format_args!("show_car_hitboxes={}\n", self.show_car_hitboxes))?;
+        file.write_fmt(format_args!("show_car_names={}\n", self.show_car_names))?;
+        file.write_fmt(format_args!("boost_trail_quality={}\n", self.boost_trail_quality))?;
+        file.write_fmt(format_args!("pov_camera={}\n", self.pov_camera))?;
+        file.write_fmt(format_args!("camera_fov={}\n", self.camera_fov))?;
+        file.write_fmt(format_args!("camera_distance={}\n", self.camera_distance))?;
+        file.write_fmt(format_args!("camera_height={}\n", self.camera_height))?;
+        file.write_fmt(format_args!("camera_angle={}\n", self.camera_angle))?;
+        file.write_fmt(format_args!("camera_stiffness={}\n", self.camera_stiffness))?;
+        file.write_fmt(format_args!("camera_swivel_speed={}\n", self.camera_swivel_speed))?;
+        file.write_fmt(format_args!("replay_on_goal={}\n", self.replay_on_goal))?;
+        file.write_fmt(format_args!("show_pad_cooldowns={}\n", self.show_pad_cooldowns))?;
+        file.write_fmt(format_args!("show_ball_path={}\n", self.show_ball_path))?;
+        file.write_fmt(format_args!("ball_path_seconds={}\n", self.ball_path_seconds))?;
+        file.write_fmt(format_args!("master_volume={}\n", self.master_volume))?;
+        file.write_fmt(format_args!("mute={}\n", self.mute))?;
+        file.write_fmt(format_args!("capture_path={}\n", self.capture_path))?;
+        file.write_fmt(format_args!("capture_fps={}\n", self.capture_fps))?;
+        file.write_fmt(format_args!("wall_transparency={}\n", self.wall_transparency))?;
+        file.write_fmt(format_args!("wall_opacity={}\n", self.wall_opacity))?;
+        file.write_fmt(format_args!("remote_host={}\n", self.remote_host))?;
+        file.write_fmt(format_args!("close_on_quit={}\n", self.close_on_quit))?;
+        file.write_fmt(format_args!("telemetry_format={}\n", self.telemetry_format))?;
+        file.write_fmt(format_args!("field_style={}\n", self.field_style))?;
+        file.write_fmt(format_args!("spectator_collision={}\n", self.spectator_collision))?;
+        file.write_fmt(format_args!("spectator_follow_ball={}\n", self.spectator_follow_ball))?;
+        file.write_fmt(format_args!(
+            "spectator_follow_smoothing={}\n",
+            self.spectator_follow_smoothing
+        ))?;
+        file.write_fmt(format_args!("split_screen={}\n", self.split_screen))?;
+        file.write_fmt(format_args!(
+            "split_screen_views={}\n",
+            serde_json::to_string(&self.split_screen_views)?
+        ))?;
+        file.write_fmt(format_args!("show_team_hud={}\n", self.show_team_hud))?;
+        file.write_fmt(format_args!("lighting_preset={}\n", self.lighting_preset))?;
+        file.write_fmt(format_args!("render_delay={}\n", self.render_delay))?;
+        file.write_fmt(format_args!("ambient_occlusion={}\n", self.ambient_occlusion))?;
+        file.write_fmt(format_args!("show_hit_markers={}\n", self.show_hit_markers))?;
+        file.write_fmt(format_args!("show_contact_normals={}\n", self.show_contact_normals))?;
+        file.write_fmt(format_args!("flash_car_bumps={}\n", self.flash_car_bumps))?;
+        file.write_fmt(format_args!("goal_flash={}\n", self.goal_flash))?;
+        file.write_fmt(format_args!("goal_particles={}\n", self.goal_particles))?;
+        file.write_fmt(format_args!("goal_camera_shake={}\n", self.goal_camera_shake))?;
+        file.write_fmt(format_args!("goal_slow_motion={}\n", self.goal_slow_motion))?;
+        file.write_fmt(format_args!("nudge_step={}\n", self.nudge_step))?;
+        file.write_fmt(format_args!(
+            "camera_bookmarks={}\n",
+            serde_json::to_string(&self.camera_bookmarks)?
+        ))?;
+        file.write_fmt(format_args!("show_field_bounds={}\n", self.show_field_bounds))?;
+        file.write_fmt(format_args!("show_goal_lines={}\n", self.show_goal_lines))?;
+        file.write_fmt(format_args!("car_lod_distance={}\n", self.car_lod_distance))?;
+        file.write_fmt(format_args!("show_input_viewer={}\n", self.show_input_viewer))?;
+        file.write_fmt(format_args!("show_crowd={}\n", self.show_crowd))?;
+        file.write_fmt(format_args!("camera_transition_time={}\n", self.camera_transition_time))?;
+        file.write_fmt(format_args!("smooth_director_cuts={}\n", self.smooth_director_cuts))?;
+        file.write_fmt(format_args!("show_ball_trail={}\n", self.show_ball_trail))?;
+        file.write_fmt(format_args!("ball_trail_length={}\n", self.ball_trail_length))?;
+        file.write_fmt(format_args!("ball_trail_width={}\n", self.ball_trail_width))?;
+        file.write_fmt(format_args!("window_mode={}\n", self.window_mode))?;
+        file.write_fmt(format_args!("window_monitor={}\n", self.window_monitor))?;
+        file.write_fmt(format_args!("window_width={}\n", self.window_width))?;
+        file.write_fmt(format_args!("window_height={}\n", self.window_height))?;
+        file.write_fmt(format_args!("show_message_feed={}\n", self.show_message_feed))?;
+        file.write_fmt(format_args!("bloom={}\n", self.bloom))?;
+        file.write_fmt(format_args!("bloom_intensity={}\n", self.bloom_intensity))?;
+        file.write_fmt(format_args!("show_ball_spin={}\n", self.show_ball_spin))?;
+        file.write_fmt(format_args!("screenshot_scale={}\n", self.screenshot_scale))?;
+        file.write_fmt(format_args!("impact_camera_shake={}\n", self.impact_camera_shake))?;
 
         Ok(())
     }
@@ -177,10 +556,80 @@ impl Options {
             || self.allow_rendering != other.allow_rendering
             || self.packet_smoothing != other.packet_smoothing
             || self.calc_ball_rot != other.calc_ball_rot
+            || self.smooth_boost_meter != other.smooth_boost_meter
+            || self.blueprint != other.blueprint
+            || self.blueprint_color != other.blueprint_color
+            || self.blueprint_background != other.blueprint_background
+            || self.clamp_state_inputs != other.clamp_state_inputs
+            || self.show_bounce_point != other.show_bounce_point
+            || self.hide_demoed_cars != other.hide_demoed_cars
+            || self.screenshot_on_goal != other.screenshot_on_goal
+            || self.show_car_hitboxes != other.show_car_hitboxes
+            || self.show_car_names != other.show_car_names
+            || self.boost_trail_quality != other.boost_trail_quality
+            || self.pov_camera != other.pov_camera
+            || self.camera_fov != other.camera_fov
+            || self.camera_distance != other.camera_distance
+            || self.camera_height != other.camera_height
+            || self.camera_angle != other.camera_angle
+            || self.camera_stiffness != other.camera_stiffness
+            || self.camera_swivel_speed != other.camera_swivel_speed
+            || self.replay_on_goal != other.replay_on_goal
+            || self.show_pad_cooldowns != other.show_pad_cooldowns
+            || self.show_ball_path != other.show_ball_path
+            || self.ball_path_seconds != other.ball_path_seconds
+            || self.master_volume != other.master_volume
+            || self.mute != other.mute
+            || self.capture_path != other.capture_path
+            || self.capture_fps != other.capture_fps
+            || self.wall_transparency != other.wall_transparency
+            || self.wall_opacity != other.wall_opacity
+            || self.remote_host != other.remote_host
+            || self.close_on_quit != other.close_on_quit
+            || self.telemetry_format != other.telemetry_format
+            || self.field_style != other.field_style
+            || self.spectator_collision != other.spectator_collision
+            || self.spectator_follow_ball != other.spectator_follow_ball
+            || self.spectator_follow_smoothing != other.spectator_follow_smoothing
+            || self.split_screen != other.split_screen
+            || self.split_screen_views != other.split_screen_views
+            || self.show_team_hud != other.show_team_hud
+            || self.lighting_preset != other.lighting_preset
+            || self.render_delay != other.render_delay
+            || self.ambient_occlusion != other.ambient_occlusion
+            || self.show_hit_markers != other.show_hit_markers
+            || self.show_contact_normals != other.show_contact_normals
+            || self.flash_car_bumps != other.flash_car_bumps
+            || self.goal_flash != other.goal_flash
+            || self.goal_particles != other.goal_particles
+            || self.goal_camera_shake != other.goal_camera_shake
+            || self.goal_slow_motion != other.goal_slow_motion
+            || self.nudge_step != other.nudge_step
+            || self.camera_bookmarks != other.camera_bookmarks
+            || self.show_field_bounds != other.show_field_bounds
+            || self.show_goal_lines != other.show_goal_lines
+            || self.car_lod_distance != other.car_lod_distance
+            || self.show_input_viewer != other.show_input_viewer
+            || self.show_crowd != other.show_crowd
+            || self.camera_transition_time != other.camera_transition_time
+            || self.smooth_director_cuts != other.smooth_director_cuts
+            || self.show_ball_trail != other.show_ball_trail
+            || self.ball_trail_length != other.ball_trail_length
+            || self.ball_trail_width != other.ball_trail_width
+            || self.window_mode != other.window_mode
+            || self.window_monitor != other.window_monitor
+            || self.window_width != other.window_width
+            || self.window_height != other.window_height
+            || self.show_message_feed != other.show_message_feed
+            || self.bloom != other.bloom
+            || self.bloom_intensity != other.bloom_intensity
+            || self.show_ball_spin != other.show_ball_spin
+            || self.screenshot_scale != other.screenshot_scale
+            || self.impact_camera_shake != other.impact_camera_shake
     }
 }
 
-#[derive(Clone, Copy, Resource, Default)]
+#[derive(Clone, Copy, Resource, Default, PartialEq, Eq)]
 pub enum PacketSmoothing {
     None,
     #[default]
@@ -199,6 +648,169 @@ impl PacketSmoothing {
     }
 }
 
+/// How far behind the newest game state playback is held, in seconds, to smooth out packets arriving unevenly
+#[derive(Resource, Default)]
+pub struct RenderDelay(pub f32);
+
+#[derive(Clone, Copy, Resource, Default, PartialEq, Eq)]
+pub enum BoostTrailQuality {
+    Off,
+    Low,
+    #[default]
+    Medium,
+    High,
+}
+
+impl BoostTrailQuality {
+    pub fn from_usize(value: usize) -> Self {
+        match value {
+            0 => Self::Off,
+            1 => Self::Low,
+            2 => Self::Medium,
+            3 => Self::High,
+            _ => unreachable!(),
+        }
+    }
+
+    /// How many points are kept along the length of the trail
+    pub const fn num_points(self) -> usize {
+        match self {
+            Self::Off => 0,
+            Self::Low => 8,
+            Self::Medium => 16,
+            Self::High => 32,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Resource, Default, PartialEq, Eq)]
+pub enum FieldStyle {
+    #[default]
+    Normal,
+    /// Plain, untextured materials
+    FlatShaded,
+    /// Only the edges of the field's triangles, on top of a dark unlit surface
+    Wireframe,
+}
+
+impl FieldStyle {
+    pub fn from_usize(value: usize) -> Self {
+        match value {
+            0 => Self::Normal,
+            1 => Self::FlatShaded,
+            2 => Self::Wireframe,
+            _ => unreachable!(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Resource, Default, PartialEq, Eq)]
+pub enum LightingPreset {
+    /// The sun moves across the sky, see `DaylightOffset`
+    #[default]
+    DayCycle,
+    Noon,
+    Sunset,
+    /// A dark sky with floodlights around the stadium
+    Night,
+    /// Bright, even light from every direction with barely any shading
+    Studio,
+}
+
+impl LightingPreset {
+    pub fn from_usize(value: usize) -> Self {
+        match value {
+            0 => Self::DayCycle,
+            1 => Self::Noon,
+            2 => Self::Sunset,
+            3 => Self::Night,
+            4 => Self::Studio,
+            _ => unreachable!(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+pub enum WallTransparencyMode {
+    #[default]
+    Off,
+    Always,
+    /// Only while the camera is outside of the arena
+    WhenOutside,
+}
+
+impl WallTransparencyMode {
+    pub fn from_usize(value: usize) -> Self {
+        match value {
+            0 => Self::Off,
+            1 => Self::Always,
+            2 => Self::WhenOutside,
+            _ => unreachable!(),
+        }
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct ShowCrowd(pub bool);
+
+#[derive(Resource, Default, PartialEq, Eq)]
+pub struct ShowBallSpin(pub bool);
+
+#[derive(Resource)]
+pub struct WallTransparency {
+    pub mode: WallTransparencyMode,
+    /// How opaque the walls are once they've been faded out
+    pub opacity: f32,
+}
+
+impl Default for WallTransparency {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            mode: WallTransparencyMode::Off,
+            opacity: 0.25,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+pub enum SplitScreenLayout {
+    #[default]
+    Off,
+    SideBySide,
+    Stacked,
+    /// Two by two
+    Quad,
+}
+
+impl SplitScreenLayout {
+    pub fn from_usize(value: usize) -> Self {
+        match value {
+            0 => Self::Off,
+            1 => Self::SideBySide,
+            2 => Self::Stacked,
+            3 => Self::Quad,
+            _ => unreachable!(),
+        }
+    }
+
+    /// How many viewports the window is split into, including the one for the primary camera
+    pub const fn num_viewports(self) -> usize {
+        match self {
+            Self::Off => 1,
+            Self::SideBySide | Self::Stacked => 2,
+            Self::Quad => 4,
+        }
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct SplitScreen {
+    pub layout: SplitScreenLayout,
+    /// What the viewports after the primary one show, 0 for the Director or the car key whose car to follow
+    pub views: [u32; 3],
+}
+
 #[derive(Resource, PartialEq, Eq, DerefMut, Deref)]
 pub struct MenuFocused(pub bool);
 
@@ -209,6 +821,9 @@ impl Default for MenuFocused {
     }
 }
 
+#[derive(Resource, Default)]
+pub struct ReplayOnGoal(pub bool);
+
 #[derive(Resource)]
 pub struct CalcBallRot(pub bool);
 
@@ -219,12 +834,154 @@ impl Default for CalcBallRot {
     }
 }
 
+#[derive(Resource, Default)]
+pub struct SmoothBoostMeter(pub bool);
+
+#[derive(Resource, Default)]
+pub struct ShowBouncePoint(pub bool);
+
+#[derive(Resource)]
+pub struct ShowBallPath {
+    pub enabled: bool,
+    /// How far into the future the path is predicted
+    pub seconds: f32,
+}
+
+impl Default for ShowBallPath {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            seconds: 3.,
+        }
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct ShowPadCooldowns(pub bool);
+
+#[derive(Resource, Default)]
+pub struct HideDemoedCars(pub bool);
+
+#[derive(Resource, Default)]
+pub struct ScreenshotOnGoal(pub bool);
+
+/// Shake the camera when a car is demolished or the ball is hit hard nearby, while following a car
+#[derive(Resource, Default)]
+pub struct ImpactCameraShake(pub bool);
+
+#[derive(Resource, Default)]
+pub struct ShowCarHitboxes(pub bool);
+
+/// How far away from the camera cars are drawn as their hitbox, or 0 to always draw their body
+#[derive(Resource, Default)]
+pub struct CarLodDistance(pub f32);
+
+#[derive(Resource)]
+pub struct ShowCarNames(pub bool);
+
+impl Default for ShowCarNames {
+    #[inline]
+    fn default() -> Self {
+        Self(true)
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct ShowTeamHud(pub bool);
+
+#[derive(Resource, Default)]
+pub struct ShowInputViewer(pub bool);
+
+#[derive(Resource)]
+pub struct ShowMessageFeed(pub bool);
+
+impl Default for ShowMessageFeed {
+    #[inline]
+    fn default() -> Self {
+        Self(true)
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct PhysicsDebug {
+    pub hit_markers: bool,
+    pub contact_normals: bool,
+    pub bump_flashes: bool,
+    pub field_bounds: bool,
+    pub goal_lines: bool,
+}
+
+/// The ribbon that follows the ball around
+#[derive(Resource, Default)]
+pub struct BallTrail {
+    pub enabled: bool,
+    /// How long it takes for a point on the trail to fade out, in seconds
+    pub length: f32,
+    pub width: f32,
+}
+
+/// Which parts of the effect to play when a goal is scored
+#[derive(Resource, Default)]
+pub struct GoalExplosion {
+    pub flash: bool,
+    pub particles: bool,
+    pub camera_shake: bool,
+    pub slow_motion: bool,
+}
+
+#[derive(Resource, Default)]
+pub struct ClampStateInputs(pub bool);
+
+/// How far the arrow keys move the ball or car whose info window is focused
+#[derive(Resource)]
+pub struct NudgeStep(pub f32);
+
+impl Default for NudgeStep {
+    #[inline]
+    fn default() -> Self {
+        Self(50.)
+    }
+}
+
+impl ClampStateInputs {
+    /// Returns the bounds to clamp user input to, if clamping is enabled
+    #[inline]
+    pub const fn bounds<'a, T>(&self, bounds: &'a T) -> Option<&'a T> {
+        if self.0 {
+            Some(bounds)
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct Blueprint {
+    pub enabled: bool,
+    pub color: [f32; 3],
+    pub background: [f32; 3],
+}
+
 #[derive(Resource, Default)]
 pub struct GameSpeed {
     pub paused: bool,
     pub speed: f32,
 }
 
+impl GameSpeed {
+    pub const MIN: f32 = 0.01;
+    pub const MAX: f32 = 10.;
+    /// How much one press of the speed keys or one line of scrolling changes the game speed by
+    const STEP: f32 = 1.122_462;
+
+    /// Scales the speed exponentially, so each step feels the same whether the game is at 0.05x or 5x
+    #[must_use]
+    pub fn scale(speed: f32, steps: f32) -> f32 {
+        (speed * Self::STEP.powf(steps)).clamp(Self::MIN, Self::MAX)
+    }
+}
+
 #[derive(Resource)]
 pub struct BallCam {
     pub enabled: bool,
@@ -237,6 +994,37 @@ impl Default for BallCam {
     }
 }
 
+/// Camera settings that work the same way as Rocket League's, used when following a car
+#[derive(Resource)]
+pub struct PovCamera {
+    pub enabled: bool,
+    /// Horizontal field of view, in degrees
+    pub fov: f32,
+    pub distance: f32,
+    pub height: f32,
+    /// Pitch of the camera, in degrees
+    pub angle: f32,
+    /// 0 lets the camera lag far behind the car, 1 keeps it locked in place
+    pub stiffness: f32,
+    /// How quickly the camera turns to follow the car or the ball
+    pub swivel_speed: f32,
+}
+
+impl Default for PovCamera {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            fov: 110.,
+            distance: 270.,
+            height: 100.,
+            angle: -4.,
+            stiffness: 0.5,
+            swivel_speed: 5.,
+        }
+    }
+}
+
 #[derive(Resource)]
 pub struct ShowTime {
     pub enabled: bool,
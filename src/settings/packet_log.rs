@@ -0,0 +1,190 @@
+use super::{gui::Toast, options::MenuFocused};
+use crate::udp::UdpPacketTypes;
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+use std::{
+    collections::VecDeque,
+    fmt::Write,
+    fs, io,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+    time::Duration,
+};
+
+pub struct PacketLogPlugin;
+
+impl Plugin for PacketLogPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(PacketLog::default()).add_systems(
+            Update,
+            (
+                collect_packets,
+                update_packet_log_menu
+                    .run_if(|log: Res<PacketLog>| log.open)
+                    .run_if(resource_equals(MenuFocused::default())),
+            )
+                .chain(),
+        );
+    }
+}
+
+/// How many of the most recent packets are kept
+const MAX_LOGGED_PACKETS: usize = 200;
+
+/// Only true while the window is open and not paused, so the networking threads don't copy every packet for nothing
+static RECORDING: AtomicBool = AtomicBool::new(false);
+/// Packets received by the networking threads that haven't been moved into `PacketLog` yet
+static INBOX: Mutex<VecDeque<LoggedPacket>> = Mutex::new(VecDeque::new());
+
+struct LoggedPacket {
+    packet_type: UdpPacketTypes,
+    bytes: Vec<u8>,
+    tick: Option<u64>,
+    decode_time: Duration,
+}
+
+/// Called by the networking threads with the payload of every packet they receive
+pub fn record(packet_type: UdpPacketTypes, bytes: &[u8], tick: Option<u64>, decode_time: Duration) {
+    if !RECORDING.load(Ordering::Relaxed) {
+        return;
+    }
+
+    let mut inbox = INBOX.lock().unwrap();
+    if inbox.len() == MAX_LOGGED_PACKETS {
+        inbox.pop_front();
+    }
+
+    inbox.push_back(LoggedPacket {
+        packet_type,
+        bytes: bytes.to_vec(),
+        tick,
+        decode_time,
+    });
+}
+
+#[derive(Resource, Default)]
+pub struct PacketLog {
+    open: bool,
+    paused: bool,
+    /// Each packet is numbered in the order it was received
+    packets: VecDeque<(u64, LoggedPacket)>,
+    next_index: u64,
+    selected: Option<u64>,
+}
+
+impl PacketLog {
+    const FOLDER: &'static str = "packets";
+
+    pub fn toggle(&mut self) {
+        self.open = !self.open;
+    }
+
+    fn clear(&mut self) {
+        self.packets.clear();
+        self.selected = None;
+    }
+
+    /// Writes the payload as space separated hex bytes, 16 per line, which `xxd -r -p` can turn back into binary
+    fn dump(&self, index: u64) -> Option<io::Result<PathBuf>> {
+        let (_, packet) = self.packets.iter().find(|(i, _)| *i == index)?;
+
+        let mut hex = String::with_capacity(packet.bytes.len() * 3);
+        for line in packet.bytes.chunks(16) {
+            for byte in line {
+                write!(hex, "{byte:02x} ").unwrap();
+            }
+            hex.pop();
+            hex.push('\n');
+        }
+
+        let path = Path::new(Self::FOLDER).join(format!("packet_{index}_{:?}.hex", packet.packet_type));
+        Some(
+            fs::create_dir_all(Self::FOLDER)
+                .and_then(|()| fs::write(&path, hex))
+                .map(|()| path),
+        )
+    }
+}
+
+fn collect_packets(mut log: ResMut<PacketLog>) {
+    let recording = log.open && !log.paused;
+    RECORDING.store(recording, Ordering::Relaxed);
+
+    if !recording {
+        return;
+    }
+
+    let log = log.as_mut();
+    for packet in INBOX.lock().unwrap().drain(..) {
+        if log.packets.len() == MAX_LOGGED_PACKETS {
+            log.packets.pop_front();
+        }
+
+        log.packets.push_back((log.next_index, packet));
+        log.next_index += 1;
+    }
+}
+
+fn update_packet_log_menu(mut contexts: EguiContexts, mut log: ResMut<PacketLog>, mut toast: ResMut<Toast>) {
+    let log = log.as_mut();
+    let mut open = log.open;
+
+    egui::Window::new("Packet log")
+        .open(&mut open)
+        .show(contexts.ctx_mut(), |ui| {
+            ui.horizontal(|ui| {
+                if ui.button(if log.paused { "Resume" } else { "Pause" }).clicked() {
+                    log.paused = !log.paused;
+                }
+
+                if ui.button("Clear").clicked() {
+                    log.clear();
+                }
+
+                if ui
+                    .add_enabled(log.selected.is_some(), egui::Button::new("Dump selected"))
+                    .on_hover_text(format!("Save the payload as hex to the {} folder", PacketLog::FOLDER))
+                    .clicked()
+                {
+                    match log.selected.and_then(|index| log.dump(index)) {
+                        Some(Ok(path)) => toast.show(format!("Saved packet to {}", path.display())),
+                        Some(Err(e)) => error!("Failed to save packet due to: {e}"),
+                        None => {}
+                    }
+                }
+            });
+
+            ui.separator();
+
+            egui::ScrollArea::vertical()
+                .max_height(400.)
+                .stick_to_bottom(!log.paused)
+                .show(ui, |ui| {
+                    egui::Grid::new("packet_log").num_columns(5).striped(true).show(ui, |ui| {
+                        ui.strong("#");
+                        ui.strong("Type");
+                        ui.strong("Size");
+                        ui.strong("Tick");
+                        ui.strong("Decode");
+                        ui.end_row();
+
+                        for (index, packet) in &log.packets {
+                            if ui.selectable_label(log.selected == Some(*index), index.to_string()).clicked() {
+                                log.selected = Some(*index);
+                            }
+
+                            ui.label(format!("{:?}", packet.packet_type));
+                            ui.label(format!("{} B", packet.bytes.len()));
+                            ui.label(packet.tick.map(|tick| tick.to_string()).unwrap_or_default());
+                            ui.label(format!("{:.1} µs", packet.decode_time.as_secs_f64() * 1_000_000.));
+                            ui.end_row();
+                        }
+                    });
+                });
+        });
+
+    log.open = open;
+}
@@ -0,0 +1,70 @@
+use bevy::{prelude::*, utils::HashSet};
+
+/// Systems that can be paused from the debug window to help isolate bugs
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum DebugSystem {
+    UpdateBall,
+    UpdateBallRotation,
+    UpdateCar,
+    UpdateCarExtra,
+    UpdateCarWheels,
+    UpdateCamera,
+    InterpolatePackets,
+    ExtrapolatePacket,
+    UpdatePads,
+    UpdateBoostMeter,
+    UpdateTime,
+}
+
+#[cfg(debug_assertions)]
+impl DebugSystem {
+    pub const ALL: [Self; 11] = [
+        Self::UpdateBall,
+        Self::UpdateBallRotation,
+        Self::UpdateCar,
+        Self::UpdateCarExtra,
+        Self::UpdateCarWheels,
+        Self::UpdateCamera,
+        Self::InterpolatePackets,
+        Self::ExtrapolatePacket,
+        Self::UpdatePads,
+        Self::UpdateBoostMeter,
+        Self::UpdateTime,
+    ];
+
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::UpdateBall => "update_ball",
+            Self::UpdateBallRotation => "update_ball_rotation",
+            Self::UpdateCar => "update_car",
+            Self::UpdateCarExtra => "update_car_extra",
+            Self::UpdateCarWheels => "update_car_wheels",
+            Self::UpdateCamera => "update_camera",
+            Self::InterpolatePackets => "interpolate_packets",
+            Self::ExtrapolatePacket => "extrapolate_packet",
+            Self::UpdatePads => "update_pads",
+            Self::UpdateBoostMeter => "update_boost_meter",
+            Self::UpdateTime => "update_time",
+        }
+    }
+}
+
+/// Nothing is ever paused in release builds because the debug window doesn't exist there
+#[derive(Resource, Default)]
+pub struct PausedSystems(pub HashSet<DebugSystem>);
+
+#[cfg(debug_assertions)]
+impl PausedSystems {
+    pub fn set_paused(&mut self, system: DebugSystem, paused: bool) {
+        if paused {
+            self.0.insert(system);
+        } else {
+            self.0.remove(&system);
+        }
+    }
+}
+
+/// Run condition that skips the system while it's paused in the debug window
+pub fn not_paused(system: DebugSystem) -> impl Fn(Res<PausedSystems>) -> bool + Clone {
+    move |paused: Res<PausedSystems>| !paused.0.contains(&system)
+}
@@ -0,0 +1,194 @@
+use super::options::Options;
+use bevy::prelude::*;
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+pub struct ProfilesPlugin;
+
+impl Plugin for ProfilesPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<StartupProfile>()
+            .insert_resource(SettingsProfiles::default())
+            .add_systems(Startup, apply_startup_profile);
+    }
+}
+
+/// The profiles that are always in the list, and are created from `builtin_profile` the first time they're used
+pub const BUILTIN_PROFILES: [&str; 2] = ["performance", "quality"];
+
+/// The settings that a profile changes, everything else is left as it was
+const PROFILE_KEYS: [&str; 16] = [
+    "vsync",
+    "uncap_fps",
+    "fps_limit",
+    "msaa",
+    "shadows",
+    "ambient_occlusion",
+    "bloom",
+    "bloom_intensity",
+    "boost_trail_quality",
+    "field_style",
+    "show_crowd",
+    "car_lod_distance",
+    "lighting_preset",
+    "stop_day",
+    "daytime",
+    "day_speed",
+];
+
+/// The profile given with `--profile <name>`, which is applied over the settings file on startup
+#[derive(Resource, Default)]
+pub struct StartupProfile(pub Option<String>);
+
+#[derive(Resource)]
+pub struct SettingsProfiles {
+    /// The profiles saved next to the settings file, only looked for on startup and after saving one
+    pub names: Vec<String>,
+    /// The name typed in to save the current settings as
+    pub new_name: String,
+}
+
+impl Default for SettingsProfiles {
+    fn default() -> Self {
+        Self {
+            names: profile_names(),
+            new_name: String::new(),
+        }
+    }
+}
+
+impl SettingsProfiles {
+    pub fn refresh(&mut self) {
+        self.names = profile_names();
+    }
+}
+
+/// Profiles are saved next to the settings file, as `settings.<name>.txt`
+pub fn profile_path(name: &str) -> PathBuf {
+    Options::path().with_file_name(format!("settings.{name}.txt"))
+}
+
+fn profile_names() -> Vec<String> {
+    let mut names = BUILTIN_PROFILES.map(String::from).to_vec();
+
+    // the parent of a bare file name is empty instead of the current folder
+    let folder = Options::path()
+        .parent()
+        .filter(|folder| !folder.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    let Ok(entries) = fs::read_dir(folder) else {
+        return names;
+    };
+
+    for entry in entries.flatten() {
+        let file_name = entry.file_name();
+        let Some(name) = file_name
+            .to_str()
+            .and_then(|file_name| file_name.strip_prefix("settings."))
+            .and_then(|name| name.strip_suffix(".txt"))
+        else {
+            continue;
+        };
+
+        if !name.is_empty() && !names.iter().any(|n| n == name) {
+            names.push(name.to_string());
+        }
+    }
+
+    names[BUILTIN_PROFILES.len()..].sort_unstable();
+    names
+}
+
+/// The defaults for the built-in profiles, on top of the default settings
+fn builtin_profile(name: &str) -> Option<Options> {
+    let mut options = Options::default();
+
+    match name {
+        "performance" => {
+            options.vsync = false;
+            options.uncap_fps = false;
+            options.fps_limit = 60.;
+            options.msaa = 0;
+            options.shadows = 0;
+            options.ambient_occlusion = 0;
+            options.bloom = false;
+            options.boost_trail_quality = 1;
+            // untextured materials, like builds without the `full_load` feature
+            options.field_style = 1;
+            options.show_crowd = false;
+            options.car_lod_distance = 3000.;
+            options.lighting_preset = 1;
+            options.stop_day = true;
+        }
+        "quality" => {
+            options.vsync = true;
+            options.msaa = 3;
+            options.shadows = 3;
+            options.ambient_occlusion = 3;
+            options.bloom = true;
+            options.boost_trail_quality = 3;
+            options.field_style = 0;
+            options.show_crowd = true;
+            options.car_lod_distance = 0.;
+            options.lighting_preset = 0;
+            options.stop_day = false;
+        }
+        _ => return None,
+    }
+
+    Some(options)
+}
+
+/// Saves the settings that profiles change to `settings.<name>.txt`, replacing the profile if it already exists
+pub fn save_profile(name: &str, options: &Options) -> io::Result<()> {
+    let mut settings = Vec::new();
+    options.write_options(&mut settings)?;
+
+    let profile = String::from_utf8_lossy(&settings)
+        .lines()
+        .filter(|line| line.split_once('=').is_some_and(|(key, _)| PROFILE_KEYS.contains(&key)))
+        .fold(String::new(), |profile, line| profile + line + "\n");
+
+    fs::write(profile_path(name), profile)
+}
+
+/// Changes the settings in the profile, leaving `options` as it was if the profile can't be read
+pub fn apply_profile(name: &str, options: &mut Options) -> io::Result<()> {
+    let path = profile_path(name);
+
+    let profile = match fs::read_to_string(&path) {
+        Ok(profile) => profile,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {
+            let Some(builtin) = builtin_profile(name) else {
+                return Err(e);
+            };
+
+            // write it out so it can be tweaked by hand
+            save_profile(name, &builtin)?;
+            fs::read_to_string(&path)?
+        }
+        Err(e) => return Err(e),
+    };
+
+    let mut new_options = options.clone();
+    new_options.apply_settings(&profile)?;
+    *options = new_options;
+
+    Ok(())
+}
+
+fn apply_startup_profile(startup_profile: Res<StartupProfile>, mut options: ResMut<Options>) {
+    let Some(name) = &startup_profile.0 else {
+        return;
+    };
+
+    match apply_profile(name, &mut options) {
+        Ok(()) => info!("Using the {name} settings profile"),
+        Err(e) => error!(
+            "Failed to apply the {name} settings profile from {} due to: {e}",
+            profile_path(name).display()
+        ),
+    }
+}
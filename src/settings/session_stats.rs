@@ -0,0 +1,214 @@
+use super::{gui::Toast, options::MenuFocused};
+use crate::{
+    capture::unix_timestamp, goal::GoalScored, nametags::CarNames, replay::not_replaying, rocketsim::Team, udp::GameStates,
+};
+use bevy::{prelude::*, utils::HashMap};
+use bevy_egui::{egui, EguiContexts};
+use serde::Serialize;
+use std::{
+    collections::BTreeMap,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+pub struct SessionStatsPlugin;
+
+impl Plugin for SessionStatsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(SessionStats::default())
+            .insert_resource(EnableSessionStats::default())
+            .add_systems(
+                Update,
+                (
+                    (accumulate_stats, credit_goals.run_if(on_event::<GoalScored>))
+                        .chain()
+                        .run_if(not_replaying),
+                    update_session_stats_menu
+                        .run_if(resource_equals(EnableSessionStats(true)))
+                        .run_if(resource_equals(MenuFocused::default())),
+                ),
+            );
+    }
+}
+
+#[derive(Clone, Serialize)]
+struct CarStats {
+    team: Team,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+    touches: u32,
+    goals: u32,
+    /// How many times the car was demolished
+    demos: u32,
+    boost_used: f32,
+    /// Seconds spent supersonic
+    supersonic_time: f32,
+}
+
+/// What a car was doing in the last tick, to find what changed since
+struct LastCarState {
+    tick_count_when_hit: u64,
+    boost: f32,
+    is_demoed: bool,
+}
+
+/// Per-car statistics accumulated from the changes between game states since the session was started or reset
+#[derive(Resource, Default)]
+pub struct SessionStats {
+    cars: BTreeMap<u32, CarStats>,
+    last_states: HashMap<u32, LastCarState>,
+    last_tick: Option<u64>,
+}
+
+impl SessionStats {
+    const FOLDER: &'static str = "stats";
+
+    fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    fn export(&self) -> io::Result<PathBuf> {
+        let path = Path::new(Self::FOLDER).join(format!("session_{}.json", unix_timestamp()));
+        fs::create_dir_all(Self::FOLDER)?;
+        fs::write(&path, serde_json::to_string_pretty(&self.cars)?)?;
+        Ok(path)
+    }
+}
+
+fn accumulate_stats(game_states: Res<GameStates>, car_names: Res<CarNames>, mut stats: ResMut<SessionStats>) {
+    // `next` is always the newest state that was received, no matter the packet smoothing
+    let state = &game_states.next;
+    let Some(last_tick) = stats.last_tick.replace(state.tick_count) else {
+        return;
+    };
+
+    // the tick count going backwards means a new game was started, which doesn't tell us how much time passed
+    if state.tick_count <= last_tick {
+        return;
+    }
+
+    let delta_time = (state.tick_count - last_tick) as f32 / state.tick_rate;
+
+    let stats = stats.as_mut();
+    for car in &*state.cars {
+        let car_state = &car.state;
+        let car_stats = stats.cars.entry(car.id).or_insert_with(|| CarStats {
+            team: car.team,
+            name: None,
+            touches: 0,
+            goals: 0,
+            demos: 0,
+            boost_used: 0.,
+            supersonic_time: 0.,
+        });
+        car_stats.team = car.team;
+        car_stats.name = car_names.0.get(&car.id).cloned();
+
+        // cars that just showed up shouldn't be counted for what they did before
+        if let Some(last) = stats.last_states.get(&car.id) {
+            if car_state.ball_hit_info.is_valid && car_state.ball_hit_info.tick_count_when_hit != last.tick_count_when_hit {
+                car_stats.touches += 1;
+            }
+
+            if car_state.is_demoed && !last.is_demoed {
+                car_stats.demos += 1;
+            }
+
+            // boost going up is from pads or respawning
+            if car_state.boost < last.boost {
+                car_stats.boost_used += last.boost - car_state.boost;
+            }
+        }
+
+        if car_state.is_supersonic {
+            car_stats.supersonic_time += delta_time;
+        }
+
+        stats.last_states.insert(
+            car.id,
+            LastCarState {
+                tick_count_when_hit: car_state.ball_hit_info.tick_count_when_hit,
+                boost: car_state.boost,
+                is_demoed: car_state.is_demoed,
+            },
+        );
+    }
+}
+
+/// Gives the goal to whoever on the scoring team touched the ball last
+fn credit_goals(game_states: Res<GameStates>, mut events: EventReader<GoalScored>, mut stats: ResMut<SessionStats>) {
+    for event in events.read() {
+        let scorer = game_states
+            .current
+            .cars
+            .iter()
+            .filter(|car| car.team == event.team && car.state.ball_hit_info.is_valid)
+            .max_by_key(|car| car.state.ball_hit_info.tick_count_when_hit);
+
+        if let Some(car_stats) = scorer.and_then(|car| stats.cars.get_mut(&car.id)) {
+            car_stats.goals += 1;
+        }
+    }
+}
+
+#[derive(Resource, Default, PartialEq, Eq)]
+pub struct EnableSessionStats(bool);
+
+impl EnableSessionStats {
+    pub fn toggle(&mut self) {
+        self.0 = !self.0;
+    }
+}
+
+fn update_session_stats_menu(
+    mut contexts: EguiContexts,
+    mut stats: ResMut<SessionStats>,
+    mut enable_menu: ResMut<EnableSessionStats>,
+    mut toast: ResMut<Toast>,
+) {
+    egui::Window::new("Session stats")
+        .open(&mut enable_menu.0)
+        .show(contexts.ctx_mut(), |ui| {
+            egui::Grid::new("session_stats").num_columns(7).striped(true).show(ui, |ui| {
+                ui.strong("Car");
+                ui.strong("Team");
+                ui.strong("Touches");
+                ui.strong("Goals")
+                    .on_hover_text("Credited to the last player on the scoring team to touch the ball");
+                ui.strong("Demos").on_hover_text("How many times the car was demolished");
+                ui.strong("Boost used");
+                ui.strong("Supersonic");
+                ui.end_row();
+
+                for (id, car_stats) in &stats.cars {
+                    ui.label(car_stats.name.clone().unwrap_or_else(|| format!("Car {id}")));
+                    ui.label(format!("{:?}", car_stats.team));
+                    ui.label(car_stats.touches.to_string());
+                    ui.label(car_stats.goals.to_string());
+                    ui.label(car_stats.demos.to_string());
+                    ui.label(format!("{:.0}", car_stats.boost_used));
+                    ui.label(format!("{:.1} s", car_stats.supersonic_time));
+                    ui.end_row();
+                }
+            });
+
+            ui.add_space(10.);
+
+            ui.horizontal(|ui| {
+                if ui.button("Reset").clicked() {
+                    stats.reset();
+                }
+
+                if ui
+                    .button("Export JSON")
+                    .on_hover_text(format!("Save the stats to the {} folder", SessionStats::FOLDER))
+                    .clicked()
+                {
+                    match stats.export() {
+                        Ok(path) => toast.show(format!("Saved stats to {}", path.display())),
+                        Err(e) => error!("Failed to save session stats due to: {e}"),
+                    }
+                }
+            });
+        });
+}
@@ -0,0 +1,267 @@
+use super::options::MenuFocused;
+use crate::{
+    bytes::TryFromBytes,
+    rocketsim::{CarInfo, GameState},
+    udp::GameStates,
+};
+use bevy::{math::Vec3A, prelude::*};
+use bevy_egui::{egui, EguiContexts};
+use std::fs;
+
+pub struct SnapshotDiffPlugin;
+
+impl Plugin for SnapshotDiffPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(EnableSnapshotDiff::default())
+            .insert_resource(Snapshots::default())
+            .add_systems(
+                Update,
+                update_snapshot_diff
+                    .run_if(resource_equals(EnableSnapshotDiff(true)))
+                    .run_if(resource_equals(MenuFocused::default())),
+            );
+    }
+}
+
+#[derive(Resource, Default, PartialEq, Eq)]
+pub struct EnableSnapshotDiff(bool);
+
+impl EnableSnapshotDiff {
+    pub fn toggle(&mut self) {
+        self.0 = !self.0;
+    }
+}
+
+#[derive(Default)]
+struct Snapshot {
+    path: String,
+    state: Option<GameState>,
+    error: Option<String>,
+}
+
+impl Snapshot {
+    fn load(&mut self) {
+        match fs::read(&self.path) {
+            Ok(bytes) => {
+                let Some(state) = GameState::try_from_bytes(&bytes) else {
+                    self.error = Some(String::from("File is not a valid game state"));
+                    return;
+                };
+
+                self.state = Some(state);
+                self.error = None;
+            }
+            Err(e) => self.error = Some(e.to_string()),
+        }
+    }
+}
+
+/// How much a field has to change by to be highlighted
+struct DiffThresholds {
+    pos: f32,
+    vel: f32,
+    ang_vel: f32,
+    boost: f32,
+}
+
+impl Default for DiffThresholds {
+    fn default() -> Self {
+        Self {
+            pos: 100.,
+            vel: 500.,
+            ang_vel: 2.,
+            // a small pad gives 12 boost, so anything more in a single tick is suspicious
+            boost: 12.,
+        }
+    }
+}
+
+#[derive(Resource, Default)]
+struct Snapshots {
+    states: [Snapshot; 2],
+    /// Compare the last and current game states instead of the loaded snapshots
+    live: bool,
+    /// Hide cars that have nothing above the thresholds
+    only_changed: bool,
+    thresholds: DiffThresholds,
+}
+
+const SNAPSHOT_NAMES: [&str; 2] = ["A", "B"];
+
+fn snapshot_ui(ui: &mut egui::Ui, name: &str, snapshot: &mut Snapshot, current: &GameState) {
+    ui.horizontal(|ui| {
+        ui.label(format!("{name}: "));
+        ui.add(egui::TextEdit::singleline(&mut snapshot.path).desired_width(200.));
+        if ui
+            .button("Load")
+            .on_hover_text("Load a saved game state from this path")
+            .clicked()
+        {
+            snapshot.load();
+        }
+        if ui.button("Current").on_hover_text("Use the current game state").clicked() {
+            snapshot.state = Some(current.clone());
+            snapshot.error = None;
+        }
+    });
+
+    if let Some(error) = &snapshot.error {
+        ui.colored_label(egui::Color32::RED, error);
+    } else if let Some(state) = &snapshot.state {
+        ui.label(format!("Tick {} ({} cars)", state.tick_count, state.cars.len()));
+    }
+}
+
+const HIGHLIGHT_COLOR: egui::Color32 = egui::Color32::from_rgb(255, 100, 100);
+
+/// A label that stands out if `highlight` is set
+fn delta_label(ui: &mut egui::Ui, text: String, highlight: bool) {
+    if highlight {
+        ui.colored_label(HIGHLIGHT_COLOR, text);
+    } else {
+        ui.label(text);
+    }
+}
+
+fn vec_delta_row(ui: &mut egui::Ui, name: &str, a: Vec3A, b: Vec3A, threshold: f32) {
+    let delta = b - a;
+    let highlight = delta.length() > threshold;
+
+    delta_label(ui, name.to_string(), highlight);
+    delta_label(ui, format!("[{:.1}, {:.1}, {:.1}]", delta.x, delta.y, delta.z), highlight);
+    delta_label(ui, format!("{:.1}", delta.length()), highlight);
+    ui.end_row();
+}
+
+/// Whether anything about the car changed by more than the thresholds
+fn car_changed(a: &CarInfo, b: &CarInfo, thresholds: &DiffThresholds) -> bool {
+    a.state.pos.distance(b.state.pos) > thresholds.pos
+        || a.state.vel.distance(b.state.vel) > thresholds.vel
+        || a.state.ang_vel.distance(b.state.ang_vel) > thresholds.ang_vel
+        || (b.state.boost - a.state.boost).abs() > thresholds.boost
+        || a.state.is_demoed != b.state.is_demoed
+}
+
+fn thresholds_ui(ui: &mut egui::Ui, thresholds: &mut DiffThresholds) {
+    ui.collapsing("Thresholds", |ui| {
+        egui::Grid::new("diff_thresholds").show(ui, |ui| {
+            for (name, value, speed) in [
+                ("Position", &mut thresholds.pos, 1.),
+                ("Velocity", &mut thresholds.vel, 1.),
+                ("Angular velocity", &mut thresholds.ang_vel, 0.01),
+                ("Boost", &mut thresholds.boost, 0.1),
+            ] {
+                ui.label(name);
+                ui.add(egui::DragValue::new(value).speed(speed).range(0.0..=f32::MAX));
+                ui.end_row();
+            }
+        });
+    });
+}
+
+fn diff_ui(ui: &mut egui::Ui, a: &GameState, b: &GameState, thresholds: &DiffThresholds, only_changed: bool) {
+    ui.label(format!("Tick delta: {}", b.tick_count.wrapping_sub(a.tick_count) as i64));
+
+    ui.separator();
+    ui.label("Ball");
+    egui::Grid::new("ball_diff").striped(true).show(ui, |ui| {
+        ui.strong("Field");
+        ui.strong("Delta");
+        ui.strong("Length");
+        ui.end_row();
+
+        vec_delta_row(ui, "Position", a.ball.pos, b.ball.pos, thresholds.pos);
+        vec_delta_row(ui, "Velocity", a.ball.vel, b.ball.vel, thresholds.vel);
+        vec_delta_row(ui, "Angular velocity", a.ball.ang_vel, b.ball.ang_vel, thresholds.ang_vel);
+    });
+
+    for car_b in &b.cars {
+        let Some(car_a) = a.cars.iter().find(|car| car.id == car_b.id) else {
+            ui.separator();
+            ui.label(format!("{:?} Car {} only exists in B", car_b.team, car_b.id));
+            continue;
+        };
+
+        if only_changed && !car_changed(car_a, car_b, thresholds) {
+            continue;
+        }
+
+        ui.separator();
+
+        ui.label(format!("{:?} Car {}", car_b.team, car_b.id));
+        egui::Grid::new(("car_diff", car_b.id)).striped(true).show(ui, |ui| {
+            ui.strong("Field");
+            ui.strong("Delta");
+            ui.strong("Length");
+            ui.end_row();
+
+            vec_delta_row(ui, "Position", car_a.state.pos, car_b.state.pos, thresholds.pos);
+            vec_delta_row(ui, "Velocity", car_a.state.vel, car_b.state.vel, thresholds.vel);
+            vec_delta_row(
+                ui,
+                "Angular velocity",
+                car_a.state.ang_vel,
+                car_b.state.ang_vel,
+                thresholds.ang_vel,
+            );
+
+            let boost_delta = car_b.state.boost - car_a.state.boost;
+            let highlight = boost_delta.abs() > thresholds.boost;
+            delta_label(ui, String::from("Boost"), highlight);
+            delta_label(ui, format!("{boost_delta:.1}"), highlight);
+            ui.end_row();
+
+            if car_a.state.is_demoed != car_b.state.is_demoed {
+                delta_label(ui, String::from("Demoed"), true);
+                delta_label(ui, format!("{} -> {}", car_a.state.is_demoed, car_b.state.is_demoed), true);
+                ui.end_row();
+            }
+        });
+    }
+
+    for car_a in a.cars.iter().filter(|car| !b.cars.iter().any(|other| other.id == car.id)) {
+        ui.separator();
+        ui.label(format!("{:?} Car {} only exists in A", car_a.team, car_a.id));
+    }
+}
+
+fn update_snapshot_diff(
+    mut contexts: EguiContexts,
+    game_states: Res<GameStates>,
+    mut enable_menu: ResMut<EnableSnapshotDiff>,
+    mut snapshots: ResMut<Snapshots>,
+) {
+    egui::Window::new("Snapshot diff")
+        .open(&mut enable_menu.0)
+        .show(contexts.ctx_mut(), |ui| {
+            let snapshots = snapshots.as_mut();
+
+            ui.checkbox(&mut snapshots.live, "Live").on_hover_text(
+                "Compare the last and current game states instead, to find state setting that teleports cars or changes their boost",
+            );
+
+            if !snapshots.live {
+                for (name, snapshot) in SNAPSHOT_NAMES.into_iter().zip(&mut snapshots.states) {
+                    snapshot_ui(ui, name, snapshot, &game_states.current);
+                }
+            }
+
+            ui.checkbox(&mut snapshots.only_changed, "Only show changed cars");
+            thresholds_ui(ui, &mut snapshots.thresholds);
+
+            let (a, b) = if snapshots.live {
+                (&game_states.last, &game_states.current)
+            } else {
+                let [a, b] = &snapshots.states;
+                let (Some(a), Some(b)) = (&a.state, &b.state) else {
+                    return;
+                };
+
+                (a, b)
+            };
+
+            egui::ScrollArea::vertical().max_height(500.).show(ui, |ui| {
+                diff_ui(ui, a, b, &snapshots.thresholds, snapshots.only_changed);
+            });
+        });
+}
@@ -0,0 +1,421 @@
+use super::{
+    options::ClampStateInputs,
+    state_setting::{
+        clamp_f32, clamp_vec3, parse_f32, BALL_ANG_VEL_BOUNDS, BALL_VEL_BOUNDS, BOOST_BOUNDS, CAR_ANG_VEL_BOUNDS,
+        CAR_VEL_BOUNDS, DEMO_RESPAWN_TIMER_BOUNDS, PAD_TIMER_BOUNDS, POS_BOUNDS,
+    },
+};
+use crate::{
+    capture::unix_timestamp,
+    udp::{Connection, GameStates, SendableUdp},
+};
+use bevy::{math::Vec3A, prelude::*};
+use std::{
+    collections::VecDeque,
+    fmt,
+    fs::{self, File},
+    io::{self, Write},
+    path::Path,
+    str::FromStr,
+    time::Instant,
+};
+
+pub struct StateScriptPlugin;
+
+impl Plugin for StateScriptPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(StateScriptRecorder::default())
+            .insert_resource(StateScriptPlayback::default())
+            .init_resource::<StartupStateScript>()
+            .add_event::<RunStateScript>()
+            .add_systems(
+                Update,
+                (
+                    run_startup_script.run_if(|startup: Res<StartupStateScript>| startup.0.is_some()),
+                    load_state_script.run_if(on_event::<RunStateScript>),
+                    play_state_script.run_if(|playback: Res<StateScriptPlayback>| !playback.actions.is_empty()),
+                )
+                    .chain()
+                    .run_if(resource_exists::<Connection>),
+            );
+    }
+}
+
+/// A single state setting action
+///
+/// Each action is written as one line in the script, prefixed by the seconds since recording started:
+/// ```text
+/// 0.00 ball pos 0 0 93.15
+/// 1.52 car 1 boost 100
+/// 2.10 pad 4 true 0
+/// ```
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum StateAction {
+    BallPos(Vec3A),
+    BallVel(Vec3A),
+    BallAngVel(Vec3A),
+    CarPos(u32, Vec3A),
+    CarVel(u32, Vec3A),
+    CarAngVel(u32, Vec3A),
+    CarJumped(u32, bool),
+    CarDoubleJumped(u32, bool),
+    CarFlipped(u32, bool),
+    CarBoost(u32, f32),
+    CarDemoRespawnTimer(u32, f32),
+    Pad(usize, bool, f32),
+}
+
+impl fmt::Display for StateAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::BallPos(v) => write!(f, "ball pos {} {} {}", v.x, v.y, v.z),
+            Self::BallVel(v) => write!(f, "ball vel {} {} {}", v.x, v.y, v.z),
+            Self::BallAngVel(v) => write!(f, "ball ang_vel {} {} {}", v.x, v.y, v.z),
+            Self::CarPos(id, v) => write!(f, "car {id} pos {} {} {}", v.x, v.y, v.z),
+            Self::CarVel(id, v) => write!(f, "car {id} vel {} {} {}", v.x, v.y, v.z),
+            Self::CarAngVel(id, v) => write!(f, "car {id} ang_vel {} {} {}", v.x, v.y, v.z),
+            Self::CarJumped(id, b) => write!(f, "car {id} has_jumped {b}"),
+            Self::CarDoubleJumped(id, b) => write!(f, "car {id} has_double_jumped {b}"),
+            Self::CarFlipped(id, b) => write!(f, "car {id} has_flipped {b}"),
+            Self::CarBoost(id, boost) => write!(f, "car {id} boost {boost}"),
+            Self::CarDemoRespawnTimer(id, timer) => write!(f, "car {id} demo_respawn_timer {timer}"),
+            Self::Pad(index, is_active, cooldown) => write!(f, "pad {index} {is_active} {cooldown}"),
+        }
+    }
+}
+
+fn parse_vec3<'a>(mut parts: impl Iterator<Item = &'a str>) -> Option<Vec3A> {
+    Some(Vec3A::new(
+        parse_f32(parts.next()?)?,
+        parse_f32(parts.next()?)?,
+        parse_f32(parts.next()?)?,
+    ))
+}
+
+impl FromStr for StateAction {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split_whitespace();
+
+        let action = match parts.next().ok_or(())? {
+            "ball" => match parts.next().ok_or(())? {
+                "pos" => Self::BallPos(parse_vec3(&mut parts).ok_or(())?),
+                "vel" => Self::BallVel(parse_vec3(&mut parts).ok_or(())?),
+                "ang_vel" => Self::BallAngVel(parse_vec3(&mut parts).ok_or(())?),
+                _ => return Err(()),
+            },
+            "car" => {
+                let id = parts.next().ok_or(())?.parse().map_err(|_| ())?;
+
+                match parts.next().ok_or(())? {
+                    "pos" => Self::CarPos(id, parse_vec3(&mut parts).ok_or(())?),
+                    "vel" => Self::CarVel(id, parse_vec3(&mut parts).ok_or(())?),
+                    "ang_vel" => Self::CarAngVel(id, parse_vec3(&mut parts).ok_or(())?),
+                    "has_jumped" => Self::CarJumped(id, parts.next().ok_or(())?.parse().map_err(|_| ())?),
+                    "has_double_jumped" => Self::CarDoubleJumped(id, parts.next().ok_or(())?.parse().map_err(|_| ())?),
+                    "has_flipped" => Self::CarFlipped(id, parts.next().ok_or(())?.parse().map_err(|_| ())?),
+                    "boost" => Self::CarBoost(id, parts.next().and_then(parse_f32).ok_or(())?),
+                    "demo_respawn_timer" => Self::CarDemoRespawnTimer(id, parts.next().and_then(parse_f32).ok_or(())?),
+                    _ => return Err(()),
+                }
+            }
+            "pad" => Self::Pad(
+                parts.next().ok_or(())?.parse().map_err(|_| ())?,
+                parts.next().ok_or(())?.parse().map_err(|_| ())?,
+                parts.next().and_then(parse_f32).ok_or(())?,
+            ),
+            _ => return Err(()),
+        };
+
+        // anything left over means the line wasn't what it looked like
+        if parts.next().is_some() {
+            return Err(());
+        }
+
+        Ok(action)
+    }
+}
+
+impl StateAction {
+    /// Clamps the values to the same bounds as the state setting menu, when `ClampStateInputs` is enabled
+    fn clamped(self, clamp: &ClampStateInputs) -> Self {
+        match self {
+            Self::BallPos(pos) => Self::BallPos(clamp_vec3(pos, clamp.bounds(&POS_BOUNDS))),
+            Self::BallVel(vel) => Self::BallVel(clamp_vec3(vel, clamp.bounds(&BALL_VEL_BOUNDS))),
+            Self::BallAngVel(ang_vel) => Self::BallAngVel(clamp_vec3(ang_vel, clamp.bounds(&BALL_ANG_VEL_BOUNDS))),
+            Self::CarPos(id, pos) => Self::CarPos(id, clamp_vec3(pos, clamp.bounds(&POS_BOUNDS))),
+            Self::CarVel(id, vel) => Self::CarVel(id, clamp_vec3(vel, clamp.bounds(&CAR_VEL_BOUNDS))),
+            Self::CarAngVel(id, ang_vel) => Self::CarAngVel(id, clamp_vec3(ang_vel, clamp.bounds(&CAR_ANG_VEL_BOUNDS))),
+            Self::CarBoost(id, boost) => Self::CarBoost(id, clamp_f32(boost, clamp.bounds(&BOOST_BOUNDS))),
+            Self::CarDemoRespawnTimer(id, timer) => {
+                Self::CarDemoRespawnTimer(id, clamp_f32(timer, clamp.bounds(&DEMO_RESPAWN_TIMER_BOUNDS)))
+            }
+            Self::Pad(index, is_active, cooldown) => {
+                Self::Pad(index, is_active, clamp_f32(cooldown, clamp.bounds(&PAD_TIMER_BOUNDS)))
+            }
+            Self::CarJumped(..) | Self::CarDoubleJumped(..) | Self::CarFlipped(..) => self,
+        }
+    }
+
+    /// Applies the action to both the current and next game states, clamped like input from the menu
+    pub fn apply(self, game_states: &mut GameStates, clamp: &ClampStateInputs) {
+        let GameStates { current, next, .. } = game_states;
+        let action = self.clamped(clamp);
+
+        match action {
+            Self::BallPos(pos) => {
+                current.ball.pos = pos;
+                next.ball.pos = pos;
+            }
+            Self::BallVel(vel) => {
+                current.ball.vel = vel;
+                next.ball.vel = vel;
+            }
+            Self::BallAngVel(ang_vel) => {
+                current.ball.ang_vel = ang_vel;
+                next.ball.ang_vel = ang_vel;
+            }
+            Self::Pad(index, is_active, cooldown) => {
+                for pad in [current.pads.get_mut(index), next.pads.get_mut(index)].into_iter().flatten() {
+                    pad.state.is_active = is_active;
+                    pad.state.cooldown = cooldown;
+                }
+            }
+            Self::CarPos(id, _)
+            | Self::CarVel(id, _)
+            | Self::CarAngVel(id, _)
+            | Self::CarJumped(id, _)
+            | Self::CarDoubleJumped(id, _)
+            | Self::CarFlipped(id, _)
+            | Self::CarBoost(id, _)
+            | Self::CarDemoRespawnTimer(id, _) => {
+                let cars = current.cars.iter_mut().chain(next.cars.iter_mut());
+
+                for car in cars.filter(|car| car.id == id) {
+                    let state = &mut car.state;
+
+                    match action {
+                        Self::CarPos(_, pos) => state.pos = pos,
+                        Self::CarVel(_, vel) => state.vel = vel,
+                        Self::CarAngVel(_, ang_vel) => state.ang_vel = ang_vel,
+                        Self::CarJumped(_, has_jumped) => state.has_jumped = has_jumped,
+                        Self::CarDoubleJumped(_, has_double_jumped) => state.has_double_jumped = has_double_jumped,
+                        Self::CarFlipped(_, has_flipped) => state.has_flipped = has_flipped,
+                        Self::CarBoost(_, boost) => state.boost = boost,
+                        Self::CarDemoRespawnTimer(_, timer) => {
+                            state.demo_respawn_timer = timer;
+                            if timer != 0. {
+                                state.is_demoed = true;
+                            }
+                        }
+                        _ => unreachable!(),
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Writes state setting actions to a script file as they happen
+#[derive(Resource, Default)]
+pub struct StateScriptRecorder(Option<(File, Instant)>);
+
+impl StateScriptRecorder {
+    const FOLDER: &'static str = "state_scripts";
+
+    #[inline]
+    pub const fn is_recording(&self) -> bool {
+        self.0.is_some()
+    }
+
+    pub fn toggle(&mut self) {
+        if self.0.take().is_some() {
+            return;
+        }
+
+        let path = Path::new(Self::FOLDER).join(format!("session_{}.txt", unix_timestamp()));
+        match fs::create_dir_all(Self::FOLDER).and_then(|()| File::create(&path)) {
+            Ok(file) => {
+                info!("Recording state setting actions to {}", path.display());
+                self.0 = Some((file, Instant::now()));
+            }
+            Err(e) => error!("Failed to create {} due to: {e}", path.display()),
+        }
+    }
+
+    pub fn record(&mut self, action: StateAction) {
+        let Some((file, start)) = &mut self.0 else {
+            return;
+        };
+
+        if let Err(e) = writeln!(file, "{:.2} {action}", start.elapsed().as_secs_f32()) {
+            error!("Failed to record state setting action due to: {e}");
+            self.0 = None;
+        }
+    }
+}
+
+#[derive(Event)]
+pub struct RunStateScript(pub String);
+
+/// The script given with `--replay <file>`, which is run once the simulation is connected
+#[derive(Resource, Default)]
+pub struct StartupStateScript(pub Option<String>);
+
+/// Actions from a script that are waiting to be applied, in order
+#[derive(Resource, Default)]
+pub struct StateScriptPlayback {
+    pub path: String,
+    actions: VecDeque<(f32, StateAction)>,
+    elapsed: f32,
+}
+
+fn read_state_script(path: &str) -> io::Result<VecDeque<(f32, StateAction)>> {
+    let file = fs::read_to_string(path)?;
+
+    let mut actions = VecDeque::new();
+    for (i, line) in file.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let parsed = line
+            .split_once(' ')
+            .and_then(|(time, action)| Some((parse_f32(time)?, action.parse().ok()?)));
+
+        match parsed {
+            Some(action) => actions.push_back(action),
+            None => warn!("Skipping invalid line {} in {path}: {line}", i + 1),
+        }
+    }
+
+    Ok(actions)
+}
+
+fn load_state_script(mut events: EventReader<RunStateScript>, mut playback: ResMut<StateScriptPlayback>) {
+    for event in events.read() {
+        match read_state_script(&event.0) {
+            Ok(actions) => {
+                info!("Running {} state setting actions from {}", actions.len(), event.0);
+                playback.actions = actions;
+                playback.elapsed = 0.;
+            }
+            Err(e) => error!("Failed to read state script {} due to: {e}", event.0),
+        }
+    }
+}
+
+fn run_startup_script(
+    mut startup: ResMut<StartupStateScript>,
+    mut playback: ResMut<StateScriptPlayback>,
+    mut run_script: EventWriter<RunStateScript>,
+) {
+    let Some(path) = startup.0.take() else {
+        return;
+    };
+
+    // show it in the menu, as if it was run from there
+    playback.path.clone_from(&path);
+    run_script.send(RunStateScript(path));
+}
+
+fn play_state_script(
+    time: Res<Time>,
+    mut playback: ResMut<StateScriptPlayback>,
+    mut game_states: ResMut<GameStates>,
+    clamp: Res<ClampStateInputs>,
+    socket: Res<Connection>,
+) {
+    playback.elapsed += time.delta_secs();
+
+    let mut changed = false;
+    while let Some(&(at, action)) = playback.actions.front() {
+        if at > playback.elapsed {
+            break;
+        }
+
+        action.apply(&mut game_states, &clamp);
+        playback.actions.pop_front();
+        changed = true;
+    }
+
+    if changed {
+        socket.send(SendableUdp::State(game_states.next.clone())).unwrap();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn state_action_round_trip() {
+        let actions = [
+            StateAction::BallPos(Vec3A::new(0., -2000., 93.15)),
+            StateAction::BallVel(Vec3A::new(1., 2., 3.)),
+            StateAction::BallAngVel(Vec3A::new(-1., 0.5, 0.)),
+            StateAction::CarPos(1, Vec3A::new(100., 200., 17.)),
+            StateAction::CarVel(2, Vec3A::new(0., 2300., 0.)),
+            StateAction::CarAngVel(3, Vec3A::new(0., 0., 5.5)),
+            StateAction::CarJumped(1, true),
+            StateAction::CarDoubleJumped(1, false),
+            StateAction::CarFlipped(1, true),
+            StateAction::CarBoost(4, 33.3),
+            StateAction::CarDemoRespawnTimer(4, 1.5),
+            StateAction::Pad(4, true, 0.),
+        ];
+
+        for action in actions {
+            assert_eq!(action.to_string().parse(), Ok(action), "{action}");
+        }
+    }
+
+    #[test]
+    fn reject_non_finite_numbers() {
+        for line in [
+            "ball pos NaN 0 0",
+            "ball vel 0 inf 0",
+            "car 1 ang_vel 0 0 -inf",
+            "car 1 boost NaN",
+            "car 1 demo_respawn_timer inf",
+            "pad 4 true NaN",
+        ] {
+            assert_eq!(line.parse::<StateAction>(), Err(()), "{line}");
+        }
+    }
+
+    #[test]
+    fn reject_malformed_lines() {
+        for line in [
+            "",
+            "ball",
+            "ball pos 0 0",
+            "ball pos 0 0 0 0",
+            "ball spin 0 0 0",
+            "car pos 0 0 0",
+            "car 1 boost 50 extra",
+            "car 1 has_jumped yes",
+            "pad -1 true 0",
+        ] {
+            assert_eq!(line.parse::<StateAction>(), Err(()), "{line}");
+        }
+    }
+
+    #[test]
+    fn clamp_state_actions() {
+        let clamp = ClampStateInputs(true);
+
+        assert_eq!(
+            StateAction::BallPos(Vec3A::new(9999., -9999., -50.)).clamped(&clamp),
+            StateAction::BallPos(Vec3A::new(4096., -6000., 0.))
+        );
+        assert_eq!(StateAction::CarBoost(1, 250.).clamped(&clamp), StateAction::CarBoost(1, 100.));
+        // nothing changes when clamping is turned off
+        assert_eq!(
+            StateAction::CarBoost(1, 250.).clamped(&ClampStateInputs(false)),
+            StateAction::CarBoost(1, 250.)
+        );
+    }
+}
@@ -1,10 +1,24 @@
-use super::options::MenuFocused;
+use super::{
+    keybinds::{Action, Keybinds},
+    options::{ClampStateInputs, MenuFocused, NudgeStep},
+    state_script::{StateAction, StateScriptRecorder},
+};
 use crate::{
+    bytes::{ToBytes, TryFromBytes},
+    camera::{HighlightedEntity, PrimaryCamera},
+    mesh::{can_state_set, StateSetTime},
     morton::Morton,
-    udp::{Connection, GameStates, SendableUdp},
+    rocketsim::GameState,
+    udp::{BoostPadI, Connection, DumpGameState, GameStates, SendableUdp},
+};
+use bevy::{math::Vec3A, prelude::*, time::Stopwatch, utils::HashMap};
+use bevy_egui::{egui, EguiClipboard, EguiContexts};
+use std::{
+    fs, io,
+    ops::RangeInclusive,
+    path::{Path, PathBuf},
+    time::Duration,
 };
-use bevy::{math::Vec3A, prelude::*, utils::HashMap};
-use bevy_egui::{egui, EguiContexts};
 
 pub struct StateSettingInterface;
 
@@ -16,19 +30,27 @@ impl Plugin for StateSettingInterface {
             .insert_resource(UserCarStates::default())
             .insert_resource(EnablePadInfo::default())
             .insert_resource(UserPadStates::default())
+            .insert_resource(EnableStateSnapshots::default())
+            .insert_resource(StateSnapshots::default())
+            .insert_resource(NudgeTarget::default())
             .add_event::<UserSetBallState>()
             .add_event::<UserSetCarState>()
             .add_event::<UserSetPadState>()
+            .add_event::<UserLoadStateSnapshot>()
             .add_systems(
                 Update,
                 (
                     update_ball_info.run_if(resource_equals(EnableBallInfo(true))),
                     update_car_info.run_if(|enable_menu: Res<EnableCarInfo>| !enable_menu.0.is_empty()),
                     update_boost_pad_info.run_if(|enable_menu: Res<EnablePadInfo>| !enable_menu.0.is_empty()),
+                    boost_pad_tooltip,
+                    update_state_snapshots.run_if(resource_equals(EnableStateSnapshots(true))),
                     (
                         set_user_ball_state.run_if(on_event::<UserSetBallState>),
                         set_user_car_state.run_if(on_event::<UserSetCarState>),
                         set_user_pad_state.run_if(on_event::<UserSetPadState>),
+                        load_state_snapshot.run_if(on_event::<UserLoadStateSnapshot>),
+                        nudge_focused.run_if(|nudge_target: Res<NudgeTarget>| nudge_target.0.is_some()),
                     )
                         .run_if(resource_exists::<Connection>),
                 )
@@ -37,16 +59,74 @@ impl Plugin for StateSettingInterface {
     }
 }
 
-fn set_f32_from_str(num: &mut f32, s: &str) {
-    if let Ok(f) = s.parse() {
-        *num = f;
+// Bounds that user input gets clamped to when `ClampStateInputs` is enabled
+pub(super) const POS_BOUNDS: [RangeInclusive<f32>; 3] = [-4096.0..=4096.0, -6000.0..=6000.0, 0.0..=2044.0];
+pub(super) const BALL_VEL_BOUNDS: [RangeInclusive<f32>; 3] = [-6000.0..=6000.0, -6000.0..=6000.0, -6000.0..=6000.0];
+pub(super) const BALL_ANG_VEL_BOUNDS: [RangeInclusive<f32>; 3] = [-6.0..=6.0, -6.0..=6.0, -6.0..=6.0];
+pub(super) const CAR_VEL_BOUNDS: [RangeInclusive<f32>; 3] = [-2300.0..=2300.0, -2300.0..=2300.0, -2300.0..=2300.0];
+pub(super) const CAR_ANG_VEL_BOUNDS: [RangeInclusive<f32>; 3] = [-5.5..=5.5, -5.5..=5.5, -5.5..=5.5];
+pub(super) const BOOST_BOUNDS: RangeInclusive<f32> = 0.0..=100.0;
+pub(super) const DEMO_RESPAWN_TIMER_BOUNDS: RangeInclusive<f32> = 0.0..=3.0;
+pub(super) const PAD_TIMER_BOUNDS: RangeInclusive<f32> = 0.0..=10.0;
+
+/// Parses a number, rejecting NaN and infinity so they never reach the simulation
+pub(super) fn parse_f32(s: &str) -> Option<f32> {
+    s.parse().ok().filter(|f: &f32| f.is_finite())
+}
+
+pub(super) fn clamp_f32(f: f32, bounds: Option<&RangeInclusive<f32>>) -> f32 {
+    bounds.map_or(f, |bounds| f.clamp(*bounds.start(), *bounds.end()))
+}
+
+pub(super) fn clamp_vec3(vec: Vec3A, bounds: Option<&[RangeInclusive<f32>; 3]>) -> Vec3A {
+    let Some(bounds) = bounds else {
+        return vec;
+    };
+
+    Vec3A::new(
+        clamp_f32(vec.x, Some(&bounds[0])),
+        clamp_f32(vec.y, Some(&bounds[1])),
+        clamp_f32(vec.z, Some(&bounds[2])),
+    )
+}
+
+fn set_f32_from_str(num: &mut f32, s: &str, bounds: Option<&RangeInclusive<f32>>) {
+    if let Some(f) = parse_f32(s) {
+        *num = clamp_f32(f, bounds);
     }
 }
 
-fn set_vec3_from_arr_str(vec: &mut Vec3A, arr: &[String; 3]) {
-    set_f32_from_str(&mut vec.x, &arr[0]);
-    set_f32_from_str(&mut vec.y, &arr[1]);
-    set_f32_from_str(&mut vec.z, &arr[2]);
+fn set_vec3_from_arr_str(vec: &mut Vec3A, arr: &[String; 3], bounds: Option<&[RangeInclusive<f32>; 3]>) {
+    set_f32_from_str(&mut vec.x, &arr[0], bounds.map(|bounds| &bounds[0]));
+    set_f32_from_str(&mut vec.y, &arr[1], bounds.map(|bounds| &bounds[1]));
+    set_f32_from_str(&mut vec.z, &arr[2], bounds.map(|bounds| &bounds[2]));
+}
+
+/// A text box for a number that's highlighted when the input will be ignored or clamped
+fn f32_text_edit(ui: &mut egui::Ui, text: &mut String, width: f32, bounds: Option<&RangeInclusive<f32>>) {
+    let warning = if text.is_empty() {
+        None
+    } else {
+        match parse_f32(text) {
+            Some(f) => bounds.filter(|bounds| !bounds.contains(&f)).map(|bounds| {
+                (
+                    egui::Color32::YELLOW,
+                    format!("Will be clamped to {} - {}", bounds.start(), bounds.end()),
+                )
+            }),
+            None => Some((egui::Color32::RED, String::from("Not a valid number, will be ignored"))),
+        }
+    };
+
+    let response = ui.add(
+        egui::TextEdit::singleline(text)
+            .desired_width(width)
+            .text_color_opt(warning.as_ref().map(|(color, _)| *color)),
+    );
+
+    if let Some((_, warning)) = warning {
+        response.on_hover_text(warning);
+    }
 }
 
 fn set_half_bool_from_usize(b: &mut bool, i: usize) {
@@ -112,6 +192,7 @@ fn update_boost_pad_info(
     mut enable_menu: ResMut<EnablePadInfo>,
     mut set_user_state: EventWriter<UserSetPadState>,
     mut user_pads: ResMut<UserPadStates>,
+    clamp: Res<ClampStateInputs>,
 ) {
     const USER_BOOL_NAMES: [&str; 3] = ["", "True", "False"];
 
@@ -149,7 +230,7 @@ fn update_boost_pad_info(
                 });
                 ui.vertical(|ui| {
                     ui.label(format!("Timer: {:.1}", pad.state.cooldown));
-                    ui.add(egui::TextEdit::singleline(&mut user_pad.timer).desired_width(60.));
+                    f32_text_edit(ui, &mut user_pad.timer, 60., clamp.bounds(&PAD_TIMER_BOUNDS));
                 });
             });
 
@@ -164,11 +245,67 @@ fn update_boost_pad_info(
     }
 }
 
+/// How far the tooltip is from the center of the hovered boost pad, in pixels
+const PAD_TOOLTIP_OFFSET: egui::Vec2 = egui::vec2(16., -16.);
+
+/// Shows a quick summary of the boost pad under the cursor next to it, without opening its window
+fn boost_pad_tooltip(
+    mut contexts: EguiContexts,
+    game_states: Res<GameStates>,
+    hovered_pads: Query<(&GlobalTransform, &BoostPadI), With<HighlightedEntity>>,
+    camera: Query<(&Camera, &GlobalTransform), With<PrimaryCamera>>,
+) {
+    let Some((pad_transform, pad_id)) = hovered_pads.iter().next() else {
+        return;
+    };
+
+    let Ok((camera, camera_transform)) = camera.get_single() else {
+        return;
+    };
+
+    let Ok(screen_pos) = camera.world_to_viewport(camera_transform, pad_transform.translation()) else {
+        return;
+    };
+
+    let morton_generator = Morton::default();
+    let Some((i, pad)) = game_states
+        .current
+        .pads
+        .iter()
+        .enumerate()
+        .find(|(_, pad)| morton_generator.get_code(pad.position) == pad_id.id())
+    else {
+        return;
+    };
+
+    egui::Area::new(egui::Id::new("boost_pad_tooltip"))
+        .fixed_pos(egui::pos2(screen_pos.x, screen_pos.y) + PAD_TOOLTIP_OFFSET)
+        .order(egui::Order::Tooltip)
+        .interactable(false)
+        .show(contexts.ctx_mut(), |ui| {
+            egui::Frame::popup(ui.style()).show(ui, |ui| {
+                ui.strong(format!("Boost pad {i} ({})", if pad.is_big { "big" } else { "small" }));
+                ui.label(format!(
+                    "Position: [{:.0}, {:.0}, {:.0}]",
+                    pad.position.x, pad.position.y, pad.position.z
+                ));
+
+                if pad.state.is_active {
+                    ui.label("Active");
+                } else {
+                    ui.label(format!("Cooldown: {:.1}s", pad.state.cooldown));
+                }
+            });
+        });
+}
+
 fn set_user_pad_state(
     mut events: EventReader<UserSetPadState>,
     mut game_states: ResMut<GameStates>,
     user_pads: Res<UserPadStates>,
     socket: Res<Connection>,
+    clamp: Res<ClampStateInputs>,
+    mut recorder: ResMut<StateScriptRecorder>,
 ) {
     let morton_generator = Morton::default();
     let mut sorted_pads = game_states
@@ -193,7 +330,7 @@ fn set_user_pad_state(
             let pad = &mut game_states.current.pads[sorted_pads[index].0];
 
             set_bool_from_usize(&mut pad.state.is_active, user_pad.is_active);
-            set_f32_from_str(&mut pad.state.cooldown, &user_pad.timer);
+            set_f32_from_str(&mut pad.state.cooldown, &user_pad.timer, clamp.bounds(&PAD_TIMER_BOUNDS));
 
             (pad.state.is_active, pad.state.cooldown)
         };
@@ -201,6 +338,8 @@ fn set_user_pad_state(
         let pad = &mut game_states.next.pads[sorted_pads[index].0];
         pad.state.is_active = is_active;
         pad.state.cooldown = cooldown;
+
+        recorder.record(StateAction::Pad(sorted_pads[index].0, is_active, cooldown));
     }
 
     socket.send(SendableUdp::State(game_states.next.clone())).unwrap();
@@ -237,30 +376,54 @@ fn set_user_ball_state(
     mut game_states: ResMut<GameStates>,
     user_ball: Res<UserBallState>,
     socket: Res<Connection>,
+    clamp: Res<ClampStateInputs>,
+    mut recorder: ResMut<StateScriptRecorder>,
 ) {
     for event in events.read() {
         match event.0 {
             SetBallStateAmount::Pos => {
-                set_vec3_from_arr_str(&mut game_states.current.ball.pos, &user_ball.pos);
+                set_vec3_from_arr_str(&mut game_states.current.ball.pos, &user_ball.pos, clamp.bounds(&POS_BOUNDS));
                 game_states.next.ball.pos = game_states.current.ball.pos;
+                recorder.record(StateAction::BallPos(game_states.current.ball.pos));
             }
             SetBallStateAmount::Vel => {
-                set_vec3_from_arr_str(&mut game_states.current.ball.vel, &user_ball.vel);
+                set_vec3_from_arr_str(
+                    &mut game_states.current.ball.vel,
+                    &user_ball.vel,
+                    clamp.bounds(&BALL_VEL_BOUNDS),
+                );
                 game_states.next.ball.vel = game_states.current.ball.vel;
+                recorder.record(StateAction::BallVel(game_states.current.ball.vel));
             }
             SetBallStateAmount::AngVel => {
-                set_vec3_from_arr_str(&mut game_states.current.ball.ang_vel, &user_ball.ang_vel);
+                set_vec3_from_arr_str(
+                    &mut game_states.current.ball.ang_vel,
+                    &user_ball.ang_vel,
+                    clamp.bounds(&BALL_ANG_VEL_BOUNDS),
+                );
                 game_states.next.ball.ang_vel = game_states.current.ball.ang_vel;
+                recorder.record(StateAction::BallAngVel(game_states.current.ball.ang_vel));
             }
             SetBallStateAmount::All => {
-                set_vec3_from_arr_str(&mut game_states.current.ball.pos, &user_ball.pos);
+                set_vec3_from_arr_str(&mut game_states.current.ball.pos, &user_ball.pos, clamp.bounds(&POS_BOUNDS));
                 game_states.next.ball.pos = game_states.current.ball.pos;
+                recorder.record(StateAction::BallPos(game_states.current.ball.pos));
 
-                set_vec3_from_arr_str(&mut game_states.current.ball.vel, &user_ball.vel);
+                set_vec3_from_arr_str(
+                    &mut game_states.current.ball.vel,
+                    &user_ball.vel,
+                    clamp.bounds(&BALL_VEL_BOUNDS),
+                );
                 game_states.next.ball.vel = game_states.current.ball.vel;
+                recorder.record(StateAction::BallVel(game_states.current.ball.vel));
 
-                set_vec3_from_arr_str(&mut game_states.current.ball.ang_vel, &user_ball.ang_vel);
+                set_vec3_from_arr_str(
+                    &mut game_states.current.ball.ang_vel,
+                    &user_ball.ang_vel,
+                    clamp.bounds(&BALL_ANG_VEL_BOUNDS),
+                );
                 game_states.next.ball.ang_vel = game_states.current.ball.ang_vel;
+                recorder.record(StateAction::BallAngVel(game_states.current.ball.ang_vel));
             }
         }
     }
@@ -274,63 +437,69 @@ fn update_ball_info(
     mut enable_menu: ResMut<EnableBallInfo>,
     mut set_user_state: EventWriter<UserSetBallState>,
     mut user_ball: ResMut<UserBallState>,
+    mut nudge_target: ResMut<NudgeTarget>,
+    clamp: Res<ClampStateInputs>,
 ) {
-    egui::Window::new("Ball")
-        .open(&mut enable_menu.0)
-        .show(contexts.ctx_mut(), |ui| {
-            ui.label(format!(
-                "Position: [{:.1}, {:.1}, {:.1}]",
-                game_states.current.ball.pos.x, game_states.current.ball.pos.y, game_states.current.ball.pos.z
-            ));
-            ui.horizontal(|ui| {
-                ui.label("X: ");
-                ui.add(egui::TextEdit::singleline(&mut user_ball.pos[0]).desired_width(50.));
-                ui.label("Y: ");
-                ui.add(egui::TextEdit::singleline(&mut user_ball.pos[1]).desired_width(50.));
-                ui.label("Z: ");
-                ui.add(egui::TextEdit::singleline(&mut user_ball.pos[2]).desired_width(50.));
-                if ui.button("Set").on_hover_text("Set ball position").clicked() {
-                    set_user_state.send(UserSetBallState(SetBallStateAmount::Pos));
-                }
-            });
-            ui.label(format!(
-                "Velocity: [{:.1}, {:.1}, {:.1}]",
-                game_states.current.ball.vel.x, game_states.current.ball.vel.y, game_states.current.ball.vel.z
-            ));
-            ui.horizontal(|ui| {
-                ui.label("X: ");
-                ui.add(egui::TextEdit::singleline(&mut user_ball.vel[0]).desired_width(50.));
-                ui.label("Y: ");
-                ui.add(egui::TextEdit::singleline(&mut user_ball.vel[1]).desired_width(50.));
-                ui.label("Z: ");
-                ui.add(egui::TextEdit::singleline(&mut user_ball.vel[2]).desired_width(50.));
-                if ui.button("Set").on_hover_text("Set ball velocity").clicked() {
-                    set_user_state.send(UserSetBallState(SetBallStateAmount::Vel));
-                }
-            });
-            ui.label(format!(
-                "Angular velocity: [{:.1}, {:.1}, {:.1}]",
-                game_states.current.ball.ang_vel.x, game_states.current.ball.ang_vel.y, game_states.current.ball.ang_vel.z
-            ));
-            ui.horizontal(|ui| {
-                ui.label("X: ");
-                ui.add(egui::TextEdit::singleline(&mut user_ball.ang_vel[0]).desired_width(50.));
-                ui.label("Y: ");
-                ui.add(egui::TextEdit::singleline(&mut user_ball.ang_vel[1]).desired_width(50.));
-                ui.label("Z: ");
-                ui.add(egui::TextEdit::singleline(&mut user_ball.ang_vel[2]).desired_width(50.));
-                if ui.button("Set").on_hover_text("Set ball angular velocity").clicked() {
-                    set_user_state.send(UserSetBallState(SetBallStateAmount::AngVel));
-                }
-            });
-            if ui
-                .button("     Set all     ")
-                .on_hover_text("Set all (defined) ball properties")
-                .clicked()
-            {
-                set_user_state.send(UserSetBallState(SetBallStateAmount::All));
+    let ctx = contexts.ctx_mut();
+
+    let window = egui::Window::new("Ball").open(&mut enable_menu.0).show(ctx, |ui| {
+        ui.label(format!(
+            "Position: [{:.1}, {:.1}, {:.1}]",
+            game_states.current.ball.pos.x, game_states.current.ball.pos.y, game_states.current.ball.pos.z
+        ));
+        ui.horizontal(|ui| {
+            ui.label("X: ");
+            f32_text_edit(ui, &mut user_ball.pos[0], 50., clamp.bounds(&POS_BOUNDS[0]));
+            ui.label("Y: ");
+            f32_text_edit(ui, &mut user_ball.pos[1], 50., clamp.bounds(&POS_BOUNDS[1]));
+            ui.label("Z: ");
+            f32_text_edit(ui, &mut user_ball.pos[2], 50., clamp.bounds(&POS_BOUNDS[2]));
+            if ui.button("Set").on_hover_text("Set ball position").clicked() {
+                set_user_state.send(UserSetBallState(SetBallStateAmount::Pos));
             }
         });
+        ui.label(format!(
+            "Velocity: [{:.1}, {:.1}, {:.1}]",
+            game_states.current.ball.vel.x, game_states.current.ball.vel.y, game_states.current.ball.vel.z
+        ));
+        ui.horizontal(|ui| {
+            ui.label("X: ");
+            f32_text_edit(ui, &mut user_ball.vel[0], 50., clamp.bounds(&BALL_VEL_BOUNDS[0]));
+            ui.label("Y: ");
+            f32_text_edit(ui, &mut user_ball.vel[1], 50., clamp.bounds(&BALL_VEL_BOUNDS[1]));
+            ui.label("Z: ");
+            f32_text_edit(ui, &mut user_ball.vel[2], 50., clamp.bounds(&BALL_VEL_BOUNDS[2]));
+            if ui.button("Set").on_hover_text("Set ball velocity").clicked() {
+                set_user_state.send(UserSetBallState(SetBallStateAmount::Vel));
+            }
+        });
+        ui.label(format!(
+            "Angular velocity: [{:.1}, {:.1}, {:.1}]",
+            game_states.current.ball.ang_vel.x, game_states.current.ball.ang_vel.y, game_states.current.ball.ang_vel.z
+        ));
+        ui.horizontal(|ui| {
+            ui.label("X: ");
+            f32_text_edit(ui, &mut user_ball.ang_vel[0], 50., clamp.bounds(&BALL_ANG_VEL_BOUNDS[0]));
+            ui.label("Y: ");
+            f32_text_edit(ui, &mut user_ball.ang_vel[1], 50., clamp.bounds(&BALL_ANG_VEL_BOUNDS[1]));
+            ui.label("Z: ");
+            f32_text_edit(ui, &mut user_ball.ang_vel[2], 50., clamp.bounds(&BALL_ANG_VEL_BOUNDS[2]));
+            if ui.button("Set").on_hover_text("Set ball angular velocity").clicked() {
+                set_user_state.send(UserSetBallState(SetBallStateAmount::AngVel));
+            }
+        });
+        if ui
+            .button("     Set all     ")
+            .on_hover_text("Set all (defined) ball properties")
+            .clicked()
+        {
+            set_user_state.send(UserSetBallState(SetBallStateAmount::All));
+        }
+    });
+
+    if window.is_some_and(|window| ctx.top_layer_id() == Some(window.response.layer_id)) {
+        nudge_target.0 = Some(Nudged::Ball);
+    }
 }
 
 #[derive(Event)]
@@ -405,6 +574,8 @@ fn set_user_car_state(
     mut game_states: ResMut<GameStates>,
     user_cars: Res<UserCarStates>,
     socket: Res<Connection>,
+    clamp: Res<ClampStateInputs>,
+    mut recorder: ResMut<StateScriptRecorder>,
 ) {
     for event in events.read() {
         let Some(car_index) = game_states.current.cars.iter().position(|car| car.id == event.0) else {
@@ -416,20 +587,42 @@ fn set_user_car_state(
 
         match event.1 {
             SetCarStateAmount::Pos => {
-                set_vec3_from_arr_str(&mut game_states.current.cars[car_index].state.pos, &user_car.pos);
+                set_vec3_from_arr_str(
+                    &mut game_states.current.cars[car_index].state.pos,
+                    &user_car.pos,
+                    clamp.bounds(&POS_BOUNDS),
+                );
                 game_states.next.cars[car_index].state.pos = game_states.current.cars[car_index].state.pos;
+                recorder.record(StateAction::CarPos(event.0, game_states.current.cars[car_index].state.pos));
             }
             SetCarStateAmount::Vel => {
-                set_vec3_from_arr_str(&mut game_states.current.cars[car_index].state.vel, &user_car.vel);
+                set_vec3_from_arr_str(
+                    &mut game_states.current.cars[car_index].state.vel,
+                    &user_car.vel,
+                    clamp.bounds(&CAR_VEL_BOUNDS),
+                );
                 game_states.next.cars[car_index].state.vel = game_states.current.cars[car_index].state.vel;
+                recorder.record(StateAction::CarVel(event.0, game_states.current.cars[car_index].state.vel));
             }
             SetCarStateAmount::AngVel => {
-                set_vec3_from_arr_str(&mut game_states.current.cars[car_index].state.ang_vel, &user_car.ang_vel);
+                set_vec3_from_arr_str(
+                    &mut game_states.current.cars[car_index].state.ang_vel,
+                    &user_car.ang_vel,
+                    clamp.bounds(&CAR_ANG_VEL_BOUNDS),
+                );
                 game_states.next.cars[car_index].state.ang_vel = game_states.current.cars[car_index].state.ang_vel;
+                recorder.record(StateAction::CarAngVel(
+                    event.0,
+                    game_states.current.cars[car_index].state.ang_vel,
+                ));
             }
             SetCarStateAmount::Jumped => {
                 set_half_bool_from_usize(&mut game_states.current.cars[car_index].state.has_jumped, user_car.has_jumped);
                 set_half_bool_from_usize(&mut game_states.next.cars[car_index].state.has_jumped, user_car.has_jumped);
+                recorder.record(StateAction::CarJumped(
+                    event.0,
+                    game_states.current.cars[car_index].state.has_jumped,
+                ));
             }
             SetCarStateAmount::DoubleJumped => {
                 set_half_bool_from_usize(
@@ -438,6 +631,10 @@ fn set_user_car_state(
                 );
                 game_states.next.cars[car_index].state.has_double_jumped =
                     game_states.current.cars[car_index].state.has_double_jumped;
+                recorder.record(StateAction::CarDoubleJumped(
+                    event.0,
+                    game_states.current.cars[car_index].state.has_double_jumped,
+                ));
             }
             SetCarStateAmount::Flipped => {
                 set_half_bool_from_usize(
@@ -445,18 +642,35 @@ fn set_user_car_state(
                     user_car.has_flipped,
                 );
                 game_states.next.cars[car_index].state.has_flipped = game_states.current.cars[car_index].state.has_flipped;
+                recorder.record(StateAction::CarFlipped(
+                    event.0,
+                    game_states.current.cars[car_index].state.has_flipped,
+                ));
             }
             SetCarStateAmount::Boost => {
-                set_f32_from_str(&mut game_states.current.cars[car_index].state.boost, &user_car.boost);
+                set_f32_from_str(
+                    &mut game_states.current.cars[car_index].state.boost,
+                    &user_car.boost,
+                    clamp.bounds(&BOOST_BOUNDS),
+                );
                 game_states.next.cars[car_index].state.boost = game_states.current.cars[car_index].state.boost;
+                recorder.record(StateAction::CarBoost(
+                    event.0,
+                    game_states.current.cars[car_index].state.boost,
+                ));
             }
             SetCarStateAmount::DemoRespawnTimer => {
                 set_f32_from_str(
                     &mut game_states.current.cars[car_index].state.demo_respawn_timer,
                     &user_car.demo_respawn_timer,
+                    clamp.bounds(&DEMO_RESPAWN_TIMER_BOUNDS),
                 );
                 game_states.next.cars[car_index].state.demo_respawn_timer =
                     game_states.current.cars[car_index].state.demo_respawn_timer;
+                recorder.record(StateAction::CarDemoRespawnTimer(
+                    event.0,
+                    game_states.current.cars[car_index].state.demo_respawn_timer,
+                ));
 
                 if game_states.current.cars[car_index].state.demo_respawn_timer != 0. {
                     game_states.current.cars[car_index].state.is_demoed = true;
@@ -464,17 +678,39 @@ fn set_user_car_state(
                 }
             }
             SetCarStateAmount::All => {
-                set_vec3_from_arr_str(&mut game_states.current.cars[car_index].state.pos, &user_car.pos);
+                set_vec3_from_arr_str(
+                    &mut game_states.current.cars[car_index].state.pos,
+                    &user_car.pos,
+                    clamp.bounds(&POS_BOUNDS),
+                );
                 game_states.next.cars[car_index].state.pos = game_states.current.cars[car_index].state.pos;
+                recorder.record(StateAction::CarPos(event.0, game_states.current.cars[car_index].state.pos));
 
-                set_vec3_from_arr_str(&mut game_states.current.cars[car_index].state.vel, &user_car.vel);
+                set_vec3_from_arr_str(
+                    &mut game_states.current.cars[car_index].state.vel,
+                    &user_car.vel,
+                    clamp.bounds(&CAR_VEL_BOUNDS),
+                );
                 game_states.next.cars[car_index].state.vel = game_states.current.cars[car_index].state.vel;
+                recorder.record(StateAction::CarVel(event.0, game_states.current.cars[car_index].state.vel));
 
-                set_vec3_from_arr_str(&mut game_states.current.cars[car_index].state.ang_vel, &user_car.ang_vel);
+                set_vec3_from_arr_str(
+                    &mut game_states.current.cars[car_index].state.ang_vel,
+                    &user_car.ang_vel,
+                    clamp.bounds(&CAR_ANG_VEL_BOUNDS),
+                );
                 game_states.next.cars[car_index].state.ang_vel = game_states.current.cars[car_index].state.ang_vel;
+                recorder.record(StateAction::CarAngVel(
+                    event.0,
+                    game_states.current.cars[car_index].state.ang_vel,
+                ));
 
                 set_half_bool_from_usize(&mut game_states.current.cars[car_index].state.has_jumped, user_car.has_jumped);
                 game_states.next.cars[car_index].state.has_jumped = game_states.current.cars[car_index].state.has_jumped;
+                recorder.record(StateAction::CarJumped(
+                    event.0,
+                    game_states.current.cars[car_index].state.has_jumped,
+                ));
 
                 set_half_bool_from_usize(
                     &mut game_states.current.cars[car_index].state.has_double_jumped,
@@ -482,22 +718,43 @@ fn set_user_car_state(
                 );
                 game_states.next.cars[car_index].state.has_double_jumped =
                     game_states.current.cars[car_index].state.has_double_jumped;
+                recorder.record(StateAction::CarDoubleJumped(
+                    event.0,
+                    game_states.current.cars[car_index].state.has_double_jumped,
+                ));
 
                 set_half_bool_from_usize(
                     &mut game_states.current.cars[car_index].state.has_flipped,
                     user_car.has_flipped,
                 );
                 game_states.next.cars[car_index].state.has_flipped = game_states.current.cars[car_index].state.has_flipped;
+                recorder.record(StateAction::CarFlipped(
+                    event.0,
+                    game_states.current.cars[car_index].state.has_flipped,
+                ));
 
-                set_f32_from_str(&mut game_states.current.cars[car_index].state.boost, &user_car.boost);
+                set_f32_from_str(
+                    &mut game_states.current.cars[car_index].state.boost,
+                    &user_car.boost,
+                    clamp.bounds(&BOOST_BOUNDS),
+                );
                 game_states.next.cars[car_index].state.boost = game_states.current.cars[car_index].state.boost;
+                recorder.record(StateAction::CarBoost(
+                    event.0,
+                    game_states.current.cars[car_index].state.boost,
+                ));
 
                 set_f32_from_str(
                     &mut game_states.current.cars[car_index].state.demo_respawn_timer,
                     &user_car.demo_respawn_timer,
+                    clamp.bounds(&DEMO_RESPAWN_TIMER_BOUNDS),
                 );
                 game_states.next.cars[car_index].state.demo_respawn_timer =
                     game_states.current.cars[car_index].state.demo_respawn_timer;
+                recorder.record(StateAction::CarDemoRespawnTimer(
+                    event.0,
+                    game_states.current.cars[car_index].state.demo_respawn_timer,
+                ));
 
                 if game_states.current.cars[car_index].state.demo_respawn_timer != 0. {
                     game_states.current.cars[car_index].state.is_demoed = true;
@@ -516,6 +773,8 @@ fn update_car_info(
     mut enable_menu: ResMut<EnableCarInfo>,
     mut set_user_state: EventWriter<UserSetCarState>,
     mut user_cars: ResMut<UserCarStates>,
+    mut nudge_target: ResMut<NudgeTarget>,
+    clamp: Res<ClampStateInputs>,
 ) {
     const USER_BOOL_NAMES: [&str; 2] = ["", "False"];
 
@@ -532,7 +791,7 @@ fn update_car_info(
 
         let user_car = user_cars.0.entry(car.id).or_default();
 
-        egui::Window::new(format!("{:?} Car {}", car.team, car.id))
+        let window = egui::Window::new(format!("{:?} Car {}", car.team, car.id))
             .open(entry)
             .show(ctx, |ui| {
                 ui.horizontal(|ui| {
@@ -559,11 +818,11 @@ fn update_car_info(
                         ));
                         ui.horizontal(|ui| {
                             ui.label("X: ");
-                            ui.add(egui::TextEdit::singleline(&mut user_car.pos[0]).desired_width(50.));
+                            f32_text_edit(ui, &mut user_car.pos[0], 50., clamp.bounds(&POS_BOUNDS[0]));
                             ui.label("Y: ");
-                            ui.add(egui::TextEdit::singleline(&mut user_car.pos[1]).desired_width(50.));
+                            f32_text_edit(ui, &mut user_car.pos[1], 50., clamp.bounds(&POS_BOUNDS[1]));
                             ui.label("Z: ");
-                            ui.add(egui::TextEdit::singleline(&mut user_car.pos[2]).desired_width(50.));
+                            f32_text_edit(ui, &mut user_car.pos[2], 50., clamp.bounds(&POS_BOUNDS[2]));
                             if ui.button("Set").on_hover_text("Set car position").clicked() {
                                 set_user_state.send(UserSetCarState(car.id, SetCarStateAmount::Pos));
                             }
@@ -575,11 +834,11 @@ fn update_car_info(
                         ));
                         ui.horizontal(|ui| {
                             ui.label("X: ");
-                            ui.add(egui::TextEdit::singleline(&mut user_car.vel[0]).desired_width(50.));
+                            f32_text_edit(ui, &mut user_car.vel[0], 50., clamp.bounds(&CAR_VEL_BOUNDS[0]));
                             ui.label("Y: ");
-                            ui.add(egui::TextEdit::singleline(&mut user_car.vel[1]).desired_width(50.));
+                            f32_text_edit(ui, &mut user_car.vel[1], 50., clamp.bounds(&CAR_VEL_BOUNDS[1]));
                             ui.label("Z: ");
-                            ui.add(egui::TextEdit::singleline(&mut user_car.vel[2]).desired_width(50.));
+                            f32_text_edit(ui, &mut user_car.vel[2], 50., clamp.bounds(&CAR_VEL_BOUNDS[2]));
                             if ui.button("Set").on_hover_text("Set car velocity").clicked() {
                                 set_user_state.send(UserSetCarState(car.id, SetCarStateAmount::Vel));
                             }
@@ -591,11 +850,11 @@ fn update_car_info(
                         ));
                         ui.horizontal(|ui| {
                             ui.label("X: ");
-                            ui.add(egui::TextEdit::singleline(&mut user_car.ang_vel[0]).desired_width(50.));
+                            f32_text_edit(ui, &mut user_car.ang_vel[0], 50., clamp.bounds(&CAR_ANG_VEL_BOUNDS[0]));
                             ui.label("Y: ");
-                            ui.add(egui::TextEdit::singleline(&mut user_car.ang_vel[1]).desired_width(50.));
+                            f32_text_edit(ui, &mut user_car.ang_vel[1], 50., clamp.bounds(&CAR_ANG_VEL_BOUNDS[1]));
                             ui.label("Z: ");
-                            ui.add(egui::TextEdit::singleline(&mut user_car.ang_vel[2]).desired_width(50.));
+                            f32_text_edit(ui, &mut user_car.ang_vel[2], 50., clamp.bounds(&CAR_ANG_VEL_BOUNDS[2]));
                             if ui.button("Set").on_hover_text("Set car angular velocity").clicked() {
                                 set_user_state.send(UserSetCarState(car.id, SetCarStateAmount::AngVel));
                             }
@@ -658,7 +917,7 @@ fn update_car_info(
 
                                 ui.label(format!("Boost: {:.0}", car.state.boost));
                                 ui.horizontal(|ui| {
-                                    ui.add(egui::TextEdit::singleline(&mut user_car.boost).desired_width(60.));
+                                    f32_text_edit(ui, &mut user_car.boost, 60., clamp.bounds(&BOOST_BOUNDS));
                                     if ui.button("Set").on_hover_text("Set car boost").clicked() {
                                         set_user_state.send(UserSetCarState(car.id, SetCarStateAmount::Boost));
                                     }
@@ -666,7 +925,12 @@ fn update_car_info(
 
                                 ui.label(format!("Demo respawn timer: {:.1}", car.state.demo_respawn_timer));
                                 ui.horizontal(|ui| {
-                                    ui.add(egui::TextEdit::singleline(&mut user_car.demo_respawn_timer).desired_width(60.));
+                                    f32_text_edit(
+                                        ui,
+                                        &mut user_car.demo_respawn_timer,
+                                        60.,
+                                        clamp.bounds(&DEMO_RESPAWN_TIMER_BOUNDS),
+                                    );
                                     if ui.button("Set").on_hover_text("Set car demo respawn timer").clicked() {
                                         set_user_state.send(UserSetCarState(car.id, SetCarStateAmount::DemoRespawnTimer));
                                     }
@@ -692,5 +956,331 @@ fn update_car_info(
                     });
                 });
             });
+
+        if window.is_some_and(|window| ctx.top_layer_id() == Some(window.response.layer_id)) {
+            nudge_target.0 = Some(Nudged::Car(car.id));
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Nudged {
+    Ball,
+    Car(u32),
+}
+
+/// The ball or car whose info window was focused last, which the nudge keys move around
+#[derive(Resource, Default)]
+struct NudgeTarget(Option<Nudged>);
+
+/// The actions that nudge the focused ball or car, and the direction they move it in
+const NUDGE_ACTIONS: [(Action, Vec3A); 6] = [
+    (Action::NudgeLeft, Vec3A::NEG_X),
+    (Action::NudgeRight, Vec3A::X),
+    (Action::NudgeForward, Vec3A::Y),
+    (Action::NudgeBackward, Vec3A::NEG_Y),
+    (Action::NudgeUp, Vec3A::Z),
+    (Action::NudgeDown, Vec3A::NEG_Z),
+];
+
+/// How long a nudge key has to be held before it starts repeating
+const NUDGE_REPEAT_DELAY: Duration = Duration::from_millis(400);
+
+fn nudge_focused(
+    time: Res<Time>,
+    keys: Res<ButtonInput<KeyCode>>,
+    keybinds: Res<Keybinds>,
+    mut contexts: EguiContexts,
+    nudge_target: Res<NudgeTarget>,
+    (enable_ball_info, enable_car_info): (Res<EnableBallInfo>, Res<EnableCarInfo>),
+    nudge_step: Res<NudgeStep>,
+    clamp: Res<ClampStateInputs>,
+    mut game_states: ResMut<GameStates>,
+    mut recorder: ResMut<StateScriptRecorder>,
+    mut last_state_set: ResMut<StateSetTime>,
+    mut held: Local<Stopwatch>,
+    socket: Res<Connection>,
+) {
+    // the arrow keys also move the cursor in text fields
+    if contexts.ctx_mut().wants_keyboard_input() {
+        return;
+    }
+
+    let Some(target) = nudge_target.0 else {
+        return;
+    };
+
+    let is_open = match target {
+        Nudged::Ball => enable_ball_info.0,
+        Nudged::Car(id) => enable_car_info.0.get(&id).copied().unwrap_or_default(),
+    };
+
+    if !is_open {
+        return;
+    }
+
+    let direction = NUDGE_ACTIONS
+        .into_iter()
+        .filter(|&(action, _)| keybinds.pressed(&keys, action))
+        .map(|(_, direction)| direction)
+        .sum::<Vec3A>();
+
+    if direction == Vec3A::ZERO {
+        held.reset();
+        return;
+    }
+
+    if NUDGE_ACTIONS
+        .into_iter()
+        .any(|(action, _)| keybinds.just_pressed(&keys, action))
+    {
+        held.reset();
+    } else {
+        // holding a key down keeps nudging after a moment, like typing
+        held.tick(time.delta());
+        if held.elapsed() < NUDGE_REPEAT_DELAY || !can_state_set(&last_state_set) {
+            return;
+        }
+    }
+
+    let mut step = nudge_step.0;
+    if keybinds.pressed(&keys, Action::NudgeCoarse) {
+        step *= 10.;
+    } else if keybinds.pressed(&keys, Action::NudgeFine) {
+        step *= 0.1;
+    }
+
+    let offset = direction * step;
+    match target {
+        Nudged::Ball => {
+            let pos = clamp_vec3(game_states.current.ball.pos + offset, clamp.bounds(&POS_BOUNDS));
+            game_states.current.ball.pos = pos;
+            game_states.next.ball.pos = pos;
+            recorder.record(StateAction::BallPos(pos));
+        }
+        Nudged::Car(id) => {
+            let Some(car) = game_states.current.cars.iter_mut().find(|car| car.id == id) else {
+                return;
+            };
+
+            let pos = clamp_vec3(car.state.pos + offset, clamp.bounds(&POS_BOUNDS));
+            car.state.pos = pos;
+            if let Some(next_car) = game_states.next.cars.iter_mut().find(|car| car.id == id) {
+                next_car.state.pos = pos;
+            }
+            recorder.record(StateAction::CarPos(id, pos));
+        }
+    }
+
+    last_state_set.reset();
+    socket.send(SendableUdp::State(game_states.next.clone())).unwrap();
+}
+
+#[derive(Event)]
+enum UserLoadStateSnapshot {
+    /// The name of a saved snapshot
+    Saved(String),
+    /// A game state as JSON, like the one copied by "Copy JSON"
+    Json(String),
+}
+
+#[derive(Resource, Default, PartialEq, Eq)]
+pub struct EnableStateSnapshots(bool);
+
+impl EnableStateSnapshots {
+    pub fn toggle(&mut self) {
+        self.0 = !self.0;
+    }
+}
+
+/// Named game states saved to `state_snapshots/{name}.bin`
+///
+/// The files are in the same format as the `GameState` packet, so they can also be opened in the snapshot diff
+#[derive(Resource, Default)]
+struct StateSnapshots {
+    name: String,
+    /// `None` when the folder has to be read again
+    saved: Option<Vec<String>>,
+    error: Option<String>,
+}
+
+impl StateSnapshots {
+    const FOLDER: &'static str = "state_snapshots";
+    const EXTENSION: &'static str = "bin";
+
+    fn path(name: &str) -> PathBuf {
+        Path::new(Self::FOLDER).join(format!("{name}.{}", Self::EXTENSION))
     }
+
+    fn read_saved() -> io::Result<Vec<String>> {
+        let mut names = Vec::new();
+
+        for entry in fs::read_dir(Self::FOLDER)? {
+            let path = entry?.path();
+            if path.extension().is_some_and(|ext| ext == Self::EXTENSION) {
+                if let Some(name) = path.file_stem().and_then(|name| name.to_str()) {
+                    names.push(name.to_string());
+                }
+            }
+        }
+
+        names.sort_unstable();
+        Ok(names)
+    }
+
+    fn refresh(&mut self) {
+        self.saved = Some(match Self::read_saved() {
+            Ok(names) => names,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => {
+                self.error = Some(format!("Failed to read {} due to: {e}", Self::FOLDER));
+                Vec::new()
+            }
+        });
+    }
+
+    fn save(&mut self, state: &GameState) {
+        let name = self.name.trim();
+        if name.is_empty() || !name.chars().all(|c| c.is_alphanumeric() || matches!(c, ' ' | '-' | '_')) {
+            self.error = Some(String::from("Names can only contain letters, numbers, spaces, - and _"));
+            return;
+        }
+
+        let path = Self::path(name);
+        match fs::create_dir_all(Self::FOLDER).and_then(|()| fs::write(&path, state.to_bytes())) {
+            Ok(()) => {
+                info!("Saved state snapshot to {}", path.display());
+                self.error = None;
+                self.saved = None;
+            }
+            Err(e) => self.error = Some(format!("Failed to write {} due to: {e}", path.display())),
+        }
+    }
+
+    fn read(name: &str) -> Result<GameState, String> {
+        let path = Self::path(name);
+        let bytes = fs::read(&path).map_err(|e| format!("Failed to read {} due to: {e}", path.display()))?;
+
+        GameState::try_from_bytes(&bytes).ok_or_else(|| format!("{} is not a valid game state", path.display()))
+    }
+
+    fn delete(&mut self, name: &str) {
+        let path = Self::path(name);
+        if let Err(e) = fs::remove_file(&path) {
+            self.error = Some(format!("Failed to delete {} due to: {e}", path.display()));
+        }
+
+        self.saved = None;
+    }
+}
+
+fn load_state_snapshot(
+    mut events: EventReader<UserLoadStateSnapshot>,
+    mut game_states: ResMut<GameStates>,
+    mut snapshots: ResMut<StateSnapshots>,
+    socket: Res<Connection>,
+) {
+    for event in events.read() {
+        let state = match event {
+            UserLoadStateSnapshot::Saved(name) => StateSnapshots::read(name),
+            UserLoadStateSnapshot::Json(json) => serde_json::from_str::<GameState>(json)
+                .map_err(|e| format!("The clipboard doesn't contain a valid game state: {e}")),
+        };
+
+        let state = match state {
+            Ok(state) => state,
+            Err(e) => {
+                snapshots.error = Some(e);
+                continue;
+            }
+        };
+
+        socket.send(SendableUdp::State(state.clone())).unwrap();
+
+        game_states.current = state.clone();
+        game_states.next = state;
+        snapshots.error = None;
+    }
+}
+
+fn update_state_snapshots(
+    mut contexts: EguiContexts,
+    game_states: Res<GameStates>,
+    mut enable_menu: ResMut<EnableStateSnapshots>,
+    mut snapshots: ResMut<StateSnapshots>,
+    mut load_snapshot: EventWriter<UserLoadStateSnapshot>,
+    mut dump_state: EventWriter<DumpGameState>,
+    mut clipboard: ResMut<EguiClipboard>,
+) {
+    if snapshots.saved.is_none() {
+        snapshots.refresh();
+    }
+
+    egui::Window::new("State snapshots")
+        .open(&mut enable_menu.0)
+        .show(contexts.ctx_mut(), |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Name: ");
+                ui.add(egui::TextEdit::singleline(&mut snapshots.name).desired_width(150.));
+                if ui.button("Save").on_hover_text("Save the current game state").clicked() {
+                    snapshots.save(&game_states.current);
+                }
+            });
+
+            ui.horizontal(|ui| {
+                ui.label("Clipboard: ");
+                if ui
+                    .button("Copy JSON")
+                    .on_hover_text("Copy the current game state as JSON, to share it in a bug report")
+                    .clicked()
+                {
+                    dump_state.send(DumpGameState);
+                }
+                if ui
+                    .button("Paste JSON")
+                    .on_hover_text("Set the game to the state copied as JSON")
+                    .clicked()
+                {
+                    match clipboard.get_contents() {
+                        Some(json) => {
+                            load_snapshot.send(UserLoadStateSnapshot::Json(json));
+                        }
+                        None => snapshots.error = Some(String::from("Failed to read the clipboard")),
+                    }
+                }
+            });
+
+            if let Some(error) = &snapshots.error {
+                ui.colored_label(egui::Color32::RED, error);
+            }
+
+            ui.separator();
+
+            let saved = snapshots.saved.clone().unwrap_or_default();
+            if saved.is_empty() {
+                ui.label("No saved snapshots");
+            }
+
+            egui::ScrollArea::vertical().max_height(300.).show(ui, |ui| {
+                for name in saved {
+                    ui.horizontal(|ui| {
+                        ui.label(&name);
+                        if ui.button("Load").on_hover_text("Set the game to this state").clicked() {
+                            load_snapshot.send(UserLoadStateSnapshot::Saved(name.clone()));
+                        }
+                        if ui.button("Delete").clicked() {
+                            snapshots.delete(&name);
+                        }
+                    });
+                }
+            });
+
+            if ui
+                .button("Refresh")
+                .on_hover_text("Read the snapshots folder again")
+                .clicked()
+            {
+                snapshots.saved = None;
+            }
+        });
 }
@@ -0,0 +1,301 @@
+use super::options::{MenuFocused, Options};
+use crate::{
+    capture::unix_timestamp,
+    rocketsim::{CarControls, GameState, Team},
+    udp::GameStates,
+};
+use bevy::{math::Vec3A, prelude::*, utils::HashSet};
+use bevy_egui::{egui, EguiContexts};
+use serde::Serialize;
+use std::{
+    fs::{self, File},
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+
+pub struct TelemetryPlugin;
+
+impl Plugin for TelemetryPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(TelemetryRecorder::default())
+            .insert_resource(EnableTelemetryMenu::default())
+            .add_systems(
+                Update,
+                (
+                    write_telemetry.run_if(|recorder: Res<TelemetryRecorder>| recorder.is_recording()),
+                    update_telemetry_menu
+                        .run_if(resource_equals(EnableTelemetryMenu(true)))
+                        .run_if(resource_equals(MenuFocused::default())),
+                ),
+            );
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum TelemetryFormat {
+    Csv,
+    /// One JSON object per line, which pandas can read with `read_json(path, lines=True)`
+    JsonLines,
+}
+
+impl TelemetryFormat {
+    const NAMES: [&'static str; 2] = ["CSV", "JSON lines"];
+
+    pub fn from_usize(value: usize) -> Self {
+        match value {
+            0 => Self::Csv,
+            1 => Self::JsonLines,
+            _ => unreachable!(),
+        }
+    }
+
+    const fn extension(self) -> &'static str {
+        match self {
+            Self::Csv => "csv",
+            Self::JsonLines => "jsonl",
+        }
+    }
+}
+
+/// One row per entity per tick, with the controls left empty for the ball
+const CSV_HEADER: &str = "tick,entity,id,team,pos_x,pos_y,pos_z,vel_x,vel_y,vel_z,ang_vel_x,ang_vel_y,ang_vel_z,\
+                          throttle,steer,pitch,yaw,roll,boost,jump,handbrake";
+
+#[derive(Serialize)]
+struct BallTelemetry {
+    pos: Vec3A,
+    vel: Vec3A,
+    ang_vel: Vec3A,
+}
+
+#[derive(Serialize)]
+struct CarTelemetry {
+    id: u32,
+    team: Team,
+    pos: Vec3A,
+    vel: Vec3A,
+    ang_vel: Vec3A,
+    controls: CarControls,
+}
+
+#[derive(Serialize)]
+struct TickTelemetry {
+    tick: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    ball: Option<BallTelemetry>,
+    cars: Vec<CarTelemetry>,
+}
+
+/// Writes the ball and car kinematics of every tick to a file in the telemetry folder
+#[derive(Resource)]
+pub struct TelemetryRecorder {
+    output: Option<(BufWriter<File>, TelemetryFormat)>,
+    last_tick: Option<u64>,
+    pub include_ball: bool,
+    /// Cars are included unless they've been unchecked, so ones that join later are recorded too
+    pub excluded_cars: HashSet<u32>,
+}
+
+impl Default for TelemetryRecorder {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            output: None,
+            last_tick: None,
+            include_ball: true,
+            excluded_cars: HashSet::default(),
+        }
+    }
+}
+
+impl TelemetryRecorder {
+    const FOLDER: &'static str = "telemetry";
+
+    #[inline]
+    pub const fn is_recording(&self) -> bool {
+        self.output.is_some()
+    }
+
+    pub fn toggle(&mut self, format: TelemetryFormat) {
+        if self.output.take().is_some() {
+            return;
+        }
+
+        let path = Path::new(Self::FOLDER).join(format!("telemetry_{}.{}", unix_timestamp(), format.extension()));
+        let file = fs::create_dir_all(Self::FOLDER).and_then(|()| {
+            let mut file = BufWriter::new(File::create(&path)?);
+            if format == TelemetryFormat::Csv {
+                writeln!(file, "{CSV_HEADER}")?;
+            }
+
+            Ok(file)
+        });
+
+        match file {
+            Ok(file) => {
+                info!("Recording telemetry to {}", path.display());
+                self.output = Some((file, format));
+                self.last_tick = None;
+            }
+            Err(e) => error!("Failed to create {} due to: {e}", path.display()),
+        }
+    }
+
+    fn write(&mut self, state: &GameState) -> io::Result<()> {
+        let Some((file, format)) = &mut self.output else {
+            return Ok(());
+        };
+
+        let cars = state.cars.iter().filter(|car| !self.excluded_cars.contains(&car.id));
+
+        match format {
+            TelemetryFormat::Csv => {
+                let tick = state.tick_count;
+
+                if self.include_ball {
+                    let ball = &state.ball;
+                    writeln!(
+                        file,
+                        "{tick},ball,,,{},{},{},{},{},{},{},{},{},,,,,,,,",
+                        ball.pos.x,
+                        ball.pos.y,
+                        ball.pos.z,
+                        ball.vel.x,
+                        ball.vel.y,
+                        ball.vel.z,
+                        ball.ang_vel.x,
+                        ball.ang_vel.y,
+                        ball.ang_vel.z,
+                    )?;
+                }
+
+                for car in cars {
+                    let car_state = &car.state;
+                    let controls = &car_state.last_controls;
+                    writeln!(
+                        file,
+                        "{tick},car,{},{:?},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{}",
+                        car.id,
+                        car.team,
+                        car_state.pos.x,
+                        car_state.pos.y,
+                        car_state.pos.z,
+                        car_state.vel.x,
+                        car_state.vel.y,
+                        car_state.vel.z,
+                        car_state.ang_vel.x,
+                        car_state.ang_vel.y,
+                        car_state.ang_vel.z,
+                        controls.throttle,
+                        controls.steer,
+                        controls.pitch,
+                        controls.yaw,
+                        controls.roll,
+                        controls.boost,
+                        controls.jump,
+                        controls.handbrake,
+                    )?;
+                }
+            }
+            TelemetryFormat::JsonLines => {
+                let tick = TickTelemetry {
+                    tick: state.tick_count,
+                    ball: self.include_ball.then_some(BallTelemetry {
+                        pos: state.ball.pos,
+                        vel: state.ball.vel,
+                        ang_vel: state.ball.ang_vel,
+                    }),
+                    cars: cars
+                        .map(|car| CarTelemetry {
+                            id: car.id,
+                            team: car.team,
+                            pos: car.state.pos,
+                            vel: car.state.vel,
+                            ang_vel: car.state.ang_vel,
+                            controls: car.state.last_controls,
+                        })
+                        .collect(),
+                };
+
+                serde_json::to_writer(&mut *file, &tick)?;
+                writeln!(file)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn write_telemetry(game_states: Res<GameStates>, mut recorder: ResMut<TelemetryRecorder>) {
+    // `next` is always the newest state that was received, no matter the packet smoothing
+    let state = &game_states.next;
+    if recorder.last_tick == Some(state.tick_count) {
+        return;
+    }
+    recorder.last_tick = Some(state.tick_count);
+
+    if let Err(e) = recorder.write(state) {
+        error!("Failed to write telemetry due to: {e}");
+        recorder.output = None;
+    }
+}
+
+#[derive(Resource, Default, PartialEq, Eq)]
+pub struct EnableTelemetryMenu(bool);
+
+impl EnableTelemetryMenu {
+    pub fn toggle(&mut self) {
+        self.0 = !self.0;
+    }
+}
+
+fn update_telemetry_menu(
+    mut contexts: EguiContexts,
+    game_states: Res<GameStates>,
+    mut options: ResMut<Options>,
+    mut recorder: ResMut<TelemetryRecorder>,
+    mut enable_menu: ResMut<EnableTelemetryMenu>,
+) {
+    egui::Window::new("Telemetry")
+        .open(&mut enable_menu.0)
+        .show(contexts.ctx_mut(), |ui| {
+            let recording = recorder.is_recording();
+
+            ui.add_enabled_ui(!recording, |ui| {
+                egui::ComboBox::from_label("Format").width(100.).show_index(
+                    ui,
+                    &mut options.telemetry_format,
+                    TelemetryFormat::NAMES.len(),
+                    |i| TelemetryFormat::NAMES[i],
+                );
+            });
+
+            ui.add_space(10.);
+
+            ui.checkbox(&mut recorder.include_ball, "Ball");
+            for car in &*game_states.current.cars {
+                let mut included = !recorder.excluded_cars.contains(&car.id);
+                if ui
+                    .checkbox(&mut included, format!("Car {} ({:?})", car.id, car.team))
+                    .changed()
+                {
+                    if included {
+                        recorder.excluded_cars.remove(&car.id);
+                    } else {
+                        recorder.excluded_cars.insert(car.id);
+                    }
+                }
+            }
+
+            ui.add_space(10.);
+
+            let label = if recording { "Stop recording" } else { "Start recording" };
+            if ui
+                .button(label)
+                .on_hover_text("Save the position, velocity and controls of every tick to the telemetry folder")
+                .clicked()
+            {
+                recorder.toggle(TelemetryFormat::from_usize(options.telemetry_format));
+            }
+        });
+}
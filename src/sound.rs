@@ -0,0 +1,170 @@
+use crate::{
+    goal::GoalScored,
+    udp::{GameStates, ToBevyVec},
+};
+use bevy::{
+    audio::{Pitch, PlaybackMode, SpatialScale, Volume},
+    prelude::*,
+    utils::HashMap,
+};
+use std::{path::Path, time::Duration};
+
+pub struct SoundPlugin;
+
+impl Plugin for SoundPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, load_sounds).add_systems(
+            Update,
+            (play_car_sounds, play_goal_sound.run_if(on_event::<GoalScored>)).run_if(resource_exists::<Sounds>),
+        );
+    }
+}
+
+/// Rocket League's units are centimeters, so this keeps sounds across the field from being silent
+const SPATIAL_SCALE: f32 = 1. / 500.;
+/// Distance between the ears of the camera, in uu
+pub const EAR_GAP: f32 = 200.;
+
+/// Things that make a sound, along with the file in `assets/sounds` that's played for it
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+enum SoundEffect {
+    BallHit,
+    Boost,
+    Demo,
+    Goal,
+}
+
+impl SoundEffect {
+    const ALL: [Self; 4] = [Self::BallHit, Self::Boost, Self::Demo, Self::Goal];
+
+    const fn path(self) -> &'static str {
+        match self {
+            Self::BallHit => "sounds/ball_hit.ogg",
+            Self::Boost => "sounds/boost.ogg",
+            Self::Demo => "sounds/demo.ogg",
+            Self::Goal => "sounds/goal.ogg",
+        }
+    }
+
+    /// A simple tone that's used when there's no file for the sound
+    fn fallback_tone(self) -> Pitch {
+        let (frequency, millis) = match self {
+            Self::BallHit => (330., 60),
+            Self::Boost => (110., 150),
+            Self::Demo => (82., 400),
+            Self::Goal => (523., 800),
+        };
+
+        Pitch::new(frequency, Duration::from_millis(millis))
+    }
+
+    const fn volume(self) -> f32 {
+        match self {
+            Self::BallHit | Self::Demo => 1.,
+            Self::Boost => 0.4,
+            Self::Goal => 0.8,
+        }
+    }
+}
+
+enum SoundSource {
+    File(Handle<AudioSource>),
+    Tone(Handle<Pitch>),
+}
+
+#[derive(Resource)]
+struct Sounds(HashMap<SoundEffect, SoundSource>);
+
+fn load_sounds(mut commands: Commands, asset_server: Res<AssetServer>, mut pitches: ResMut<Assets<Pitch>>) {
+    let sounds = SoundEffect::ALL
+        .into_iter()
+        .map(|effect| {
+            let source = if Path::new("assets").join(effect.path()).exists() {
+                SoundSource::File(asset_server.load(effect.path()))
+            } else {
+                SoundSource::Tone(pitches.add(effect.fallback_tone()))
+            };
+
+            (effect, source)
+        })
+        .collect();
+
+    commands.insert_resource(Sounds(sounds));
+}
+
+impl Sounds {
+    /// Plays the sound at `pos`, or without any panning if `pos` is `None`
+    fn play(&self, commands: &mut Commands, effect: SoundEffect, pos: Option<Vec3>) {
+        let settings = PlaybackSettings {
+            mode: PlaybackMode::Despawn,
+            volume: Volume::new(effect.volume()),
+            spatial: pos.is_some(),
+            spatial_scale: Some(SpatialScale::new(SPATIAL_SCALE)),
+            ..default()
+        };
+
+        let transform = Transform::from_translation(pos.unwrap_or_default());
+
+        match &self.0[&effect] {
+            SoundSource::File(handle) => commands.spawn((AudioPlayer(handle.clone()), settings, transform)),
+            SoundSource::Tone(handle) => commands.spawn((AudioPlayer(handle.clone()), settings, transform)),
+        };
+    }
+}
+
+struct LastCarState {
+    tick_count_when_hit: u64,
+    boosting: bool,
+    is_demoed: bool,
+}
+
+fn play_car_sounds(
+    states: Res<GameStates>,
+    sounds: Res<Sounds>,
+    mut commands: Commands,
+    mut last_states: Local<HashMap<u32, LastCarState>>,
+) {
+    last_states.retain(|id, _| states.current.cars.iter().any(|car| car.id == *id));
+
+    for car in &*states.current.cars {
+        let state = &car.state;
+        let boosting = state.last_controls.boost && state.boost > 0. && !state.is_demoed;
+        let pos = state.pos.to_bevy();
+
+        // cars that just showed up shouldn't make any sounds for what they did before
+        let Some(last) = last_states.get_mut(&car.id) else {
+            last_states.insert(
+                car.id,
+                LastCarState {
+                    tick_count_when_hit: state.ball_hit_info.tick_count_when_hit,
+                    boosting,
+                    is_demoed: state.is_demoed,
+                },
+            );
+            continue;
+        };
+
+        // compared with != instead of > so hits are heard again during replays
+        if state.ball_hit_info.is_valid && state.ball_hit_info.tick_count_when_hit != last.tick_count_when_hit {
+            sounds.play(&mut commands, SoundEffect::BallHit, Some(states.current.ball.pos.to_bevy()));
+        }
+
+        if boosting && !last.boosting {
+            sounds.play(&mut commands, SoundEffect::Boost, Some(pos));
+        }
+
+        if state.is_demoed && !last.is_demoed {
+            sounds.play(&mut commands, SoundEffect::Demo, Some(pos));
+        }
+
+        last.tick_count_when_hit = state.ball_hit_info.tick_count_when_hit;
+        last.boosting = boosting;
+        last.is_demoed = state.is_demoed;
+    }
+}
+
+fn play_goal_sound(mut events: EventReader<GoalScored>, sounds: Res<Sounds>, mut commands: Commands) {
+    for _ in events.read() {
+        sounds.play(&mut commands, SoundEffect::Goal, None);
+    }
+}
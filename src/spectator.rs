@@ -1,11 +1,19 @@
 //! Copied over from `bevy_spectator` because base functions aren't public and changes are required to make it work nice
 
-use crate::camera::PrimaryCamera;
+use crate::{
+    camera::PrimaryCamera,
+    camera_path::CameraPath,
+    rocketsim::GameMode,
+    settings::keybinds::{Action, Keybinds},
+    udp::{GameStates, ToBevyVec},
+};
 use bevy::{
     input::mouse::MouseMotion,
     prelude::*,
     window::{CursorGrabMode, PrimaryWindow},
 };
+use serde::{Deserialize, Serialize};
+use std::f32::consts::FRAC_1_SQRT_2;
 
 /// A marker `Component` for spectating cameras.
 ///
@@ -28,6 +36,7 @@ pub struct SpectatorPlugin;
 impl Plugin for SpectatorPlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<SpectatorSettings>()
+            .init_resource::<BookmarkFlight>()
             .add_systems(PostStartup, spectator_init)
             .add_systems(Update, spectator_update);
     }
@@ -51,13 +60,179 @@ fn spectator_init(cameras: Query<Entity, With<Spectator>>, mut settings: ResMut<
     }
 }
 
+/// How close the camera can get to the arena's surfaces when collision is enabled
+const CAMERA_RADIUS: f32 = 50.;
+
+/// A plane facing into the arena, where points with `normal.dot(point) >= distance` are on the inside
+struct ArenaPlane {
+    normal: Vec3,
+    distance: f32,
+}
+
+impl ArenaPlane {
+    const fn new(normal: Vec3, distance: f32) -> Self {
+        Self { normal, distance }
+    }
+
+    #[inline]
+    fn signed_distance(&self, point: Vec3) -> f32 {
+        self.normal.dot(point) - self.distance
+    }
+}
+
+/// The floor, ceiling, walls and corners of a standard arena, which together make up a low-poly version of it
+const SOCCAR_PLANES: [ArenaPlane; 10] = [
+    ArenaPlane::new(Vec3::Y, 0.),
+    ArenaPlane::new(Vec3::NEG_Y, -2044.),
+    ArenaPlane::new(Vec3::X, -4096.),
+    ArenaPlane::new(Vec3::NEG_X, -4096.),
+    ArenaPlane::new(Vec3::Z, -5120.),
+    ArenaPlane::new(Vec3::NEG_Z, -5120.),
+    ArenaPlane::new(Vec3::new(FRAC_1_SQRT_2, 0., FRAC_1_SQRT_2), -8064. * FRAC_1_SQRT_2),
+    ArenaPlane::new(Vec3::new(-FRAC_1_SQRT_2, 0., FRAC_1_SQRT_2), -8064. * FRAC_1_SQRT_2),
+    ArenaPlane::new(Vec3::new(FRAC_1_SQRT_2, 0., -FRAC_1_SQRT_2), -8064. * FRAC_1_SQRT_2),
+    ArenaPlane::new(Vec3::new(-FRAC_1_SQRT_2, 0., -FRAC_1_SQRT_2), -8064. * FRAC_1_SQRT_2),
+];
+
+const HOOPS_PLANES: [ArenaPlane; 6] = [
+    ArenaPlane::new(Vec3::Y, 0.),
+    ArenaPlane::new(Vec3::NEG_Y, -1820.),
+    ArenaPlane::new(Vec3::X, -2966.),
+    ArenaPlane::new(Vec3::NEG_X, -2966.),
+    ArenaPlane::new(Vec3::Z, -3581.),
+    ArenaPlane::new(Vec3::NEG_Z, -3581.),
+];
+
+const fn arena_planes(game_mode: GameMode) -> &'static [ArenaPlane] {
+    match game_mode {
+        GameMode::TheVoid => &[],
+        GameMode::Hoops => &HOOPS_PLANES,
+        _ => &SOCCAR_PLANES,
+    }
+}
+
+/// How many camera bookmarks can be saved, one for each of the track car keys
+pub const BOOKMARK_SLOTS: usize = 8;
+
+/// How long it takes to fly to a bookmark, in seconds
+const BOOKMARK_FLIGHT_SECS: f32 = 1.;
+
+/// A saved free camera pose
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct CameraBookmark {
+    translation: Vec3,
+    rotation: Quat,
+}
+
+impl From<&Transform> for CameraBookmark {
+    fn from(transform: &Transform) -> Self {
+        Self {
+            translation: transform.translation,
+            rotation: transform.rotation,
+        }
+    }
+}
+
+/// The free camera's flight to a bookmark, if it's on its way to one
+#[derive(Resource, Default)]
+pub struct BookmarkFlight(Option<Flight>);
+
+struct Flight {
+    from: CameraBookmark,
+    to: CameraBookmark,
+    timer: Timer,
+}
+
+impl BookmarkFlight {
+    pub fn start(&mut self, from: &Transform, to: CameraBookmark) {
+        self.0 = Some(Flight {
+            from: from.into(),
+            to,
+            timer: Timer::from_seconds(BOOKMARK_FLIGHT_SECS, TimerMode::Once),
+        });
+    }
+
+    /// Moves the camera along the flight, returning false once there's nowhere left to go
+    fn step(&mut self, delta: std::time::Duration, camera_transform: &mut Transform) -> bool {
+        let Some(flight) = &mut self.0 else {
+            return false;
+        };
+
+        // ease in and out so the camera doesn't jerk when leaving or arriving
+        let t = flight.timer.tick(delta).fraction();
+        let t = t * t * (3. - 2. * t);
+
+        camera_transform.translation = flight.from.translation.lerp(flight.to.translation, t);
+        camera_transform.rotation = flight.from.rotation.slerp(flight.to.rotation, t);
+
+        if flight.timer.finished() {
+            self.0 = None;
+        }
+
+        true
+    }
+}
+
+/// Stops the camera from moving through the arena, so it stays on whichever side of the walls it was on
+fn collide_with_arena(game_mode: GameMode, last_pos: Vec3, pos: &mut Vec3) {
+    let planes = arena_planes(game_mode);
+    if planes.is_empty() {
+        return;
+    }
+
+    // the ground outside of the arena is at the same height as the field
+    pos.y = pos.y.max(CAMERA_RADIUS);
+
+    let was_inside = planes.iter().all(|plane| plane.signed_distance(last_pos) >= 0.);
+
+    if was_inside {
+        // push back out of every surface that was passed through, which handles corners too
+        for plane in planes {
+            let depth = plane.signed_distance(*pos) - CAMERA_RADIUS;
+            if depth < 0. {
+                *pos -= plane.normal * depth;
+            }
+        }
+    } else if let Some((plane, depth)) = planes
+        .iter()
+        .map(|plane| (plane, plane.signed_distance(*pos) + CAMERA_RADIUS))
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+        .filter(|&(_, depth)| depth > 0.)
+    {
+        // every plane is satisfied, so the camera is about to enter the arena through the closest surface
+        *pos -= plane.normal * depth;
+    }
+}
+
+/// How far away from the ball the camera can drift before it's pulled back in when following it
+const FOLLOW_BALL_DISTANCE: f32 = 3000.;
+
+/// Eases the camera towards looking at the ball, and towards the ball itself if it gets too far away
+fn follow_ball(camera_transform: &mut Transform, ball_pos: Vec3, smoothing: f32, delta: f32) {
+    let t = if smoothing > 0. { 1. - (-delta / smoothing).exp() } else { 1. };
+
+    let target_rotation = camera_transform.looking_at(ball_pos, Vec3::Y).rotation;
+    camera_transform.rotation = camera_transform.rotation.slerp(target_rotation, t);
+
+    let offset = camera_transform.translation - ball_pos;
+    let distance = offset.length();
+    if distance > FOLLOW_BALL_DISTANCE {
+        let target = ball_pos + offset / distance * FOLLOW_BALL_DISTANCE;
+        camera_transform.translation = camera_transform.translation.lerp(target, t);
+    }
+}
+
 fn spectator_update(
     time: Res<Time>,
+    states: Res<GameStates>,
     keys: Res<ButtonInput<KeyCode>>,
+    keybinds: Res<Keybinds>,
     windows: Query<&Window, With<PrimaryWindow>>,
     primary_camera: Query<&PrimaryCamera>,
     mut motion: EventReader<MouseMotion>,
     mut settings: ResMut<SpectatorSettings>,
+    mut flight: ResMut<BookmarkFlight>,
+    camera_path: Res<CameraPath>,
     mut camera_transforms: Query<&mut Transform, With<Spectator>>,
 ) {
     let Some(camera_id) = settings.active_spectator else {
@@ -69,17 +244,11 @@ fn spectator_update(
         .get_single()
         .is_ok_and(|state| *state != PrimaryCamera::Spectator)
     {
+        flight.0 = None;
         motion.clear();
         return;
     }
 
-    if let Ok(window) = windows.get_single() {
-        if window.cursor_options.grab_mode == CursorGrabMode::None {
-            motion.clear();
-            return;
-        }
-    }
-
     let Ok(mut camera_transform) = camera_transforms.get_mut(camera_id) else {
         error!("Failed to find camera for active camera entity ({camera_id:?})");
         settings.active_spectator = None;
@@ -87,6 +256,42 @@ fn spectator_update(
         return;
     };
 
+    // a camera path that's playing moves the camera by itself
+    if flight.step(time.delta(), &mut camera_transform) || camera_path.is_playing() {
+        motion.clear();
+        return;
+    }
+
+    let last_pos = camera_transform.translation;
+
+    if settings.follow_ball {
+        let ball_pos = states.current.ball.pos.to_bevy();
+        follow_ball(&mut camera_transform, ball_pos, settings.follow_smoothing, time.delta_secs());
+    }
+
+    let grabbed = windows
+        .get_single()
+        .map_or(true, |window| window.cursor_options.grab_mode != CursorGrabMode::None);
+
+    if grabbed {
+        move_spectator(&time, &keys, &keybinds, &mut motion, &settings, &mut camera_transform);
+    }
+
+    if settings.collision {
+        collide_with_arena(states.current.game_mode, last_pos, &mut camera_transform.translation);
+    }
+
+    motion.clear();
+}
+
+fn move_spectator(
+    time: &Time,
+    keys: &ButtonInput<KeyCode>,
+    keybinds: &Keybinds,
+    motion: &mut EventReader<MouseMotion>,
+    settings: &SpectatorSettings,
+    camera_transform: &mut Transform,
+) {
     // rotation
     {
         let mouse_delta = motion.read().fold(Vec2::ZERO, |acc, d| acc + d.delta) * -settings.sensitivity;
@@ -103,14 +308,14 @@ fn spectator_update(
 
     // translation
     {
-        let forward = f32::from(keys.pressed(KeyCode::KeyW));
-        let backward = f32::from(keys.pressed(KeyCode::KeyS));
-        let right = f32::from(keys.pressed(KeyCode::KeyD));
-        let left = f32::from(keys.pressed(KeyCode::KeyA));
-        let up = f32::from(keys.pressed(KeyCode::Space));
-        let down = f32::from(keys.pressed(KeyCode::ControlLeft));
-
-        let speed = if keys.pressed(KeyCode::ShiftLeft) {
+        let forward = f32::from(keybinds.pressed(keys, Action::MoveForward));
+        let backward = f32::from(keybinds.pressed(keys, Action::MoveBackward));
+        let right = f32::from(keybinds.pressed(keys, Action::MoveRight));
+        let left = f32::from(keybinds.pressed(keys, Action::MoveLeft));
+        let up = f32::from(keybinds.pressed(keys, Action::MoveUp));
+        let down = f32::from(keybinds.pressed(keys, Action::MoveDown));
+
+        let speed = if keybinds.pressed(keys, Action::MoveSlow) {
             settings.alt_speed
         } else {
             settings.base_speed
@@ -131,8 +336,6 @@ fn spectator_update(
 
         camera_transform.translation += result * time.delta_secs();
     }
-
-    motion.clear();
 }
 
 /// A `Resource` for controlling [`Spectator`]s.
@@ -158,6 +361,12 @@ pub struct SpectatorSettings {
     ///
     /// Use this to control how fast the [`Spectator`] turns when you move the mouse.
     pub sensitivity: f32,
+    /// Whether the [`Spectator`] is stopped from flying through the floor and walls of the arena. (Default: `false`)
+    pub collision: bool,
+    /// Whether the [`Spectator`] loosely follows the ball. (Default: `false`)
+    pub follow_ball: bool,
+    /// Roughly how many seconds it takes to catch up with the ball when following it. (Default: `0.5`)
+    pub follow_smoothing: f32,
 }
 
 impl Default for SpectatorSettings {
@@ -167,6 +376,9 @@ impl Default for SpectatorSettings {
             base_speed: 2500.,
             alt_speed: 750.,
             sensitivity: 0.001,
+            collision: false,
+            follow_ball: false,
+            follow_smoothing: 0.5,
         }
     }
 }
@@ -0,0 +1,201 @@
+use crate::{
+    camera::{CameraTransition, CarSlots, PrimaryCamera, DEFAULT_FOV},
+    director::Director,
+    settings::options::{BallCam, SplitScreen, SplitScreenLayout},
+    udp::{follow_car, Car, GameStates, ToBevyVec},
+};
+use bevy::{core_pipeline::tonemapping::Tonemapping, prelude::*, render::camera::Viewport, window::PrimaryWindow};
+
+pub struct SplitScreenPlugin;
+
+impl Plugin for SplitScreenPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (spawn_viewport_cameras, update_viewport_cameras, set_viewports).chain(),
+        );
+    }
+}
+
+/// A camera that renders to one of the extra viewports when split screen is on
+#[derive(Component)]
+struct ViewportCamera {
+    /// 1 to 3, the primary camera is always in the first viewport
+    slot: usize,
+    /// Each viewport gets its own so they can follow different cars
+    director: Director,
+    /// Glides the camera over when its Director cuts to another car
+    transition: CameraTransition,
+}
+
+/// The position and size of each viewport in the window, in the order they're assigned to cameras
+fn viewport_rects(layout: SplitScreenLayout, size: UVec2) -> Vec<(UVec2, UVec2)> {
+    let half = size / 2;
+    let rest = size - half;
+
+    match layout {
+        SplitScreenLayout::Off => Vec::new(),
+        SplitScreenLayout::SideBySide => vec![
+            (UVec2::ZERO, UVec2::new(half.x, size.y)),
+            (UVec2::new(half.x, 0), UVec2::new(rest.x, size.y)),
+        ],
+        SplitScreenLayout::Stacked => vec![
+            (UVec2::ZERO, UVec2::new(size.x, half.y)),
+            (UVec2::new(0, half.y), UVec2::new(size.x, rest.y)),
+        ],
+        SplitScreenLayout::Quad => vec![
+            (UVec2::ZERO, half),
+            (UVec2::new(half.x, 0), UVec2::new(rest.x, half.y)),
+            (UVec2::new(0, half.y), UVec2::new(half.x, rest.y)),
+            (half, rest),
+        ],
+    }
+}
+
+fn spawn_viewport_cameras(
+    split_screen: Res<SplitScreen>,
+    primary_camera: Query<&Msaa, With<PrimaryCamera>>,
+    cameras: Query<(Entity, &ViewportCamera)>,
+    mut commands: Commands,
+) {
+    let num_slots = split_screen.layout.num_viewports() - 1;
+
+    for (entity, camera) in &cameras {
+        if camera.slot > num_slots {
+            commands.entity(entity).despawn();
+        }
+    }
+
+    let msaa = primary_camera.get_single().copied().unwrap_or_default();
+
+    for slot in 1..=num_slots {
+        if cameras.iter().any(|(_, camera)| camera.slot == slot) {
+            continue;
+        }
+
+        commands.spawn((
+            ViewportCamera {
+                slot,
+                director: Director::default(),
+                transition: CameraTransition::default(),
+            },
+            Camera3d::default(),
+            Projection::Perspective(PerspectiveProjection {
+                near: 5.,
+                far: 500_000.,
+                fov: DEFAULT_FOV,
+                ..default()
+            }),
+            Transform::from_translation(Vec3::new(-3000., 1000., 0.)).looking_to(Vec3::X, Vec3::Y),
+            // render before the primary camera, which has to stay right before the UI camera
+            Camera {
+                order: -(slot as isize),
+                hdr: true,
+                ..default()
+            },
+            Tonemapping::ReinhardLuminance,
+            msaa,
+        ));
+    }
+}
+
+fn update_viewport_cameras(
+    time: Res<Time>,
+    states: Res<GameStates>,
+    split_screen: Res<SplitScreen>,
+    ballcam: Res<BallCam>,
+    car_slots: Res<CarSlots>,
+    transition: Res<CameraTransition>,
+    cars: Query<(&Transform, &Car)>,
+    mut cameras: Query<(&mut ViewportCamera, &mut Transform), Without<Car>>,
+) {
+    let ball_pos = states.current.ball.pos.to_bevy();
+
+    for (mut camera, mut camera_transform) in &mut cameras {
+        let view = split_screen.views[camera.slot - 1];
+        let is_director = view == 0;
+
+        let car_id = if is_director {
+            let car_id = camera.director.update(&states.current, time.delta_secs());
+
+            // the viewports glide over the same way the primary camera does
+            camera.transition.duration = transition.duration;
+            camera.transition.smooth_cuts = transition.smooth_cuts;
+            camera
+                .transition
+                .update(PrimaryCamera::Director(car_id), car_id, &camera_transform, time.delta_secs());
+
+            car_id
+        } else {
+            let Some(id) = car_slots.car_id(view as usize - 1) else {
+                continue;
+            };
+            id
+        };
+
+        let Some((car_transform, _)) = cars.iter().find(|(_, car)| car.id() == car_id) else {
+            continue;
+        };
+
+        let Some(target_car) = states.current.cars.iter().find(|car_info| car_id == car_info.id) else {
+            continue;
+        };
+
+        follow_car(
+            &mut camera_transform,
+            car_transform,
+            target_car.state.vel,
+            ball_pos,
+            ballcam.enabled,
+        );
+
+        if is_director {
+            camera.transition.blend(&mut camera_transform);
+        }
+    }
+}
+
+fn set_viewport(mut camera: Mut<Camera>, rect: Option<(UVec2, UVec2)>) {
+    let current = camera
+        .viewport
+        .as_ref()
+        .map(|viewport| (viewport.physical_position, viewport.physical_size));
+
+    // only write when something changed so the projection isn't recalculated every frame
+    if current != rect {
+        camera.viewport = rect.map(|(physical_position, physical_size)| Viewport {
+            physical_position,
+            physical_size,
+            ..default()
+        });
+    }
+}
+
+fn set_viewports(
+    split_screen: Res<SplitScreen>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    mut primary_camera: Query<&mut Camera, With<PrimaryCamera>>,
+    mut cameras: Query<(&ViewportCamera, &mut Camera), Without<PrimaryCamera>>,
+) {
+    let Ok(window) = windows.get_single() else {
+        return;
+    };
+
+    let size = window.physical_size();
+    // the window is minimized
+    if size.x == 0 || size.y == 0 {
+        return;
+    }
+
+    let rects = viewport_rects(split_screen.layout, size);
+
+    if let Ok(camera) = primary_camera.get_single_mut() {
+        set_viewport(camera, rects.first().copied());
+    }
+
+    for (viewport_camera, camera) in &mut cameras {
+        if let Some(&rect) = rects.get(viewport_camera.slot) {
+            set_viewport(camera, Some(rect));
+        }
+    }
+}
@@ -0,0 +1,366 @@
+use crate::{
+    camera::PrimaryCamera,
+    mesh::{can_state_set, get_move_object_target, project_ray_to_plane, StateSetTime},
+    rocketsim::GameState,
+    settings::options::MenuFocused,
+    udp::{Connection, GameStates, SendableUdp, ToBevyVec},
+};
+use bevy::{
+    color::palettes::css,
+    math::{Mat3A, Vec3A},
+    prelude::*,
+    window::PrimaryWindow,
+};
+use bevy_egui::EguiContexts;
+use std::f32::consts::{FRAC_PI_2, TAU};
+
+pub struct StateGizmoPlugin;
+
+impl Plugin for StateGizmoPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(StateSetGizmo::default()).add_systems(
+            Update,
+            (
+                drag_handles
+                    .run_if(resource_exists::<Connection>)
+                    .run_if(resource_equals(MenuFocused::default())),
+                draw_handles,
+            )
+                .chain()
+                .run_if(|gizmo: Res<StateSetGizmo>| gizmo.selected.is_some()),
+        );
+    }
+}
+
+/// How long the position arrows are
+const AXIS_LENGTH: f32 = 300.;
+/// The part of the position arrows closer than this to the object is left for the velocity handle
+const AXIS_GRAB_START: f32 = 0.3;
+/// The world axes in Bevy's coordinates, in the order of RocketSim's X, Y and Z
+const AXES: [Vec3; 3] = [Vec3::X, Vec3::Z, Vec3::Y];
+const AXIS_COLORS: [Srgba; 3] = [css::RED, css::LIME, css::BLUE];
+/// How far the velocity arrow reaches for each uu/s, so 2000 uu/s is 500 units long
+const VELOCITY_SCALE: f32 = 0.25;
+const VELOCITY_COLOR: Srgba = css::YELLOW;
+const VELOCITY_HANDLE_RADIUS: f32 = 20.;
+const YAW_RING_RADIUS: f32 = 200.;
+const YAW_RING_COLOR: Srgba = css::FUCHSIA;
+const ACTIVE_COLOR: Srgba = css::WHITE;
+/// How close the cursor has to be to a handle to grab it, in pixels
+const GRAB_DISTANCE: f32 = 12.;
+const BALL_MAX_SPEED: f32 = 6000.;
+const CAR_MAX_SPEED: f32 = 2300.;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum GizmoTarget {
+    Ball,
+    Car(u32),
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Handle {
+    /// Moves the object along one of the world axes
+    Axis(usize),
+    /// Sets the velocity to wherever the tip of the arrow is dragged
+    Velocity,
+    /// Turns a car around the world's up axis
+    Yaw,
+}
+
+struct ActiveDrag {
+    handle: Handle,
+    /// Where the object was when the drag started, in Bevy's coordinates
+    start_pos: Vec3,
+    start_rot: Mat3A,
+    /// How far along the axis or around the ring the cursor was when the drag started
+    start_param: f32,
+}
+
+/// The object that state setting handles are shown for, and the handle that's being dragged
+#[derive(Resource, Default)]
+pub struct StateSetGizmo {
+    selected: Option<GizmoTarget>,
+    dragging: Option<ActiveDrag>,
+}
+
+impl StateSetGizmo {
+    /// Selects the target, or deselects it if it was already selected
+    pub fn toggle(&mut self, target: GizmoTarget) {
+        self.selected = if self.selected == Some(target) { None } else { Some(target) };
+        self.dragging = None;
+    }
+
+    pub fn is_dragging(&self) -> bool {
+        self.dragging.is_some()
+    }
+}
+
+/// The position, rotation and velocity of the target in RocketSim's coordinates
+fn physics(state: &GameState, target: GizmoTarget) -> Option<(Vec3A, Mat3A, Vec3A)> {
+    match target {
+        GizmoTarget::Ball => Some((state.ball.pos, state.ball.rot_mat, state.ball.vel)),
+        GizmoTarget::Car(id) => state
+            .cars
+            .iter()
+            .find(|car| car.id == id && !car.state.is_demoed)
+            .map(|car| (car.state.pos, car.state.rot_mat, car.state.vel)),
+    }
+}
+
+fn set_physics(state: &mut GameState, target: GizmoTarget, pos: Vec3A, rot_mat: Mat3A, vel: Vec3A) {
+    match target {
+        GizmoTarget::Ball => {
+            state.ball.pos = pos;
+            state.ball.vel = vel;
+        }
+        GizmoTarget::Car(id) => {
+            if let Some(car) = state.cars.iter_mut().find(|car| car.id == id) {
+                car.state.pos = pos;
+                car.state.rot_mat = rot_mat;
+                car.state.vel = vel;
+            }
+        }
+    }
+}
+
+fn to_rocketsim(pos: Vec3) -> Vec3A {
+    Vec3A::from(pos).xzy()
+}
+
+/// Points around a car's yaw ring, in Bevy's coordinates
+fn yaw_ring_points(center: Vec3) -> impl Iterator<Item = Vec3> {
+    const RESOLUTION: usize = 32;
+
+    (0..=RESOLUTION).map(move |i| {
+        let angle = i as f32 / RESOLUTION as f32 * TAU;
+        center + Vec3::new(angle.cos(), 0., angle.sin()) * YAW_RING_RADIUS
+    })
+}
+
+fn distance_to_segment(point: Vec2, start: Vec2, end: Vec2) -> f32 {
+    let segment = end - start;
+    let t = ((point - start).dot(segment) / segment.length_squared().max(f32::EPSILON)).clamp(0., 1.);
+    point.distance(start + segment * t)
+}
+
+/// How far along the line the closest point to the cursor ray is, `None` if they're parallel
+fn closest_on_line(line_point: Vec3A, line_dir: Vec3A, ray_origin: Vec3A, ray_dir: Vec3A) -> Option<f32> {
+    let b = line_dir.dot(ray_dir);
+    let denom = 1. - b * b;
+    if denom < 1e-4 {
+        return None;
+    }
+
+    let w = line_point - ray_origin;
+    Some((b * ray_dir.dot(w) - line_dir.dot(w)) / denom)
+}
+
+/// The angle around the yaw ring that the cursor ray points at, `None` if it doesn't hit the ring's plane
+fn yaw_ring_angle(center: Vec3A, ray_origin: Vec3A, ray_dir: Vec3A) -> Option<f32> {
+    if ray_dir.y.abs() < 1e-4 {
+        return None;
+    }
+
+    let lambda = (center.y - ray_origin.y) / ray_dir.y;
+    if lambda < 0. {
+        return None;
+    }
+
+    // RocketSim's X and Y are Bevy's X and Z
+    let offset = ray_origin + lambda * ray_dir - center;
+    Some(offset.z.atan2(offset.x))
+}
+
+/// Finds the handle under the cursor, if any
+fn handle_under_cursor(
+    camera: &Camera,
+    global_transform: &GlobalTransform,
+    cursor: Vec2,
+    target: GizmoTarget,
+    center: Vec3,
+    vel: Vec3,
+) -> Option<Handle> {
+    let to_screen = |pos: Vec3| camera.world_to_viewport(global_transform, pos).ok();
+
+    let mut handles = Vec::with_capacity(5);
+
+    if let Some(tip) = to_screen(center + vel * VELOCITY_SCALE) {
+        handles.push((Handle::Velocity, cursor.distance(tip)));
+    }
+
+    for (i, axis) in AXES.into_iter().enumerate() {
+        if let (Some(start), Some(end)) = (
+            to_screen(center + axis * AXIS_LENGTH * AXIS_GRAB_START),
+            to_screen(center + axis * AXIS_LENGTH),
+        ) {
+            handles.push((Handle::Axis(i), distance_to_segment(cursor, start, end)));
+        }
+    }
+
+    if matches!(target, GizmoTarget::Car(_)) {
+        let points = yaw_ring_points(center).map(to_screen).collect::<Option<Vec<_>>>();
+        if let Some(points) = points {
+            let distance = points
+                .windows(2)
+                .map(|segment| distance_to_segment(cursor, segment[0], segment[1]))
+                .fold(f32::INFINITY, f32::min);
+            handles.push((Handle::Yaw, distance));
+        }
+    }
+
+    handles
+        .into_iter()
+        .filter(|(_, distance)| *distance <= GRAB_DISTANCE)
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(handle, _)| handle)
+}
+
+fn drag_handles(
+    mouse: Res<ButtonInput<MouseButton>>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    camera: Query<(&Camera, &GlobalTransform), With<PrimaryCamera>>,
+    socket: Res<Connection>,
+    mut contexts: EguiContexts,
+    mut gizmo: ResMut<StateSetGizmo>,
+    mut game_states: ResMut<GameStates>,
+    mut last_state_set: ResMut<StateSetTime>,
+) {
+    if mouse.just_released(MouseButton::Left) {
+        gizmo.dragging = None;
+        return;
+    }
+
+    let Some(target) = gizmo.selected else {
+        return;
+    };
+
+    let Some((pos, rot_mat, vel)) = physics(&game_states.current, target) else {
+        return;
+    };
+
+    if mouse.just_pressed(MouseButton::Left) {
+        if contexts.ctx_mut().is_pointer_over_area() {
+            return;
+        }
+
+        let Some(cursor) = windows.single().cursor_position() else {
+            return;
+        };
+
+        let center = pos.to_bevy();
+        let (cam, global_transform) = camera.single();
+        let Some(handle) = handle_under_cursor(cam, global_transform, cursor, target, center, vel.to_bevy()) else {
+            return;
+        };
+
+        let Some([cam_pos, cursor_dir, _]) = project_ray_to_plane(&camera, &windows) else {
+            return;
+        };
+
+        let start_param = match handle {
+            Handle::Axis(i) => closest_on_line(Vec3A::from(center), Vec3A::from(AXES[i]), cam_pos, cursor_dir),
+            Handle::Yaw => yaw_ring_angle(Vec3A::from(center), cam_pos, cursor_dir),
+            Handle::Velocity => Some(0.),
+        };
+
+        if let Some(start_param) = start_param {
+            gizmo.dragging = Some(ActiveDrag {
+                handle,
+                start_pos: center,
+                start_rot: rot_mat,
+                start_param,
+            });
+        }
+
+        return;
+    }
+
+    // the button was let go of while the window wasn't focused
+    if !mouse.pressed(MouseButton::Left) {
+        gizmo.dragging = None;
+        return;
+    }
+
+    let Some(drag) = &gizmo.dragging else {
+        return;
+    };
+
+    // Limit state setting to avoid bogging down the simulation with state setting requests
+    if !can_state_set(&last_state_set) {
+        return;
+    }
+
+    let Some([cam_pos, cursor_dir, plane_normal]) = project_ray_to_plane(&camera, &windows) else {
+        return;
+    };
+
+    let (mut pos, mut rot_mat, mut vel) = (pos, rot_mat, vel);
+    match drag.handle {
+        Handle::Axis(i) => {
+            let axis = AXES[i];
+            let Some(param) = closest_on_line(Vec3A::from(drag.start_pos), Vec3A::from(axis), cam_pos, cursor_dir) else {
+                return;
+            };
+
+            pos = to_rocketsim(drag.start_pos + axis * (param - drag.start_param));
+        }
+        Handle::Velocity => {
+            let center = Vec3A::from(pos.to_bevy());
+            let tip = get_move_object_target(cam_pos, cursor_dir, plane_normal, center);
+            let max_speed = match target {
+                GizmoTarget::Ball => BALL_MAX_SPEED,
+                GizmoTarget::Car(_) => CAR_MAX_SPEED,
+            };
+
+            vel = ((tip - center) / VELOCITY_SCALE).xzy().clamp_length_max(max_speed);
+        }
+        Handle::Yaw => {
+            let Some(angle) = yaw_ring_angle(Vec3A::from(drag.start_pos), cam_pos, cursor_dir) else {
+                return;
+            };
+
+            // turning from one angle to another in Bevy's XZ plane is the same turn in RocketSim's XY plane
+            rot_mat = Mat3A::from_rotation_z(angle - drag.start_param) * drag.start_rot;
+        }
+    }
+
+    set_physics(&mut game_states.current, target, pos, rot_mat, vel);
+    set_physics(&mut game_states.next, target, pos, rot_mat, vel);
+
+    last_state_set.reset();
+    socket.send(SendableUdp::State(game_states.next.clone())).unwrap();
+}
+
+fn draw_handles(game_states: Res<GameStates>, mut gizmo: ResMut<StateSetGizmo>, mut gizmos: Gizmos) {
+    let Some(target) = gizmo.selected else {
+        return;
+    };
+
+    // the car left the game or was demolished
+    let Some((pos, _, vel)) = physics(&game_states.current, target) else {
+        gizmo.selected = None;
+        gizmo.dragging = None;
+        return;
+    };
+
+    let active = gizmo.dragging.as_ref().map(|drag| drag.handle);
+    let color = |handle: Handle, color: Srgba| if active == Some(handle) { ACTIVE_COLOR } else { color };
+
+    let center = pos.to_bevy();
+    for (i, (axis, axis_color)) in AXES.into_iter().zip(AXIS_COLORS).enumerate() {
+        gizmos.arrow(center, center + axis * AXIS_LENGTH, color(Handle::Axis(i), axis_color));
+    }
+
+    let tip = center + vel.to_bevy() * VELOCITY_SCALE;
+    let velocity_color = color(Handle::Velocity, VELOCITY_COLOR);
+    gizmos.line(center, tip, velocity_color);
+    gizmos.sphere(tip, VELOCITY_HANDLE_RADIUS, velocity_color);
+
+    if matches!(target, GizmoTarget::Car(_)) {
+        gizmos.circle(
+            Isometry3d::new(center, Quat::from_rotation_x(FRAC_PI_2)),
+            YAW_RING_RADIUS,
+            color(Handle::Yaw, YAW_RING_COLOR),
+        );
+    }
+}
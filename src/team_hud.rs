@@ -0,0 +1,222 @@
+use crate::{
+    nametags::CarNames,
+    rocketsim::Team,
+    settings::options::{ShowTeamHud, UiOverlayScale},
+    udp::{get_color_from_team, GameStates},
+};
+use bevy::prelude::*;
+
+pub struct TeamHudPlugin;
+
+impl Plugin for TeamHudPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, setup)
+            .add_systems(Update, (rebuild_rows, update_rows).chain());
+    }
+}
+
+const HUD_MARGIN: f32 = 20.;
+const HUD_TOP: f32 = 200.;
+const HUD_FONT_SIZE: f32 = 16.;
+const NAME_WIDTH: f32 = 110.;
+const BOOST_BAR_WIDTH: f32 = 100.;
+const BOOST_BAR_HEIGHT: f32 = 10.;
+const ROW_PADDING: f32 = 4.;
+
+const BOOST_BAR_BACKGROUND: Color = Color::srgba(0., 0., 0., 0.6);
+const BOOST_BAR_COLOR: Color = Color::srgb(1., 0.8, 0.1);
+
+#[derive(Component)]
+struct TeamHudRoot;
+
+/// One row of the HUD per car
+#[derive(Component)]
+struct TeamHudRow;
+
+#[derive(Component)]
+struct TeamHudBoostFill(u32);
+
+#[derive(Component)]
+enum TeamHudText {
+    Name(u32),
+    Boost(u32),
+    /// Supersonic or the demo respawn timer
+    Status(u32),
+}
+
+fn setup(mut commands: Commands) {
+    commands.spawn((
+        Node {
+            position_type: PositionType::Absolute,
+            left: Val::Px(HUD_MARGIN),
+            top: Val::Px(HUD_TOP),
+            flex_direction: FlexDirection::Column,
+            row_gap: Val::Px(ROW_PADDING),
+            ..default()
+        },
+        Visibility::Hidden,
+        TeamHudRoot,
+    ));
+}
+
+fn spawn_row(parent: &mut ChildBuilder, id: u32, team: Team, scale: f32) {
+    let font = TextFont {
+        font_size: HUD_FONT_SIZE * scale,
+        ..default()
+    };
+
+    parent
+        .spawn((
+            Node {
+                align_items: AlignItems::Center,
+                column_gap: Val::Px(ROW_PADDING * 2. * scale),
+                padding: UiRect::all(Val::Px(ROW_PADDING * scale)),
+                ..default()
+            },
+            BackgroundColor(get_color_from_team(team).with_alpha(0.6)),
+            TeamHudRow,
+        ))
+        .with_children(|row| {
+            row.spawn((
+                Text::new(""),
+                font.clone(),
+                Node {
+                    width: Val::Px(NAME_WIDTH * scale),
+                    overflow: Overflow::clip(),
+                    ..default()
+                },
+                TeamHudText::Name(id),
+            ));
+
+            row.spawn((
+                Node {
+                    width: Val::Px(BOOST_BAR_WIDTH * scale),
+                    height: Val::Px(BOOST_BAR_HEIGHT * scale),
+                    ..default()
+                },
+                BackgroundColor(BOOST_BAR_BACKGROUND),
+            ))
+            .with_children(|bar| {
+                bar.spawn((
+                    Node {
+                        width: Val::Percent(0.),
+                        height: Val::Percent(100.),
+                        ..default()
+                    },
+                    BackgroundColor(BOOST_BAR_COLOR),
+                    TeamHudBoostFill(id),
+                ));
+            });
+
+            row.spawn((
+                Text::new(""),
+                font.clone(),
+                Node {
+                    width: Val::Px(HUD_FONT_SIZE * 2. * scale),
+                    ..default()
+                },
+                TeamHudText::Boost(id),
+            ));
+
+            row.spawn((Text::new(""), font, TeamHudText::Status(id)));
+        });
+}
+
+/// Respawns the rows whenever cars are added or removed, change teams, or the UI scale changes
+fn rebuild_rows(
+    mut commands: Commands,
+    states: Res<GameStates>,
+    show_team_hud: Res<ShowTeamHud>,
+    ui_scale: Res<UiOverlayScale>,
+    mut root: Query<(Entity, &mut Visibility), With<TeamHudRoot>>,
+    rows: Query<Entity, With<TeamHudRow>>,
+    mut last_layout: Local<(Vec<(u32, Team)>, f32)>,
+) {
+    let Ok((root, mut visibility)) = root.get_single_mut() else {
+        return;
+    };
+
+    visibility.set_if_neq(if show_team_hud.0 {
+        Visibility::Inherited
+    } else {
+        Visibility::Hidden
+    });
+
+    if !show_team_hud.0 {
+        return;
+    }
+
+    // blue on top, then orange, in the same order as the car cameras within each team
+    let mut cars = states
+        .current
+        .cars
+        .iter()
+        .map(|car_info| (car_info.id, car_info.team))
+        .collect::<Vec<_>>();
+    cars.sort_unstable_by_key(|&(id, team)| (team as u8, id));
+
+    if last_layout.0 == cars && last_layout.1 == ui_scale.scale {
+        return;
+    }
+
+    for row in &rows {
+        commands.entity(row).despawn_recursive();
+    }
+
+    commands.entity(root).with_children(|parent| {
+        for &(id, team) in &cars {
+            spawn_row(parent, id, team, ui_scale.scale);
+        }
+    });
+
+    *last_layout = (cars, ui_scale.scale);
+}
+
+fn update_rows(
+    states: Res<GameStates>,
+    show_team_hud: Res<ShowTeamHud>,
+    car_names: Res<CarNames>,
+    mut texts: Query<(&mut Text, &TeamHudText)>,
+    mut boost_fills: Query<(&mut Node, &TeamHudBoostFill)>,
+) {
+    if !show_team_hud.0 {
+        return;
+    }
+
+    for (mut node, fill) in &mut boost_fills {
+        let Some(car_info) = states.current.cars.iter().find(|car_info| car_info.id == fill.0) else {
+            continue;
+        };
+
+        let width = Val::Percent(car_info.state.boost.clamp(0., 100.));
+        if node.width != width {
+            node.width = width;
+        }
+    }
+
+    for (mut text, field) in &mut texts {
+        let (TeamHudText::Name(id) | TeamHudText::Boost(id) | TeamHudText::Status(id)) = *field;
+        let Some(car_info) = states.current.cars.iter().find(|car_info| car_info.id == id) else {
+            continue;
+        };
+
+        let car_state = &car_info.state;
+        let new_text = match field {
+            TeamHudText::Name(_) => car_names.0.get(&id).cloned().unwrap_or_else(|| format!("Car {id}")),
+            TeamHudText::Boost(_) => format!("{:.0}", car_state.boost),
+            TeamHudText::Status(_) => {
+                if car_state.is_demoed || car_state.demo_respawn_timer > f32::EPSILON {
+                    format!("Demo {:.1}s", car_state.demo_respawn_timer)
+                } else if car_state.is_supersonic {
+                    String::from("Supersonic")
+                } else {
+                    String::new()
+                }
+            }
+        };
+
+        if text.0 != new_text {
+            text.0 = new_text;
+        }
+    }
+}
@@ -0,0 +1,365 @@
+use crate::{
+    camera::PrimaryCamera,
+    rocketsim::GameState,
+    settings::{
+        car_bodies::CarBodies,
+        options::{BallTrail, BoostTrailQuality},
+    },
+    udp::{car_preset_index, get_color_from_team, Ball, Boosting, Car, CarBoost, GameStates},
+};
+use bevy::{
+    pbr::{NotShadowCaster, NotShadowReceiver},
+    prelude::*,
+    render::{
+        mesh::{Indices, PrimitiveTopology, VertexAttributeValues},
+        render_asset::RenderAssetUsages,
+        view::NoFrustumCulling,
+    },
+    utils::HashMap,
+};
+use std::collections::VecDeque;
+
+pub struct TrailPlugin;
+
+impl Plugin for TrailPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, (update_boost_trails, update_ball_trail));
+    }
+}
+
+/// How long it takes for a point on the trail to fade out
+const TRAIL_LIFETIME: f32 = 0.5;
+const TRAIL_WIDTH: f32 = 30.;
+
+/// A ribbon that follows a car around while it's boosting
+///
+/// The mesh changes every frame so the bounds calculated when it's spawned are wrong, hence `NoFrustumCulling`
+#[derive(Component)]
+#[require(NotShadowCaster, NotShadowReceiver, NoFrustumCulling)]
+struct BoostTrail {
+    car: Entity,
+    /// Newest first, with how many seconds ago they were added
+    points: VecDeque<(Vec3, f32)>,
+    since_last_point: f32,
+}
+
+/// Rebuilds the ribbon mesh, with each point facing the camera and fading out with age
+fn build_ribbon(mesh: &mut Mesh, points: &[(Vec3, f32)], camera_pos: Vec3) {
+    let mut positions = Vec::with_capacity(points.len() * 2);
+    let mut normals = Vec::with_capacity(points.len() * 2);
+    let mut colors = Vec::with_capacity(points.len() * 2);
+
+    for (i, &(pos, age)) in points.iter().enumerate() {
+        let prev = points[i.saturating_sub(1)].0;
+        let next = points[(i + 1).min(points.len() - 1)].0;
+
+        let to_camera = (camera_pos - pos).try_normalize().unwrap_or(Vec3::Y);
+        let side = (prev - next).cross(to_camera).try_normalize().unwrap_or(Vec3::Y);
+
+        let life = 1. - (age / TRAIL_LIFETIME).clamp(0., 1.);
+        let half_width = TRAIL_WIDTH * (0.25 + 0.75 * life) / 2.;
+
+        for offset in [side * half_width, side * -half_width] {
+            positions.push(pos + offset);
+            normals.push(to_camera);
+            colors.push([1., 1., 1., life * 0.8]);
+        }
+    }
+
+    let indices = (0..points.len().saturating_sub(1) as u32)
+        .flat_map(|i| {
+            let [a, b, c, d] = [i * 2, i * 2 + 1, i * 2 + 2, i * 2 + 3];
+            [a, b, c, b, d, c]
+        })
+        .collect();
+
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, colors);
+    mesh.insert_indices(Indices::U32(indices));
+}
+
+fn update_boost_trails(
+    mut commands: Commands,
+    time: Res<Time>,
+    states: Res<GameStates>,
+    quality: Res<BoostTrailQuality>,
+    car_bodies: Res<CarBodies>,
+    cars: Query<(Entity, &Car, &Boosting)>,
+    exhausts: Query<(&GlobalTransform, &Parent), With<CarBoost>>,
+    camera: Query<&GlobalTransform, With<PrimaryCamera>>,
+    mut trails: Query<(Entity, &mut BoostTrail, &Mesh3d, &mut Visibility)>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    if *quality == BoostTrailQuality::Off {
+        for (entity, ..) in &trails {
+            commands.entity(entity).despawn();
+        }
+
+        return;
+    }
+
+    let spacing = TRAIL_LIFETIME / quality.num_points() as f32;
+    let delta = time.delta_secs();
+    let camera_pos = camera.single().translation();
+
+    let exhausts = exhausts
+        .iter()
+        .map(|(transform, parent)| (parent.get(), transform.translation()))
+        .collect::<HashMap<_, _>>();
+
+    let mut has_trail = Vec::new();
+
+    for (entity, mut trail, mesh, mut visibility) in &mut trails {
+        let (Ok((_, _, boosting)), Some(&exhaust)) = (cars.get(trail.car), exhausts.get(&trail.car)) else {
+            commands.entity(entity).despawn();
+            continue;
+        };
+
+        has_trail.push(trail.car);
+
+        for (_, age) in &mut trail.points {
+            *age += delta;
+        }
+
+        while trail.points.back().is_some_and(|&(_, age)| age > TRAIL_LIFETIME) {
+            trail.points.pop_back();
+        }
+
+        trail.since_last_point += delta;
+        if boosting.0 && (trail.since_last_point >= spacing || trail.points.is_empty()) {
+            trail.points.push_front((exhaust, 0.));
+            trail.since_last_point = 0.;
+        }
+
+        // the trail should always stay attached to the car while it's boosting
+        let head = boosting.0.then_some((exhaust, 0.));
+        let points = head.into_iter().chain(trail.points.iter().copied()).collect::<Vec<_>>();
+
+        if points.len() < 2 {
+            visibility.set_if_neq(Visibility::Hidden);
+            continue;
+        }
+
+        visibility.set_if_neq(Visibility::Inherited);
+        build_ribbon(meshes.get_mut(&mesh.0).unwrap(), &points, camera_pos);
+    }
+
+    for (car_entity, car, _) in &cars {
+        if has_trail.contains(&car_entity) {
+            continue;
+        }
+
+        let Some(car_info) = states.current.cars.iter().find(|car_info| car_info.id == car.id()) else {
+            continue;
+        };
+
+        let color = car_bodies
+            .get(car_info.id, car_preset_index(car_info))
+            .primary_color
+            .unwrap_or_else(|| get_color_from_team(car_info.team));
+
+        let mut mesh = Mesh::new(PrimitiveTopology::TriangleList, RenderAssetUsages::default());
+        build_ribbon(&mut mesh, &[(Vec3::ZERO, TRAIL_LIFETIME); 2], camera_pos);
+
+        commands.spawn((
+            Mesh3d(meshes.add(mesh)),
+            MeshMaterial3d(materials.add(StandardMaterial {
+                base_color: color,
+                alpha_mode: AlphaMode::Blend,
+                unlit: true,
+                cull_mode: None,
+                ..default()
+            })),
+            Visibility::Hidden,
+            BoostTrail {
+                car: car_entity,
+                points: VecDeque::with_capacity(quality.num_points() + 1),
+                since_last_point: 0.,
+            },
+        ));
+    }
+}
+
+/// How many points the ball trail keeps, however long it is
+const BALL_TRAIL_POINTS: usize = 96;
+/// If the ball moves further than this between two points, it was reset and the trail starts over
+const BALL_TRAIL_MAX_JUMP: f32 = 1000.;
+
+/// A ribbon that follows the ball, colored by the team that touched it last at each point
+///
+/// The points are kept in a ring buffer and the mesh's buffers are rewritten in place,
+/// so nothing is allocated while the trail is running
+#[derive(Component)]
+#[require(NotShadowCaster, NotShadowReceiver, NoFrustumCulling)]
+struct BallTrailRibbon {
+    /// Newest first, with how many seconds ago they were added and the color of the last touch at that time
+    points: VecDeque<(Vec3, f32, LinearRgba)>,
+    since_last_point: f32,
+}
+
+/// The color of the team that touched the ball last, white if nobody has yet
+fn last_touch_color(state: &GameState) -> LinearRgba {
+    state
+        .cars
+        .iter()
+        .filter(|car| car.state.ball_hit_info.is_valid)
+        .max_by_key(|car| car.state.ball_hit_info.tick_count_when_hit)
+        .map_or(Color::WHITE, |car| get_color_from_team(car.team))
+        .to_linear()
+}
+
+/// A mesh with room for every point of the ball trail, where the indices never change
+fn ball_ribbon_mesh() -> Mesh {
+    let num_verts = (BALL_TRAIL_POINTS + 1) * 2;
+    let indices = (0..BALL_TRAIL_POINTS as u32)
+        .flat_map(|i| {
+            let [a, b, c, d] = [i * 2, i * 2 + 1, i * 2 + 2, i * 2 + 3];
+            [a, b, c, b, d, c]
+        })
+        .collect();
+
+    Mesh::new(PrimitiveTopology::TriangleList, RenderAssetUsages::default())
+        .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, vec![[0f32; 3]; num_verts])
+        .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, vec![[0f32; 3]; num_verts])
+        .with_inserted_attribute(Mesh::ATTRIBUTE_COLOR, vec![[0f32; 4]; num_verts])
+        .with_inserted_indices(Indices::U32(indices))
+}
+
+/// Rewrites the ball ribbon's vertices, the same way as `build_ribbon` but without reallocating the mesh's buffers
+///
+/// The vertices that aren't needed are collapsed onto the last point, so they don't draw anything
+fn update_ball_ribbon(mesh: &mut Mesh, points: &[(Vec3, f32, LinearRgba)], camera_pos: Vec3, settings: &BallTrail) {
+    let (
+        Some(VertexAttributeValues::Float32x3(mut positions)),
+        Some(VertexAttributeValues::Float32x3(mut normals)),
+        Some(VertexAttributeValues::Float32x4(mut colors)),
+    ) = (
+        mesh.remove_attribute(Mesh::ATTRIBUTE_POSITION),
+        mesh.remove_attribute(Mesh::ATTRIBUTE_NORMAL),
+        mesh.remove_attribute(Mesh::ATTRIBUTE_COLOR),
+    )
+    else {
+        return;
+    };
+
+    let last = points.len() - 1;
+    for i in 0..positions.len() / 2 {
+        let (pos, age, color) = points[i.min(last)];
+        let prev = points[i.saturating_sub(1).min(last)].0;
+        let next = points[(i + 1).min(last)].0;
+
+        let to_camera = (camera_pos - pos).try_normalize().unwrap_or(Vec3::Y);
+        let side = (prev - next).cross(to_camera).try_normalize().unwrap_or(Vec3::Y);
+
+        let (life, half_width) = if i > last {
+            (0., 0.)
+        } else {
+            let life = 1. - (age / settings.length).clamp(0., 1.);
+            (life, settings.width * (0.25 + 0.75 * life) / 2.)
+        };
+
+        for (j, offset) in [side * half_width, side * -half_width].into_iter().enumerate() {
+            positions[i * 2 + j] = (pos + offset).to_array();
+            normals[i * 2 + j] = to_camera.to_array();
+            colors[i * 2 + j] = color.with_alpha(life * 0.6).to_f32_array();
+        }
+    }
+
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, colors);
+}
+
+fn update_ball_trail(
+    mut commands: Commands,
+    time: Res<Time>,
+    states: Res<GameStates>,
+    settings: Res<BallTrail>,
+    ball: Query<&Transform, With<Ball>>,
+    camera: Query<&GlobalTransform, With<PrimaryCamera>>,
+    mut trails: Query<(Entity, &mut BallTrailRibbon, &Mesh3d, &mut Visibility)>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut points: Local<Vec<(Vec3, f32, LinearRgba)>>,
+) {
+    if !settings.enabled || settings.length <= 0. {
+        for (entity, ..) in &trails {
+            commands.entity(entity).despawn();
+        }
+
+        return;
+    }
+
+    let Ok(ball) = ball.get_single() else {
+        return;
+    };
+
+    let Ok((_, mut trail, mesh, mut visibility)) = trails.get_single_mut() else {
+        commands.spawn((
+            Mesh3d(meshes.add(ball_ribbon_mesh())),
+            MeshMaterial3d(materials.add(StandardMaterial {
+                alpha_mode: AlphaMode::Blend,
+                unlit: true,
+                cull_mode: None,
+                ..default()
+            })),
+            Visibility::Hidden,
+            BallTrailRibbon {
+                points: VecDeque::with_capacity(BALL_TRAIL_POINTS),
+                since_last_point: 0.,
+            },
+        ));
+        return;
+    };
+
+    let ball_pos = ball.translation;
+    let delta = time.delta_secs();
+
+    if trail
+        .points
+        .front()
+        .is_some_and(|&(pos, ..)| pos.distance_squared(ball_pos) > BALL_TRAIL_MAX_JUMP * BALL_TRAIL_MAX_JUMP)
+    {
+        trail.points.clear();
+    }
+
+    for (_, age, _) in &mut trail.points {
+        *age += delta;
+    }
+
+    while trail.points.back().is_some_and(|&(_, age, _)| age > settings.length) {
+        trail.points.pop_back();
+    }
+
+    let color = last_touch_color(&states.current);
+
+    trail.since_last_point += delta;
+    if trail.since_last_point >= settings.length / BALL_TRAIL_POINTS as f32 || trail.points.is_empty() {
+        if trail.points.len() == BALL_TRAIL_POINTS {
+            trail.points.pop_back();
+        }
+
+        trail.points.push_front((ball_pos, 0., color));
+        trail.since_last_point = 0.;
+    }
+
+    // the trail always starts right at the ball, even between points
+    points.clear();
+    points.push((ball_pos, 0., color));
+    points.extend(trail.points.iter().copied());
+
+    if points.len() < 2 {
+        visibility.set_if_neq(Visibility::Hidden);
+        return;
+    }
+
+    visibility.set_if_neq(Visibility::Inherited);
+    update_ball_ribbon(
+        meshes.get_mut(&mesh.0).unwrap(),
+        &points,
+        camera.single().translation(),
+        &settings,
+    );
+}
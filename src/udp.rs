@@ -1,17 +1,31 @@
 use crate::{
     assets::{get_material, get_mesh_info, BoostPickupGlows, CarWheelMesh},
-    bytes::{FromBytes, ToBytes, ToBytesExact},
-    camera::{PrimaryCamera, TimeDisplay},
+    bytes::{FromBytes, FromBytesExact, ToBytes, ToBytesExact, TryFromBytes},
+    camera::{CameraTransition, PrimaryCamera, TimeDisplay, DEFAULT_FOV, GLOW_STRENGTH},
+    director::Director,
     mesh::LargeBoostPadLocRots,
+    message_feed::BotMessage,
     morton::Morton,
+    nametags::{CarMetadata, CarNames},
     renderer::{RenderGroups, RenderMessage, UdpRendererPlugin},
+    replay::Replay,
     rocketsim::{CarInfo, GameMode, GameState, Team},
-    settings::options::{BallCam, CalcBallRot, GameSpeed, Options, PacketSmoothing, ShowTime},
-    GameLoadState, ServerPort,
+    settings::{
+        car_bodies::{CarBodies, CarCustomization},
+        keybinds::{Action, Keybinds},
+        options::{
+            BallCam, CalcBallRot, CarLodDistance, GameSpeed, HideDemoedCars, MenuFocused, Options, PacketSmoothing,
+            PovCamera, RenderDelay, ShowCarHitboxes, ShowPadCooldowns, ShowTime, SmoothBoostMeter,
+        },
+        packet_log,
+        paused_systems::{not_paused, DebugSystem, PausedSystems},
+    },
+    GameLoadState, ServerPort, Transport,
 };
 use bevy::{
     app::AppExit,
     asset::LoadState,
+    input::mouse::{MouseScrollUnit, MouseWheel},
     math::{Mat3A, Vec3A},
     pbr::{NotShadowCaster, NotShadowReceiver},
     prelude::*,
@@ -19,17 +33,28 @@ use bevy::{
     time::Stopwatch,
     utils::HashMap,
 };
-use crossbeam_channel::{Receiver, Sender};
+use crossbeam_channel::{Receiver, RecvTimeoutError, Sender, TryRecvError};
 use itertools::izip;
 use std::{
-    f32::consts::PI,
+    collections::VecDeque,
+    f32::consts::{PI, TAU},
+    fmt::Display,
     fs,
+    io::{self, Read, Write},
+    iter,
     mem::{replace, swap},
-    net::{IpAddr, Ipv4Addr, SocketAddr, UdpSocket},
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, Shutdown, SocketAddr, TcpStream, UdpSocket},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
     thread,
-    time::Duration,
+    time::{Duration, Instant},
 };
 
+#[cfg(feature = "replay_import")]
+use std::path::PathBuf;
+
 use crate::{
     camera::{BoostAmount, HighlightedEntity, BOOST_INDICATOR_FONT_SIZE, BOOST_INDICATOR_POS},
     mesh::{BoostPadClicked, CarClicked, ChangeCarPos},
@@ -57,7 +82,7 @@ impl BoostPadI {
 pub struct Ball;
 
 #[derive(Component)]
-#[require(Mesh3d, MeshMaterial3d<StandardMaterial>)]
+#[require(Mesh3d, MeshMaterial3d<StandardMaterial>, Boosting, CarLod)]
 pub struct Car(u32);
 
 impl Car {
@@ -71,30 +96,238 @@ impl Car {
 #[require(Mesh3d, MeshMaterial3d<StandardMaterial>)]
 pub struct CarBody;
 
-#[derive(Resource)]
-struct DirectorTimer(Timer);
-
 #[derive(Resource, Deref)]
 pub struct Connection(Sender<SendableUdp>);
 
+/// The version of the byte layout used in the packets
+///
+/// Bump this whenever a change to `bytes.rs` would make older clients mis-parse packets
+pub const PROTOCOL_VERSION: u32 = 1;
+
 pub enum SendableUdp {
+    Hello(u32),
     Paused(bool),
     Speed(f32),
     State(GameState),
 }
 
-fn establish_connection(port: Res<ServerPort>, mut commands: Commands, mut state: ResMut<NextState<GameLoadState>>) {
-    let out_addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), port.primary_port);
-    let recv_addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(0, 0, 0, 0)), port.secondary_port);
-    let socket = UdpSocket::bind(recv_addr).unwrap();
+impl SendableUdp {
+    fn into_packet(self) -> (UdpPacketTypes, Vec<u8>) {
+        match self {
+            Self::Hello(version) => (UdpPacketTypes::Hello, version.to_bytes().to_vec()),
+            Self::Paused(paused) => (UdpPacketTypes::Paused, vec![paused as u8]),
+            Self::Speed(speed) => (UdpPacketTypes::Speed, speed.to_bytes().to_vec()),
+            Self::State(state) => (UdpPacketTypes::GameState, state.to_bytes()),
+        }
+    }
+}
+
+fn establish_connection(
+    mut port: ResMut<ServerPort>,
+    options: Res<Options>,
+    ball: Query<(), With<Ball>>,
+    bound_socket: Option<Res<BoundUdpSocket>>,
+    mut commands: Commands,
+    mut state: ResMut<NextState<GameLoadState>>,
+) {
+    if port.host.is_none() {
+        match options.remote_host.parse() {
+            Ok(host) => port.host = Some(host),
+            Err(_) => warn!(
+                "Invalid remote_host {} in settings, connecting to localhost instead",
+                options.remote_host
+            ),
+        }
+    }
+
+    // the old socket can take a moment to be released after losing the connection, so just try again next frame
+    if let Err(e) = start_connection(&port, bound_socket.as_ref().map(|socket| &socket.0), &mut commands) {
+        debug!("Failed to connect to {} due to: {e}", port.out_addr());
+        return;
+    }
+
+    // when reconnecting, the field is already loaded
+    state.set(if ball.is_empty() {
+        GameLoadState::FieldExtra
+    } else {
+        GameLoadState::None
+    });
+}
+
+/// The socket that UDP packets are received on, kept around so reconnecting doesn't have to bind it again,
+/// along with the secondary port it was bound for
+#[derive(Resource)]
+struct BoundUdpSocket(UdpSocket, u16);
+
+/// The unspecified address of the same IP version as the simulation, so packets from anywhere can be received
+fn bind_ip(out_addr: SocketAddr) -> IpAddr {
+    if out_addr.is_ipv6() {
+        IpAddr::V6(Ipv6Addr::UNSPECIFIED)
+    } else {
+        IpAddr::V4(Ipv4Addr::UNSPECIFIED)
+    }
+}
+
+/// Binds the secondary port if it's free, then the first free one in `--port-retry`, and otherwise lets the OS pick one
+///
+/// The simulation learns which port was picked from where the connection packet came from
+fn bind_udp_socket(bind_ip: IpAddr, port: &ServerPort) -> io::Result<UdpSocket> {
+    let candidates = iter::once(port.secondary_port)
+        .chain(port.port_retry.clone().into_iter().flatten())
+        .chain(iter::once(0));
+
+    let mut last_error = None;
+    for candidate in candidates {
+        match UdpSocket::bind(SocketAddr::new(bind_ip, candidate)) {
+            Ok(socket) => {
+                let bound_port = socket.local_addr()?.port();
+                if bound_port != port.secondary_port {
+                    warn!(
+                        "Port {} is already in use, listening on port {bound_port} instead",
+                        port.secondary_port
+                    );
+                }
+
+                return Ok(socket);
+            }
+            Err(e) if e.kind() == io::ErrorKind::AddrInUse => last_error = Some(e),
+            Err(e) => return Err(e),
+        }
+    }
+
+    Err(last_error.unwrap())
+}
+
+/// Inserts the `Connection` and `UdpUpdateStream` resources and starts the networking threads
+///
+/// Over UDP, `bound_socket` is reused if there is one so the simulation can keep sending to the same port
+pub fn start_connection(port: &ServerPort, bound_socket: Option<&UdpSocket>, commands: &mut Commands) -> io::Result<()> {
+    let out_addr = port.out_addr();
+    let (tx, rx) = crossbeam_channel::unbounded();
+
+    match port.transport {
+        Transport::Udp => {
+            let socket = match bound_socket {
+                Some(socket) => socket.try_clone()?,
+                None => bind_udp_socket(bind_ip(out_addr), port)?,
+            };
+
+            start_udp_recv_handler(socket.try_clone()?, commands);
+            start_udp_send_handler(socket.try_clone()?, out_addr, rx);
+            commands.insert_resource(BoundUdpSocket(socket, port.secondary_port));
+        }
+        Transport::Tcp => start_tcp_handlers(out_addr, rx, commands),
+        #[cfg(feature = "rlbot")]
+        Transport::RLBot => start_rlbot_handlers(out_addr, rx, commands),
+        #[cfg(feature = "replay_import")]
+        Transport::ReplayFile => start_replay_file_handlers(port.replay_file.clone(), rx, commands),
+    }
+
+    commands.insert_resource(Connection(tx));
+    Ok(())
+}
+
+/// Whether nothing has been heard from the simulation since connecting to it
+#[derive(Resource, PartialEq, Eq)]
+pub struct WaitingForSimulator(pub bool);
+
+impl Default for WaitingForSimulator {
+    #[inline]
+    fn default() -> Self {
+        Self(true)
+    }
+}
+
+/// Drops the connection and goes back to `GameLoadState::Connect`, which starts the networking threads again
+///
+/// The UDP socket stays bound, otherwise the port could be taken in the meantime and the new one would be a different port
+fn wait_for_simulator(
+    commands: &mut Commands,
+    load_state: &mut NextState<GameLoadState>,
+    waiting: &mut WaitingForSimulator,
+) {
+    commands.remove_resource::<Connection>();
+    commands.remove_resource::<UdpUpdateStream>();
 
+    waiting.0 = true;
+    load_state.set(GameLoadState::Connect);
+}
+
+/// Connect to the simulation at the host in the settings and the current primary port
+#[derive(Event)]
+pub struct Reconnect;
+
+fn reconnect(
+    mut port: ResMut<ServerPort>,
+    options: Res<Options>,
+    bound_socket: Option<Res<BoundUdpSocket>>,
+    connection: Option<Res<Connection>>,
+    mut commands: Commands,
+) {
+    let Ok(host) = options.remote_host.parse::<IpAddr>() else {
+        error!("Can't reconnect, {} isn't an IPv4 or IPv6 address", options.remote_host);
+        return;
+    };
+
+    if let Some(socket) = &bound_socket {
+        if socket.0.local_addr().is_ok_and(|addr| addr.is_ipv6() != host.is_ipv6()) {
+            error!("Switching between IPv4 and IPv6 over UDP requires a restart");
+            return;
+        }
+    }
+
+    port.host = Some(host);
+
+    // another launch can hand off a different secondary port, which needs a new socket
+    let rebind =
+        port.transport == Transport::Udp && bound_socket.as_ref().is_some_and(|socket| socket.1 != port.secondary_port);
+
+    // while waiting for the simulation, `establish_connection` will pick up the new address by itself
+    if connection.is_none() {
+        if rebind {
+            commands.remove_resource::<BoundUdpSocket>();
+        }
+
+        return;
+    }
+
+    let out_addr = port.out_addr();
+    info!("Reconnecting to {out_addr}");
+
+    // the old socket is closed once its recv thread notices that nobody is reading its packets anymore
+    let new_socket = if rebind {
+        match bind_udp_socket(bind_ip(out_addr), &port) {
+            Ok(socket) => Some(socket),
+            Err(e) => {
+                error!("Failed to bind port {} due to: {e}", port.secondary_port);
+                return;
+            }
+        }
+    } else {
+        None
+    };
+
+    // replacing the connection drops the old sender, which stops the old sending thread
     let (tx, rx) = crossbeam_channel::unbounded();
     commands.insert_resource(Connection(tx));
 
-    start_udp_recv_handler(socket.try_clone().unwrap(), &mut commands);
-    start_udp_send_handler(socket, out_addr, rx);
+    if let Some(socket) = new_socket {
+        start_udp_recv_handler(socket.try_clone().unwrap(), &mut commands);
+        start_udp_send_handler(socket.try_clone().unwrap(), out_addr, rx);
+        commands.insert_resource(BoundUdpSocket(socket, port.secondary_port));
+        return;
+    }
 
-    state.set(GameLoadState::FieldExtra);
+    match (port.transport, bound_socket) {
+        // packets are received from anyone, so only the sending side has to change
+        (Transport::Udp, Some(socket)) => start_udp_send_handler(socket.0.try_clone().unwrap(), out_addr, rx),
+        #[cfg(feature = "rlbot")]
+        (Transport::RLBot, _) => start_rlbot_handlers(out_addr, rx, &mut commands),
+        // plays the replay again from the start
+        #[cfg(feature = "replay_import")]
+        (Transport::ReplayFile, _) => start_replay_file_handlers(port.replay_file.clone(), rx, &mut commands),
+        _ => start_tcp_handlers(out_addr, rx, &mut commands),
+    }
 }
 
 pub trait ToBevyVec {
@@ -133,7 +366,7 @@ impl ToBevyVec for Vec3 {
     }
 }
 
-trait ToBevyMat {
+pub trait ToBevyMat {
     fn to_bevy(self) -> Quat;
 }
 
@@ -147,7 +380,7 @@ impl ToBevyMat for Mat3A {
 
 const NUM_CAR_BODIES: usize = 6;
 
-const CAR_BODIES: [&str; NUM_CAR_BODIES] = [
+pub const CAR_BODIES: [&str; NUM_CAR_BODIES] = [
     "Body_Octane.SkeletalMesh3.Body_Octane_SK",
     "Body_MuscleCar.SkeletalMesh3.Body_MuscleCar_SK",
     "Body_Darkcar.SkeletalMesh3.Body_Darkcar_SK",
@@ -180,27 +413,61 @@ pub const ORANGE_COLOR: Srgba = if cfg!(feature = "full_load") {
 
 #[inline]
 /// Use colors that are a bit darker if we don't have the `full_load` feature
-const fn get_color_from_team(team: Team) -> Color {
+pub const fn get_color_from_team(team: Team) -> Color {
     match team {
         Team::Blue => Color::Srgba(BLUE_COLOR),
         Team::Orange => Color::Srgba(ORANGE_COLOR),
     }
 }
 
+/// Where the boost trail comes out of the car
 #[derive(Component)]
-#[require(Mesh3d, MeshMaterial3d<StandardMaterial>)]
+#[require(Transform)]
 pub struct CarBoost;
 
+#[derive(Component, Default, PartialEq, Eq)]
+pub struct Boosting(pub bool);
+
+/// Whether the car is far enough from the camera to be drawn as its hitbox instead of its body and wheels
+#[derive(Component, Default)]
+struct CarLod(bool);
+
+/// How much closer than the LOD distance a car has to come to get its body back,
+/// so cars right at the edge don't keep swapping back and forth
+const CAR_LOD_HYSTERESIS: f32 = 0.9;
+
+/// The car's collision hitbox, shown instead of the body when `ShowCarHitboxes` is enabled or the car is far away
+#[derive(Component)]
+#[require(Mesh3d, MeshMaterial3d<StandardMaterial>)]
+struct CarHitbox;
+
 #[derive(Component)]
 #[require(Mesh3d, MeshMaterial3d<StandardMaterial>)]
 struct CarWheel {
     front: bool,
     left: bool,
+    /// Where the wheel sits when the suspension is at rest, relative to the car
+    rest_pos: Vec3,
+    /// How far the wheel has rolled, in radians
+    spin: f32,
+    /// How far the wheel has dropped below its resting position
+    droop: f32,
 }
 
 impl CarWheel {
-    fn new(front: bool, left: bool) -> Self {
-        Self { front, left }
+    fn new(front: bool, left: bool, rest_pos: Vec3) -> Self {
+        Self {
+            front,
+            left,
+            rest_pos,
+            spin: 0.,
+            droop: 0.,
+        }
+    }
+
+    /// The index of this wheel in `CarState::wheels_with_contact`
+    const fn index(&self) -> usize {
+        (if self.front { 0 } else { 2 }) + (if self.left { 0 } else { 1 })
     }
 }
 
@@ -229,20 +496,11 @@ pub fn send_event<E: Clone, S: Event + From<E>>(mut trigger: Trigger<E>, mut eve
     events.send(S::from(trigger.event().to_owned()));
 }
 
-fn spawn_car(
-    car_info: &CarInfo,
-    commands: &mut Commands,
-    meshes: &mut Assets<Mesh>,
-    materials: &mut Assets<StandardMaterial>,
-    asset_server: &AssetServer,
-    car_wheel_mesh: &CarWheelMesh,
-    images: &mut Assets<Image>,
-    render_device: Option<&RenderDevice>,
-) {
+/// The index of the built-in car body, and hitbox preset, closest to the car's hitbox
+pub fn car_preset_index(car_info: &CarInfo) -> usize {
     let hitbox = car_info.config.hitbox_size.to_bevy();
-    let base_color = get_color_from_team(car_info.team);
 
-    let car_index = if (120f32..121.).contains(&hitbox.x) {
+    if (120f32..121.).contains(&hitbox.x) {
         // octane
         0
     } else if (130f32..131.).contains(&hitbox.x) {
@@ -263,14 +521,48 @@ fn spawn_car(
     } else {
         // spawn octane by default
         0
-    };
+    }
+}
+
+fn spawn_car(
+    car_info: &CarInfo,
+    commands: &mut Commands,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<StandardMaterial>,
+    asset_server: &AssetServer,
+    car_wheel_mesh: &CarWheelMesh,
+    images: &mut Assets<Image>,
+    render_device: Option<&RenderDevice>,
+    car_bodies: &CarBodies,
+) {
+    let hitbox = car_info.config.hitbox_size.to_bevy();
+    let base_color = get_color_from_team(car_info.team);
+    let car_index = car_preset_index(car_info);
 
     #[cfg(debug_assertions)]
     let name = CAR_BODY_NAMES[car_index];
-    let mesh_id = CAR_BODIES[car_index];
 
-    let mesh_info = get_mesh_info(mesh_id, meshes)
-        .unwrap_or_else(|| vec![meshes.add(Cuboid::new(hitbox.x * 2., hitbox.y * 2., hitbox.z * 2.))]);
+    let customization = car_bodies.get(car_info.id, car_index);
+
+    let custom_mesh = customization.body.as_deref().and_then(|mesh_id| {
+        let mesh_info = get_mesh_info(mesh_id, meshes);
+        if mesh_info.is_none() {
+            error!(
+                "Failed to load car body {mesh_id} for car {}, falling back to {}",
+                car_info.id, CAR_BODIES[car_index]
+            );
+        }
+
+        mesh_info.map(|mesh_info| (mesh_id, mesh_info))
+    });
+
+    let (mesh_id, mesh_info) = custom_mesh.unwrap_or_else(|| {
+        let mesh_id = CAR_BODIES[car_index];
+        let mesh_info = get_mesh_info(mesh_id, meshes)
+            .unwrap_or_else(|| vec![meshes.add(Cuboid::new(hitbox.x * 2., hitbox.y * 2., hitbox.z * 2.))]);
+
+        (mesh_id, mesh_info)
+    });
 
     commands
         .spawn((
@@ -290,8 +582,6 @@ fn spawn_car(
         .observe(send_event::<Pointer<Drag>, ChangeCarPos>)
         .observe(send_event::<Pointer<Click>, CarClicked>)
         .with_children(|parent| {
-            const CAR_BOOST_LENGTH: f32 = 50.;
-
             if cfg!(feature = "full_load") {
                 let mesh_materials = get_car_mesh_materials(
                     mesh_id,
@@ -303,11 +593,16 @@ fn spawn_car(
                     render_device,
                 );
 
-                for (mesh, material) in mesh_info.into_iter().zip(mesh_materials) {
+                let mut mesh_materials = mesh_materials.into_iter();
+                for (i, mesh) in mesh_info.into_iter().enumerate() {
+                    // custom meshes don't come with materials
+                    let material = mesh_materials.next().unwrap_or_else(|| materials.add(base_color));
+                    let material = customize_material(material, i == 0, &customization, materials, asset_server);
                     parent.spawn((CarBody, Mesh3d(mesh), MeshMaterial3d(material)));
                 }
             } else {
                 let material = materials.add(base_color);
+                let material = customize_material(material, true, &customization, materials, asset_server);
 
                 for mesh in mesh_info {
                     parent.spawn((CarBody, Mesh3d(mesh), MeshMaterial3d(material.clone())));
@@ -315,22 +610,23 @@ fn spawn_car(
             }
 
             parent.spawn((
-                Mesh3d(meshes.add(Cylinder::new(10., CAR_BOOST_LENGTH))),
+                Mesh3d(meshes.add(Cuboid::from_size(hitbox))),
                 MeshMaterial3d(materials.add(StandardMaterial {
-                    base_color: Color::srgba(1., 1., 0., 0.),
-                    alpha_mode: AlphaMode::Add,
-                    cull_mode: None,
+                    base_color: base_color.with_alpha(0.6),
+                    alpha_mode: AlphaMode::Blend,
                     ..default()
                 })),
-                Transform {
-                    translation: Vec3::new((hitbox.x + CAR_BOOST_LENGTH) / -2., hitbox.y / 2., 0.),
-                    rotation: Quat::from_rotation_z(PI / 2.),
-                    ..default()
-                },
+                Transform::from_translation(car_info.config.hitbox_pos_offset.to_bevy()),
+                Visibility::Hidden,
+                CarHitbox,
+            ));
+
+            parent.spawn((
+                Transform::from_translation(Vec3::new(hitbox.x / -2., hitbox.y / 2., 0.)),
                 CarBoost,
             ));
 
-            let wheel_material = materials.add(base_color);
+            let wheel_material = materials.add(customization.secondary_color.unwrap_or(base_color));
             let wheel_pairs = [car_info.config.front_wheels, car_info.config.back_wheels];
 
             for (i, wheel_pair) in wheel_pairs.iter().enumerate() {
@@ -339,22 +635,59 @@ fn spawn_car(
                 for side in 0..=1 {
                     let offset = Vec3::new(1., 1., 1. - (2. * side as f32));
 
+                    let rest_pos = wheel_pair.connection_point_offset.to_bevy() * offset + wheel_offset;
+
                     parent.spawn((
                         Mesh3d(car_wheel_mesh.mesh.clone()),
                         MeshMaterial3d(wheel_material.clone()),
                         Transform {
-                            translation: wheel_pair.connection_point_offset.to_bevy() * offset + wheel_offset,
+                            translation: rest_pos,
                             rotation: Quat::from_rotation_x(PI * side as f32),
                             ..default()
                         },
-                        CarWheel::new(i == 0, side == 0),
+                        CarWheel::new(i == 0, side == 0, rest_pos),
                     ));
                 }
             }
         });
 }
 
-fn get_car_mesh_materials(
+/// Applies the user's colors and decal to a copy of a car body material
+///
+/// The first material of a body is its paint, which gets the primary color and the decal,
+/// while the rest get the secondary color
+fn customize_material(
+    material: Handle<StandardMaterial>,
+    is_paint: bool,
+    customization: &CarCustomization,
+    materials: &mut Assets<StandardMaterial>,
+    asset_server: &AssetServer,
+) -> Handle<StandardMaterial> {
+    let (color, decal) = if is_paint {
+        (customization.primary_color, customization.decal.as_deref())
+    } else {
+        (customization.secondary_color, None)
+    };
+
+    if color.is_none() && decal.is_none() {
+        return material;
+    }
+
+    // materials are shared between cars, so only change a copy
+    let mut material = materials.get(&material).cloned().unwrap_or_default();
+
+    if let Some(color) = color {
+        material.base_color = color;
+    }
+
+    if let Some(decal) = decal {
+        material.base_color_texture = Some(asset_server.load(decal.to_string()));
+    }
+
+    materials.add(material)
+}
+
+pub fn get_car_mesh_materials(
     mesh_id: &str,
     materials: &mut Assets<StandardMaterial>,
     asset_server: &AssetServer,
@@ -364,7 +697,9 @@ fn get_car_mesh_materials(
     render_device: Option<&RenderDevice>,
 ) -> Vec<Handle<StandardMaterial>> {
     let mesh_path = mesh_id.replace('.', "/");
-    let props = fs::read_to_string(format!("./assets/{mesh_path}.props.txt")).unwrap();
+    let Ok(props) = fs::read_to_string(format!("./assets/{mesh_path}.props.txt")) else {
+        return Vec::new();
+    };
     let mut mesh_materials = Vec::with_capacity(2);
 
     let mut inside_mats = false;
@@ -400,6 +735,7 @@ fn get_car_mesh_materials(
 }
 
 #[repr(u8)]
+#[derive(Clone, Copy, Debug)]
 pub enum UdpPacketTypes {
     Quit,
     GameState,
@@ -407,6 +743,9 @@ pub enum UdpPacketTypes {
     Paused,
     Speed,
     Render,
+    CarMetadata,
+    Hello,
+    Message,
 }
 
 impl UdpPacketTypes {
@@ -418,6 +757,9 @@ impl UdpPacketTypes {
             3 => Some(Self::Paused),
             4 => Some(Self::Speed),
             5 => Some(Self::Render),
+            6 => Some(Self::CarMetadata),
+            7 => Some(Self::Hello),
+            8 => Some(Self::Message),
             _ => None,
         }
     }
@@ -429,185 +771,620 @@ pub struct SpeedUpdate(pub f32);
 #[derive(Event)]
 pub struct PausedUpdate(pub bool);
 
-enum UdpUpdate {
-    State(GameState),
+#[derive(Event)]
+pub struct DumpGameState;
+
+pub enum UdpUpdate {
+    /// A game state, and when it was read off of the socket so `NetworkStats` can tell how long it waited
+    State(Arc<GameState>, Instant),
     Render(RenderMessage),
+    CarMetadata(CarMetadata),
+    Message(BotMessage),
     Speed(f32),
     Paused(bool),
+    Hello(u32),
     Connection,
     Exit,
 }
 
+/// The protocol version the simulation said it uses, when it isn't `PROTOCOL_VERSION`
+///
+/// Packets that depend on the byte layout are dropped instead of decoded while they don't match
+#[derive(Resource, Default)]
+pub struct ProtocolMismatch(pub Option<u32>);
+
 #[derive(Resource, Deref)]
-struct UdpUpdateStream(Receiver<UdpUpdate>);
+pub struct UdpUpdateStream(Receiver<UdpUpdate>);
+
+/// How often the connection packet is sent again while waiting for the simulation to answer
+const CONNECTION_RETRY_INTERVAL: Duration = Duration::from_secs(1);
 
 fn start_udp_send_handler(socket: UdpSocket, out_addr: SocketAddr, outgoing: Receiver<SendableUdp>) {
-    socket.send_to(&[UdpPacketTypes::Connection as u8], out_addr).unwrap();
+    drop(socket.send_to(&[UdpPacketTypes::Connection as u8], out_addr));
+
+    // dedicated, named threads that live outside of Bevy's task pools
+    // so networking stays responsive no matter how busy rendering is
+    thread::Builder::new()
+        .name(String::from("rlviser-udp-send"))
+        .spawn(move || {
+            let bytes_received_at_start = BYTES_RECEIVED.load(Ordering::Relaxed);
+
+            loop {
+                let packet = match outgoing.recv_timeout(CONNECTION_RETRY_INTERVAL) {
+                    Ok(packet) => packet,
+                    Err(RecvTimeoutError::Timeout) => {
+                        // the simulation might not have been running yet, or it restarted
+                        if BYTES_RECEIVED.load(Ordering::Relaxed) == bytes_received_at_start {
+                            drop(socket.send_to(&[UdpPacketTypes::Connection as u8], out_addr));
+                        }
+                        continue;
+                    }
+                    Err(RecvTimeoutError::Disconnected) => break,
+                };
 
-    thread::spawn(move || loop {
-        match outgoing.recv() {
-            Ok(SendableUdp::State(state)) => {
-                let bytes = state.to_bytes();
+                let (packet_type, bytes) = packet.into_packet();
 
-                if socket.send_to(&[UdpPacketTypes::GameState as u8], out_addr).is_err() {
+                if socket.send_to(&[packet_type as u8], out_addr).is_err() {
                     continue;
                 }
 
-                if socket.send_to(&bytes, out_addr).is_err() {
-                    continue;
-                }
+                drop(socket.send_to(&bytes, out_addr));
             }
-            Ok(SendableUdp::Speed(speed)) => {
-                let bytes = speed.to_bytes();
+        })
+        .unwrap();
+}
 
-                if socket.send_to(&[UdpPacketTypes::Speed as u8], out_addr).is_err() {
-                    continue;
+/// A packet that's been read off of the socket, waiting to be decoded
+enum RawPacket {
+    State(Vec<u8>),
+    Render(Vec<u8>),
+    CarMetadata(Vec<u8>),
+    Message(Vec<u8>),
+    /// Packets that are small enough to not need decoding, passed along so everything stays in order
+    Decoded(UdpUpdate),
+}
+
+/// Decodes packets on their own thread so the recv thread can get straight back to reading the socket,
+/// handing the buffers back afterwards so they can be reused for the next packets
+fn start_udp_decode_handler(raw_packets: Receiver<RawPacket>, buffers: Sender<Vec<u8>>) -> Receiver<UdpUpdate> {
+    let (tx, rx) = crossbeam_channel::unbounded();
+
+    thread::Builder::new()
+        .name(String::from("rlviser-udp-decode"))
+        .spawn(move || {
+            for packet in raw_packets {
+                let decode_start = Instant::now();
+
+                let (buf, update) = match packet {
+                    RawPacket::State(buf) => {
+                        let state = GameState::try_from_bytes(&buf);
+                        let tick = state.as_ref().map(|state| state.tick_count);
+                        packet_log::record(UdpPacketTypes::GameState, &buf, tick, decode_start.elapsed());
+                        (Some(buf), state.map(|state| UdpUpdate::State(Arc::new(state), decode_start)))
+                    }
+                    RawPacket::Render(buf) => {
+                        let render_message = RenderMessage::try_from_bytes(&buf);
+                        packet_log::record(UdpPacketTypes::Render, &buf, None, decode_start.elapsed());
+                        (Some(buf), render_message.map(UdpUpdate::Render))
+                    }
+                    RawPacket::CarMetadata(buf) => {
+                        let metadata = CarMetadata::try_from_bytes(&buf);
+                        packet_log::record(UdpPacketTypes::CarMetadata, &buf, None, decode_start.elapsed());
+                        (Some(buf), metadata.map(UdpUpdate::CarMetadata))
+                    }
+                    RawPacket::Message(buf) => {
+                        let message = BotMessage::try_from_bytes(&buf);
+                        packet_log::record(UdpPacketTypes::Message, &buf, None, decode_start.elapsed());
+                        (Some(buf), message.map(UdpUpdate::Message))
+                    }
+                    RawPacket::Decoded(update) => (None, Some(update)),
+                };
+
+                if let Some(buf) = buf {
+                    // the recv thread might've already stopped, in which case it doesn't need them anymore
+                    drop(buffers.send(buf));
                 }
 
-                if socket.send_to(&bytes, out_addr).is_err() {
+                let Some(update) = update else {
+                    RecvError::Malformed.record("its contents couldn't be decoded");
                     continue;
+                };
+
+                if tx.send(update).is_err() {
+                    return;
                 }
             }
-            Ok(SendableUdp::Paused(paused)) => {
-                let paused = [paused as u8];
+        })
+        .unwrap();
 
-                if socket.send_to(&[UdpPacketTypes::Paused as u8], out_addr).is_err() {
-                    continue;
-                }
+    rx
+}
 
-                if socket.send_to(&paused, out_addr).is_err() {
-                    continue;
+fn start_udp_recv_handler(socket: UdpSocket, commands: &mut Commands) {
+    let (tx, raw_packets) = crossbeam_channel::unbounded();
+    let (used_buffers, buffers) = crossbeam_channel::unbounded();
+    commands.insert_resource(UdpUpdateStream(start_udp_decode_handler(raw_packets, used_buffers)));
+
+    thread::Builder::new()
+        .name(String::from("rlviser-udp-recv"))
+        .spawn(move || {
+            // clients that don't send a hello are assumed to be up to date
+            let mut protocol_matches = true;
+            let mut last_tick_count = 0;
+
+            loop {
+                let packet = match recv_udp_packet(&socket, &buffers, &mut protocol_matches, &mut last_tick_count) {
+                    Ok(Some(packet)) => packet,
+                    Ok(None) => continue,
+                    Err(e) => {
+                        error!("Stopped receiving UDP packets due to: {e}");
+                        return;
+                    }
+                };
+
+                let is_exit = matches!(packet, RawPacket::Decoded(UdpUpdate::Exit));
+                if tx.send(packet).is_err() || is_exit {
+                    return;
                 }
             }
-            Err(_) => return,
+        })
+        .unwrap();
+}
+
+/// Why a UDP packet from the simulation was dropped instead of stopping the recv thread
+#[derive(Clone, Copy)]
+pub enum RecvError {
+    /// Reading from the socket failed in a way that might not happen again
+    Socket,
+    /// The packet type byte didn't match any packet type, which usually means a payload was read as one
+    UnknownPacketType,
+    /// The payload was shorter than its header said it would be
+    Truncated,
+    /// The game state was older than one that had already been received
+    OutOfOrder,
+    /// Something in the packet didn't make sense, like an unknown team or a length longer than a datagram
+    Malformed,
+}
+
+static RECV_ERRORS: [AtomicU64; RecvError::ALL.len()] = [const { AtomicU64::new(0) }; RecvError::ALL.len()];
+
+impl RecvError {
+    pub const ALL: [Self; 5] = [
+        Self::Socket,
+        Self::UnknownPacketType,
+        Self::Truncated,
+        Self::OutOfOrder,
+        Self::Malformed,
+    ];
+
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Socket => "Socket errors",
+            Self::UnknownPacketType => "Unknown packet types",
+            Self::Truncated => "Truncated packets",
+            Self::OutOfOrder => "Out of order states",
+            Self::Malformed => "Malformed packets",
         }
-    });
+    }
+
+    pub const fn description(self) -> &'static str {
+        match self {
+            Self::Socket => "Reading from the socket failed, e.g. when Windows reports that the simulation restarted",
+            Self::UnknownPacketType => "The packet type byte wasn't a known packet type, so the packet was skipped",
+            Self::Truncated => "The payload was shorter than its header said it would be",
+            Self::OutOfOrder => "A game state arrived after a newer one, so it was dropped",
+            Self::Malformed => "The packet had a value that doesn't mean anything, like an unknown team or render type",
+        }
+    }
+
+    /// How many packets have been dropped for this reason since the visualizer started
+    pub fn count(self) -> u64 {
+        RECV_ERRORS[self as usize].load(Ordering::Relaxed)
+    }
+
+    /// Counts a dropped packet, only logging the first of each kind so a misbehaving client doesn't flood the log
+    fn record(self, details: impl Display) {
+        if RECV_ERRORS[self as usize].fetch_add(1, Ordering::Relaxed) == 0 {
+            warn!("Dropped a UDP packet because {details}; more are counted in the Network window");
+        }
+    }
 }
 
-fn start_udp_recv_handler(socket: UdpSocket, commands: &mut Commands) {
-    let (tx, rx) = crossbeam_channel::unbounded();
+/// Game states that are at most this many ticks behind the last one arrived out of order,
+/// while ones further behind are from a new game
+const OUT_OF_ORDER_TICKS: u64 = 120;
+
+/// `WSAEMSGSIZE`, which Windows returns when a datagram is bigger than the buffer even though the buffer was filled
+const WSAEMSGSIZE: i32 = 10040;
+
+/// Whether the socket might work again after this error, instead of the connection being gone for good
+fn is_transient(e: &io::Error) -> bool {
+    // Windows reports packets we sent to a closed port as an error on our socket,
+    // which happens whenever the simulation restarts
+    matches!(
+        e.kind(),
+        io::ErrorKind::ConnectionReset
+            | io::ErrorKind::ConnectionRefused
+            | io::ErrorKind::Interrupted
+            | io::ErrorKind::WouldBlock
+            | io::ErrorKind::TimedOut
+    ) || (cfg!(windows) && e.raw_os_error() == Some(WSAEMSGSIZE))
+}
 
-    thread::spawn(move || {
-        let mut packet_type_buffer = [0];
-        let mut initial_state_buffer = [0; GameState::MIN_NUM_BYTES];
-        let mut initial_render_buffer = [0; RenderMessage::MIN_NUM_BYTES];
-        let mut speed_buffer = [0; 4];
-        let mut paused_buffer = [0];
+/// Counts transient errors and turns them into `None`, so only unrecoverable ones are returned
+fn recover<T>(result: io::Result<T>) -> io::Result<Option<T>> {
+    match result {
+        Ok(value) => Ok(Some(value)),
+        Err(e) if is_transient(&e) => {
+            RecvError::Socket.record(&e);
+            Ok(None)
+        }
+        Err(e) => Err(e),
+    }
+}
 
-        let mut buf = Vec::new();
-        let mut render_buf = Vec::new();
-        let mut last_game_state = GameState::default();
+/// The most a single UDP datagram can carry, so anything a header says is longer can't be real
+const MAX_DATAGRAM_LEN: usize = 65507;
 
-        loop {
-            if socket.recv_from(&mut packet_type_buffer).is_err() {
-                return;
-            }
+/// Takes the next datagram off of the socket without looking at it
+fn skip_datagram(socket: &UdpSocket) -> io::Result<Option<RawPacket>> {
+    recover(socket.recv_from(&mut [0])).map(|_| None)
+}
 
-            let Some(packet_type) = UdpPacketTypes::new(packet_type_buffer[0]) else {
-                return;
+/// Waits for the payload that follows a packet type and reads its header without taking it off of the socket,
+/// returning how many bytes of the header there were
+fn peek_header(socket: &UdpSocket, header: &mut [u8]) -> io::Result<usize> {
+    loop {
+        match socket.peek_from(header) {
+            Ok((num_bytes, _)) => return Ok(num_bytes),
+            // Windows throws this despite the fact that the peek actually worked
+            Err(e) if cfg!(windows) && e.raw_os_error() == Some(WSAEMSGSIZE) => return Ok(header.len()),
+            // it should arrive VERY quickly, so a loop with no delay is fine
+            Err(e) if is_transient(&e) => {}
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Reads a payload that's as long as its header says, or drops it if it's too short
+fn recv_payload(
+    socket: &UdpSocket,
+    buffers: &Receiver<Vec<u8>>,
+    header: &[u8],
+    get_num_bytes: fn(&[u8]) -> usize,
+) -> io::Result<Option<Vec<u8>>> {
+    let mut buf = buffers.try_recv().unwrap_or_default();
+    buf.resize(get_num_bytes(header), 0);
+
+    let Some((num_bytes, _)) = recover(socket.recv_from(&mut buf))? else {
+        return Ok(None);
+    };
+    count_received(num_bytes);
+
+    if num_bytes != buf.len() {
+        RecvError::Truncated.record(format_args!("it was {num_bytes} bytes instead of {}", buf.len()));
+        return Ok(None);
+    }
+
+    Ok(Some(buf))
+}
+
+/// Reads a fixed size payload, or drops it if it's too short
+fn recv_exact<const N: usize>(socket: &UdpSocket) -> io::Result<Option<[u8; N]>> {
+    let mut buf = [0; N];
+    let Some((num_bytes, _)) = recover(socket.recv_from(&mut buf))? else {
+        return Ok(None);
+    };
+    count_received(num_bytes);
+
+    if num_bytes != N {
+        RecvError::Truncated.record(format_args!("it was {num_bytes} bytes instead of {N}"));
+        return Ok(None);
+    }
+
+    Ok(Some(buf))
+}
+
+/// Reads the next packet type and its payload, returning `None` for packets that were dropped
+/// and only failing when the socket can't be read from anymore
+fn recv_udp_packet(
+    socket: &UdpSocket,
+    buffers: &Receiver<Vec<u8>>,
+    protocol_matches: &mut bool,
+    last_tick_count: &mut u64,
+) -> io::Result<Option<RawPacket>> {
+    let mut packet_type_buffer = [0];
+    if recover(socket.recv_from(&mut packet_type_buffer))?.is_none() {
+        return Ok(None);
+    }
+    count_received(packet_type_buffer.len());
+
+    let Some(packet_type) = UdpPacketTypes::new(packet_type_buffer[0]) else {
+        RecvError::UnknownPacketType.record(format_args!("{} isn't a packet type", packet_type_buffer[0]));
+        return Ok(None);
+    };
+
+    // the rest of the packets have a header that says how long they are
+    let (header_len, get_num_bytes, into_packet): (_, fn(&[u8]) -> usize, fn(Vec<u8>) -> RawPacket) = match packet_type {
+        UdpPacketTypes::GameState => (GameState::MIN_NUM_BYTES, GameState::get_num_bytes, RawPacket::State),
+        UdpPacketTypes::Render => (RenderMessage::MIN_NUM_BYTES, RenderMessage::get_num_bytes, RawPacket::Render),
+        UdpPacketTypes::CarMetadata => (CarMetadata::MIN_NUM_BYTES, CarMetadata::get_num_bytes, RawPacket::CarMetadata),
+        UdpPacketTypes::Message => (BotMessage::MIN_NUM_BYTES, BotMessage::get_num_bytes, RawPacket::Message),
+        UdpPacketTypes::Quit => {
+            packet_log::record(packet_type, &[], None, Duration::ZERO);
+            return Ok(Some(RawPacket::Decoded(UdpUpdate::Exit)));
+        }
+        UdpPacketTypes::Connection => {
+            packet_log::record(packet_type, &[], None, Duration::ZERO);
+            return Ok(Some(RawPacket::Decoded(UdpUpdate::Connection)));
+        }
+        UdpPacketTypes::Speed => {
+            let Some(speed_buffer) = recv_exact::<4>(socket)? else {
+                return Ok(None);
             };
 
-            match packet_type {
-                UdpPacketTypes::Quit => {
-                    drop(tx.send(UdpUpdate::Exit));
-                    return;
-                }
-                UdpPacketTypes::GameState => {
-                    // wait until we receive the packet
-                    // it should arrive VERY quickly, so a loop with no delay is fine
-                    // if it doesn't, then there are other problems lol
-                    // UPDATE: Windows throws a specific error that we need to look for
-                    // despite the fact that it actually worked
-
-                    #[cfg(windows)]
-                    {
-                        while let Err(e) = socket.peek_from(&mut initial_state_buffer) {
-                            if let Some(code) = e.raw_os_error() {
-                                if code == 10040 {
-                                    break;
-                                }
-                            }
-                        }
-                    }
+            let speed = f32::from_le_bytes(speed_buffer);
+            packet_log::record(packet_type, &speed_buffer, None, Duration::ZERO);
+            return Ok(Some(RawPacket::Decoded(UdpUpdate::Speed(speed))));
+        }
+        UdpPacketTypes::Paused => {
+            let Some(paused_buffer) = recv_exact::<1>(socket)? else {
+                return Ok(None);
+            };
 
-                    #[cfg(not(windows))]
-                    {
-                        while socket.peek_from(&mut initial_state_buffer).is_err() {}
-                    }
+            let paused = paused_buffer[0] != 0;
+            packet_log::record(packet_type, &paused_buffer, None, Duration::ZERO);
+            return Ok(Some(RawPacket::Decoded(UdpUpdate::Paused(paused))));
+        }
+        UdpPacketTypes::Hello => {
+            let Some(hello_buffer) = recv_exact::<4>(socket)? else {
+                return Ok(None);
+            };
 
-                    let new_tick_count = GameState::read_tick_count(&initial_state_buffer);
-                    if new_tick_count > 15 && last_game_state.tick_count > new_tick_count {
-                        drop(socket.recv_from(&mut [0]));
-                        return;
-                    }
+            let version = u32::from_le_bytes(hello_buffer);
+            packet_log::record(packet_type, &hello_buffer, None, Duration::ZERO);
 
-                    buf.resize(GameState::get_num_bytes(&initial_state_buffer), 0);
-                    if socket.recv_from(&mut buf).is_err() {
-                        return;
-                    }
+            *protocol_matches = version == PROTOCOL_VERSION;
+            return Ok(Some(RawPacket::Decoded(UdpUpdate::Hello(version))));
+        }
+    };
 
-                    last_game_state = GameState::from_bytes(&buf);
-                    if tx.send(UdpUpdate::State(last_game_state.clone())).is_err() {
-                        return;
+    // every header fits in the biggest one, which is the game state's
+    let mut header_buffer = [0; GameState::MIN_NUM_BYTES];
+    let header = &mut header_buffer[..header_len];
+
+    let num_bytes = peek_header(socket, header)?;
+    if num_bytes < header.len() {
+        RecvError::Truncated.record(format_args!("its header was {num_bytes} bytes instead of {}", header.len()));
+        return skip_datagram(socket);
+    }
+
+    if !*protocol_matches {
+        return skip_datagram(socket);
+    }
+
+    let is_state = matches!(packet_type, UdpPacketTypes::GameState);
+    if is_state {
+        let new_tick_count = GameState::read_tick_count(header);
+        let ticks_behind = last_tick_count.saturating_sub(new_tick_count);
+        if new_tick_count > 15 && ticks_behind > 0 && ticks_behind <= OUT_OF_ORDER_TICKS {
+            RecvError::OutOfOrder.record(format_args!("tick {new_tick_count} arrived after tick {last_tick_count}"));
+            return skip_datagram(socket);
+        }
+    }
+
+    let num_bytes = get_num_bytes(header);
+    if num_bytes > MAX_DATAGRAM_LEN {
+        RecvError::Malformed.record(format_args!(
+            "its header said it was {num_bytes} bytes, more than fits in a datagram"
+        ));
+        return skip_datagram(socket);
+    }
+
+    let Some(buf) = recv_payload(socket, buffers, header, get_num_bytes)? else {
+        return Ok(None);
+    };
+
+    if is_state {
+        *last_tick_count = GameState::read_tick_count(header);
+    }
+
+    Ok(Some(into_packet(buf)))
+}
+
+/// Total bytes read by the networking threads, used for `NetworkStats`
+static BYTES_RECEIVED: AtomicU64 = AtomicU64::new(0);
+
+#[inline]
+fn count_received(num_bytes: usize) {
+    BYTES_RECEIVED.fetch_add(num_bytes as u64, Ordering::Relaxed);
+}
+
+/// The largest payload accepted over TCP, so a bad length can't make us allocate gigabytes
+const MAX_TCP_PACKET_LEN: usize = 8 * 1024 * 1024;
+
+/// Writes a packet as its type, the length of the payload as a little-endian u32, and then the payload
+fn write_tcp_packet(stream: &mut TcpStream, packet_type: UdpPacketTypes, bytes: &[u8]) -> io::Result<()> {
+    let mut packet = Vec::with_capacity(1 + u32::NUM_BYTES + bytes.len());
+    packet.push(packet_type as u8);
+    packet.extend_from_slice(&(bytes.len() as u32).to_le_bytes());
+    packet.extend_from_slice(bytes);
+
+    stream.write_all(&packet)
+}
+
+/// Reads the next packet, skipping over the ones that depend on the byte layout if `protocol_matches` is false
+fn read_tcp_packet(stream: &mut TcpStream, buf: &mut Vec<u8>, protocol_matches: bool) -> io::Result<Option<UdpUpdate>> {
+    let mut header = [0; 1 + u32::NUM_BYTES];
+    stream.read_exact(&mut header)?;
+
+    let invalid = |msg: &str| io::Error::new(io::ErrorKind::InvalidData, msg);
+
+    let packet_type = UdpPacketTypes::new(header[0]).ok_or_else(|| invalid("unknown packet type"))?;
+    let len = u32::from_bytes(&header[1..]) as usize;
+    if len > MAX_TCP_PACKET_LEN {
+        return Err(invalid("packet is longer than the maximum allowed length"));
+    }
+
+    buf.resize(len, 0);
+    stream.read_exact(buf)?;
+    count_received(header.len() + buf.len());
+
+    if !protocol_matches
+        && matches!(
+            packet_type,
+            UdpPacketTypes::GameState | UdpPacketTypes::Render | UdpPacketTypes::CarMetadata | UdpPacketTypes::Message
+        )
+    {
+        return Ok(None);
+    }
+
+    let decode_start = Instant::now();
+    let update = match packet_type {
+        UdpPacketTypes::Quit => UdpUpdate::Exit,
+        UdpPacketTypes::Connection => UdpUpdate::Connection,
+        UdpPacketTypes::GameState => {
+            let state = GameState::try_from_bytes(buf).ok_or_else(|| invalid("game state couldn't be decoded"))?;
+            UdpUpdate::State(Arc::new(state), decode_start)
+        }
+        UdpPacketTypes::Render => {
+            if buf.len() < RenderMessage::MIN_NUM_BYTES || RenderMessage::get_num_bytes(buf) != buf.len() {
+                return Err(invalid("render message length doesn't match its contents"));
+            }
+
+            UdpUpdate::Render(
+                RenderMessage::try_from_bytes(buf).ok_or_else(|| invalid("render message couldn't be decoded"))?,
+            )
+        }
+        UdpPacketTypes::CarMetadata => {
+            if buf.len() < CarMetadata::MIN_NUM_BYTES || CarMetadata::get_num_bytes(buf) != buf.len() {
+                return Err(invalid("car metadata length doesn't match its contents"));
+            }
+
+            UdpUpdate::CarMetadata(
+                CarMetadata::try_from_bytes(buf).ok_or_else(|| invalid("car metadata couldn't be decoded"))?,
+            )
+        }
+        UdpPacketTypes::Message => {
+            if buf.len() < BotMessage::MIN_NUM_BYTES || BotMessage::get_num_bytes(buf) != buf.len() {
+                return Err(invalid("message length doesn't match its contents"));
+            }
+
+            UdpUpdate::Message(BotMessage::try_from_bytes(buf).ok_or_else(|| invalid("message couldn't be decoded"))?)
+        }
+        UdpPacketTypes::Speed => {
+            let speed = buf.as_slice().try_into().map_err(|_| invalid("speed must be 4 bytes"))?;
+            UdpUpdate::Speed(f32::from_le_bytes(speed))
+        }
+        UdpPacketTypes::Paused => UdpUpdate::Paused(buf.first().is_some_and(|&paused| paused != 0)),
+        UdpPacketTypes::Hello => {
+            let version = buf.as_slice().try_into().map_err(|_| invalid("hello must be 4 bytes"))?;
+            UdpUpdate::Hello(u32::from_le_bytes(version))
+        }
+    };
+
+    let tick = match &update {
+        UdpUpdate::State(state, _) => Some(state.tick_count),
+        _ => None,
+    };
+    packet_log::record(packet_type, buf, tick, decode_start.elapsed());
+
+    Ok(Some(update))
+}
+
+fn start_tcp_handlers(out_addr: SocketAddr, outgoing: Receiver<SendableUdp>, commands: &mut Commands) {
+    let (tx, rx) = crossbeam_channel::unbounded();
+    commands.insert_resource(UdpUpdateStream(rx));
+
+    thread::Builder::new()
+        .name(String::from("rlviser-tcp-send"))
+        .spawn(move || {
+            // like with UDP, the other side doesn't have to be running before we start
+            let mut stream = loop {
+                match TcpStream::connect(out_addr) {
+                    Ok(stream) => break stream,
+                    // waiting on the channel instead of sleeping lets a reconnect stop this thread
+                    Err(_) => {
+                        if matches!(
+                            outgoing.recv_timeout(Duration::from_secs(1)),
+                            Err(RecvTimeoutError::Disconnected)
+                        ) {
+                            return;
+                        }
                     }
                 }
-                UdpPacketTypes::Render => {
-                    #[cfg(windows)]
-                    {
-                        while let Err(e) = socket.peek_from(&mut initial_render_buffer) {
-                            if let Some(code) = e.raw_os_error() {
-                                if code == 10040 {
-                                    break;
-                                }
+            };
+
+            info!("Connected to {out_addr} over TCP");
+            drop(stream.set_nodelay(true));
+
+            // anything queued up while we were waiting is stale now
+            outgoing.try_iter().for_each(drop);
+
+            let mut recv_stream = stream.try_clone().unwrap();
+            thread::Builder::new()
+                .name(String::from("rlviser-tcp-recv"))
+                .spawn(move || {
+                    let mut buf = Vec::new();
+                    let mut protocol_matches = true;
+
+                    loop {
+                        let update = match read_tcp_packet(&mut recv_stream, &mut buf, protocol_matches) {
+                            Ok(Some(update)) => update,
+                            Ok(None) => continue,
+                            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return,
+                            Err(e) => {
+                                error!("Failed to read TCP packet due to: {e}");
+                                return;
                             }
+                        };
+
+                        if let UdpUpdate::Hello(version) = update {
+                            protocol_matches = version == PROTOCOL_VERSION;
                         }
-                    }
 
-                    #[cfg(not(windows))]
-                    {
-                        while socket.peek_from(&mut initial_render_buffer).is_err() {}
+                        let is_exit = matches!(update, UdpUpdate::Exit);
+                        if tx.send(update).is_err() || is_exit {
+                            return;
+                        }
                     }
+                })
+                .unwrap();
 
-                    render_buf.resize(RenderMessage::get_num_bytes(&initial_render_buffer), 0);
-                    if socket.recv_from(&mut render_buf).is_err() {
-                        return;
-                    }
+            if write_tcp_packet(&mut stream, UdpPacketTypes::Connection, &[]).is_ok() {
+                for packet in &outgoing {
+                    let (packet_type, bytes) = packet.into_packet();
 
-                    let render_message = RenderMessage::from_bytes(&render_buf);
-                    if tx.send(UdpUpdate::Render(render_message)).is_err() {
-                        return;
+                    if let Err(e) = write_tcp_packet(&mut stream, packet_type, &bytes) {
+                        error!("Failed to send TCP packet due to: {e}");
+                        break;
                     }
                 }
-                UdpPacketTypes::Speed => {
-                    if socket.recv_from(&mut speed_buffer).is_err() {
-                        return;
-                    }
+            }
 
-                    let speed = f32::from_le_bytes(speed_buffer);
-                    if tx.send(UdpUpdate::Speed(speed)).is_err() {
-                        return;
-                    }
-                }
-                UdpPacketTypes::Paused => {
-                    if socket.recv_from(&mut paused_buffer).is_err() {
-                        return;
-                    }
+            // keep the channel open after the connection closes so sending never fails
+            while outgoing.recv().is_ok() {}
 
-                    let paused = paused_buffer[0] != 0;
-                    if tx.send(UdpUpdate::Paused(paused)).is_err() {
-                        return;
-                    }
-                }
-                UdpPacketTypes::Connection => {
-                    if tx.send(UdpUpdate::Connection).is_err() {
-                        return;
-                    }
-                }
-            }
-        }
-    });
+            // the connection was replaced, so stop the receiving thread too
+            drop(stream.shutdown(Shutdown::Both));
+        })
+        .unwrap();
+}
+
+#[cfg(feature = "rlbot")]
+fn start_rlbot_handlers(out_addr: SocketAddr, outgoing: Receiver<SendableUdp>, commands: &mut Commands) {
+    let (tx, rx) = crossbeam_channel::unbounded();
+    commands.insert_resource(UdpUpdateStream(rx));
 
+    crate::rlbot::start_rlbot_handlers(out_addr, outgoing, tx);
+}
+
+#[cfg(feature = "replay_import")]
+fn start_replay_file_handlers(path: PathBuf, outgoing: Receiver<SendableUdp>, commands: &mut Commands) {
+    let (tx, rx) = crossbeam_channel::unbounded();
     commands.insert_resource(UdpUpdateStream(rx));
+
+    crate::replay_import::start_replay_file_handlers(path, outgoing, tx);
 }
 
 fn apply_udp_updates(
@@ -618,35 +1395,88 @@ fn apply_udp_updates(
     calc_ball_rot: Res<CalcBallRot>,
     packet_smoothing: Res<PacketSmoothing>,
     mut game_states: ResMut<GameStates>,
-    mut exit: EventWriter<AppExit>,
+    (mut exit, options): (EventWriter<AppExit>, Res<Options>),
+    (mut commands, mut load_state, mut waiting): (Commands, ResMut<NextState<GameLoadState>>, ResMut<WaitingForSimulator>),
     mut packet_updated: ResMut<PacketUpdated>,
     mut render_groups: ResMut<RenderGroups>,
-    mut packet_time_elapsed: ResMut<PacketTimeElapsed>,
-    mut last_packet_time_elapsed: ResMut<LastPacketTimesElapsed>,
-    mut speed_update: EventWriter<SpeedUpdate>,
-    mut paused_update: EventWriter<PausedUpdate>,
+    mut car_names: ResMut<CarNames>,
+    (mut packet_time_elapsed, mut last_packet_time_elapsed, mut network_stats): (
+        ResMut<PacketTimeElapsed>,
+        ResMut<LastPacketTimesElapsed>,
+        ResMut<NetworkStats>,
+    ),
+    (mut speed_update, mut paused_update, mut bot_messages): (
+        EventWriter<SpeedUpdate>,
+        EventWriter<PausedUpdate>,
+        EventWriter<BotMessage>,
+    ),
+    mut protocol_mismatch: ResMut<ProtocolMismatch>,
+    (mut replay, mut step_queue, mut jitter_buffer, render_delay): (
+        ResMut<Replay>,
+        ResMut<StepQueue>,
+        ResMut<JitterBuffer>,
+        Res<RenderDelay>,
+    ),
 ) {
     packet_time_elapsed.tick(time.delta());
 
     let mut new_game_state = None;
 
-    for update in udp_updates.try_iter() {
+    loop {
+        let update = match udp_updates.try_recv() {
+            Ok(update) => update,
+            Err(TryRecvError::Empty) => break,
+            // the networking threads stop when something goes wrong with the connection
+            Err(TryRecvError::Disconnected) => {
+                warn!("Lost connection to the simulation, waiting for it to come back");
+                // the socket itself might be what broke, so bind a fresh one
+                commands.remove_resource::<BoundUdpSocket>();
+                wait_for_simulator(&mut commands, &mut load_state, &mut waiting);
+                return;
+            }
+        };
+
+        waiting.set_if_neq(WaitingForSimulator(false));
+
         match update {
-            UdpUpdate::Exit => {
+            UdpUpdate::Exit if options.close_on_quit => {
                 exit.send(AppExit::Success);
                 return;
             }
-            UdpUpdate::State(new_state) => {
+            UdpUpdate::Exit => {
+                info!("The simulation quit, waiting for it to come back");
+                wait_for_simulator(&mut commands, &mut load_state, &mut waiting);
+                return;
+            }
+            UdpUpdate::State(new_state, received) if game_speed.paused => {
+                network_stats.record_state(new_state.tick_count, received);
+                step_queue.push(new_state);
+            }
+            UdpUpdate::State(new_state, received) if render_delay.0 > 0. => {
+                network_stats.record_state(new_state.tick_count, received);
+                jitter_buffer.push(new_state);
+            }
+            UdpUpdate::State(new_state, received) => {
+                network_stats.record_state(new_state.tick_count, received);
                 new_game_state = Some(new_state);
             }
             UdpUpdate::Render(render_message) => match render_message {
                 RenderMessage::AddRender(group_id, renders) => {
-                    render_groups.groups.insert(group_id, renders);
+                    render_groups.insert(group_id, renders);
                 }
                 RenderMessage::RemoveRender(group_id) => {
-                    render_groups.groups.remove(&group_id);
+                    render_groups.remove(group_id);
+                }
+                RenderMessage::NameGroup(group_id, name) => {
+                    render_groups.names.insert(group_id, name);
                 }
             },
+            UdpUpdate::CarMetadata(metadata) => {
+                car_names.0.extend(metadata.0);
+            }
+            UdpUpdate::Message(message) => {
+                bot_messages.send(message);
+            }
             UdpUpdate::Speed(speed) => {
                 last_packet_time_elapsed.reset();
                 speed_update.send(SpeedUpdate(speed));
@@ -654,6 +1484,15 @@ fn apply_udp_updates(
             UdpUpdate::Paused(paused) => {
                 paused_update.send(PausedUpdate(paused));
             }
+            UdpUpdate::Hello(version) => {
+                // let the simulation know which version we support so it can adapt
+                socket.send(SendableUdp::Hello(PROTOCOL_VERSION)).unwrap();
+
+                protocol_mismatch.0 = (version != PROTOCOL_VERSION).then_some(version);
+                if let Some(version) = protocol_mismatch.0 {
+                    error!("Simulation uses protocol version {version}, but only version {PROTOCOL_VERSION} is supported");
+                }
+            }
             UdpUpdate::Connection => {
                 socket.send(SendableUdp::Paused(game_speed.paused)).unwrap();
                 socket.send(SendableUdp::Speed(game_speed.speed)).unwrap();
@@ -661,6 +1500,42 @@ fn apply_udp_updates(
         }
     }
 
+    if game_speed.paused {
+        if let Some(new_state) = step_queue.next_step(&socket) {
+            new_game_state = Some(new_state);
+        }
+
+        jitter_buffer.clear();
+    } else {
+        step_queue.clear();
+
+        if render_delay.0 > 0. {
+            new_game_state = jitter_buffer.next_state(time.delta_secs() * game_speed.speed, render_delay.0);
+        } else {
+            jitter_buffer.clear();
+        }
+    }
+
+    // stepping or unpausing goes back to the live game
+    if new_game_state.is_some() || !game_speed.paused {
+        replay.stop_scrubbing();
+    }
+
+    if let Some(new_state) = &new_game_state {
+        replay.record(new_state);
+    }
+
+    // while paused, the state picked on the timeline is shown
+    let scrubbed_state = replay.take_scrubbed_state();
+    if new_game_state.is_none() {
+        new_game_state = scrubbed_state;
+    }
+
+    // live states keep being recorded, but the replay is shown until it's over
+    if replay.is_playing() {
+        new_game_state = replay.next_state(if game_speed.paused { 0. } else { time.delta_secs() });
+    }
+
     match new_game_state {
         Some(new_state) => {
             last_packet_time_elapsed.push(packet_time_elapsed.0.elapsed_secs());
@@ -700,6 +1575,8 @@ fn update_ball(
 }
 
 const MIN_CAMERA_BALLCAM_HEIGHT: f32 = 30.;
+/// How quickly the Rocket League camera catches up to the car at 0 stiffness
+const CAMERA_FOLLOW_RATE: f32 = 10.;
 
 fn update_car(states: Res<GameStates>, mut cars: Query<(&mut Transform, &Car)>) {
     for (mut car_transform, car) in &mut cars {
@@ -714,16 +1591,15 @@ fn update_car(states: Res<GameStates>, mut cars: Query<(&mut Transform, &Car)>)
 
 fn update_car_extra(
     states: Res<GameStates>,
-    mut cars: Query<(&Car, &Children)>,
-    mut car_boosts: Query<&MeshMaterial3d<StandardMaterial>, With<CarBoost>>,
+    hide_demoed_cars: Res<HideDemoedCars>,
+    mut cars: Query<(&Car, &Children, &mut Visibility, &mut Boosting)>,
     mut car_materials: Query<&MeshMaterial3d<StandardMaterial>, With<CarBody>>,
     mut car_wheels: Query<&MeshMaterial3d<StandardMaterial>, With<CarWheel>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
-    mut last_boost_states: Local<Vec<u32>>,
     mut last_demoed_states: Local<Vec<u32>>,
     mut last_boost_amounts: Local<HashMap<u32, f32>>,
 ) {
-    for (car, children) in &mut cars {
+    for (car, children, mut visibility, mut boosting) in &mut cars {
         let Some(target_car) = states.current.cars.iter().find(|car_info| car.0 == car_info.id) else {
             continue;
         };
@@ -731,6 +1607,13 @@ fn update_car_extra(
         let is_demoed = target_car.state.is_demoed || target_car.state.demo_respawn_timer > f32::EPSILON;
         let last_demoed = last_demoed_states.iter().any(|&id| id == car.id());
 
+        // checked every frame so toggling the option applies to cars that are already demoed
+        visibility.set_if_neq(if hide_demoed_cars.0 && is_demoed {
+            Visibility::Hidden
+        } else {
+            Visibility::Inherited
+        });
+
         if is_demoed != last_demoed {
             for child in children {
                 let Ok(material_handle) = car_materials.get_mut(*child) else {
@@ -772,42 +1655,24 @@ fn update_car_extra(
         let is_boosting = !is_demoed
             && target_car.state.boost > f32::EPSILON
             && (target_car.state.last_controls.boost || last_boost_amount > target_car.state.boost);
-        let last_boosted = last_boost_states.iter().any(|&id| id == car.id());
-
-        if is_boosting != last_boosted {
-            for child in children {
-                let Ok(material_handle) = car_boosts.get_mut(*child) else {
-                    continue;
-                };
-
-                let material = materials.get_mut(material_handle).unwrap();
-                if is_boosting {
-                    material.base_color.set_alpha(0.7);
-                    last_boost_states.push(car.id());
-                } else {
-                    material.base_color.set_alpha(0.0);
-                    last_boost_states.retain(|&id| id != car.id());
-                }
-
-                break;
-            }
-        }
+        boosting.set_if_neq(Boosting(is_boosting));
     }
 }
 
 fn update_car_wheels(
     states: Res<GameStates>,
     cars: Query<(&Transform, &Car, &Children)>,
-    car_wheels: Query<(&mut Transform, &CarWheel), Without<Car>>,
+    car_wheels: Query<(&mut Transform, &mut CarWheel), Without<Car>>,
     game_speed: Res<GameSpeed>,
     time: Res<Time>,
     key: Res<ButtonInput<KeyCode>>,
+    keybinds: Res<Keybinds>,
 ) {
     if game_speed.paused {
         return;
     }
 
-    let delta_time = if key.pressed(KeyCode::KeyI) {
+    let delta_time = if keybinds.pressed(&key, Action::StepWheels) {
         game_speed.speed / states.current.tick_rate
     } else {
         time.delta_secs() * game_speed.speed
@@ -816,19 +1681,49 @@ fn update_car_wheels(
     calc_car_wheel_update(&states.current, cars, car_wheels, delta_time);
 }
 
+/// The most the front wheels can turn at different forward speeds, from RocketSim
+const STEER_ANGLE_FROM_SPEED: [(f32, f32); 6] = [
+    (0., 0.533_56),
+    (500., 0.319_3),
+    (1000., 0.182_03),
+    (1500., 0.105_7),
+    (1750., 0.085_07),
+    (3000., 0.034_54),
+];
+
+fn max_steer_angle(forward_speed: f32) -> f32 {
+    let speed = forward_speed.abs();
+
+    for pair in STEER_ANGLE_FROM_SPEED.windows(2) {
+        let [(start_speed, start_angle), (end_speed, end_angle)] = [pair[0], pair[1]];
+        if speed < end_speed {
+            return start_angle.lerp(end_angle, (speed - start_speed) / (end_speed - start_speed));
+        }
+    }
+
+    STEER_ANGLE_FROM_SPEED[STEER_ANGLE_FROM_SPEED.len() - 1].1
+}
+
+/// How far the wheels drop when they aren't touching anything
+const MAX_SUSPENSION_DROOP: f32 = 12.;
+/// Roughly how long the suspension takes to settle, in seconds
+const SUSPENSION_SMOOTHING: f32 = 0.05;
+
 fn calc_car_wheel_update(
     state: &GameState,
     mut cars: Query<(&Transform, &Car, &Children)>,
-    mut car_wheels: Query<(&mut Transform, &CarWheel), Without<Car>>,
+    mut car_wheels: Query<(&mut Transform, &mut CarWheel), Without<Car>>,
     delta_time: f32,
 ) {
+    let suspension_t = 1. - (-delta_time / SUSPENSION_SMOOTHING).exp();
+
     for (car_transform, car, children) in &mut cars {
         let Some(target_car) = state.cars.iter().find(|car_info| car.0 == car_info.id) else {
             continue;
         };
 
         for child in children {
-            let Ok((mut wheel_transform, data)) = car_wheels.get_mut(*child) else {
+            let Ok((mut wheel_transform, mut data)) = car_wheels.get_mut(*child) else {
                 continue;
             };
 
@@ -839,6 +1734,7 @@ fn calc_car_wheel_update(
             };
 
             let car_vel = target_car.state.vel.to_bevy();
+            let forward = car_transform.rotation.mul_vec3(Vec3::X);
             let mut angular_velocity = car_vel.length() * delta_time / wheel_radius;
 
             if data.left {
@@ -847,7 +1743,6 @@ fn calc_car_wheel_update(
 
             if target_car.state.is_on_ground || target_car.state.wheels_with_contact.into_iter().any(|b| b) {
                 // determine if the velocity is in the same direction as the car's forward vector
-                let forward = car_transform.rotation.mul_vec3(Vec3::X);
                 let forward_dot = forward.dot(car_vel);
                 let forward_dir = forward_dot.signum();
 
@@ -856,7 +1751,82 @@ fn calc_car_wheel_update(
                 angular_velocity *= target_car.state.last_controls.throttle;
             }
 
-            wheel_transform.rotation *= Quat::from_rotation_z(angular_velocity);
+            data.spin = (data.spin + angular_velocity) % TAU;
+
+            let target_droop = if target_car.state.wheels_with_contact[data.index()] {
+                0.
+            } else {
+                MAX_SUSPENSION_DROOP
+            };
+            data.droop = data.droop.lerp(target_droop, suspension_t);
+
+            let steer_angle = if data.front {
+                target_car.state.last_controls.steer * max_steer_angle(forward.dot(car_vel))
+            } else {
+                0.
+            };
+
+            let side_rotation = if data.left { 0. } else { PI };
+            wheel_transform.translation = data.rest_pos - Vec3::Y * data.droop;
+            wheel_transform.rotation = Quat::from_rotation_y(-steer_angle)
+                * Quat::from_rotation_x(side_rotation)
+                * Quat::from_rotation_z(data.spin);
+        }
+    }
+}
+
+fn update_car_hitboxes(
+    states: Res<GameStates>,
+    show_car_hitboxes: Res<ShowCarHitboxes>,
+    car_lod_distance: Res<CarLodDistance>,
+    cameras: Query<&GlobalTransform, With<PrimaryCamera>>,
+    mut cars: Query<(&Car, &GlobalTransform, &mut CarLod, &Children)>,
+    mut visibilities: Query<
+        (&mut Visibility, Has<CarHitbox>, Has<CarWheel>),
+        Or<(With<CarBody>, With<CarHitbox>, With<CarWheel>)>,
+    >,
+) {
+    let camera_pos = cameras.get_single().ok().map(GlobalTransform::translation);
+
+    for (car, car_transform, mut lod, children) in &mut cars {
+        let is_demoed = states
+            .current
+            .cars
+            .iter()
+            .find(|car_info| car.0 == car_info.id)
+            .is_some_and(|car_info| car_info.state.is_demoed);
+
+        let low_detail = match camera_pos {
+            Some(camera_pos) if car_lod_distance.0 > 0. => {
+                let distance = camera_pos.distance(car_transform.translation());
+                let threshold = if lod.0 {
+                    car_lod_distance.0 * CAR_LOD_HYSTERESIS
+                } else {
+                    car_lod_distance.0
+                };
+
+                distance > threshold
+            }
+            _ => false,
+        };
+
+        lod.0 = low_detail;
+        let show_hitbox = show_car_hitboxes.0 || lod.0;
+
+        for child in children {
+            let Ok((mut visibility, is_hitbox, is_wheel)) = visibilities.get_mut(*child) else {
+                continue;
+            };
+
+            let visible = if is_hitbox {
+                show_hitbox && !is_demoed
+            } else if is_wheel {
+                !lod.0
+            } else {
+                !show_hitbox
+            };
+
+            visibility.set_if_neq(if visible { Visibility::Inherited } else { Visibility::Hidden });
         }
     }
 }
@@ -873,6 +1843,7 @@ fn pre_update_car(
     car_wheel_mesh: Res<CarWheelMesh>,
     mut images: ResMut<Assets<Image>>,
     render_device: Option<Res<RenderDevice>>,
+    car_bodies: Res<CarBodies>,
 ) {
     correct_car_count(
         &cars,
@@ -886,6 +1857,7 @@ fn pre_update_car(
         &car_wheel_mesh,
         &mut images,
         render_device.as_deref(),
+        &car_bodies,
     );
 }
 
@@ -893,49 +1865,45 @@ fn update_camera(
     time: Res<Time>,
     states: Res<GameStates>,
     ballcam: Res<BallCam>,
+    pov: Res<PovCamera>,
     mut cars: Query<(&mut Transform, &Car)>,
-    mut camera_query: Query<(&mut PrimaryCamera, &mut Transform), Without<Car>>,
-    mut timer: ResMut<DirectorTimer>,
+    mut camera_query: Query<(&mut PrimaryCamera, &mut Transform, &mut Projection), Without<Car>>,
+    mut director: ResMut<Director>,
+    mut transition: ResMut<CameraTransition>,
+    mut last_pov: Local<Option<(u32, Vec3)>>,
 ) {
-    timer.0.tick(time.delta());
+    let (mut primary_camera, mut camera_transform, mut projection) = camera_query.single_mut();
 
-    let (mut primary_camera, mut camera_transform) = camera_query.single_mut();
+    let is_director = matches!(*primary_camera, PrimaryCamera::Director(_));
 
-    let car_id = match primary_camera.as_mut() {
-        PrimaryCamera::TrackCar(id) => {
-            if states.current.cars.is_empty() {
-                return;
-            }
+    let following_car = !matches!(*primary_camera, PrimaryCamera::Spectator | PrimaryCamera::Orbit(_));
+    if !(pov.enabled && following_car) {
+        *last_pov = None;
+    }
 
-            let mut ids = states.current.cars.iter().map(|car_info| car_info.id).collect::<Vec<_>>();
-            radsort::sort(&mut ids);
+    if let Projection::Perspective(perspective) = &*projection {
+        let fov = if pov.enabled && following_car {
+            // Rocket League's FOV is horizontal, but Bevy's is vertical
+            2. * ((pov.fov.to_radians() / 2.).tan() / perspective.aspect_ratio).atan()
+        } else {
+            DEFAULT_FOV
+        };
 
-            let index = *id as usize - 1;
-            if index >= ids.len() {
-                return;
-            } else {
-                ids[index]
-            }
+        if (perspective.fov - fov).abs() > f32::EPSILON {
+            *projection = Projection::Perspective(PerspectiveProjection {
+                fov,
+                ..perspective.clone()
+            });
         }
-        PrimaryCamera::Director(id) => {
-            if *id == 0 || timer.0.finished() {
-                // get the car closest to the ball
-                let mut min_dist = f32::MAX;
-                let mut new_id = *id;
-                for car in &*states.current.cars {
-                    let dist = car.state.pos.distance_squared(states.current.ball.pos);
-                    if dist < min_dist {
-                        new_id = car.id;
-                        min_dist = dist;
-                    }
-                }
-
-                *id = new_id;
-            }
+    }
 
+    let car_id = match primary_camera.as_mut() {
+        PrimaryCamera::TrackCar(id) => *id,
+        PrimaryCamera::Director(id) => {
+            *id = director.update(&states.current, time.delta_secs());
             *id
         }
-        PrimaryCamera::Spectator => return,
+        PrimaryCamera::Spectator | PrimaryCamera::Orbit(_) => return,
     };
 
     let Some((car_transform, _)) = cars.iter_mut().find(|(_, car)| car.id() == car_id) else {
@@ -947,21 +1915,77 @@ fn update_camera(
     };
 
     let camera_transform = camera_transform.as_mut();
+    transition.update(*primary_camera, car_id, camera_transform, time.delta_secs());
 
-    if ballcam.enabled {
+    if pov.enabled {
         let ball_pos = states.current.ball.pos.to_bevy();
-        camera_transform.translation = car_transform.translation + (car_transform.translation - ball_pos).normalize() * 300.;
-        camera_transform.look_at(ball_pos, Vec3::Y);
-        camera_transform.translation += camera_transform.up() * 150.;
-        camera_transform.look_at(ball_pos, Vec3::Y);
+        let car_pos = car_transform.translation;
+
+        let target_look = if ballcam.enabled {
+            ball_pos - car_pos
+        } else {
+            Vec3::new(target_car.state.vel.x, 0., target_car.state.vel.y)
+        }
+        .try_normalize()
+        .unwrap_or_else(|| car_transform.forward().into());
+
+        // snap into place when switching cars instead of flying over to the new one
+        let delta = time.delta_secs();
+        let (look, follow) = match *last_pov {
+            Some((id, last_look)) if id == car_id => (
+                last_look
+                    .lerp(target_look, 1. - (-pov.swivel_speed * delta).exp())
+                    .try_normalize()
+                    .unwrap_or(target_look),
+                1. - (-CAMERA_FOLLOW_RATE * delta / (1. - pov.stiffness).max(0.01)).exp(),
+            ),
+            _ => (target_look, 1.),
+        };
+        *last_pov = Some((car_id, look));
+
+        let flat_look = Vec3::new(look.x, 0., look.z).try_normalize().unwrap_or(Vec3::X);
+        let target_pos = car_pos - flat_look * pov.distance + Vec3::Y * pov.height;
+        camera_transform.translation = camera_transform.translation.lerp(target_pos, follow);
         camera_transform.translation.y = camera_transform.translation.y.max(MIN_CAMERA_BALLCAM_HEIGHT);
+
+        if ballcam.enabled {
+            camera_transform.look_at(ball_pos, Vec3::Y);
+        } else {
+            camera_transform.look_to(flat_look, Vec3::Y);
+            camera_transform.rotation *= Quat::from_rotation_x(pov.angle.to_radians());
+        }
+    } else {
+        follow_car(
+            camera_transform,
+            &car_transform,
+            target_car.state.vel,
+            states.current.ball.pos.to_bevy(),
+            ballcam.enabled,
+        );
+    }
+
+    if is_director {
+        director.look_at_rim(camera_transform);
+    }
+
+    transition.blend(camera_transform);
+}
+
+/// Places the camera behind a car, looking at the ball if `ballcam` is enabled or in the direction the car is going otherwise
+pub fn follow_car(camera: &mut Transform, car: &Transform, car_vel: Vec3A, ball_pos: Vec3, ballcam: bool) {
+    if ballcam {
+        camera.translation = car.translation + (car.translation - ball_pos).normalize() * 300.;
+        camera.look_at(ball_pos, Vec3::Y);
+        camera.translation += camera.up() * 150.;
+        camera.look_at(ball_pos, Vec3::Y);
+        camera.translation.y = camera.translation.y.max(MIN_CAMERA_BALLCAM_HEIGHT);
     } else {
-        let car_look = Vec3::new(target_car.state.vel.x, 0., target_car.state.vel.y)
+        let car_look = Vec3::new(car_vel.x, 0., car_vel.y)
             .try_normalize()
-            .unwrap_or_else(|| car_transform.forward().into());
-        camera_transform.translation = car_transform.translation - car_look * 280. + Vec3::Y * 110.;
-        camera_transform.look_to(car_look, Vec3::Y);
-        camera_transform.rotation *= Quat::from_rotation_x(-PI / 30.);
+            .unwrap_or_else(|| car.forward().into());
+        camera.translation = car.translation - car_look * 280. + Vec3::Y * 110.;
+        camera.look_to(car_look, Vec3::Y);
+        camera.rotation *= Quat::from_rotation_x(-PI / 30.);
     }
 }
 
@@ -977,6 +2001,7 @@ fn correct_car_count(
     car_wheel_mesh: &CarWheelMesh,
     images: &mut Assets<Image>,
     render_device: Option<&RenderDevice>,
+    car_bodies: &CarBodies,
 ) {
     // remove cars that no longer exist
     for (entity, car) in car_entities {
@@ -1002,6 +2027,7 @@ fn correct_car_count(
             car_wheel_mesh,
             images,
             render_device,
+            car_bodies,
         );
     }
 }
@@ -1033,6 +2059,18 @@ fn update_pads_count(
             ..default()
         });
 
+        // every glow shares one material and only the mesh differs between big and small pads,
+        // so they're all drawn in a couple of instanced batches instead of one draw call each
+        let glow_material = materials.add(StandardMaterial {
+            // the glow comes from the emissive color so bloom can pick it up
+            base_color: Color::BLACK.with_alpha(0.6),
+            emissive: LinearRgba::rgb(0.9, 0.9, 0.1) * GLOW_STRENGTH,
+            alpha_mode: AlphaMode::Add,
+            double_sided: true,
+            cull_mode: None,
+            ..default()
+        });
+
         let large_pad_mesh = match asset_server.get_load_state(&pad_glows.large) {
             Some(LoadState::Failed(_)) => pad_glows.large_hitbox.clone(),
             _ => pad_glows.large.clone(),
@@ -1125,27 +2163,13 @@ fn update_pads_count(
                 .observe(target_remove::<Pointer<Out>, HighlightedEntity>)
                 .observe(send_event::<Pointer<Click>, BoostPadClicked>)
                 .with_children(|parent| {
-                    parent.spawn((
-                        Mesh3d(visual_mesh),
-                        MeshMaterial3d(materials.add(StandardMaterial {
-                            base_color: Color::srgba(0.9, 0.9, 0.1, 0.6),
-                            alpha_mode: AlphaMode::Add,
-                            double_sided: true,
-                            cull_mode: None,
-                            ..default()
-                        })),
-                    ));
+                    parent.spawn((Mesh3d(visual_mesh), MeshMaterial3d(glow_material.clone())));
                 });
         }
     }
 }
 
-fn update_pad_colors(
-    states: Res<GameStates>,
-    query: Query<(&Children, &BoostPadI)>,
-    mats_query: Query<&MeshMaterial3d<StandardMaterial>>,
-    mut materials: ResMut<Assets<StandardMaterial>>,
-) {
+fn update_pad_colors(states: Res<GameStates>, query: Query<(&Children, &BoostPadI)>, mut glows: Query<&mut Visibility>) {
     let morton_generator = Morton::default();
 
     let mut sorted_pads = states
@@ -1159,46 +2183,80 @@ fn update_pad_colors(
 
     for (children, id) in query.iter() {
         let index = sorted_pads.binary_search_by_key(&id.id(), |(_, code)| *code).unwrap();
-        let alpha = if states.current.pads[sorted_pads[index].0].state.is_active {
-            0.6
+
+        // hide the glow on inactive pads instead of changing the shared material
+        let visibility = if states.current.pads[sorted_pads[index].0].state.is_active {
+            Visibility::Inherited
         } else {
-            // make the glow on inactive pads dissapear
-            0.0
+            Visibility::Hidden
         };
 
         let child = children.first().unwrap();
-        let handle = mats_query.get(*child).unwrap();
-        materials.get_mut(handle).unwrap().base_color.set_alpha(alpha);
+        if let Ok(mut glow) = glows.get_mut(*child) {
+            glow.set_if_neq(visibility);
+        }
+    }
+}
+
+const BIG_PAD_COOLDOWN: f32 = 10.;
+const SMALL_PAD_COOLDOWN: f32 = 4.;
+const PAD_COOLDOWN_COLOR: Color = Color::srgb(1., 0.8, 0.2);
+
+/// Draws a ring above each inactive pad that shrinks until the pad respawns
+fn draw_pad_cooldowns(
+    states: Res<GameStates>,
+    game_speed: Res<GameSpeed>,
+    packet_time_elapsed: Res<PacketTimeElapsed>,
+    mut gizmos: Gizmos,
+) {
+    // the cooldown keeps going down between packets
+    let elapsed = if game_speed.paused {
+        0.
+    } else {
+        packet_time_elapsed.elapsed_secs() * game_speed.speed
+    };
+
+    for pad in states.current.pads.iter().filter(|pad| !pad.state.is_active) {
+        let (max_cooldown, radius) = if pad.is_big {
+            (BIG_PAD_COOLDOWN, 80.)
+        } else {
+            (SMALL_PAD_COOLDOWN, 50.)
+        };
+
+        let remaining = (pad.state.cooldown - elapsed).clamp(0., max_cooldown) / max_cooldown;
+        if remaining <= 0. {
+            continue;
+        }
+
+        let center = pad.position.to_bevy() + Vec3::Y * 100.;
+        gizmos
+            .arc_3d(
+                TAU * remaining,
+                radius,
+                Isometry3d::from_translation(center),
+                PAD_COOLDOWN_COLOR,
+            )
+            .resolution(32);
     }
 }
 
+const BOOST_METER_SMOOTHING_RATE: f32 = 12.;
+
 fn update_boost_meter(
+    time: Res<Time>,
     states: Res<GameStates>,
     ui_scale: Res<UiOverlayScale>,
+    smooth_boost_meter: Res<SmoothBoostMeter>,
     camera: Query<&PrimaryCamera>,
     windows: Query<&Window, With<PrimaryWindow>>,
     mut painter: ShapePainter,
     mut boost_amount: Query<(&mut Text, &mut Node, &mut TextFont), With<BoostAmount>>,
     mut was_last_director: Local<bool>,
+    mut displayed_boost: Local<(u32, f32)>,
 ) {
     let id = match camera.single() {
-        PrimaryCamera::TrackCar(id) => {
-            if states.current.cars.is_empty() {
-                return;
-            }
-
-            let mut ids = states.current.cars.iter().map(|car_info| car_info.id).collect::<Vec<_>>();
-            radsort::sort(&mut ids);
-
-            let index = *id as usize - 1;
-            if index >= ids.len() {
-                0
-            } else {
-                ids[index]
-            }
-        }
-        PrimaryCamera::Director(id) => *id,
-        PrimaryCamera::Spectator => 0,
+        PrimaryCamera::TrackCar(id) | PrimaryCamera::Director(id) => *id,
+        PrimaryCamera::Spectator | PrimaryCamera::Orbit(_) => 0,
     };
 
     if id == 0 {
@@ -1214,6 +2272,16 @@ fn update_boost_meter(
         return;
     };
 
+    // snap to the real value when switching cars so the meter doesn't sweep between them
+    let (last_id, boost) = &mut *displayed_boost;
+    if smooth_boost_meter.0 && *last_id == id {
+        *boost += (car_state.boost - *boost) * (1. - (-BOOST_METER_SMOOTHING_RATE * time.delta_secs()).exp());
+    } else {
+        *boost = car_state.boost;
+    }
+    *last_id = id;
+    let boost = *boost;
+
     let primary_window = windows.single();
     let window_res = Vec2::new(primary_window.width(), primary_window.height());
     let painter_pos = (window_res / 2. - (BOOST_INDICATOR_POS + 25.) * ui_scale.scale) * Vec2::new(1., -1.);
@@ -1222,7 +2290,7 @@ fn update_boost_meter(
     painter.color = Color::srgb(0.075, 0.075, 0.15);
     painter.circle(100.0 * ui_scale.scale);
 
-    let scale = car_state.boost / 100.;
+    let scale = boost / 100.;
 
     let start_angle = 7. * PI / 6.;
     let full_angle = 11. * PI / 6.;
@@ -1239,7 +2307,7 @@ fn update_boost_meter(
     style.right = Val::Px((BOOST_INDICATOR_POS.x - 25.) * ui_scale.scale);
     style.bottom = Val::Px(BOOST_INDICATOR_POS.y * ui_scale.scale);
 
-    **text_display = car_state.boost.round().to_string();
+    **text_display = boost.round().to_string();
     font.font_size = BOOST_INDICATOR_FONT_SIZE * ui_scale.scale;
 
     *was_last_director = true;
@@ -1428,11 +2496,16 @@ fn interpolate_packets(
 fn listen(
     socket: Res<Connection>,
     key: Res<ButtonInput<KeyCode>>,
+    keybinds: Res<Keybinds>,
     mut game_states: ResMut<GameStates>,
     mut options: ResMut<Options>,
+    mut dump_state: EventWriter<DumpGameState>,
+    mut step_queue: ResMut<StepQueue>,
+    menu_focused: Res<MenuFocused>,
+    mut scroll: EventReader<MouseWheel>,
 ) {
     let mut changed = false;
-    if key.just_pressed(KeyCode::KeyR) {
+    if keybinds.just_pressed(&key, Action::ShootBall) {
         changed = true;
 
         let pos = Vec3A::new(0., -2000., 1500.);
@@ -1444,25 +2517,46 @@ fn listen(
         game_states.next.ball.vel = vel;
     }
 
-    if key.just_pressed(KeyCode::KeyP) {
+    if keybinds.just_pressed(&key, Action::TogglePause) {
         options.paused = !options.paused;
     }
 
-    let shift_pressed = key.pressed(KeyCode::ShiftLeft) || key.pressed(KeyCode::ShiftRight);
+    if options.paused && keybinds.just_pressed(&key, Action::StepFrame) {
+        step_queue.step();
+    }
+
+    if keybinds.just_pressed(&key, Action::DumpGameState) {
+        dump_state.send(DumpGameState);
+    }
 
-    if key.just_pressed(KeyCode::NumpadAdd) || (shift_pressed && key.just_pressed(KeyCode::Equal)) {
-        options.game_speed = if options.game_speed < 0.5 {
-            0.5
-        } else {
-            (options.game_speed + 0.5).min(10.)
-        };
+    let mut speed_steps = 0.;
+    if keybinds.just_pressed(&key, Action::IncreaseSpeed) {
+        speed_steps += 1.;
+    }
+
+    if keybinds.just_pressed(&key, Action::DecreaseSpeed) {
+        speed_steps -= 1.;
+    }
+
+    // scrolling while holding the modifier scrubs through the game speed,
+    // but only while the menu is closed so it doesn't fight with egui
+    if !menu_focused.0 && keybinds.pressed(&key, Action::SpeedScrubModifier) {
+        speed_steps += scroll
+            .read()
+            .map(|event| match event.unit {
+                MouseScrollUnit::Line => event.y,
+                MouseScrollUnit::Pixel => event.y / 100.,
+            })
+            .sum::<f32>();
+    } else {
+        scroll.clear();
     }
 
-    if key.just_pressed(KeyCode::NumpadSubtract) || (!shift_pressed && key.just_pressed(KeyCode::Minus)) {
-        options.game_speed = (options.game_speed - 0.5).max(0.1);
+    if speed_steps != 0. {
+        options.game_speed = GameSpeed::scale(options.game_speed, speed_steps);
     }
 
-    if key.just_pressed(KeyCode::NumpadEqual) || (!shift_pressed && key.just_pressed(KeyCode::Equal)) {
+    if keybinds.just_pressed(&key, Action::ResetSpeed) {
         options.game_speed = 1.;
     }
 
@@ -1482,7 +2576,10 @@ pub struct GameStates {
 }
 
 impl GameStates {
-    pub fn advance(&mut self, packet_smoothing: PacketSmoothing, new_state: GameState, calc_ball_rot: bool) {
+    pub fn advance(&mut self, packet_smoothing: PacketSmoothing, new_state: Arc<GameState>, calc_ball_rot: bool) {
+        // only copied if something else, like the replay buffer, is still holding on to it
+        let new_state = Arc::unwrap_or_clone(new_state);
+
         match packet_smoothing {
             PacketSmoothing::None | PacketSmoothing::Extrapolate => {
                 self.last = replace(&mut self.next, new_state);
@@ -1506,6 +2603,126 @@ impl GameStates {
     }
 }
 
+/// The most states that are kept around to step through while paused
+const MAX_STEP_QUEUE_LEN: usize = 1200;
+
+/// Game states that arrived while paused, so they can be stepped through one at a time
+#[derive(Resource, Default)]
+pub struct StepQueue {
+    states: VecDeque<Arc<GameState>>,
+    /// Steps that were asked for but haven't been shown yet
+    pending_steps: u32,
+    /// Whether the simulation was unpaused to get a new state because there weren't any queued up
+    resumed_for_step: bool,
+}
+
+impl StepQueue {
+    /// Shows the next game state, only while paused
+    pub fn step(&mut self) {
+        self.pending_steps += 1;
+    }
+
+    fn push(&mut self, state: Arc<GameState>) {
+        if self.states.len() == MAX_STEP_QUEUE_LEN {
+            self.states.pop_front();
+        }
+
+        self.states.push_back(state);
+    }
+
+    fn next_step(&mut self, socket: &Connection) -> Option<Arc<GameState>> {
+        let mut state = None;
+
+        if self.pending_steps != 0 {
+            state = self.states.pop_front();
+
+            if state.is_some() {
+                self.pending_steps -= 1;
+            } else if !self.resumed_for_step {
+                // the simulation stops when paused, so let it run just long enough to send another state
+                socket.send(SendableUdp::Paused(false)).unwrap();
+                self.resumed_for_step = true;
+            }
+        }
+
+        if self.resumed_for_step && self.pending_steps == 0 {
+            // anything it sends before this arrives is queued up for the next steps
+            socket.send(SendableUdp::Paused(true)).unwrap();
+            self.resumed_for_step = false;
+        }
+
+        state
+    }
+
+    /// Forgets the queued states once unpaused, since newer ones are on their way
+    fn clear(&mut self) {
+        self.states.clear();
+        self.pending_steps = 0;
+        self.resumed_for_step = false;
+    }
+}
+
+/// The most states that are held back by the render delay, in case a burst arrives all at once
+const MAX_JITTER_BUFFER_LEN: usize = 64;
+/// How quickly playback drifts back to the render delay behind the newest state, per second
+const JITTER_BUFFER_CATCH_UP_RATE: f64 = 0.5;
+
+/// Game states that are held back by the render delay, so they're shown at the pace they were simulated in
+/// instead of the pace they arrived in
+#[derive(Resource, Default)]
+struct JitterBuffer {
+    states: VecDeque<Arc<GameState>>,
+    /// The tick that playback has reached, which trails the newest state by the render delay
+    playback_tick: f64,
+}
+
+impl JitterBuffer {
+    fn push(&mut self, state: Arc<GameState>) {
+        // the tick count going backwards means a new game was started
+        if self.states.back().is_some_and(|newest| state.tick_count < newest.tick_count) {
+            self.states.clear();
+        }
+
+        if self.states.len() == MAX_JITTER_BUFFER_LEN {
+            self.states.pop_front();
+        }
+
+        self.states.push_back(state);
+    }
+
+    /// Moves playback forward by `delta_time` seconds of game time and returns the newest state that's now due
+    fn next_state(&mut self, delta_time: f32, delay: f32) -> Option<Arc<GameState>> {
+        let newest = self.states.back()?;
+        let tick_rate = f64::from(newest.tick_rate);
+        let target_tick = newest.tick_count as f64 - f64::from(delay) * tick_rate;
+
+        self.playback_tick += f64::from(delta_time) * tick_rate;
+
+        let drift = target_tick - self.playback_tick;
+        if drift.abs() > f64::from(delay) * tick_rate {
+            // too far off to catch up smoothly, like after a lag spike or when the delay was changed
+            self.playback_tick = target_tick;
+        } else {
+            self.playback_tick += drift * (JITTER_BUFFER_CATCH_UP_RATE * f64::from(delta_time)).min(1.);
+        }
+
+        let mut state = None;
+        while self
+            .states
+            .front()
+            .is_some_and(|state| state.tick_count as f64 <= self.playback_tick)
+        {
+            state = self.states.pop_front();
+        }
+
+        state
+    }
+
+    fn clear(&mut self) {
+        self.states.clear();
+    }
+}
+
 #[derive(Resource, Default, DerefMut, Deref)]
 struct PacketTimeElapsed(Stopwatch);
 
@@ -1531,6 +2748,16 @@ impl LastPacketTimesElapsed {
         self.len = 0;
     }
 
+    /// The average difference between the recent times between packets and their mean
+    fn jitter(&self) -> f32 {
+        if self.len == 0 {
+            return 0.;
+        }
+
+        let avg = self.avg();
+        self.times[..self.len].iter().map(|time| (time - avg).abs()).sum::<f32>() / self.len as f32
+    }
+
     fn avg(&self) -> f32 {
         if self.len == 0 {
             return 1. / 120.;
@@ -1544,23 +2771,112 @@ impl LastPacketTimesElapsed {
     }
 }
 
+/// How often the rates in `NetworkStats` are recalculated, in seconds
+const NETWORK_STATS_INTERVAL: f32 = 1.;
+
+/// Diagnostics about the connection to the simulation, for debugging dropped or delayed packets
+#[derive(Resource, Default)]
+pub struct NetworkStats {
+    pub packets_per_sec: f32,
+    pub bytes_per_sec: f32,
+    /// Fraction of game states that never arrived, estimated from gaps in the tick count
+    pub packet_loss: f32,
+    /// How much the time between packets varies, in seconds
+    pub jitter: f32,
+    pub since_last_packet: f32,
+    /// Average time from a game state being read off of the socket until the main schedule picks it up, in seconds
+    pub latency: f32,
+    /// The longest that took over the last interval, in seconds
+    pub max_latency: f32,
+    /// How many UDP packets have been dropped for each `RecvError`, in the same order as `RecvError::ALL`
+    pub recv_errors: [u64; RecvError::ALL.len()],
+    elapsed: f32,
+    packets: u32,
+    last_bytes_received: u64,
+    last_tick_count: Option<u64>,
+    tick_gaps: Vec<u64>,
+    latencies: Vec<f32>,
+}
+
+impl NetworkStats {
+    fn record_state(&mut self, tick_count: u64, received: Instant) {
+        self.packets += 1;
+        self.latencies.push(received.elapsed().as_secs_f32());
+
+        // the tick count going backwards means a new game was started
+        if let Some(last_tick_count) = self.last_tick_count.replace(tick_count) {
+            if tick_count > last_tick_count {
+                self.tick_gaps.push(tick_count - last_tick_count);
+            }
+        }
+    }
+
+    /// Assumes the smallest gap is how many ticks the simulation advances between packets
+    fn estimate_packet_loss(&self) -> f32 {
+        let Some(&tick_skip) = self.tick_gaps.iter().min() else {
+            return 0.;
+        };
+
+        let expected = self.tick_gaps.iter().map(|gap| gap / tick_skip).sum::<u64>();
+        1. - self.tick_gaps.len() as f32 / expected as f32
+    }
+}
+
+fn update_network_stats(
+    time: Res<Time>,
+    packet_time_elapsed: Res<PacketTimeElapsed>,
+    last_packet_times: Res<LastPacketTimesElapsed>,
+    mut stats: ResMut<NetworkStats>,
+) {
+    stats.since_last_packet = packet_time_elapsed.elapsed_secs();
+    stats.jitter = last_packet_times.jitter();
+    stats.recv_errors = RecvError::ALL.map(RecvError::count);
+
+    stats.elapsed += time.delta_secs();
+    if stats.elapsed < NETWORK_STATS_INTERVAL {
+        return;
+    }
+
+    let bytes_received = BYTES_RECEIVED.load(Ordering::Relaxed);
+    stats.packets_per_sec = stats.packets as f32 / stats.elapsed;
+    stats.bytes_per_sec = (bytes_received - stats.last_bytes_received) as f32 / stats.elapsed;
+    stats.packet_loss = stats.estimate_packet_loss();
+    stats.latency = stats.latencies.iter().sum::<f32>() / stats.latencies.len().max(1) as f32;
+    stats.max_latency = stats.latencies.iter().copied().fold(0., f32::max);
+
+    stats.elapsed = 0.;
+    stats.packets = 0;
+    stats.last_bytes_received = bytes_received;
+    stats.tick_gaps.clear();
+    stats.latencies.clear();
+}
+
 pub struct RocketSimPlugin;
 
 impl Plugin for RocketSimPlugin {
     fn build(&self, app: &mut App) {
         app.add_event::<PausedUpdate>()
             .add_event::<SpeedUpdate>()
+            .add_event::<DumpGameState>()
+            .add_event::<Reconnect>()
             .insert_resource(GameStates::default())
-            .insert_resource(DirectorTimer(Timer::new(Duration::from_secs(12), TimerMode::Repeating)))
+            .insert_resource(ProtocolMismatch::default())
+            .insert_resource(WaitingForSimulator::default())
+            .insert_resource(Director::default())
             .insert_resource(PacketTimeElapsed::default())
             .insert_resource(LastPacketTimesElapsed::default())
+            .insert_resource(NetworkStats::default())
+            .insert_resource(StepQueue::default())
+            .insert_resource(JitterBuffer::default())
             .insert_resource(PacketUpdated::default())
             .insert_resource(GameMode::default())
+            .insert_resource(PausedSystems::default())
             .add_plugins(UdpRendererPlugin)
             .add_systems(
                 Update,
                 (
                     establish_connection.run_if(in_state(GameLoadState::Connect)),
+                    reconnect.run_if(on_event::<Reconnect>),
                     (
                         (
                             apply_udp_updates,
@@ -1571,44 +2887,78 @@ impl Plugin for RocketSimPlugin {
                                             interpolate_calc_next_ball_rot.run_if(|ps: Res<PacketSmoothing>| {
                                                 matches!(*ps, PacketSmoothing::Interpolate)
                                             }),
-                                            update_ball_rotation.run_if(|ps: Res<PacketSmoothing>| {
-                                                !matches!(*ps, PacketSmoothing::Interpolate)
-                                            }),
+                                            update_ball_rotation
+                                                .run_if(not_paused(DebugSystem::UpdateBallRotation))
+                                                .run_if(|ps: Res<PacketSmoothing>| {
+                                                    !matches!(*ps, PacketSmoothing::Interpolate)
+                                                }),
                                         )
                                             .run_if(|calc_ball_rot: Res<CalcBallRot>| calc_ball_rot.0),
-                                        update_ball,
+                                        update_ball.run_if(not_paused(DebugSystem::UpdateBall)),
                                     )
                                         .chain(),
                                     (
                                         pre_update_car,
-                                        (update_car, update_car_extra, update_car_wheels),
-                                        update_camera,
+                                        (
+                                            update_car.run_if(not_paused(DebugSystem::UpdateCar)),
+                                            update_car_extra.run_if(not_paused(DebugSystem::UpdateCarExtra)),
+                                            update_car_wheels.run_if(not_paused(DebugSystem::UpdateCarWheels)),
+                                        ),
+                                        update_camera.run_if(not_paused(DebugSystem::UpdateCamera)),
                                     )
                                         .chain(),
-                                    (update_pads_count, update_pad_colors).chain(),
+                                    (update_pads_count, update_pad_colors)
+                                        .chain()
+                                        .run_if(not_paused(DebugSystem::UpdatePads)),
                                     update_field,
                                 )
                                     .run_if(|updated: Res<PacketUpdated>| updated.0),
                                 (
                                     (
-                                        (extrapolate_packet, update_ball_rotation),
-                                        (update_ball, (update_car, update_camera).chain(), update_car_wheels),
+                                        (
+                                            extrapolate_packet.run_if(not_paused(DebugSystem::ExtrapolatePacket)),
+                                            update_ball_rotation.run_if(not_paused(DebugSystem::UpdateBallRotation)),
+                                        ),
+                                        (
+                                            update_ball.run_if(not_paused(DebugSystem::UpdateBall)),
+                                            (
+                                                update_car.run_if(not_paused(DebugSystem::UpdateCar)),
+                                                update_camera.run_if(not_paused(DebugSystem::UpdateCamera)),
+                                            )
+                                                .chain(),
+                                            update_car_wheels.run_if(not_paused(DebugSystem::UpdateCarWheels)),
+                                        ),
                                     )
                                         .chain()
                                         .run_if(|ps: Res<PacketSmoothing>| matches!(*ps, PacketSmoothing::Extrapolate)),
                                     (
-                                        interpolate_packets,
-                                        (update_ball, (update_car, update_camera).chain(), update_car_wheels),
+                                        interpolate_packets.run_if(not_paused(DebugSystem::InterpolatePackets)),
+                                        (
+                                            update_ball.run_if(not_paused(DebugSystem::UpdateBall)),
+                                            (
+                                                update_car.run_if(not_paused(DebugSystem::UpdateCar)),
+                                                update_camera.run_if(not_paused(DebugSystem::UpdateCamera)),
+                                            )
+                                                .chain(),
+                                            update_car_wheels.run_if(not_paused(DebugSystem::UpdateCarWheels)),
+                                        ),
                                     )
                                         .chain()
                                         .run_if(|ps: Res<PacketSmoothing>| matches!(*ps, PacketSmoothing::Interpolate)),
                                 )
                                     .run_if(|updated: Res<PacketUpdated>| !updated.0),
-                                (listen, update_boost_meter),
+                                (
+                                    listen,
+                                    update_boost_meter.run_if(not_paused(DebugSystem::UpdateBoostMeter)),
+                                    update_car_hitboxes,
+                                    update_network_stats,
+                                    draw_pad_cooldowns
+                                        .run_if(|show_pad_cooldowns: Res<ShowPadCooldowns>| show_pad_cooldowns.0),
+                                ),
                             ),
                         )
                             .chain(),
-                        update_time,
+                        update_time.run_if(not_paused(DebugSystem::UpdateTime)),
                     )
                         .run_if(in_state(GameLoadState::None)),
                 ),